@@ -0,0 +1,32 @@
+//! Play an audio file on an output device without the soundboard window:
+//!
+//! cargo run -p soundboard-core --example play_file -- <file> ["<output device>"]
+
+use soundboard_core::audio::SYSTEM_DEFAULT_OUTPUT;
+use soundboard_core::config::SoundConfig;
+use soundboard_core::engine::SoundboardEngine;
+use std::{thread, time::Duration};
+
+fn main() -> Result<(), String> {
+    let mut arguments = std::env::args().skip(1);
+    let path = arguments
+        .next()
+        .ok_or("Usage: play_file <file> [<output device>]")?;
+    let output = arguments
+        .next()
+        .unwrap_or_else(|| SYSTEM_DEFAULT_OUTPUT.to_string());
+
+    let mut engine = SoundboardEngine::builder()
+        .output(output)
+        .sound(SoundConfig {
+            name: "File".to_string(),
+            path,
+            ..SoundConfig::default()
+        })
+        .build()?;
+    let controls = engine.play("File")?;
+    while !controls.finished() {
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
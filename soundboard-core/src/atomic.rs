@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// An `f32` shared with the audio threads without a lock, so they never wait for the UI thread.
+/// Stored as its bits.
+#[derive(Default)]
+pub struct AtomicF32(AtomicU32);
+
+impl AtomicF32 {
+    pub fn new(value: f32) -> Self {
+        Self(AtomicU32::new(value.to_bits()))
+    }
+
+    pub fn load(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn store(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// An `f64` shared like [`AtomicF32`], that the audio threads can add to.
+#[derive(Default)]
+pub struct AtomicF64(AtomicU64);
+
+impl AtomicF64 {
+    /// Add `value` to the stored value.
+    pub fn add(&self, value: f64) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + value).to_bits())
+            });
+    }
+
+    /// Return the stored value and store 0 in its place.
+    pub fn take(&self) -> f64 {
+        f64::from_bits(self.0.swap(0.0_f64.to_bits(), Ordering::Relaxed))
+    }
+}
+
+/// An `Option<f32>` shared like [`AtomicF32`], with None stored as NaN. None by default.
+pub struct AtomicOptionF32(AtomicF32);
+
+impl Default for AtomicOptionF32 {
+    fn default() -> Self {
+        Self(AtomicF32::new(f32::NAN))
+    }
+}
+
+impl AtomicOptionF32 {
+    pub fn load(&self) -> Option<f32> {
+        Some(self.0.load()).filter(|value| !value.is_nan())
+    }
+
+    /// Store `value`. `Some(NaN)` is stored as None.
+    pub fn store(&self, value: Option<f32>) {
+        self.0.store(value.unwrap_or(f32::NAN));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_stored() {
+        let value = AtomicF32::default();
+        assert_eq!(value.load(), 0.0);
+        value.store(-3.5);
+        assert_eq!(value.load(), -3.5);
+
+        let option = AtomicOptionF32::default();
+        assert_eq!(option.load(), None);
+        option.store(Some(-6.0));
+        assert_eq!(option.load(), Some(-6.0));
+        option.store(None);
+        assert_eq!(option.load(), None);
+
+        let sum = AtomicF64::default();
+        sum.add(0.25);
+        sum.add(0.5);
+        assert_eq!(sum.take(), 0.75);
+        assert_eq!(sum.take(), 0.0);
+    }
+}
@@ -0,0 +1,1685 @@
+use crate::atomic::{AtomicF32, AtomicOptionF32};
+use crate::channel_test::{ChannelTest, ChannelTestProgress};
+use crate::config::SoundConfig;
+use crate::dsp::{
+    db_to_amplitude, ChannelFix, ChannelFixes, ChannelRoute, ClipDetector, Ducked, Ducker,
+    DuckingHold, LevelMeter, Limiter, Metered, Panned,
+};
+use crate::microphone::Microphone;
+use crate::supervisor::catch_panic;
+use rodio::cpal::{self, traits::StreamTrait, FromSample, SampleFormat, SizedSample};
+use rodio::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use rodio::source::{Done, UniformSourceIterator, Zero};
+use rodio::{Decoder, DeviceTrait, Source};
+use std::{
+    f32::consts::FRAC_PI_2,
+    fs::File,
+    io::BufReader,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, Weak,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+pub struct AudioControls {
+    playing: AtomicBool,
+    stopped: AtomicBool,
+    /// The volume of the sound in dB, with the global volume and its normalization.
+    volume_db: AtomicF32,
+    /// The number of output devices still playing this sound.
+    remaining: Arc<AtomicUsize>,
+    /// The time played before the last pause, and when playback last resumed.
+    played: Mutex<(Duration, Option<Instant>)>,
+    /// The controls of the sounds started together with this one, which stop with it.
+    group: Mutex<Vec<Weak<AudioControls>>>,
+    fade: Fade,
+}
+
+/// A fade of the gain of a sound between 0 (silent) and 1 (full volume), read by the audio
+/// threads without a lock.
+struct Fade {
+    /// The gain sources start at.
+    start: AtomicF32,
+    target: AtomicF32,
+    /// How long fading over the whole range takes, in seconds.
+    seconds: AtomicF32,
+}
+
+impl Default for AudioControls {
+    fn default() -> Self {
+        Self::new(true, false, 0.0)
+    }
+}
+
+impl AudioControls {
+    pub fn new(playing: bool, stopped: bool, volume_db: f32) -> Self {
+        Self {
+            playing: AtomicBool::new(playing),
+            stopped: AtomicBool::new(stopped),
+            volume_db: AtomicF32::new(volume_db),
+            remaining: Arc::new(AtomicUsize::new(0)),
+            played: Mutex::new((Duration::ZERO, playing.then(Instant::now))),
+            group: Mutex::new(Vec::new()),
+            fade: Fade {
+                start: AtomicF32::new(1.0),
+                target: AtomicF32::new(1.0),
+                seconds: AtomicF32::new(0.0),
+            },
+        }
+    }
+
+    /// Make every one of `group` stop when any of them is stopped.
+    pub fn link(group: &[Arc<AudioControls>]) {
+        for controls in group {
+            *controls.group.lock().unwrap() = group
+                .iter()
+                .filter(|other| !Arc::ptr_eq(other, controls))
+                .map(Arc::downgrade)
+                .collect();
+        }
+    }
+
+    /// Stop this and the sounds linked to it, after fading them out over `fade` if given.
+    /// Paused sounds stop at once.
+    pub fn stop(&self, fade: Option<Duration>) {
+        if let Some(fade) = fade.filter(|fade| !fade.is_zero()) {
+            let group: Vec<Arc<AudioControls>> = self
+                .group
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(Weak::upgrade)
+                .collect();
+            for controls in std::iter::once(self).chain(group.iter().map(Arc::as_ref)) {
+                if controls.playing() && !controls.stopped() {
+                    controls.fade_out(fade);
+                } else {
+                    controls.stop(None);
+                }
+            }
+            return;
+        }
+        self.set_playing(false);
+        if !self.stopped.swap(true, Ordering::SeqCst) {
+            for controls in self.group.lock().unwrap().iter() {
+                if let Some(controls) = controls.upgrade() {
+                    controls.stop(None);
+                }
+            }
+        }
+    }
+
+    pub fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// Return true if playback reached the end of the sound on every output device
+    /// without being stopped.
+    pub fn finished(&self) -> bool {
+        !self.stopped() && self.remaining.load(Ordering::SeqCst) == 0
+    }
+
+    pub fn set_playing(&self, playing: bool) {
+        self.playing.store(playing, Ordering::SeqCst);
+        let (played, resumed) = &mut *self.played.lock().unwrap();
+        match (playing, *resumed) {
+            (true, None) => *resumed = Some(Instant::now()),
+            (false, Some(time)) => {
+                *played += time.elapsed();
+                *resumed = None;
+            }
+            _ => (),
+        }
+    }
+
+    /// Return how long this has been playing, not counting pauses. This ignores the output
+    /// delay and assumes playback started straight away.
+    pub fn played(&self) -> Duration {
+        let (played, resumed) = *self.played.lock().unwrap();
+        played + resumed.map_or(Duration::ZERO, |time| time.elapsed())
+    }
+
+    pub fn playing(&self) -> bool {
+        self.playing.load(Ordering::SeqCst)
+    }
+
+    pub fn set_volume_db(&self, volume_db: f32) {
+        self.volume_db.store(volume_db);
+    }
+
+    pub fn volume_db(&self) -> f32 {
+        self.volume_db.load()
+    }
+
+    /// Start silent and fade in over `duration`. Call before the sound is played.
+    pub fn fade_in(&self, duration: Duration) {
+        self.fade.seconds.store(duration.as_secs_f32());
+        self.fade.start.store(0.0);
+        self.fade.target.store(1.0);
+    }
+
+    /// Fade out over `duration`, then stop and end the sound.
+    pub fn fade_out(&self, duration: Duration) {
+        // The duration first, so the audio threads never fade with the old one.
+        self.fade.seconds.store(duration.as_secs_f32());
+        self.fade.target.store(0.0);
+    }
+}
+
+/// An output stream, the mixer it plays and its channel count and sample rate.
+type OpenStream = (cpal::Stream, Arc<DynamicMixerController<f32>>, (u16, u32));
+
+/// One playback of a sound.
+pub struct PlaybackInstance {
+    pub controls: Arc<AudioControls>,
+    pub started: SystemTime,
+    /// The position in the file and the speed playback started with.
+    pub start: Duration,
+    pub speed: f32,
+}
+
+impl PlaybackInstance {
+    /// Return the current position in the file.
+    pub fn position(&self) -> Duration {
+        self.start + self.controls.played().mul_f32(self.speed)
+    }
+
+    fn active(&self) -> bool {
+        !self.controls.stopped() && !self.controls.finished()
+    }
+}
+
+/// The playback instances of one sound, oldest first. Unless the sound allows overlapping
+/// playback, there is at most one.
+#[derive(Default)]
+pub struct SoundPlayback {
+    instances: Vec<PlaybackInstance>,
+    /// The plays waiting for the playing instance to end.
+    queued: usize,
+    /// When the sound was queued behind another sound of its choke group.
+    waiting: Option<Instant>,
+    /// When the last instance was started.
+    last_start: Option<Instant>,
+}
+
+impl SoundPlayback {
+    /// Add the instance of `start`, once an output plays it. See [`SoundPlayback::add`].
+    pub fn add_start(&mut self, start: &SoundStart, limit: usize) {
+        self.add(start.controls.clone(), start.range.0, start.speed, limit);
+    }
+
+    /// Add the instance playing with `controls` from `start` at `speed`, once an output plays
+    /// it. The oldest instances are stopped so that at most `limit` play at once, or any number
+    /// if `limit` is 0.
+    pub fn add(&mut self, controls: Arc<AudioControls>, start: Duration, speed: f32, limit: usize) {
+        self.prune();
+        if limit > 0 {
+            let excess = (self.instances.len() + 1).saturating_sub(limit);
+            for instance in self.instances.drain(..excess) {
+                instance.controls.stop(None);
+            }
+        }
+        self.last_start = Some(Instant::now());
+        self.instances.push(PlaybackInstance {
+            controls,
+            started: SystemTime::now(),
+            start,
+            speed,
+        });
+    }
+
+    /// Return the instances that have not stopped or finished.
+    pub fn instances(&self) -> impl Iterator<Item = &PlaybackInstance> {
+        self.instances.iter().filter(|instance| instance.active())
+    }
+
+    /// Forget the instances that stopped or finished. Stopping the sound also drops the queued
+    /// plays.
+    pub fn prune(&mut self) {
+        if self
+            .instances
+            .iter()
+            .any(|instance| instance.controls.stopped())
+        {
+            self.queued = 0;
+        }
+        self.instances.retain(PlaybackInstance::active);
+    }
+
+    /// Play the sound again once the playing instance ends.
+    pub fn queue(&mut self) {
+        self.queued += 1;
+    }
+
+    /// Return the number of plays waiting for the playing instance to end.
+    pub fn queued(&self) -> usize {
+        self.queued
+    }
+
+    /// Return true and take a queued play if the playing instance has ended.
+    pub fn take_queued(&mut self) -> bool {
+        if self.queued == 0 || !self.idle() {
+            return false;
+        }
+        self.prune();
+        match self.queued {
+            0 => false,
+            _ => {
+                self.queued -= 1;
+                true
+            }
+        }
+    }
+
+    /// Start the sound once the sounds of its choke group queued before it have played.
+    pub fn wait(&mut self) {
+        self.waiting.get_or_insert_with(Instant::now);
+    }
+
+    /// Return when the sound was queued behind its choke group, if it is waiting.
+    pub fn waiting(&self) -> Option<Instant> {
+        self.waiting
+    }
+
+    /// Take the sound out of the queue of its choke group.
+    pub fn stop_waiting(&mut self) {
+        self.waiting = None;
+    }
+
+    /// Return how long is left of `cooldown` since the last instance was started, if any.
+    pub fn cooldown_remaining(&self, cooldown: Duration) -> Option<Duration> {
+        let elapsed = self.last_start?.elapsed();
+        cooldown
+            .checked_sub(elapsed)
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Return true if any instance is playing.
+    pub fn playing(&self) -> bool {
+        self.instances().any(|instance| instance.controls.playing())
+    }
+
+    /// Return true if no instance is playing or paused.
+    pub fn idle(&self) -> bool {
+        self.instances().next().is_none()
+    }
+
+    /// Play or pause every instance.
+    pub fn set_playing(&self, playing: bool) {
+        for instance in self.instances() {
+            instance.controls.set_playing(playing);
+        }
+    }
+
+    /// Stop every instance, after fading it out over `fade` if given.
+    pub fn stop(&self, fade: Option<Duration>) {
+        for instance in &self.instances {
+            instance.controls.stop(fade);
+        }
+    }
+
+    pub fn set_volume_db(&self, volume_db: f32) {
+        for instance in &self.instances {
+            instance.controls.set_volume_db(volume_db);
+        }
+    }
+}
+
+/// The maximum gain of an output and the hearing protection attenuation, applied after all
+/// other volume controls.
+#[derive(Default)]
+struct GainLimit {
+    max_gain: AtomicOptionF32,
+    /// Set when the limit lowered the gain of a source.
+    limited: AtomicBool,
+    attenuation: AtomicF32,
+}
+
+impl GainLimit {
+    /// Return the amplitude factor of a gain of `gain` dB, attenuated and limited to the
+    /// maximum gain.
+    fn factor(&self, gain: f32) -> f32 {
+        let gain = gain - self.attenuation.load();
+        let gain = match self.max_gain.load() {
+            Some(max_gain) if gain > max_gain => {
+                self.limited.store(true, Ordering::SeqCst);
+                max_gain
+            }
+            _ => gain,
+        };
+        db_to_amplitude(gain)
+    }
+}
+
+/// A sound to start on an output device.
+#[derive(Clone)]
+pub struct SoundStart<'a> {
+    pub filename: &'a str,
+    /// A file looped without a gap after `filename` has played, until the sound is stopped.
+    pub loop_filename: Option<&'a str>,
+    /// The position to start from and the length to play.
+    pub range: (Duration, Duration),
+    pub speed: f32,
+    pub controls: Arc<AudioControls>,
+    /// Lowers the sound while a priority sound plays, or None to leave it out of ducking.
+    pub ducker: Option<Arc<Ducker>>,
+    /// Never lowered, and lowers the other sounds of `ducker` while it plays.
+    pub priority: bool,
+    /// The position between the left (-1) and right (1) channels, see [`Panned`].
+    pub pan: f32,
+}
+
+impl<'a> SoundStart<'a> {
+    /// Describe a new instance of `sound` at `volume_db`, started `offset` into its trim points
+    /// and lowered by `ducker`.
+    pub fn new(
+        sound: &'a SoundConfig,
+        volume_db: f32,
+        offset: Duration,
+        ducker: Option<Arc<Ducker>>,
+    ) -> Self {
+        let mut range = sound.trim();
+        range.0 += offset;
+        if range.1 != Duration::MAX {
+            range.1 = range.1.saturating_sub(offset);
+        }
+        Self {
+            filename: &sound.path,
+            loop_filename: (!sound.loop_path.is_empty()).then_some(sound.loop_path.as_str()),
+            range,
+            speed: sound.speed,
+            controls: Arc::new(AudioControls::new(true, false, volume_db)),
+            ducker,
+            priority: sound.priority,
+            pan: sound.pan,
+        }
+    }
+}
+
+/// Play `starts` on `outputs`, given with the group of each, starting the sounds an output plays
+/// on the same sample. `routed` checks if the start at an index plays on the outputs of a group.
+/// Returns whether any output played each start.
+pub fn play_routed<'d>(
+    outputs: impl IntoIterator<Item = (&'d str, &'d mut OutputDevice)>,
+    starts: &[SoundStart],
+    routed: impl Fn(usize, &str) -> bool,
+) -> Vec<bool> {
+    let mut played = vec![false; starts.len()];
+    for (group, device) in outputs {
+        let routes: Vec<bool> = (0..starts.len()).map(|i| routed(i, group)).collect();
+        let routed_starts: Vec<SoundStart> = starts
+            .iter()
+            .zip(&routes)
+            .filter(|(_, routes)| **routes)
+            .map(|(start, _)| start.clone())
+            .collect();
+        if !routed_starts.is_empty() && device.play_sounds(&routed_starts) {
+            for (played, routes) in played.iter_mut().zip(&routes) {
+                *played |= routes;
+            }
+        }
+    }
+    played
+}
+
+/// The volume and processing an output device applies to the sounds played on it, shared with
+/// the sources playing. Kept apart from the device, so that sounds can be rendered without one.
+#[derive(Clone, Default)]
+struct OutputChain {
+    /// The volume in dB, added to the volume of each sound.
+    volume_db: Arc<AtomicF32>,
+    muted: Arc<AtomicBool>,
+    /// Silences sounds and the microphone like `muted`, without changing the mute the user set.
+    gated: Arc<AtomicBool>,
+    /// Applied to the mix of the sounds and the microphone, in the channels of the mix.
+    channel_fixes: Arc<ChannelFixes>,
+    /// The channel of the output, counting from 0, each channel of the mix plays on, see
+    /// [`ChannelRoute`]. The mix has a channel for each, or the channels of the output if it is
+    /// empty.
+    channel_map: Vec<u16>,
+    /// The ceiling in dBFS of the [`Limiter`] on the mix, or None for no limiter.
+    limiter: Arc<AtomicOptionF32>,
+    /// The balance of the mix, see [`Panned`].
+    pan: Arc<AtomicF32>,
+    /// Set when a sample of the mix played was above full scale.
+    clipped: Arc<AtomicBool>,
+    gain_limit: Arc<GainLimit>,
+    delay: Duration,
+}
+
+impl OutputChain {
+    /// Return the channel count of the mix played on an output with `channels`. A mix routed to
+    /// a single channel is mixed in stereo and downmixed by [`ChannelRoute`], since converting
+    /// the sounds to mono would drop their right channel.
+    fn mix_channels(&self, channels: u16) -> u16 {
+        match self.channel_map.len() {
+            0 => channels,
+            1 => 2,
+            mapped => mapped as u16,
+        }
+    }
+
+    /// Build the mix played on an output with `channels` and `sample_rate`: the sources added to
+    /// the returned controller, converted to the format of the mix, with the channel fixes, the
+    /// balance and the limiter applied to their sum and routed to the channels of the output,
+    /// watched for clipping. It plays silence while nothing else plays, so it never ends.
+    fn output_mix(
+        &self,
+        channels: u16,
+        sample_rate: u32,
+    ) -> (
+        Arc<DynamicMixerController<f32>>,
+        impl Source<Item = f32> + Send,
+    ) {
+        let mix_channels = self.mix_channels(channels);
+        let map = if self.channel_map.is_empty() {
+            (0..channels).collect()
+        } else {
+            self.channel_map.clone()
+        };
+        let (controller, mixer) = dynamic_mixer::mixer(mix_channels, sample_rate);
+        controller.add(Zero::<f32>::new(mix_channels, sample_rate));
+        (
+            controller,
+            ClipDetector::new(
+                ChannelRoute::new(
+                    Limiter::new(
+                        Panned::new(
+                            ChannelFix::new(mixer, self.channel_fixes.clone()),
+                            self.pan.clone(),
+                        ),
+                        self.limiter.clone(),
+                    ),
+                    map,
+                    channels,
+                ),
+                self.clipped.clone(),
+            ),
+        )
+    }
+
+    /// Build the source playing `sound`: the trimmed file and its loop at its speed after the
+    /// delay, following its controls, the volumes, the gain limit, ducking and its pan. Returns
+    /// None if a file cannot be read.
+    fn sound_source(&self, sound: &SoundStart) -> Option<impl Source<Item = f32> + Send> {
+        let filename = sound.filename;
+        let (start, length) = sound.range;
+
+        // Decode file and setup audio pipeline.
+        let device_volume = self.volume_db.clone();
+        let muted = self.muted.clone();
+        let gated = self.gated.clone();
+        let gain_limit = self.gain_limit.clone();
+        let controls = sound.controls.clone();
+        let mut hold = sound
+            .ducker
+            .clone()
+            .filter(|_| sound.priority)
+            .map(DuckingHold::new);
+        let file = decode(filename)?.skip_duration(start);
+        // TakeDuration overflows computing its frame length from Duration::MAX, which the mix
+        // reads when converting the source to the format of the output.
+        let intro: Box<dyn Source<Item = f32> + Send> = if length == Duration::MAX {
+            Box::new(file.convert_samples())
+        } else {
+            Box::new(file.take_duration(length).convert_samples())
+        };
+        let source: Box<dyn Source<Item = f32> + Send> = match sound.loop_filename {
+            None => Box::new(intro),
+            Some(loop_filename) => {
+                // The loop is kept in memory once decoded. Queued sources follow each other
+                // sample by sample, so the loop starts right after the last sample of the intro.
+                let part = decode(loop_filename)?
+                    .convert_samples::<f32>()
+                    .buffered()
+                    .repeat_infinite();
+                let (queue, output) = rodio::queue::queue(false);
+                queue.append(intro);
+                queue.append(part);
+                Box::new(output)
+            }
+        };
+        // A mono file is played on both sides, so that it can be panned.
+        let source: Box<dyn Source<Item = f32> + Send> =
+            if sound.pan != 0.0 && source.channels() == 1 {
+                let sample_rate = source.sample_rate();
+                Box::new(UniformSourceIterator::new(source, 2, sample_rate))
+            } else {
+                source
+            };
+        let source = source
+            .speed(sound.speed)
+            .delay(self.delay)
+            .stoppable()
+            .pausable(false)
+            .amplify(1.0)
+            .periodic_access(Duration::from_millis(200), move |src| {
+                // Update with [`AudioControls`].
+                if controls.stopped.load(Ordering::SeqCst) {
+                    src.inner_mut().inner_mut().stop();
+                }
+
+                src.inner_mut()
+                    .set_paused(!controls.playing.load(Ordering::SeqCst));
+                if let Some(hold) = &mut hold {
+                    hold.set_held(controls.playing.load(Ordering::SeqCst));
+                }
+                if muted.load(Ordering::SeqCst) || gated.load(Ordering::SeqCst) {
+                    src.set_factor(0.0);
+                } else {
+                    src.set_factor(gain_limit.factor(controls.volume_db() + device_volume.load()));
+                }
+            });
+
+        Some(Panned::new(
+            Ducked::new(
+                Faded::new(
+                    CatchPanic::new(source, filename.to_string()),
+                    sound.controls.clone(),
+                ),
+                sound.ducker.clone(),
+                sound.priority,
+            ),
+            Arc::new(AtomicF32::new(sound.pan)),
+        ))
+    }
+}
+
+/// The audio host chosen with [`set_audio_host`], or None for the default host of the system.
+static AUDIO_HOST: RwLock<Option<cpal::HostId>> = RwLock::new(None);
+
+/// Return the names of the audio hosts this build can use on this system, such as ALSA on
+/// Linux. JACK is only listed if cpal was built with its "jack" feature.
+pub fn audio_host_names() -> Vec<&'static str> {
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| id.name())
+        .collect()
+}
+
+/// List and open devices on the audio host `name` from now on, or on the default host of the
+/// system if it is empty. Returns whether the host changed. Fails, leaving the host unchanged,
+/// if there is no such host.
+pub fn set_audio_host(name: &str) -> Result<bool, String> {
+    let id = if name.is_empty() {
+        None
+    } else {
+        let id = cpal::available_hosts()
+            .into_iter()
+            .find(|id| id.name() == name)
+            .ok_or_else(|| format!("The audio host {name} is not available."))?;
+        Some(id)
+    };
+    let mut current = AUDIO_HOST.write().unwrap();
+    let changed = *current != id;
+    *current = id;
+    Ok(changed)
+}
+
+/// Return the audio host chosen with [`set_audio_host`], or the default host of the system if
+/// none was chosen or it cannot be opened.
+pub fn audio_host() -> cpal::Host {
+    if let Some(id) = *AUDIO_HOST.read().unwrap() {
+        match cpal::host_from_id(id) {
+            Ok(host) => return host,
+            Err(error) => log!(
+                Error,
+                "[Audio] Unable to open the audio host {}: {error}.",
+                id.name()
+            ),
+        }
+    }
+    cpal::default_host()
+}
+
+/// The name of the output that plays on the default output device of the system, whichever it
+/// is at the time.
+pub const SYSTEM_DEFAULT_OUTPUT: &str = "System Default";
+
+pub struct OutputDevice {
+    device: rodio::Device,
+    name: String,
+    enabled: bool,
+    chain: OutputChain,
+    meter: Arc<LevelMeter>,
+    /// The sample rate in Hz and the buffer size in frames the stream is opened with, 0 for the
+    /// defaults of the device.
+    sample_rate: u32,
+    buffer_frames: u32,
+    stream: Option<cpal::Stream>,
+    /// Plays the output mix, and the warm up and channel tests next to it, on the stream.
+    stream_mixer: Option<Arc<DynamicMixerController<f32>>>,
+    /// The channel count and sample rate of the stream while it is open.
+    format: (u16, u32),
+    /// Adds the sounds and the microphone to the [`OutputChain::output_mix`] of the stream.
+    mixer: Option<Arc<DynamicMixerController<f32>>>,
+}
+
+impl OutputDevice {
+    const WARM_UP: Duration = Duration::from_millis(250);
+
+    pub fn new(device: rodio::Device) -> Self {
+        Self {
+            name: device.name().unwrap_or_else(|_| "[Unknown]".to_string()),
+            device,
+            enabled: false,
+            chain: OutputChain::default(),
+            meter: Arc::new(LevelMeter::new()),
+            sample_rate: 0,
+            buffer_frames: 0,
+            stream: None,
+            stream_mixer: None,
+            format: (2, 48000),
+            mixer: None,
+        }
+    }
+
+    /// Play the mix on the channels of the output in `channel_map`, counting from 1, e.g. `[3, 4]`
+    /// for a stereo mix on channels 3 and 4 of an audio interface, the next time it is enabled.
+    /// The mix has a channel for each, or it plays on every channel if `channel_map` is empty.
+    /// The channel test is not routed.
+    pub fn set_channel_map(&mut self, channel_map: &[u16]) {
+        self.chain.channel_map = channel_map
+            .iter()
+            .filter(|channel| **channel > 0)
+            .map(|channel| channel - 1)
+            .collect();
+    }
+
+    /// Open the stream at `sample_rate` Hz with a buffer of `buffer_frames` frames, 0 for the
+    /// defaults of the device, the next time it is enabled. Smaller buffers lower the latency
+    /// but glitch more easily.
+    pub fn set_stream_format(&mut self, sample_rate: u32, buffer_frames: u32) {
+        self.sample_rate = sample_rate;
+        self.buffer_frames = buffer_frames;
+    }
+
+    /// Open the output stream and start playing the output mix on it.
+    pub fn enable(&mut self) {
+        // Do nothing if already enabled.
+        if self.enabled {
+            return;
+        }
+
+        // Some drivers refuse a buffer size in their supported range, so fall back to theirs.
+        let stream = self
+            .stream_config()
+            .and_then(|(mut config, sample_format)| {
+                match self.open_stream(&config, sample_format) {
+                    Err(error) if matches!(config.buffer_size, cpal::BufferSize::Fixed(_)) => {
+                        log!(
+                        Warning,
+                        "[Audio] Unable to open device {} with a buffer of {} frames: {error}. \
+                        Using the default buffer size.",
+                        self.name,
+                        self.buffer_frames
+                    );
+                        config.buffer_size = cpal::BufferSize::Default;
+                        self.open_stream(&config, sample_format)
+                    }
+                    result => result,
+                }
+            });
+        match stream {
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Audio] Unable to build an output stream from device {}: {error}.",
+                    self.name
+                );
+                self.enabled = false;
+            }
+            Ok((stream, stream_mixer, format)) => {
+                let (mixer, output) = self.chain.output_mix(format.0, format.1);
+                stream_mixer.add(output);
+                self.format = format;
+                self.stream = Some(stream);
+                self.stream_mixer = Some(stream_mixer);
+                self.mixer = Some(mixer);
+                self.enabled = true;
+                self.warm_up();
+            }
+        }
+    }
+
+    /// Return the configuration and sample format to open the stream with: the default ones of
+    /// the device, with the sample rate and buffer size set if the device supports them.
+    fn stream_config(&self) -> Result<(cpal::StreamConfig, SampleFormat), String> {
+        let default = self
+            .device
+            .default_output_config()
+            .map_err(|error| format!("there is no output configuration: {error}"))?;
+        let mut supported = default.clone();
+        let rate = cpal::SampleRate(self.sample_rate);
+        if self.sample_rate != 0 && rate != default.sample_rate() {
+            let range = self
+                .device
+                .supported_output_configs()
+                .ok()
+                .and_then(|mut ranges| {
+                    ranges.find(|range| {
+                        range.channels() == default.channels()
+                            && range.sample_format() == default.sample_format()
+                            && (range.min_sample_rate()..=range.max_sample_rate()).contains(&rate)
+                    })
+                });
+            match range {
+                Some(range) => supported = range.with_sample_rate(rate),
+                None => log!(
+                    Warning,
+                    "[Audio] Device {} does not support {} Hz. Using {} Hz.",
+                    self.name,
+                    self.sample_rate,
+                    default.sample_rate().0
+                ),
+            }
+        }
+        let mut config = supported.config();
+        if self.buffer_frames != 0 {
+            let frames = match *supported.buffer_size() {
+                cpal::SupportedBufferSize::Range { min, max } => {
+                    self.buffer_frames.max(min).min(max)
+                }
+                cpal::SupportedBufferSize::Unknown => self.buffer_frames,
+            };
+            config.buffer_size = cpal::BufferSize::Fixed(frames);
+        }
+        Ok((config, supported.sample_format()))
+    }
+
+    /// Open and start a stream with `config` in `sample_format`, playing the returned mixer.
+    /// Returns the channel count and sample rate of the stream with them.
+    fn open_stream(
+        &self,
+        config: &cpal::StreamConfig,
+        sample_format: SampleFormat,
+    ) -> Result<OpenStream, String> {
+        let (stream_mixer, mix) =
+            dynamic_mixer::mixer::<f32>(config.channels, config.sample_rate.0);
+        let stream = match sample_format {
+            SampleFormat::F32 => self.build_stream::<f32>(config, mix),
+            SampleFormat::F64 => self.build_stream::<f64>(config, mix),
+            SampleFormat::I16 => self.build_stream::<i16>(config, mix),
+            SampleFormat::I32 => self.build_stream::<i32>(config, mix),
+            SampleFormat::U16 => self.build_stream::<u16>(config, mix),
+            sample_format => {
+                return Err(format!(
+                    "the sample format {sample_format} is not supported"
+                ))
+            }
+        }
+        .map_err(|error| error.to_string())?;
+        stream.play().map_err(|error| error.to_string())?;
+        Ok((
+            stream,
+            stream_mixer,
+            (config.channels, config.sample_rate.0),
+        ))
+    }
+
+    /// Build a stream playing `mix` in samples of type `T`.
+    fn build_stream<T>(
+        &self,
+        config: &cpal::StreamConfig,
+        mut mix: DynamicMixer<f32>,
+    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: SizedSample + FromSample<f32>,
+    {
+        let name = self.name.clone();
+        self.device.build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                for sample in data {
+                    *sample = T::from_sample(mix.next().unwrap_or(0.0));
+                }
+            },
+            move |error| {
+                log!(
+                    Error,
+                    "[Audio] Output stream error on device {name}: {error}."
+                )
+            },
+            None,
+        )
+    }
+
+    /// Return the channel count and sample rate of the output stream.
+    fn output_format(&self) -> (u16, u32) {
+        self.format
+    }
+
+    /// Play a short burst of silence so the driver is running before the first sound.
+    /// Some backends click or start late on the first samples after a stream is opened.
+    fn warm_up(&self) {
+        let (channels, sample_rate) = self.output_format();
+        let silence = Zero::<f32>::new(channels, sample_rate).take_duration(Self::WARM_UP);
+        if let Some(stream_mixer) = &self.stream_mixer {
+            stream_mixer.add(silence);
+        }
+    }
+
+    /// Close the output stream.
+    pub fn disable(&mut self) {
+        // Do nothing if not enabled.
+        if !self.enabled {
+            return;
+        }
+
+        // Drop the mixers and the stream.
+        drop(self.mixer.take());
+        drop(self.stream_mixer.take());
+        drop(self.stream.take());
+        self.enabled = false;
+    }
+
+    /// Return self.enabled.
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Return the name of the device, which for [`SYSTEM_DEFAULT_OUTPUT`] is the name of the
+    /// default device it plays on.
+    #[inline]
+    #[allow(dead_code)]
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Build the source playing `sound` on this device, counted in the remaining outputs of its
+    /// controls. Returns None if the file cannot be read.
+    fn sound_source(&self, sound: &SoundStart) -> Option<impl Source<Item = f32> + Send> {
+        let source = self.chain.sound_source(sound)?;
+        let remaining = sound.controls.remaining.clone();
+        remaining.fetch_add(1, Ordering::SeqCst);
+        Some(Done::new(source, remaining))
+    }
+
+    /// Play `sounds` starting on the same sample and return true on success.
+    pub fn play_sounds(&mut self, sounds: &[SoundStart]) -> bool {
+        // Do nothing if not enabled.
+        if !self.enabled {
+            return false;
+        }
+
+        let mut sources: Vec<_> = sounds
+            .iter()
+            .filter_map(|sound| self.sound_source(sound))
+            .collect();
+        let mixer = self
+            .mixer
+            .as_ref()
+            .expect("self.mixer is None when self.enabled is true");
+
+        // Play audio. Sources added to the mix separately may start in different buffers, so
+        // several sounds are mixed before they are added.
+        match sources.len() {
+            0 => return false,
+            1 => mixer.add(Metered::new(sources.remove(0), self.meter.clone())),
+            _ => {
+                let (channels, sample_rate) = self.output_format();
+                let channels = self.chain.mix_channels(channels);
+                let (controller, group) = dynamic_mixer::mixer(channels, sample_rate);
+                for source in sources {
+                    controller.add(source);
+                }
+                mixer.add(Metered::new(group, self.meter.clone()));
+            }
+        }
+        true
+    }
+
+    /// Mix the audio captured by `microphone` into this device and return true on success.
+    pub fn play_microphone(&mut self, microphone: &Microphone) -> bool {
+        // Do nothing if not enabled.
+        if !self.enabled {
+            return false;
+        }
+
+        let device_volume = self.chain.volume_db.clone();
+        let muted = self.chain.muted.clone();
+        let gated = self.chain.gated.clone();
+        let gain_limit = self.chain.gain_limit.clone();
+        let source = microphone.source().amplify(1.0).periodic_access(
+            Duration::from_millis(200),
+            move |src| {
+                if muted.load(Ordering::SeqCst) || gated.load(Ordering::SeqCst) {
+                    src.set_factor(0.0);
+                } else {
+                    src.set_factor(gain_limit.factor(device_volume.load()));
+                }
+            },
+        );
+
+        self.mixer
+            .as_ref()
+            .expect("self.mixer is None when self.enabled is true")
+            .add(Metered::new(source, self.meter.clone()));
+        true
+    }
+
+    /// Return the number of channels of the output stream, or None if it cannot be read.
+    pub fn channels(&self) -> Option<u16> {
+        if self.enabled {
+            return Some(self.format.0);
+        }
+        self.device
+            .default_output_config()
+            .ok()
+            .map(|config| config.channels())
+    }
+
+    /// Play a [`ChannelTest`] on every channel of this device in turn and return its progress.
+    pub fn play_channel_test(&mut self) -> Option<Arc<ChannelTestProgress>> {
+        // Do nothing if not enabled.
+        if !self.enabled {
+            return None;
+        }
+
+        let test = ChannelTest::new(self.channels().unwrap_or(2));
+        let progress = test.progress();
+        let device_volume = self.chain.volume_db.clone();
+        let muted = self.chain.muted.clone();
+        let gain_limit = self.chain.gain_limit.clone();
+        let source = test
+            .amplify(1.0)
+            .periodic_access(Duration::from_millis(200), move |src| {
+                if muted.load(Ordering::SeqCst) {
+                    src.set_factor(0.0);
+                } else {
+                    src.set_factor(gain_limit.factor(device_volume.load()));
+                }
+            });
+
+        self.stream_mixer
+            .as_ref()
+            .expect("self.stream_mixer is None when self.enabled is true")
+            .add(Metered::new(source, self.meter.clone()));
+        Some(progress)
+    }
+
+    /// Set the volume in dB of everything played on this device.
+    pub fn set_volume_db(&self, volume_db: f32) {
+        self.chain.volume_db.store(volume_db)
+    }
+
+    pub fn volume_db(&self) -> f32 {
+        self.chain.volume_db.load()
+    }
+
+    /// Set the maximum gain in dB of every source on this device, or None for no limit.
+    pub fn set_max_gain(&self, max_gain: Option<f32>) {
+        self.chain.gain_limit.max_gain.store(max_gain);
+    }
+
+    /// Set the balance of everything played on this device, from -1 (left) to 1 (right).
+    pub fn set_pan(&self, pan: f32) {
+        self.chain.pan.store(pan);
+    }
+
+    /// Limit the mix of this device to `ceiling` in dBFS, or None to not limit it.
+    pub fn set_limiter(&self, ceiling: Option<f32>) {
+        self.chain.limiter.store(ceiling);
+    }
+
+    /// Return true if the maximum gain lowered the volume of a source since the last call.
+    pub fn take_limited(&self) -> bool {
+        self.chain.gain_limit.limited.swap(false, Ordering::SeqCst)
+    }
+
+    /// Return true if the mix played on this device went above full scale since the last call.
+    pub fn take_clipped(&self) -> bool {
+        self.chain.clipped.swap(false, Ordering::Relaxed)
+    }
+
+    /// Lower every source on this device by `attenuation` dB to protect the listener's hearing.
+    pub fn set_attenuation(&self, attenuation: f32) {
+        self.chain.gain_limit.attenuation.store(attenuation);
+    }
+
+    /// Return the RMS level in dBFS of everything played on this device since the last call.
+    pub fn take_level(&self) -> f32 {
+        self.meter.take_level()
+    }
+
+    /// Downmix every sound and the microphone played on this device to mono, including those
+    /// already playing. The channel test is not downmixed.
+    pub fn set_mono(&self, mono: bool) {
+        self.chain.channel_fixes.mono.store(mono, Ordering::SeqCst);
+    }
+
+    /// Swap the left and right channels of every sound and the microphone played on this
+    /// device, including those already playing. The channel test is not swapped.
+    pub fn set_swap_channels(&self, swap: bool) {
+        self.chain.channel_fixes.swap.store(swap, Ordering::SeqCst);
+    }
+
+    /// Invert the phase of the left or right channel of every sound and the microphone played
+    /// on this device, including those already playing. The channel test is not inverted.
+    pub fn set_inverted(&self, left: bool, right: bool) {
+        self.chain
+            .channel_fixes
+            .invert_left
+            .store(left, Ordering::SeqCst);
+        self.chain
+            .channel_fixes
+            .invert_right
+            .store(right, Ordering::SeqCst);
+    }
+
+    /// Set the delay added before every sound played on this device.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.chain.delay = delay;
+    }
+
+    /// Toggle muted.
+    pub fn toggle_muted(&self) {
+        self.chain.muted.fetch_xor(true, Ordering::AcqRel);
+    }
+
+    #[allow(dead_code)]
+    pub fn set_muted(&self, muted: bool) {
+        self.chain.muted.store(muted, Ordering::SeqCst);
+    }
+
+    /// Get muted.
+    #[inline]
+    pub fn muted(&self) -> bool {
+        self.chain.muted.load(Ordering::SeqCst)
+    }
+
+    /// Silence every sound and the microphone played on this device, including those already
+    /// playing, until the gate is opened again. Unlike muting, this is not shown or saved.
+    pub fn set_gated(&self, gated: bool) {
+        self.chain.gated.store(gated, Ordering::SeqCst);
+    }
+}
+
+/// Open and decode the audio file at `filename`.
+fn decode(filename: &str) -> Option<Decoder<BufReader<File>>> {
+    let file = match File::open(filename) {
+        Err(error) => {
+            log!(Error, "[Audio] Unable to read file {filename}: {error}.");
+            return None;
+        }
+        Ok(file) => BufReader::new(file),
+    };
+    match Decoder::new(file) {
+        Err(error) => {
+            log!(Error, "[Audio] Unable to decode file {filename}: {error}.");
+            None
+        }
+        Ok(source) => Some(source),
+    }
+}
+
+/// Fades a source as set with [`AudioControls::fade_in`] and [`AudioControls::fade_out`], and
+/// ends it once it has faded out. The gain follows a quarter sine, so that crossfading two
+/// sounds keeps the power constant.
+struct Faded<S> {
+    inner: S,
+    controls: Arc<AudioControls>,
+    /// The position in the fade, from 0 (silent) to 1 (full volume), and its amplitude factor.
+    level: f32,
+    factor: f32,
+    /// The samples left until the level is updated.
+    countdown: usize,
+}
+
+impl<S> Faded<S> {
+    /// The number of samples played between updates of the level.
+    const BLOCK: usize = 64;
+
+    fn new(inner: S, controls: Arc<AudioControls>) -> Self {
+        let level = controls.fade.start.load();
+        Self {
+            inner,
+            controls,
+            level,
+            factor: (level * FRAC_PI_2).sin(),
+            countdown: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Faded<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.countdown == 0 {
+            self.countdown = Self::BLOCK;
+            let target = self.controls.fade.target.load();
+            if self.level != target {
+                let samples_per_second =
+                    self.inner.sample_rate() as f32 * self.inner.channels() as f32;
+                let seconds = self.controls.fade.seconds.load();
+                let step = Self::BLOCK as f32 / (seconds * samples_per_second);
+                self.level = if !step.is_finite() || (target - self.level).abs() <= step {
+                    target
+                } else if target > self.level {
+                    self.level + step
+                } else {
+                    self.level - step
+                };
+                self.factor = (self.level * FRAC_PI_2).sin();
+            }
+            if self.level == 0.0 && target == 0.0 {
+                self.controls.stop(None);
+                return None;
+            }
+        }
+        self.countdown -= 1;
+        self.inner.next().map(|sample| sample * self.factor)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Faded<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Ends the inner [`Source`] if it panics instead of taking down the output stream with it.
+struct CatchPanic<S> {
+    inner: Option<S>,
+    filename: String,
+}
+
+impl<S> CatchPanic<S> {
+    fn new(inner: S, filename: String) -> Self {
+        Self {
+            inner: Some(inner),
+            filename,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for CatchPanic<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let inner = self.inner.as_mut()?;
+        match catch_panic(|| inner.next()) {
+            Ok(sample) => sample,
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Audio] Playback of {} panicked: {error}.",
+                    self.filename
+                );
+                // The inner source may be left in an invalid state, so it is never used again.
+                self.inner = None;
+                None
+            }
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Source for CatchPanic<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        match &self.inner {
+            Some(inner) => inner.current_frame_len(),
+            None => Some(0),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.as_ref().map_or(1, |inner| inner.channels())
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner
+            .as_ref()
+            .map_or(48000, |inner| inner.sample_rate())
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.as_ref().and_then(|inner| inner.total_duration())
+    }
+}
+
+/// Renders known inputs through the processing chain of an output and compares the levels of
+/// the output with the golden files in `tests/golden`, so that changes to the chain cannot
+/// silently change what is played. Run with `UPDATE_GOLDEN=1` to write the golden files after
+/// an intended change, and review their diff.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsp::DuckingSettings;
+    use std::f32::consts::TAU;
+    use std::{fmt::Write, fs, path::PathBuf};
+
+    const SAMPLE_RATE: u32 = 48000;
+    /// The length of the blocks the golden files hold the level of.
+    const BLOCK: Duration = Duration::from_millis(10);
+    /// The largest difference from a golden level, to allow for rounding.
+    const TOLERANCE_DB: f32 = 0.05;
+
+    /// A temporary file, removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    /// Write a 16 bit WAV file of interleaved `samples` to a new temporary file.
+    fn write_wav(name: &str, channels: u16, samples: &[f32]) -> TempFile {
+        let data_length = samples.len() as u32 * 2;
+        let mut wav = Vec::with_capacity(44 + data_length as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_length).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes()); // Format chunk length
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&channels.to_le_bytes());
+        wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+        wav.extend_from_slice(&(SAMPLE_RATE * channels as u32 * 2).to_le_bytes()); // Bytes per second
+        wav.extend_from_slice(&(channels * 2).to_le_bytes()); // Bytes per frame
+        wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_length.to_le_bytes());
+        for sample in samples {
+            let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        let path =
+            std::env::temp_dir().join(format!("soundboard-test-{}-{name}.wav", std::process::id()));
+        fs::write(&path, wav).unwrap();
+        TempFile(path)
+    }
+
+    /// Return `seconds` of a 1 kHz sine at `gain_db` dBFS, the same on each of `channels`.
+    fn sine(seconds: f32, gain_db: f32, channels: u16) -> Vec<f32> {
+        let amplitude = 10_f32.powf(gain_db / 20.0);
+        (0..(seconds * SAMPLE_RATE as f32) as u32)
+            .flat_map(|i| {
+                let sample = amplitude * (TAU * 1000.0 * i as f32 / SAMPLE_RATE as f32).sin();
+                std::iter::repeat_n(sample, channels as usize)
+            })
+            .collect()
+    }
+
+    fn sound(file: &TempFile, volume: f32) -> SoundStart<'_> {
+        SoundStart {
+            filename: file.path(),
+            loop_filename: None,
+            range: (Duration::ZERO, Duration::MAX),
+            speed: 1.0,
+            controls: Arc::new(AudioControls::new(true, false, volume)),
+            ducker: None,
+            priority: false,
+            pan: 0.0,
+        }
+    }
+
+    /// Play `sound` through `chain`, at most `limit` samples of it, and describe the output as
+    /// its format and the RMS level of each block of each channel.
+    fn render(chain: &OutputChain, sound: &SoundStart, limit: usize) -> String {
+        let mut source = chain.sound_source(sound).unwrap();
+        let samples: Vec<f32> = source.by_ref().take(limit).collect();
+        describe(&samples, source.channels(), source.sample_rate())
+    }
+
+    /// Return `frames` frames of the mix of `chain` on an output with `channels` playing `sound`.
+    fn render_output(
+        chain: &OutputChain,
+        sound: &SoundStart,
+        channels: u16,
+        frames: usize,
+    ) -> Vec<f32> {
+        let (controller, mix) = chain.output_mix(channels, SAMPLE_RATE);
+        controller.add(chain.sound_source(sound).unwrap());
+        mix.take(frames * channels as usize).collect()
+    }
+
+    /// Describe `samples` as their format and the RMS level of each block of each channel.
+    fn describe(samples: &[f32], channels: u16, sample_rate: u32) -> String {
+        let channels = channels as usize;
+        let block = (BLOCK.as_secs_f32() * sample_rate as f32) as usize * channels;
+
+        let mut description = format!(
+            "# RMS level in dBFS of each {} ms block, one column per channel.\n\
+            channels {channels}\nsample_rate {sample_rate}\nframes {}\n",
+            BLOCK.as_millis(),
+            samples.len() / channels
+        );
+        for block in samples.chunks(block) {
+            let levels: Vec<String> = (0..channels)
+                .map(|channel| {
+                    let samples: Vec<f32> = block
+                        .iter()
+                        .skip(channel)
+                        .step_by(channels)
+                        .copied()
+                        .collect();
+                    let energy = samples.iter().map(|sample| sample * sample).sum::<f32>()
+                        / samples.len().max(1) as f32;
+                    format!("{:.2}", (10.0 * energy.max(1e-10).log10()).max(-100.0))
+                })
+                .collect();
+            writeln!(description, "{}", levels.join(" ")).unwrap();
+        }
+        description
+    }
+
+    /// Compare `rendered` with the golden file `name`, or write it if `UPDATE_GOLDEN` is set.
+    fn assert_golden(name: &str, rendered: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{name}.txt"));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, rendered).unwrap();
+            return;
+        }
+        let golden = fs::read_to_string(&path).unwrap_or_else(|error| {
+            panic!(
+                "Unable to read {}: {error}. Run with UPDATE_GOLDEN=1 to write it.",
+                path.display()
+            )
+        });
+        let golden: Vec<&str> = golden.lines().collect();
+        let rendered: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            golden.len(),
+            rendered.len(),
+            "{name}: the output is {} lines long instead of {}",
+            rendered.len(),
+            golden.len()
+        );
+        for (number, (golden, rendered)) in golden.iter().zip(&rendered).enumerate() {
+            let levels = |line: &str| -> Option<Vec<f32>> {
+                line.split(' ').map(|level| level.parse().ok()).collect()
+            };
+            let matches = match (levels(golden), levels(rendered)) {
+                (Some(golden), Some(rendered)) => {
+                    golden.len() == rendered.len()
+                        && golden
+                            .iter()
+                            .zip(&rendered)
+                            .all(|(golden, rendered)| (golden - rendered).abs() <= TOLERANCE_DB)
+                }
+                _ => golden == rendered,
+            };
+            assert!(
+                matches,
+                "{name}, line {}: rendered \"{rendered}\" instead of \"{golden}\"",
+                number + 1
+            );
+        }
+    }
+
+    #[test]
+    fn unity_gain() {
+        let path = write_wav("unity_gain", 2, &sine(0.1, -6.0, 2));
+        let rendered = render(&OutputChain::default(), &sound(&path, 0.0), usize::MAX);
+        assert_golden("unity_gain", &rendered);
+    }
+
+    #[test]
+    fn volumes_add_up() {
+        let path = write_wav("volumes_add_up", 1, &sine(0.1, -6.0, 1));
+        let chain = OutputChain::default();
+        chain.volume_db.store(-3.0);
+        let rendered = render(&chain, &sound(&path, -6.0), usize::MAX);
+        assert_golden("volumes_add_up", &rendered);
+    }
+
+    #[test]
+    fn gain_limit_and_attenuation() {
+        let path = write_wav("gain_limit_and_attenuation", 1, &sine(0.1, -6.0, 1));
+        let chain = OutputChain::default();
+        chain.volume_db.store(6.0);
+        chain.gain_limit.max_gain.store(Some(0.0));
+        chain.gain_limit.attenuation.store(10.0);
+        let rendered = render(&chain, &sound(&path, 0.0), usize::MAX);
+        assert_golden("gain_limit_and_attenuation", &rendered);
+    }
+
+    #[test]
+    fn muted() {
+        let path = write_wav("muted", 1, &sine(0.05, -6.0, 1));
+        let chain = OutputChain::default();
+        chain.muted.store(true, Ordering::SeqCst);
+        let rendered = render(&chain, &sound(&path, 0.0), usize::MAX);
+        assert_golden("muted", &rendered);
+    }
+
+    #[test]
+    fn gated() {
+        let path = write_wav("gated", 1, &sine(0.05, -6.0, 1));
+        let chain = OutputChain::default();
+        chain.gated.store(true, Ordering::SeqCst);
+        let rendered = render(&chain, &sound(&path, 0.0), usize::MAX);
+        assert_golden("gated", &rendered);
+    }
+
+    #[test]
+    fn fade_in() {
+        let path = write_wav("fade_in", 1, &sine(0.1, -6.0, 1));
+        let sound = sound(&path, 0.0);
+        sound.controls.fade_in(Duration::from_millis(50));
+        let rendered = render(&OutputChain::default(), &sound, usize::MAX);
+        assert_golden("fade_in", &rendered);
+    }
+
+    #[test]
+    fn fade_out_ends_the_sound() {
+        let path = write_wav("fade_out", 1, &sine(0.5, -6.0, 1));
+        let sound = sound(&path, 0.0);
+        sound.controls.fade_out(Duration::from_millis(50));
+        // At most the whole file: the fade ends it after 50 ms.
+        let rendered = render(&OutputChain::default(), &sound, SAMPLE_RATE as usize / 2);
+        assert!(sound.controls.stopped());
+        assert_golden("fade_out", &rendered);
+    }
+
+    #[test]
+    fn stopping_fades_out() {
+        let path = write_wav("stop_fade", 1, &sine(0.5, -6.0, 1));
+        let sound = sound(&path, 0.0);
+        sound.controls.stop(Some(Duration::from_millis(50)));
+        assert!(!sound.controls.stopped());
+        // The fade ends the sound after 50 ms, long before the end of the file.
+        let source = OutputChain::default().sound_source(&sound).unwrap();
+        assert!(source.count() < SAMPLE_RATE as usize / 10);
+        assert!(sound.controls.stopped());
+
+        let paused = AudioControls::new(false, false, 0.0);
+        paused.stop(Some(Duration::from_millis(50)));
+        assert!(paused.stopped());
+    }
+
+    #[test]
+    fn audio_hosts_are_chosen() {
+        assert!(set_audio_host("No Such Host").is_err());
+        assert!(!set_audio_host("").unwrap());
+        let default = cpal::default_host().id();
+        assert!(audio_host_names().contains(&default.name()));
+        assert!(set_audio_host(default.name()).unwrap());
+        assert_eq!(audio_host().id(), default);
+        assert!(set_audio_host("").unwrap());
+    }
+
+    #[test]
+    fn trim() {
+        // Each 10 ms is 1 dB quieter than the last, so the levels show where playback starts.
+        let samples: Vec<f32> = sine(0.2, 0.0, 1)
+            .into_iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                sample * 10_f32.powf(-((i / (SAMPLE_RATE as usize / 100)) as f32) / 20.0)
+            })
+            .collect();
+        let path = write_wav("trim", 1, &samples);
+        let mut sound = sound(&path, 0.0);
+        sound.range = (Duration::from_millis(50), Duration::from_millis(100));
+        let rendered = render(&OutputChain::default(), &sound, usize::MAX);
+        assert_golden("trim", &rendered);
+    }
+
+    #[test]
+    fn speed() {
+        let path = write_wav("speed", 1, &sine(0.1, -6.0, 1));
+        let mut sound = sound(&path, 0.0);
+        sound.speed = 2.0;
+        let rendered = render(&OutputChain::default(), &sound, usize::MAX);
+        assert_golden("speed", &rendered);
+    }
+
+    #[test]
+    fn delay() {
+        let path = write_wav("delay", 1, &sine(0.05, -6.0, 1));
+        let chain = OutputChain {
+            delay: Duration::from_millis(30),
+            ..Default::default()
+        };
+        let rendered = render(&chain, &sound(&path, 0.0), usize::MAX);
+        assert_golden("delay", &rendered);
+    }
+
+    #[test]
+    fn loop_follows_intro() {
+        let intro = write_wav("loop_follows_intro", 1, &sine(0.05, -6.0, 1));
+        let part = write_wav("loop_follows_intro_loop", 1, &sine(0.02, -20.0, 1));
+        let mut sound = sound(&intro, 0.0);
+        sound.loop_filename = Some(part.path());
+        // The loop never ends, so only 100 ms are rendered.
+        let rendered = render(&OutputChain::default(), &sound, SAMPLE_RATE as usize / 10);
+        assert_golden("loop_follows_intro", &rendered);
+    }
+
+    #[test]
+    fn channel_fixes() {
+        // A left channel at -6 dBFS and a silent right channel.
+        let samples: Vec<f32> = sine(0.05, -6.0, 1)
+            .into_iter()
+            .flat_map(|sample| [sample, 0.0])
+            .collect();
+        let path = write_wav("channel_fixes", 2, &samples);
+        let frames = samples.len() / 2;
+        let chain = OutputChain::default();
+        chain.channel_fixes.swap.store(true, Ordering::SeqCst);
+        let swapped = render_output(&chain, &sound(&path, 0.0), 2, frames);
+        chain.channel_fixes.swap.store(false, Ordering::SeqCst);
+        chain.channel_fixes.mono.store(true, Ordering::SeqCst);
+        let mono = render_output(&chain, &sound(&path, 0.0), 2, frames);
+        assert_golden("channel_fixes_swap", &describe(&swapped, 2, SAMPLE_RATE));
+        assert_golden("channel_fixes_mono", &describe(&mono, 2, SAMPLE_RATE));
+    }
+
+    #[test]
+    fn panned_mono_file() {
+        let path = write_wav("panned", 1, &sine(0.05, -6.0, 1));
+        let mut sound = sound(&path, 0.0);
+        sound.pan = 0.5;
+        let rendered = render(&OutputChain::default(), &sound, usize::MAX);
+        assert_golden("panned", &rendered);
+    }
+
+    #[test]
+    fn mono_file_on_stereo_output_is_inverted() {
+        let path = write_wav("mono_on_stereo", 1, &sine(0.05, -6.0, 1));
+        let chain = OutputChain::default();
+        chain
+            .channel_fixes
+            .invert_right
+            .store(true, Ordering::SeqCst);
+        let samples = render_output(&chain, &sound(&path, 0.0), 2, 2400);
+        assert!(samples.iter().any(|sample| *sample != 0.0));
+        for frame in samples.chunks(2) {
+            assert_eq!(frame[0], -frame[1]);
+        }
+    }
+
+    #[test]
+    fn ducking() {
+        let path = write_wav("ducking", 1, &sine(0.2, -6.0, 1));
+        let ducker = Arc::new(Ducker::default());
+        ducker.set_settings(DuckingSettings {
+            depth_db: 12.0,
+            attack: Duration::from_millis(50),
+            release: Duration::from_millis(500),
+            talk_over_db: 0.0,
+        });
+        let _hold = DuckingHold::new(ducker.clone());
+        let mut sound = sound(&path, 0.0);
+        sound.ducker = Some(ducker);
+        let rendered = render(&OutputChain::default(), &sound, usize::MAX);
+        assert_golden("ducking", &rendered);
+    }
+
+    #[test]
+    fn priority_sound_ducks_while_it_plays() {
+        let path = write_wav("priority", 1, &sine(0.05, -6.0, 1));
+        let ducker = Arc::new(Ducker::default());
+        let mut priority = sound(&path, 0.0);
+        priority.ducker = Some(ducker.clone());
+        priority.priority = true;
+        let chain = OutputChain::default();
+        let source = chain.sound_source(&priority).unwrap();
+        assert!(ducker.active());
+        drop(source);
+        assert!(!ducker.active());
+
+        // Released while paused.
+        let mut source = chain.sound_source(&priority).unwrap();
+        priority.controls.playing.store(false, Ordering::SeqCst);
+        source.next();
+        assert!(!ducker.active());
+    }
+
+    #[test]
+    fn gain_limit_factor() {
+        let gain_limit = GainLimit::default();
+        assert_eq!(gain_limit.factor(0.0), 1.0);
+        assert!((gain_limit.factor(-20.0) - 0.1).abs() < 1e-6);
+        assert!((gain_limit.factor(20.0 * 0.5_f32.log10()) - 0.5).abs() < 1e-6);
+        assert!(!gain_limit.limited.load(Ordering::SeqCst));
+
+        gain_limit.max_gain.store(Some(-6.0));
+        assert_eq!(gain_limit.factor(-6.0), gain_limit.factor(-12.0 + 6.0));
+        assert!(!gain_limit.limited.load(Ordering::SeqCst));
+        assert!((gain_limit.factor(10.0) - 10_f32.powf(-6.0 / 20.0)).abs() < 1e-6);
+        assert!(gain_limit.limited.load(Ordering::SeqCst));
+
+        // The attenuation is taken off before the limit.
+        gain_limit.attenuation.store(10.0);
+        assert!((gain_limit.factor(0.0) - 10_f32.powf(-10.0 / 20.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cooldowns_start_with_the_sound() {
+        let mut playback = SoundPlayback::default();
+        let cooldown = Duration::from_secs(10);
+        assert_eq!(playback.cooldown_remaining(cooldown), None);
+        let controls = Arc::new(AudioControls::new(true, false, 0.0));
+        playback.add(controls, Duration::ZERO, 1.0, 1);
+        assert!(playback
+            .cooldown_remaining(cooldown)
+            .is_some_and(|remaining| remaining <= cooldown));
+        assert_eq!(playback.cooldown_remaining(Duration::ZERO), None);
+    }
+}
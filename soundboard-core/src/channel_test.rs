@@ -0,0 +1,166 @@
+use rodio::Source;
+use std::{
+    f32::consts::TAU,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// The usual channel names in WAVE order. Some drivers order surround channels differently.
+const CHANNEL_NAMES: [&str; 8] = [
+    "Front Left",
+    "Front Right",
+    "Front Center",
+    "LFE",
+    "Back Left",
+    "Back Right",
+    "Side Left",
+    "Side Right",
+];
+
+/// Return a description of channel `channel` (from 0) of a device with `channels` channels.
+pub fn channel_name(channel: usize, channels: usize) -> String {
+    match (channels, CHANNEL_NAMES.get(channel)) {
+        (1, _) => "Channel 1 (Mono)".to_string(),
+        (_, Some(name)) => format!("Channel {} ({name})", channel + 1),
+        (_, None) => format!("Channel {}", channel + 1),
+    }
+}
+
+/// The progress of a [`ChannelTest`], shared with the UI.
+pub struct ChannelTestProgress {
+    channel: AtomicUsize,
+    channels: usize,
+    stopped: AtomicBool,
+}
+
+impl ChannelTestProgress {
+    /// Return the channel being tested, or None once the test is over.
+    pub fn channel(&self) -> Option<usize> {
+        let channel = self.channel.load(Ordering::SeqCst);
+        if channel < self.channels && !self.stopped.load(Ordering::SeqCst) {
+            Some(channel)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Plays a sweep followed by one beep per channel number on each channel of a device in turn,
+/// silent on every other channel.
+pub struct ChannelTest {
+    channels: u16,
+    sample_rate: u32,
+    /// The index of the next sample in the current channel's part, counting every channel.
+    sample: usize,
+    progress: Arc<ChannelTestProgress>,
+}
+
+impl ChannelTest {
+    const SAMPLE_RATE: u32 = 48000;
+    const SWEEP: f32 = 0.8;
+    const BEEPS_START: f32 = 1.0;
+    const BEEP_INTERVAL: f32 = 0.16;
+    const BEEP: f32 = 0.08;
+    const PAUSE: f32 = 0.5;
+
+    pub fn new(channels: u16) -> Self {
+        Self {
+            channels: channels.max(1),
+            sample_rate: Self::SAMPLE_RATE,
+            sample: 0,
+            progress: Arc::new(ChannelTestProgress {
+                channel: AtomicUsize::new(0),
+                channels: channels.max(1) as usize,
+                stopped: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Return the progress of this test.
+    pub fn progress(&self) -> Arc<ChannelTestProgress> {
+        self.progress.clone()
+    }
+
+    /// Return the length of the part of the test played on `channel`, in seconds.
+    fn part_length(channel: usize) -> f32 {
+        Self::BEEPS_START + Self::BEEP_INTERVAL * (channel + 1) as f32 + Self::PAUSE
+    }
+
+    /// Return the test signal of `channel` at `time` seconds into its part.
+    fn signal(channel: usize, time: f32) -> f32 {
+        if time < Self::SWEEP {
+            // An exponential sweep from 200 Hz to 4000 Hz.
+            let k = (4000.0f32 / 200.0).ln() / Self::SWEEP;
+            let fade = (time / 0.01).min((Self::SWEEP - time) / 0.01).min(1.0);
+            return 0.3 * fade * (TAU * 200.0 * ((k * time).exp() - 1.0) / k).sin();
+        }
+
+        let time = time - Self::BEEPS_START;
+        if time < 0.0 {
+            return 0.0;
+        }
+        let beep = (time / Self::BEEP_INTERVAL) as usize;
+        let beep_time = time - beep as f32 * Self::BEEP_INTERVAL;
+        if beep <= channel && beep_time < Self::BEEP {
+            0.3 * (TAU * 1000.0 * beep_time).sin()
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Iterator for ChannelTest {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let channel = self.progress.channel()?;
+        let channels = self.channels as usize;
+        let frame = self.sample / channels;
+        let time = frame as f32 / self.sample_rate as f32;
+        let output_channel = self.sample % channels;
+        let value = if output_channel == channel {
+            Self::signal(channel, time)
+        } else {
+            0.0
+        };
+
+        // Move on to the next channel after the last sample of a frame.
+        if output_channel == channels - 1 && time >= Self::part_length(channel) {
+            self.sample = 0;
+            self.progress.channel.store(channel + 1, Ordering::SeqCst);
+        } else {
+            self.sample += 1;
+        }
+        Some(value)
+    }
+}
+
+impl Source for ChannelTest {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
@@ -0,0 +1,331 @@
+use crate::audio::{audio_host, SYSTEM_DEFAULT_OUTPUT};
+use crate::config::{list_profiles, read_config, Config, Input, KeyButton, CONFIG_VERSION};
+use rodio::cpal::traits::HostTrait;
+use rodio::{Decoder, DeviceTrait};
+use serde::Serialize;
+use std::{collections::BTreeMap, fs::File, io::BufReader};
+
+/// How serious a problem found by a check is.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The soundboard will not work as configured, e.g. a sound file is missing.
+    Error,
+    /// Probably a mistake, e.g. a key bound to two actions.
+    Warning,
+}
+
+/// What a problem found by a check is about.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    Config,
+    File,
+    Device,
+    Binding,
+}
+
+/// A problem found by a check.
+#[derive(Serialize, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(kind: IssueKind, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            kind,
+            message,
+        }
+    }
+
+    fn warning(kind: IssueKind, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            kind,
+            message,
+        }
+    }
+}
+
+/// The problems found in a profile.
+#[derive(Serialize)]
+pub struct ProfileReport {
+    pub profile: String,
+    pub issues: Vec<Issue>,
+}
+
+/// The result of checking the profiles, as printed by `soundboard --check`.
+#[derive(Serialize)]
+pub struct CheckReport {
+    /// True if no errors were found. Warnings do not count.
+    pub ok: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    /// The problems not found in a single profile, e.g. listing the audio devices failing.
+    pub issues: Vec<Issue>,
+    pub profiles: Vec<ProfileReport>,
+}
+
+/// Check `profile`, or every profile if None, without changing any file: whether it can be
+/// loaded, its sound files can be decoded, its devices are connected and its key binds clash.
+pub fn check_profiles(profile: Option<&str>) -> CheckReport {
+    let mut issues = Vec::new();
+    let host = audio_host();
+    let mut device_names = |kind, devices: Result<Vec<rodio::Device>, String>| match devices {
+        Ok(devices) => devices
+            .iter()
+            .filter_map(|device| device.name().ok())
+            .collect(),
+        Err(error) => {
+            issues.push(Issue::error(
+                IssueKind::Device,
+                format!("Unable to list the {kind} devices: {error}."),
+            ));
+            Vec::new()
+        }
+    };
+    let mut outputs: Vec<String> = device_names(
+        "output",
+        host.output_devices()
+            .map(Iterator::collect)
+            .map_err(|error| error.to_string()),
+    );
+    let inputs: Vec<String> = device_names(
+        "input",
+        host.input_devices()
+            .map(Iterator::collect)
+            .map_err(|error| error.to_string()),
+    );
+    if host.default_output_device().is_some() {
+        outputs.push(SYSTEM_DEFAULT_OUTPUT.to_string());
+    }
+
+    let profiles = match profile {
+        Some(profile) => vec![profile.to_string()],
+        None => list_profiles().unwrap_or_else(|error| {
+            issues.push(Issue::error(IssueKind::Config, error));
+            Vec::new()
+        }),
+    };
+    if profiles.is_empty() && issues.is_empty() {
+        issues.push(Issue::error(
+            IssueKind::Config,
+            "There are no profiles.".to_string(),
+        ));
+    }
+    let profiles: Vec<ProfileReport> = profiles
+        .into_iter()
+        .map(|profile| {
+            let issues = match read_config(&profile) {
+                Err(error) => vec![Issue::error(IssueKind::Config, error)],
+                Ok((config, version)) => {
+                    let mut issues = check_config(&config, &outputs, &inputs);
+                    if version < CONFIG_VERSION {
+                        issues.insert(
+                            0,
+                            Issue::warning(
+                                IssueKind::Config,
+                                format!(
+                                    "The profile is version {version} and will be upgraded to \
+                                    {CONFIG_VERSION} when it is loaded."
+                                ),
+                            ),
+                        );
+                    }
+                    issues
+                }
+            };
+            ProfileReport { profile, issues }
+        })
+        .collect();
+
+    let all = || {
+        issues
+            .iter()
+            .chain(profiles.iter().flat_map(|report| &report.issues))
+    };
+    let errors = all()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    let warnings = all().count() - errors;
+    CheckReport {
+        ok: errors == 0,
+        errors,
+        warnings,
+        issues,
+        profiles,
+    }
+}
+
+/// Check the sound files, devices and key binds of `config`. `outputs` and `inputs` are the
+/// names of the connected output and input devices.
+pub fn check_config(config: &Config, outputs: &[String], inputs: &[String]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    // Files. Sounds out of season are checked too, since they come back.
+    for sound in config.sounds.iter().filter(|sound| !sound.archived) {
+        if sound.path.is_empty() {
+            issues.push(Issue::error(
+                IssueKind::File,
+                format!("Sound \"{}\" has no file.", sound.name),
+            ));
+        }
+        for path in [&sound.path, &sound.loop_path] {
+            if let Some(error) = Some(path)
+                .filter(|path| !path.is_empty())
+                .and_then(decode_error)
+            {
+                issues.push(Issue::error(
+                    IssueKind::File,
+                    format!("Sound \"{}\": {error}", sound.name),
+                ));
+            }
+        }
+    }
+
+    // Devices
+    if config.outputs.is_empty() {
+        issues.push(Issue::warning(
+            IssueKind::Device,
+            "No output devices are selected.".to_string(),
+        ));
+    }
+    for name in config.outputs.keys().filter(|name| !outputs.contains(name)) {
+        issues.push(Issue::error(
+            IssueKind::Device,
+            format!("Output device \"{name}\" is not connected."),
+        ));
+    }
+    if !config.monitor.is_empty() && !outputs.contains(&config.monitor) {
+        issues.push(Issue::error(
+            IssueKind::Device,
+            format!("Monitor output \"{}\" is not connected.", config.monitor),
+        ));
+    }
+    let microphone = &config.microphone.device;
+    if !microphone.is_empty() && !inputs.contains(microphone) {
+        issues.push(Issue::error(
+            IssueKind::Device,
+            format!("Microphone \"{microphone}\" is not connected."),
+        ));
+    }
+    let groups = config.group_names();
+    for sound in config.sounds.iter().filter(|sound| !sound.archived) {
+        for group in sound.groups.iter().filter(|group| !groups.contains(group)) {
+            issues.push(Issue::warning(
+                IssueKind::Device,
+                format!(
+                    "Sound \"{}\" plays on group \"{group}\", which has no outputs.",
+                    sound.name
+                ),
+            ));
+        }
+    }
+
+    // Key binds
+    let mut bindings: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, button) in named_key_buttons(config) {
+        if button.input != Input::default() {
+            bindings.entry(button.to_string()).or_default().push(name);
+        }
+    }
+    for (binding, names) in bindings.into_iter().filter(|(_, names)| names.len() > 1) {
+        issues.push(Issue::warning(
+            IssueKind::Binding,
+            format!("{binding} is bound to {}.", names.join(", ")),
+        ));
+    }
+    issues
+}
+
+/// Return why the audio file at `path` cannot be decoded, if it cannot.
+fn decode_error(path: &String) -> Option<String> {
+    let file = match File::open(path) {
+        Err(error) => return Some(format!("Unable to read file {path}: {error}.")),
+        Ok(file) => file,
+    };
+    Decoder::new(BufReader::new(file))
+        .err()
+        .map(|error| format!("Unable to decode file {path}: {error}."))
+}
+
+/// Return the key bind buttons of [`Config::key_buttons`], each with what it is bound to.
+fn named_key_buttons(config: &Config) -> Vec<(String, &KeyButton)> {
+    let mut buttons = Vec::new();
+    for sound in config.sounds.iter().filter(|sound| !sound.hidden()) {
+        buttons.push((format!("sound \"{}\"", sound.name), &sound.key));
+    }
+    for (name, output) in &config.outputs {
+        buttons.push((format!("the mute of output \"{name}\""), &output.mute));
+    }
+    for (name, group) in &config.output_groups {
+        buttons.push((format!("the mute of group \"{name}\""), &group.mute));
+    }
+    buttons.push(("the microphone mute".to_string(), &config.microphone.mute));
+    for (action, binding) in config.shortcuts.bindings() {
+        buttons.push((format!("the {} shortcut", action.label()), binding));
+    }
+    for bank in &config.banks {
+        buttons.push((
+            format!("the stop key of bank \"{}\"", bank.name),
+            &bank.stop_key,
+        ));
+        buttons.push((
+            format!("the pause key of bank \"{}\"", bank.name),
+            &bank.pause_key,
+        ));
+    }
+    buttons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OutputConfig, SoundConfig};
+
+    #[test]
+    fn problems_are_found() {
+        let mut config: Config = toml::from_str(include_str!("default_config.toml")).unwrap();
+        config.sounds.clear();
+        let key: KeyButton = toml::Value::String("KEY_F5".to_string())
+            .try_into()
+            .unwrap();
+        for name in ["Airhorn", "Applause"] {
+            config.sounds.push(SoundConfig {
+                name: name.to_string(),
+                path: "/nonexistent/sound.wav".to_string(),
+                key: key.clone(),
+                ..SoundConfig::default()
+            });
+        }
+        config
+            .outputs
+            .insert("Speakers".to_string(), OutputConfig::default());
+        config.microphone.device = "Headset".to_string();
+
+        let issues = check_config(&config, &["Speakers".to_string()], &[]);
+        let found = |severity, kind| {
+            issues
+                .iter()
+                .filter(|issue| issue.severity == severity && issue.kind == kind)
+                .count()
+        };
+        assert_eq!(found(Severity::Error, IssueKind::File), 2);
+        assert_eq!(found(Severity::Error, IssueKind::Device), 1);
+        assert_eq!(found(Severity::Warning, IssueKind::Binding), 1);
+        assert!(issues
+            .iter()
+            .any(|issue| issue.message
+                == "KEY_F5 is bound to sound \"Airhorn\", sound \"Applause\"."));
+
+        config.outputs.clear();
+        let issues = check_config(&config, &[], &[]);
+        assert!(issues
+            .iter()
+            .any(|issue| issue.message == "No output devices are selected."));
+    }
+}
@@ -0,0 +1,2799 @@
+use crate::event::{Key, RelativeAxis};
+use crate::gamepad::GamepadButton;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The modifier keys that can be held for a key bind. The right modifier keys count as the
+/// left ones.
+pub const MODIFIER_KEYS: [Key; 4] = [
+    Key::KEY_LEFTCTRL,
+    Key::KEY_LEFTSHIFT,
+    Key::KEY_LEFTALT,
+    Key::KEY_LEFTMETA,
+];
+
+/// Return the entry of [`MODIFIER_KEYS`] that `key` counts as, if it is a modifier key.
+pub fn modifier_key(key: Key) -> Option<Key> {
+    match key {
+        Key::KEY_LEFTCTRL | Key::KEY_RIGHTCTRL => Some(Key::KEY_LEFTCTRL),
+        Key::KEY_LEFTSHIFT | Key::KEY_RIGHTSHIFT => Some(Key::KEY_LEFTSHIFT),
+        Key::KEY_LEFTALT | Key::KEY_RIGHTALT => Some(Key::KEY_LEFTALT),
+        Key::KEY_LEFTMETA | Key::KEY_RIGHTMETA => Some(Key::KEY_LEFTMETA),
+        _ => None,
+    }
+}
+
+/// A direction of a scroll wheel, bound like a key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Wheel {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Wheel {
+    pub const ALL: [Wheel; 4] = [Wheel::Up, Wheel::Down, Wheel::Left, Wheel::Right];
+
+    /// The name stored for a key bind, e.g. "WHEEL_UP".
+    pub fn name(self) -> &'static str {
+        match self {
+            Wheel::Up => "WHEEL_UP",
+            Wheel::Down => "WHEEL_DOWN",
+            Wheel::Left => "WHEEL_LEFT",
+            Wheel::Right => "WHEEL_RIGHT",
+        }
+    }
+
+    /// Return the direction of a relative axis event moving `axis` by `value`, if it is a scroll
+    /// wheel step. The high resolution axes are ignored, since mice report every step on both.
+    pub fn from_step(axis: RelativeAxis, value: i32) -> Option<Self> {
+        match (axis, value.signum()) {
+            (RelativeAxis::REL_WHEEL, 1) => Some(Wheel::Up),
+            (RelativeAxis::REL_WHEEL, -1) => Some(Wheel::Down),
+            (RelativeAxis::REL_HWHEEL, 1) => Some(Wheel::Right),
+            (RelativeAxis::REL_HWHEEL, -1) => Some(Wheel::Left),
+            _ => None,
+        }
+    }
+}
+
+/// What triggers a key bind: a key, which includes mouse buttons, a scroll wheel step or a
+/// gamepad button.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Input {
+    Key(Key),
+    Wheel(Wheel),
+    Gamepad(GamepadButton),
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Input::Key(Key::KEY_RESERVED)
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Input::Key(key) => write!(f, "{key}"),
+            Input::Wheel(wheel) => f.write_str(wheel.name()),
+            Input::Gamepad(button) => f.write_str(button.name()),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Some(wheel) = Wheel::ALL.into_iter().find(|wheel| wheel.name() == name) {
+            return Ok(Input::Wheel(wheel));
+        }
+        if let Some(button) = GamepadButton::ALL
+            .into_iter()
+            .find(|button| button.name() == name)
+        {
+            return Ok(Input::Gamepad(button));
+        }
+        name.parse()
+            .map(Input::Key)
+            .map_err(|_| format!("unknown key {name}"))
+    }
+}
+
+/// How a key bind is triggered. Double taps and long presses let a key trigger up to three
+/// binds.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum KeyTrigger {
+    /// A single press and release.
+    #[default]
+    Press,
+    /// Two presses within [`TriggerConfig::double_tap_ms`].
+    DoubleTap,
+    /// A press held for at least [`TriggerConfig::long_press_ms`].
+    LongPress,
+}
+
+impl KeyTrigger {
+    pub const ALL: [KeyTrigger; 3] = [
+        KeyTrigger::Press,
+        KeyTrigger::DoubleTap,
+        KeyTrigger::LongPress,
+    ];
+
+    /// The name stored after a key bind, e.g. "KEY_F5:DOUBLE_TAP". Single presses have none.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            KeyTrigger::Press => None,
+            KeyTrigger::DoubleTap => Some("DOUBLE_TAP"),
+            KeyTrigger::LongPress => Some("LONG_PRESS"),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyTrigger::Press => "Single Press",
+            KeyTrigger::DoubleTap => "Double Tap",
+            KeyTrigger::LongPress => "Long Press",
+        }
+    }
+}
+
+/// A released key or a scroll wheel step, the modifier keys held at the time, and how it was
+/// triggered.
+#[derive(Clone)]
+pub struct KeyRelease {
+    pub input: Input,
+    /// In the order of [`MODIFIER_KEYS`].
+    pub modifiers: Vec<Key>,
+    pub trigger: KeyTrigger,
+}
+
+impl KeyRelease {
+    /// `held` are the keys held down, not including a released key.
+    pub fn new(input: Input, held: &HashSet<Key>) -> Self {
+        let held: Vec<Key> = held.iter().filter_map(|&key| modifier_key(key)).collect();
+        Self {
+            input,
+            modifiers: MODIFIER_KEYS
+                .into_iter()
+                .filter(|modifier| held.contains(modifier))
+                .collect(),
+            trigger: KeyTrigger::Press,
+        }
+    }
+}
+
+/// A key, mouse button, scroll wheel direction or gamepad button bound to a sound or action,
+/// optionally with modifier keys that must be held, e.g. Ctrl+Shift+F5. It is stored as the
+/// names joined by "+", e.g. "KEY_LEFTCTRL+KEY_LEFTSHIFT+KEY_F5", or the name alone without
+/// modifiers. A double tap or long press trigger is stored after a ":", e.g.
+/// "KEY_F5:LONG_PRESS".
+#[derive(Clone)]
+pub struct KeyButton {
+    pub input: Input,
+    /// In the order of [`MODIFIER_KEYS`].
+    pub modifiers: Vec<Key>,
+    pub trigger: KeyTrigger,
+    /// Set while the button binding the key is waiting for a key to be released.
+    pub listening: bool,
+}
+
+impl fmt::Display for KeyButton {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{modifier}+")?;
+        }
+        write!(f, "{}", self.input)?;
+        if let Some(trigger) = self.trigger.name() {
+            write!(f, ":{trigger}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for KeyButton {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyButton {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        let (name, trigger) = match name.rsplit_once(':') {
+            None => (name.as_str(), KeyTrigger::Press),
+            Some((name, trigger_name)) => match KeyTrigger::ALL
+                .into_iter()
+                .find(|trigger| trigger.name() == Some(trigger_name))
+            {
+                Some(trigger) => (name, trigger),
+                None => {
+                    return Err(serde::de::Error::custom(format!(
+                        "unknown trigger {trigger_name}"
+                    )))
+                }
+            },
+        };
+        let (modifier_names, input) = name.rsplit_once('+').unwrap_or(("", name));
+        let mut held = HashSet::new();
+        for modifier in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            match modifier.parse::<Key>() {
+                Ok(key) if modifier_key(key).is_some() => held.insert(key),
+                _ => {
+                    return Err(serde::de::Error::custom(format!(
+                        "{modifier} is not a modifier key"
+                    )))
+                }
+            };
+        }
+        let input = input.parse().map_err(serde::de::Error::custom)?;
+        let release = KeyRelease::new(input, &held);
+        let mut button = Self::default();
+        button.bind(&release);
+        button.trigger = trigger;
+        Ok(button)
+    }
+}
+
+impl Default for KeyButton {
+    fn default() -> Self {
+        Self::new(Input::default())
+    }
+}
+
+impl KeyButton {
+    pub fn new(input: Input) -> Self {
+        Self {
+            input,
+            modifiers: Vec::new(),
+            trigger: KeyTrigger::Press,
+            listening: false,
+        }
+    }
+
+    /// Bind the input and modifier keys of `release`, keeping the trigger.
+    pub fn bind(&mut self, release: &KeyRelease) {
+        self.input = release.input;
+        self.modifiers = release.modifiers.clone();
+    }
+
+    /// Check if `release` triggers this key bind. The modifier keys held must be exactly the bound
+    /// ones, so F5 and Ctrl+F5 can be bound to different sounds.
+    pub fn matches(&self, release: &KeyRelease) -> bool {
+        self.input != Input::default()
+            && self.input == release.input
+            && self.modifiers == release.modifiers
+            && self.trigger == release.trigger
+    }
+}
+
+/// Holds configuration values read from a profile's toml file.
+#[derive(Serialize, Deserialize)]
+pub struct Config {
+    /// The version of the configuration format, see [`CONFIG_VERSION`].
+    #[serde(default)]
+    pub version: u32,
+    pub server_address: String,
+    pub api_key: String,
+    /// The names of the remote input server's devices to receive events from, as reported by
+    /// evdev. Empty receives events from every device.
+    #[serde(default)]
+    pub remote_devices: Vec<String>,
+    /// The global volume in dB, added to every sound.
+    pub volume_db: f32,
+    /// How the volume sliders show their values. Volumes are stored in dB either way.
+    #[serde(default)]
+    pub volume_scale: VolumeScale,
+    pub outputs: HashMap<String, OutputConfig>,
+    /// The settings of the groups of [`OutputConfig::group`], by name. A group without settings
+    /// has the default ones.
+    #[serde(default)]
+    pub output_groups: BTreeMap<String, OutputGroupConfig>,
+    #[serde(serialize_with = "serialize_profile_items")]
+    pub sounds: Vec<SoundConfig>,
+    pub shortcuts: ShortcutsConfig,
+    #[serde(default, serialize_with = "serialize_profile_items")]
+    pub cues: Vec<CueConfig>,
+    #[serde(default)]
+    pub microphone: MicrophoneConfig,
+    #[serde(default)]
+    pub integrations: IntegrationsConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Check the releases feed for a new version at startup.
+    #[serde(default)]
+    pub check_for_updates: bool,
+    /// Transcode sounds added to the soundboard into the media library, see
+    /// [`get_library_directory`].
+    #[serde(default)]
+    pub import_to_library: bool,
+    /// The address of the HTTP API of the soundboard last compared with in the Sync window.
+    #[serde(default)]
+    pub sync_address: String,
+    #[serde(default, serialize_with = "serialize_profile_items")]
+    pub banks: Vec<BankConfig>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    /// The name of the output device used for monitoring, e.g. headphones.
+    #[serde(default)]
+    pub monitor: String,
+    /// Read the gamepads connected to this computer, so their buttons can be bound.
+    #[serde(default)]
+    pub gamepads: bool,
+    #[serde(default)]
+    pub hearing_protection: HearingProtectionConfig,
+    #[serde(default)]
+    pub big_screen: BigScreenConfig,
+    /// Show the sounds as a grid of pads in the main window instead of the sounds table, e.g.
+    /// on a touchscreen.
+    #[serde(default)]
+    pub pad_view: bool,
+    /// Hide the widgets that change the sounds, key binds and settings, leaving the play state
+    /// and the volumes, so nothing is rebound or deleted by accident while live.
+    #[serde(default)]
+    pub locked: bool,
+    /// The code of the language of the user interface, such as "de". Empty for English.
+    #[serde(default)]
+    pub language: String,
+    #[serde(default)]
+    pub triggers: TriggerConfig,
+    #[serde(default)]
+    pub ducking: DuckingConfig,
+    #[serde(default)]
+    pub background: BackgroundConfig,
+    /// The settings of the groups of [`SoundConfig::choke_group`], by name. A group without
+    /// settings has the default ones.
+    #[serde(default)]
+    pub choke_groups: BTreeMap<String, ChokeGroupConfig>,
+    /// The settings of the groups of [`SoundConfig::random_group`], by name. A group without
+    /// settings has the default ones.
+    #[serde(default)]
+    pub random_groups: BTreeMap<String, RandomGroupConfig>,
+    /// The settings of the sequences of [`SoundConfig::sequence`], by name. A sequence without
+    /// settings has the default ones.
+    #[serde(default)]
+    pub sequences: BTreeMap<String, SequenceConfig>,
+    /// How long stopping every sound, e.g. with the Stop shortcut, fades them out. 0 cuts them.
+    #[serde(default)]
+    pub stop_fade_ms: u64,
+    /// The name of the audio host devices are opened on, e.g. "ALSA" or "JACK", or empty for
+    /// the default host of the system.
+    #[serde(default)]
+    pub audio_host: String,
+    #[serde(default)]
+    pub loudness: LoudnessConfig,
+    /// What the configuration fragments merged in, so it is not saved to the profile.
+    #[serde(skip)]
+    pub fragment_changes: FragmentChanges,
+}
+
+/// The values merged into a profile from configuration fragments and the values of the profile
+/// before they were merged.
+#[derive(Default)]
+pub struct FragmentChanges {
+    merged: toml::Table,
+    profile: toml::Table,
+}
+
+impl Config {
+    /// Serialize the configuration as it is saved to the profile, without the values merged in
+    /// from configuration fragments. Values changed since they were merged are kept.
+    pub fn to_profile_string(&self) -> Result<String, String> {
+        let serialize_error =
+            |error: toml::ser::Error| format!("Unable to serialize configuration file: {error}.");
+        let mut config = match toml::Value::try_from(self).map_err(serialize_error)? {
+            toml::Value::Table(config) => config,
+            _ => return Err("Unable to serialize configuration file: not a table.".to_string()),
+        };
+        unmerge_value(
+            &mut config,
+            &self.fragment_changes.merged,
+            &self.fragment_changes.profile,
+        );
+        toml::to_string_pretty(&config).map_err(serialize_error)
+    }
+
+    /// Replace values that cannot be played, such as a speed out of [`SPEED_RANGE`] or an
+    /// output delay over [`MAX_OUTPUT_DELAY_MS`], which may have been written by hand.
+    fn sanitize(&mut self) {
+        for sound in &mut self.sounds {
+            let speed = if sound.speed.is_finite() {
+                sound.speed.clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end())
+            } else {
+                default_speed()
+            };
+            if speed != sound.speed {
+                log!(
+                    Warning,
+                    "[Configuration Loader] {} has an invalid speed {}. Using {speed}.",
+                    sound.name,
+                    sound.speed
+                );
+                sound.speed = speed;
+            }
+        }
+        for (name, output) in &mut self.outputs {
+            if output.delay_ms > MAX_OUTPUT_DELAY_MS {
+                log!(
+                    Warning,
+                    "[Configuration Loader] Output {name} has a delay of {} ms. Using {MAX_OUTPUT_DELAY_MS} ms.",
+                    output.delay_ms
+                );
+                output.delay_ms = MAX_OUTPUT_DELAY_MS;
+            }
+        }
+    }
+
+    /// Return the names of the groups the outputs are in, sorted.
+    pub fn group_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .outputs
+            .values()
+            .filter(|output| !output.group.is_empty())
+            .map(|output| output.group.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Return the names of the choke groups the sounds are in, sorted.
+    pub fn choke_group_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .sounds
+            .iter()
+            .filter(|sound| !sound.choke_group.is_empty())
+            .map(|sound| sound.choke_group.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Return the names of the random groups the sounds are in, sorted.
+    pub fn random_group_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .sounds
+            .iter()
+            .filter(|sound| !sound.random_group.is_empty())
+            .map(|sound| sound.random_group.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Return the names of the sequences the sounds are in, sorted.
+    pub fn sequence_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .sounds
+            .iter()
+            .filter(|sound| !sound.sequence.is_empty())
+            .map(|sound| sound.sequence.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Return the settings of the sequence `name`.
+    pub fn sequence(&self, name: &str) -> SequenceConfig {
+        self.sequences.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Return the indices of the sounds of the sequence `name` that are not hidden, in order.
+    pub fn sequence_members(&self, name: &str) -> Vec<usize> {
+        self.sounds
+            .iter()
+            .enumerate()
+            .filter(|(_, sound)| sound.sequence == name && !sound.hidden())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Return the settings of the random group `name`.
+    pub fn random_group(&self, name: &str) -> RandomGroupConfig {
+        self.random_groups.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Return what starting a sound of the choke group `name` does while another one plays.
+    pub fn choke_policy(&self, name: &str) -> ChokePolicy {
+        self.choke_groups
+            .get(name)
+            .map(|group| group.policy)
+            .unwrap_or_default()
+    }
+
+    /// Return the group settings of the output `name`, or None if it is in no group.
+    pub fn output_group(&self, name: &str) -> Option<OutputGroupConfig> {
+        let group = &self.outputs.get(name)?.group;
+        if group.is_empty() {
+            return None;
+        }
+        Some(self.output_groups.get(group).cloned().unwrap_or_default())
+    }
+
+    /// Return every key bind button of the sounds that are not hidden, the outputs, the
+    /// microphone, the shortcuts and the banks.
+    pub fn key_buttons(&self) -> impl Iterator<Item = &KeyButton> {
+        self.sounds
+            .iter()
+            .filter(|sound| !sound.hidden())
+            .map(|sound| &sound.key)
+            .chain(self.outputs.values().map(|output| &output.mute))
+            .chain(self.output_groups.values().map(|group| &group.mute))
+            .chain(std::iter::once(&self.microphone.mute))
+            .chain(self.shortcuts.bindings.values())
+            .chain(
+                self.banks
+                    .iter()
+                    .flat_map(|bank| [&bank.stop_key, &bank.pause_key]),
+            )
+    }
+}
+
+/// A sound, cue or bank, which may have been merged in from a configuration fragment.
+trait FragmentItem {
+    fn fragment(&self) -> &str;
+}
+
+impl FragmentItem for SoundConfig {
+    fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+impl FragmentItem for CueConfig {
+    fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+impl FragmentItem for BankConfig {
+    fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+/// Serialize the items saved in the profile, leaving out those merged in from fragments.
+fn serialize_profile_items<T, S>(items: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + FragmentItem,
+    S: serde::Serializer,
+{
+    serializer.collect_seq(items.iter().filter(|item| item.fragment().is_empty()))
+}
+
+/// The longest latency compensation of an output.
+pub const MAX_OUTPUT_DELAY_MS: u64 = 2000;
+
+/// Holds audio output configuration
+#[derive(Serialize, Deserialize, Default)]
+pub struct OutputConfig {
+    pub volume_db: f32,
+    pub mute: KeyButton,
+    /// Latency compensation added before every sound played on this output, up to
+    /// [`MAX_OUTPUT_DELAY_MS`].
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// The most gain (in dB) any sound is played at on this output after all other volume
+    /// controls, or None for no limit.
+    #[serde(default)]
+    pub max_gain_db: Option<f32>,
+    /// The ceiling (in dBFS) of the limiter on the mix of everything played on this output, so
+    /// stacked sounds do not clip, or None for no limiter.
+    #[serde(default)]
+    pub limiter_db: Option<f32>,
+    /// The balance of everything played on this output, from -1 (left only) to 1 (right only).
+    #[serde(default)]
+    pub pan: f32,
+    /// Play the average of the channels on every channel, e.g. for a venue PA feed.
+    #[serde(default)]
+    pub mono: bool,
+    /// Swap the left and right channels, e.g. for a miswired venue feed.
+    #[serde(default)]
+    pub swap_channels: bool,
+    /// Invert the phase of the left channel.
+    #[serde(default)]
+    pub invert_left: bool,
+    /// Invert the phase of the right channel.
+    #[serde(default)]
+    pub invert_right: bool,
+    /// The name of the group this output is in, e.g. "stream", or empty for none.
+    #[serde(default)]
+    pub group: String,
+    /// The sample rate (in Hz) the output is opened with, or 0 for the default of the device.
+    #[serde(default)]
+    pub sample_rate: u32,
+    /// The buffer size (in frames) the output is opened with, or 0 for the default of the
+    /// driver. Smaller buffers lower the latency but glitch more easily.
+    #[serde(default)]
+    pub buffer_frames: u32,
+    /// The channels of the output, counting from 1, that the channels of the mix play on, e.g.
+    /// `[3, 4]` to play a stereo mix on channels 3 and 4 of an audio interface, or empty to play
+    /// on every channel.
+    #[serde(default)]
+    pub channel_map: Vec<u16>,
+}
+
+/// The controls shared by the outputs of a group.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct OutputGroupConfig {
+    /// Added to the volume of each output in the group, in dB.
+    pub volume_db: f32,
+    /// Mutes or unmutes every output in the group.
+    pub mute: KeyButton,
+    /// A disabled group plays nothing, without losing the settings of its outputs.
+    pub enabled: bool,
+}
+
+impl Default for OutputGroupConfig {
+    fn default() -> Self {
+        Self {
+            volume_db: 0.0,
+            mute: KeyButton::default(),
+            enabled: true,
+        }
+    }
+}
+
+/// Holds microphone passthrough configuration.
+/// An empty `device` disables the passthrough.
+#[derive(Serialize, Deserialize, Default)]
+pub struct MicrophoneConfig {
+    pub device: String,
+    pub volume_db: f32,
+    pub mute: KeyButton,
+}
+
+/// Holds the enabled state of each network integration. Disabled integrations start no threads.
+#[derive(Serialize, Deserialize)]
+pub struct IntegrationsConfig {
+    #[serde(default = "enabled")]
+    pub remote_input: bool,
+    #[serde(default)]
+    pub http: bool,
+    /// Whether `soundboard play` and the other command line commands reach this soundboard.
+    #[serde(default = "enabled")]
+    pub command_line: bool,
+}
+
+impl Default for IntegrationsConfig {
+    fn default() -> Self {
+        Self {
+            remote_input: enabled(),
+            http: false,
+            command_line: enabled(),
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// A network integration that can be enabled in [`IntegrationsConfig`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Integration {
+    RemoteInput,
+    Http,
+    CommandLine,
+}
+
+impl Integration {
+    pub const ALL: [Integration; 3] = [
+        Integration::RemoteInput,
+        Integration::Http,
+        Integration::CommandLine,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Integration::RemoteInput => "Remote Input Server",
+            Integration::Http => "HTTP API",
+            Integration::CommandLine => "Command Line",
+        }
+    }
+}
+
+impl IntegrationsConfig {
+    /// Return the enabled flag of `integration`.
+    pub fn enabled_mut(&mut self, integration: Integration) -> &mut bool {
+        match integration {
+            Integration::RemoteInput => &mut self.remote_input,
+            Integration::Http => &mut self.http,
+            Integration::CommandLine => &mut self.command_line,
+        }
+    }
+}
+
+/// Holds HTTP API configuration. Requests must carry `token`, so the API
+/// refuses every request while it is empty.
+#[derive(Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// The address to listen on. Only local programs can connect by default.
+    #[serde(default = "default_http_address")]
+    pub address: String,
+    pub port: u16,
+    pub token: String,
+}
+
+fn default_http_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            address: default_http_address(),
+            port: 8651,
+            token: String::new(),
+        }
+    }
+}
+
+/// The play mode of a sound: what triggering it does while it is playing or paused.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Retrigger {
+    /// Stop the playing instance and start again from the beginning.
+    #[default]
+    Restart,
+    /// Pause or resume the playing instance, like the modifier does.
+    Toggle,
+    /// Play again once the playing instance ends.
+    Queue,
+    /// Start another instance over the ones already playing.
+    Overlap,
+    /// Keep playing and do nothing.
+    Ignore,
+}
+
+impl Retrigger {
+    pub const ALL: [Retrigger; 5] = [
+        Retrigger::Restart,
+        Retrigger::Toggle,
+        Retrigger::Queue,
+        Retrigger::Overlap,
+        Retrigger::Ignore,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Retrigger::Restart => "Restart",
+            Retrigger::Toggle => "Toggle",
+            Retrigger::Queue => "Queue",
+            Retrigger::Overlap => "Overlap",
+            Retrigger::Ignore => "Ignore",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Retrigger::Restart => "Stop the sound and play it again from the beginning",
+            Retrigger::Toggle => "Pause or resume the sound",
+            Retrigger::Queue => "Play it again once it ends",
+            Retrigger::Overlap => "Play again without stopping the instances already playing",
+            Retrigger::Ignore => "Keep playing and do nothing",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// An option that follows the setting of the operating system unless it is turned on or off.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemSetting {
+    /// Follow the operating system, or off where its setting cannot be read.
+    #[default]
+    System,
+    On,
+    Off,
+}
+
+impl SystemSetting {
+    /// Check if the option is on, given whether it is on in the operating system.
+    pub fn enabled(self, system: bool) -> bool {
+        match self {
+            SystemSetting::System => system,
+            SystemSetting::On => true,
+            SystemSetting::Off => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeScale {
+    /// Show volumes in dB.
+    #[default]
+    Decibels,
+    /// Show volumes as a percentage of the amplitude.
+    Linear,
+}
+
+/// Holds the theme and the status colors, each as `[red, green, blue]`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub mode: ThemeMode,
+    /// The background of selected widgets.
+    pub accent: [u8; 3],
+    pub playing: [u8; 3],
+    /// Also used for paused sounds and the standby cue.
+    pub paused: [u8; 3],
+    /// Also used for muted outputs and the Connect button.
+    pub stopped: [u8; 3],
+    /// Black and white colors with solid outlines.
+    pub high_contrast: SystemSetting,
+    /// No animations and no flashing indicators.
+    pub reduced_motion: SystemSetting,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            mode: ThemeMode::Dark,
+            accent: [0, 92, 128],
+            playing: [0, 255, 0],
+            paused: [255, 255, 0],
+            stopped: [255, 0, 0],
+            high_contrast: SystemSetting::System,
+            reduced_motion: SystemSetting::System,
+        }
+    }
+}
+
+/// Holds big screen configuration. The big screen shows only the sounds as large pads, with
+/// high contrast colors and fonts scaled by `scale`, for displays such as a TV at a venue.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct BigScreenConfig {
+    pub enabled: bool,
+    pub scale: f32,
+}
+
+impl Default for BigScreenConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scale: 2.0,
+        }
+    }
+}
+
+/// Holds the timing of double tap and long press key binds, in milliseconds.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriggerConfig {
+    /// The longest time between the releases of a double tap.
+    pub double_tap_ms: u64,
+    /// The shortest time a key is held for a long press.
+    pub long_press_ms: u64,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self {
+            double_tap_ms: 300,
+            long_press_ms: 500,
+        }
+    }
+}
+
+/// Holds hearing protection configuration. A warning is shown when the level of the monitor
+/// output stays above `threshold_db` for `duration_s` seconds.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct HearingProtectionConfig {
+    pub enabled: bool,
+    /// The RMS level in dBFS.
+    pub threshold_db: f32,
+    pub duration_s: u64,
+    /// Also lower the monitor output by `attenuation_db` when warning.
+    pub attenuate: bool,
+    pub attenuation_db: f32,
+}
+
+impl Default for HearingProtectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -10.0,
+            duration_s: 30,
+            attenuate: false,
+            attenuation_db: 12.0,
+        }
+    }
+}
+
+/// How far and how fast the other sounds are lowered while a priority sound plays.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuckingConfig {
+    pub depth_db: f32,
+    pub attack_ms: u64,
+    pub release_ms: u64,
+    /// How far every sound is lowered while the talk-over shortcut is held.
+    pub talk_over_db: f32,
+}
+
+impl Default for DuckingConfig {
+    fn default() -> Self {
+        Self {
+            depth_db: 12.0,
+            attack_ms: 50,
+            release_ms: 500,
+            talk_over_db: 10.0,
+        }
+    }
+}
+
+/// How background sounds take over from each other.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundConfig {
+    /// How long starting a background sound crossfades from the one playing.
+    pub crossfade_ms: u64,
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self { crossfade_ms: 2000 }
+    }
+}
+
+/// What starting a sound does while another sound of its choke group is playing or paused.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ChokePolicy {
+    /// Stop the other sound.
+    #[default]
+    Cut,
+    /// Start once the other sound and the sounds queued before this one have ended.
+    Queue,
+}
+
+impl ChokePolicy {
+    pub const ALL: [ChokePolicy; 2] = [ChokePolicy::Cut, ChokePolicy::Queue];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChokePolicy::Cut => "Cut",
+            ChokePolicy::Queue => "Queue",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ChokePolicy::Cut => "Stop the sound playing in the group",
+            ChokePolicy::Queue => "Wait for the sound playing in the group to end",
+        }
+    }
+}
+
+/// The settings of a group of sounds that never play at the same time, e.g. callers on a line.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ChokeGroupConfig {
+    pub policy: ChokePolicy,
+}
+
+/// What moves a sequence on to its next sound.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceAdvance {
+    /// Each trigger plays the next sound, starting over after the last one.
+    #[default]
+    Trigger,
+    /// A trigger plays the first sound, and each sound starts the next one when it ends.
+    Finish,
+}
+
+impl SequenceAdvance {
+    pub const ALL: [SequenceAdvance; 2] = [SequenceAdvance::Trigger, SequenceAdvance::Finish];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SequenceAdvance::Trigger => "Each Trigger",
+            SequenceAdvance::Finish => "When Finished",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            SequenceAdvance::Trigger => "Play the next sound each time the key is pressed",
+            SequenceAdvance::Finish => {
+                "Play the sounds from the first one, each starting when the one before ends"
+            }
+        }
+    }
+}
+
+/// The settings of a sequence of sounds played in order from one key bind, e.g. an intro, a
+/// stinger and a loop.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SequenceConfig {
+    pub advance: SequenceAdvance,
+}
+
+impl SequenceConfig {
+    /// Return the sound of `members`, in order, that a trigger plays, given the one started
+    /// last.
+    pub fn triggered<T: PartialEq + Copy>(&self, members: &[T], last: Option<T>) -> Option<T> {
+        let position = last.and_then(|last| members.iter().position(|&member| member == last));
+        match (self.advance, position) {
+            (SequenceAdvance::Trigger, Some(position)) => {
+                members.get(position + 1).or(members.first()).copied()
+            }
+            _ => members.first().copied(),
+        }
+    }
+
+    /// Return the sound of `members` that starts when `finished` ends, if it advances on its
+    /// own and `finished` is not the last one.
+    pub fn after<T: PartialEq + Copy>(&self, members: &[T], finished: T) -> Option<T> {
+        if self.advance != SequenceAdvance::Finish {
+            return None;
+        }
+        let position = members.iter().position(|&member| member == finished)?;
+        members.get(position + 1).copied()
+    }
+}
+
+/// The settings of a group of variants of a sound, of which a key bind they share plays one at
+/// random, e.g. several takes of a laugh.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RandomGroupConfig {
+    /// Never play the variant played last twice in a row.
+    pub no_repeat: bool,
+}
+
+impl RandomGroupConfig {
+    /// Pick one of the variants in `candidates` with the random number `random`. `last` is the
+    /// variant played last, which is left out if the group does not repeat and there are others.
+    pub fn pick<T: PartialEq + Copy>(
+        &self,
+        candidates: &[T],
+        last: Option<T>,
+        random: u64,
+    ) -> Option<T> {
+        let fresh: Vec<T> = candidates
+            .iter()
+            .copied()
+            .filter(|&candidate| !self.no_repeat || Some(candidate) != last)
+            .collect();
+        let choices = if fresh.is_empty() { candidates } else { &fresh };
+        if choices.is_empty() {
+            return None;
+        }
+        Some(choices[(random % choices.len() as u64) as usize])
+    }
+}
+
+/// Loudness normalization, which moves every sound to the same integrated loudness measured as
+/// in EBU R128, instead of normalizing the peaks of some.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoudnessConfig {
+    pub enabled: bool,
+    /// The integrated loudness every sound is moved to, in LUFS.
+    pub target_lufs: f32,
+}
+
+impl Default for LoudnessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_lufs: -16.0,
+        }
+    }
+}
+
+/// The integrated loudness of the file of a sound, measured once in the background.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct MeasuredLoudness {
+    /// The file measured, so the loudness is measured again when the file changes.
+    pub path: String,
+    /// The integrated loudness in LUFS, or None if the file is silent.
+    pub lufs: Option<f32>,
+    /// The amplitude of the loudest peak, from 0.0 to 1.0, or None if measured by an older
+    /// version.
+    #[serde(default)]
+    pub peak: Option<f32>,
+}
+
+/// A global action that can be bound to a key in [`ShortcutsConfig`].
+/// New actions can be added without changing the configuration format.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    Pause,
+    Stop,
+    Modifier,
+    Go,
+    NextProfile,
+    SupportBundle,
+    BigScreen,
+    /// Lowers every sound while its key is held, see [`DuckingConfig::talk_over_db`].
+    TalkOver,
+    /// Toggles the switch that enables the whole soundboard.
+    Enable,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 9] = [
+        ShortcutAction::Pause,
+        ShortcutAction::Stop,
+        ShortcutAction::Modifier,
+        ShortcutAction::Go,
+        ShortcutAction::NextProfile,
+        ShortcutAction::SupportBundle,
+        ShortcutAction::BigScreen,
+        ShortcutAction::TalkOver,
+        ShortcutAction::Enable,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::Pause => "Pause",
+            ShortcutAction::Stop => "Stop",
+            ShortcutAction::Modifier => "Modifier",
+            ShortcutAction::Go => "GO",
+            ShortcutAction::NextProfile => "Next Profile",
+            ShortcutAction::SupportBundle => "Create Support Bundle",
+            ShortcutAction::BigScreen => "Toggle Big Screen",
+            ShortcutAction::TalkOver => "Talk-Over",
+            ShortcutAction::Enable => "Toggle Enabled",
+        }
+    }
+}
+
+/// Holds shortcut configuration as a map of actions to key binds, e.g. `pause = "KEY_P"`.
+/// Actions without a key bind are unbound.
+#[derive(Default)]
+pub struct ShortcutsConfig {
+    bindings: BTreeMap<ShortcutAction, KeyButton>,
+    /// The shortcuts of actions this version does not know, such as ones added by a newer
+    /// version, saved back as they were.
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl Serialize for ShortcutsConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.bindings.len() + self.unknown.len()))?;
+        for (action, binding) in &self.bindings {
+            map.serialize_entry(action, binding)?;
+        }
+        for (name, binding) in &self.unknown {
+            map.serialize_entry(name, binding)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ShortcutsConfig {
+    /// Keep actions this version does not know without reading their key binds, instead of
+    /// failing the whole profile.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, IntoDeserializer};
+        let mut shortcuts = Self::default();
+        for (name, binding) in BTreeMap::<String, toml::Value>::deserialize(deserializer)? {
+            let action: Result<ShortcutAction, serde::de::value::Error> =
+                ShortcutAction::deserialize(name.as_str().into_deserializer());
+            match action {
+                Ok(action) => {
+                    let binding = KeyButton::deserialize(binding).map_err(D::Error::custom)?;
+                    shortcuts.bindings.insert(action, binding);
+                }
+                Err(_) => {
+                    log!(
+                        Warning,
+                        "[Configuration Loader] Keeping the shortcut for unknown action {name} as it is."
+                    );
+                    shortcuts.unknown.insert(name, binding);
+                }
+            }
+        }
+        Ok(shortcuts)
+    }
+}
+
+impl ShortcutsConfig {
+    /// Return the actions with a key bind button and their buttons.
+    pub fn bindings(&self) -> impl Iterator<Item = (ShortcutAction, &KeyButton)> {
+        self.bindings
+            .iter()
+            .map(|(&action, binding)| (action, binding))
+    }
+
+    /// Return the key bind button of `action`, adding an unbound one if needed.
+    pub fn binding_mut(&mut self, action: ShortcutAction) -> &mut KeyButton {
+        self.bindings.entry(action).or_default()
+    }
+
+    /// Check if `release` triggers the key bind of `action`.
+    pub fn is_bound(&self, action: ShortcutAction, release: &KeyRelease) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|binding| binding.matches(release))
+    }
+
+    /// Check if any key bind button is listening for a key.
+    pub fn listening(&self) -> bool {
+        self.bindings.values().any(|binding| binding.listening)
+    }
+}
+
+/// Holds a sound configuration.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SoundConfig {
+    pub path: String,
+    pub name: String,
+    pub volume_db: f32,
+    pub key: KeyButton,
+    /// The position playback starts from.
+    #[serde(default)]
+    pub start_ms: u64,
+    /// The position playback ends at, or 0 to play to the end of the file.
+    #[serde(default)]
+    pub end_ms: u64,
+    /// The playback speed. This also changes the pitch.
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    /// Archived sounds are kept in the configuration but hidden and never triggered.
+    #[serde(default)]
+    pub archived: bool,
+    /// Set while the bank of the sound is out of season. Hides the sound like `archived`, but
+    /// is never saved, so the season does not change what the user archived.
+    #[serde(skip)]
+    pub out_of_season: bool,
+    /// The name of the bank this sound belongs to, or empty for none.
+    #[serde(default)]
+    pub bank: String,
+    /// What triggering the sound again while it is playing does.
+    #[serde(default)]
+    pub retrigger: Retrigger,
+    /// The most instances of an overlapping sound played at once, or 0 for any number. The
+    /// oldest are stopped first.
+    #[serde(default)]
+    pub max_instances: usize,
+    /// The shortest time between two plays of the sound, or 0 for none. Triggers during the
+    /// cooldown are rejected.
+    #[serde(default)]
+    pub cooldown_ms: u64,
+    /// The names of the sounds started on the same sample as this one, e.g. a music bed under a
+    /// stinger. Stopping any of them stops all of them.
+    #[serde(default)]
+    pub with: Vec<String>,
+    /// A file looped without a gap after the trimmed part of `path` has played once, e.g. the
+    /// loop of a music bed split into an intro and a loop. Empty to play `path` alone.
+    #[serde(default)]
+    pub loop_path: String,
+    /// Change the volume so the loudest peak of the file is just below full scale.
+    #[serde(default)]
+    pub normalize: bool,
+    /// Lower the other sounds while this one plays, see [`DuckingConfig`].
+    #[serde(default)]
+    pub priority: bool,
+    /// Crossfade from the background sound playing when this one starts, see
+    /// [`BackgroundConfig`].
+    #[serde(default)]
+    pub background: bool,
+    /// Start each play at a random position in the trimmed part of the file, so layered or
+    /// retriggered ambiences do not phase or obviously repeat.
+    #[serde(default)]
+    pub random_start: bool,
+    /// The position of this sound between the left (-1) and right (1) channels.
+    #[serde(default)]
+    pub pan: f32,
+    /// The output groups this sound plays on, or empty to play on every output.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// The name of the choke group this sound is in, or empty for none. Starting a sound stops
+    /// the other sounds of its group, or waits for them, see [`ChokeGroupConfig`].
+    #[serde(default)]
+    pub choke_group: String,
+    /// The name of the random group this sound is in, or empty for none. A key bind shared by
+    /// sounds of a group plays one of them at random, see [`RandomGroupConfig`].
+    #[serde(default)]
+    pub random_group: String,
+    /// The name of the sequence this sound is in, or empty for none. A key bind shared by the
+    /// sounds of a sequence plays them one after another, in the order of the sounds, see
+    /// [`SequenceConfig`]. Starting one stops the others.
+    #[serde(default)]
+    pub sequence: String,
+    /// The color of the sound's pad while it is stopped, as `[red, green, blue]`, or None for
+    /// the color of the other buttons.
+    #[serde(default)]
+    pub color: Option<[u8; 3]>,
+    /// The loudness of the file, see [`LoudnessConfig`].
+    #[serde(default)]
+    pub loudness: Option<MeasuredLoudness>,
+    /// The file name of the configuration fragment this sound was merged in from, or empty if
+    /// it is saved in the profile.
+    #[serde(default, skip_serializing)]
+    pub fragment: String,
+}
+
+/// The playback speeds a sound can have.
+pub const SPEED_RANGE: RangeInclusive<f32> = 0.25..=4.0;
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            name: String::new(),
+            volume_db: 0.0,
+            key: KeyButton::default(),
+            start_ms: 0,
+            end_ms: 0,
+            speed: default_speed(),
+            archived: false,
+            out_of_season: false,
+            bank: String::new(),
+            retrigger: Retrigger::Restart,
+            max_instances: 0,
+            cooldown_ms: 0,
+            with: Vec::new(),
+            loop_path: String::new(),
+            normalize: false,
+            priority: false,
+            background: false,
+            random_start: false,
+            pan: 0.0,
+            groups: Vec::new(),
+            choke_group: String::new(),
+            random_group: String::new(),
+            sequence: String::new(),
+            color: None,
+            loudness: None,
+            fragment: String::new(),
+        }
+    }
+}
+
+impl SoundConfig {
+    /// Check if the sound is archived or out of season, so it is hidden and never triggered.
+    pub fn hidden(&self) -> bool {
+        self.archived || self.out_of_season
+    }
+
+    /// Return the part of the file to play as a start position and a length.
+    pub fn trim(&self) -> (Duration, Duration) {
+        let start = Duration::from_millis(self.start_ms);
+        if self.end_ms == 0 {
+            (start, Duration::MAX)
+        } else {
+            (
+                start,
+                Duration::from_millis(self.end_ms.saturating_sub(self.start_ms)),
+            )
+        }
+    }
+
+    /// Check if the sound plays on the outputs of `group`, or on the outputs in no group if
+    /// `group` is empty.
+    pub fn plays_on(&self, group: &str) -> bool {
+        self.groups.is_empty() || (!group.is_empty() && self.groups.iter().any(|g| g == group))
+    }
+
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_millis(self.cooldown_ms)
+    }
+
+    /// Return the most instances played at once, or 0 for any number.
+    pub fn instance_limit(&self) -> usize {
+        match self.retrigger {
+            Retrigger::Overlap => self.max_instances,
+            Retrigger::Restart | Retrigger::Toggle | Retrigger::Queue | Retrigger::Ignore => 1,
+        }
+    }
+}
+
+/// Holds a show mode cue. Cues refer to sounds by name.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct CueConfig {
+    pub sound: String,
+    #[serde(default)]
+    pub follow: Follow,
+    #[serde(default)]
+    pub follow_delay_ms: u64,
+    /// See [`SoundConfig::fragment`].
+    #[serde(default, skip_serializing)]
+    pub fragment: String,
+}
+
+/// When a cue fires automatically after the previous cue.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Follow {
+    /// Only fire when GO is pressed.
+    #[default]
+    Manual,
+    /// Fire `follow_delay_ms` after the previous cue started.
+    AfterStart,
+    /// Fire `follow_delay_ms` after the previous cue's sound ended.
+    AfterEnd,
+}
+
+/// Holds a bank of sounds. Sounds join a bank by its name.
+/// A bank with a season is only active from `active_from` to `active_until` ("MM-DD", inclusive,
+/// wrapping around the new year). Its sounds are hidden outside of the season.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct BankConfig {
+    pub name: String,
+    #[serde(default)]
+    pub active_from: String,
+    #[serde(default)]
+    pub active_until: String,
+    /// Stops every sound in the bank.
+    #[serde(default)]
+    pub stop_key: KeyButton,
+    /// Pauses the playing sounds in the bank, or resumes them if none are playing.
+    #[serde(default)]
+    pub pause_key: KeyButton,
+    /// See [`SoundConfig::fragment`].
+    #[serde(default, skip_serializing)]
+    pub fragment: String,
+}
+
+impl BankConfig {
+    /// Check if the bank is in season on `month` and `day`.
+    /// Returns None if the bank has no valid season.
+    pub fn in_season(&self, month: u32, day: u32) -> Option<bool> {
+        let from = parse_month_day(&self.active_from)?;
+        let until = parse_month_day(&self.active_until)?;
+        let today = (month, day);
+        Some(if from <= until {
+            from <= today && today <= until
+        } else {
+            today >= from || today <= until
+        })
+    }
+}
+
+/// Parse a "MM-DD" date into a month and day.
+pub fn parse_month_day(date: &str) -> Option<(u32, u32)> {
+    let (month, day) = date.trim().split_once('-')?;
+    let month = month.parse().ok()?;
+    let day = day.parse().ok()?;
+    if (1..=12).contains(&month) && (1..=31).contains(&day) {
+        Some((month, day))
+    } else {
+        None
+    }
+}
+
+/// Get the directory holding this executable.
+fn get_executable_directory() -> Result<PathBuf, String> {
+    match std::env::current_exe() {
+        Err(exe_path_error) => Err(format!(
+            "Unable to obtain executable directory: {exe_path_error}."
+        )),
+        Ok(exe_path) => match exe_path.parent() {
+            None => Err("Unable to obtain executable directory.".to_string()),
+            Some(parent_dir) => Ok(parent_dir.to_path_buf()),
+        },
+    }
+}
+
+/// The configuration directory given with `--config`.
+static CONFIG_DIRECTORY_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Store the profiles and other files in `directory` instead of the platform configuration
+/// directory. Only the first call has an effect.
+pub fn set_config_directory(directory: PathBuf) {
+    let _ = CONFIG_DIRECTORY_OVERRIDE.set(directory);
+}
+
+/// Get the platform's directory for per-user configuration files.
+/// %APPDATA%\Soundboard on Windows, ~/Library/Application Support/Soundboard on macOS and
+/// $XDG_CONFIG_HOME/soundboard (by default ~/.config/soundboard) elsewhere.
+fn get_platform_config_directory() -> Option<PathBuf> {
+    let non_empty = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(windows) {
+        Some(PathBuf::from(non_empty("APPDATA")?).join("Soundboard"))
+    } else if cfg!(target_os = "macos") {
+        Some(PathBuf::from(non_empty("HOME")?).join("Library/Application Support/Soundboard"))
+    } else if let Some(config_home) = non_empty("XDG_CONFIG_HOME") {
+        Some(PathBuf::from(config_home).join("soundboard"))
+    } else {
+        Some(PathBuf::from(non_empty("HOME")?).join(".config/soundboard"))
+    }
+}
+
+/// Get the directory holding the profiles, statistics and demo sounds.
+/// The `--config` directory if given, otherwise [`get_platform_config_directory`], falling back
+/// to this executable's directory if the platform directory is unknown.
+pub fn get_config_directory() -> Result<PathBuf, String> {
+    if let Some(directory) = CONFIG_DIRECTORY_OVERRIDE.get() {
+        return Ok(directory.clone());
+    }
+    match get_platform_config_directory() {
+        None => get_executable_directory(),
+        Some(directory) => Ok(directory),
+    }
+}
+
+/// Get the path of the file holding the name of the profile loaded at startup.
+/// [configuration directory]/default_profile.txt
+pub fn get_default_profile_path() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("default_profile.txt"))
+}
+
+/// Return the name of the profile loaded at startup without asking, if one is set.
+pub fn read_default_profile() -> Option<String> {
+    let profile = fs::read_to_string(get_default_profile_path().ok()?).ok()?;
+    Some(profile.trim().to_string()).filter(|profile| !profile.is_empty())
+}
+
+/// Load `profile` at startup without asking from now on, or ask again if None.
+pub fn set_default_profile(profile: Option<&str>) -> Result<(), String> {
+    let path = get_default_profile_path()?;
+    let result = match profile {
+        Some(profile) => fs::write(&path, profile),
+        None if path.exists() => fs::remove_file(&path),
+        None => Ok(()),
+    };
+    result.map_err(|error| format!("Unable to write \"{}\": {error}.", path.display()))
+}
+
+/// Return the profile of `profiles` to load at startup: the only one, or the `default` one if
+/// it still exists. Returns None if the user has to choose.
+pub fn startup_profile(profiles: &[String], default: Option<&str>) -> Option<String> {
+    match profiles {
+        [profile] => Some(profile.clone()),
+        _ => default
+            .filter(|default| profiles.iter().any(|profile| profile == default))
+            .map(str::to_string),
+    }
+}
+
+/// Get the path of the local usage statistics file.
+/// [configuration directory]/stats.jsonl
+pub fn get_stats_path() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("stats.jsonl"))
+}
+
+/// Get the path of the file the missed triggers are appended to.
+/// [configuration directory]/missed_triggers.jsonl
+pub fn get_missed_triggers_path() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("missed_triggers.jsonl"))
+}
+
+/// Get the path of the directory the demo sounds are written to.
+/// [configuration directory]/demo
+pub fn get_demo_directory() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("demo"))
+}
+
+/// Get the path of the media library that imported sounds are transcoded into.
+/// [configuration directory]/library
+pub fn get_library_directory() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("library"))
+}
+
+/// Get the path of the directory of configuration fragments merged into every profile.
+/// [configuration directory]/conf.d
+pub fn get_fragment_directory() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("conf.d"))
+}
+
+/// Get the path of the directory of translations added by the user.
+/// [configuration directory]/languages
+pub fn get_language_directory() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("languages"))
+}
+
+/// Get the path of the profile directory.
+/// [configuration directory]/profiles
+pub fn get_profile_directory() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("profiles"))
+}
+
+/// Get the path of a profile's configuration file.
+/// [profile directory]/[profile].toml
+pub fn get_profile_path(profile: &str) -> Result<PathBuf, String> {
+    Ok(get_profile_directory()?.join(format!("{profile}.toml")))
+}
+
+/// Get the path of a backup of a profile's configuration file. Backup 1 is the newest.
+/// [profile directory]/[profile].toml.bak[backup]
+pub fn get_backup_path(profile: &str, backup: usize) -> Result<PathBuf, String> {
+    Ok(get_profile_directory()?.join(format!("{profile}.toml.bak{backup}")))
+}
+
+/// List the backups of `profile`, newest first, with the time each was saved in seconds since
+/// the Unix epoch.
+pub fn list_backups(profile: &str) -> Vec<(usize, u64)> {
+    (1..=ConfigSaver::BACKUPS)
+        .filter_map(|backup| {
+            let modified = fs::metadata(get_backup_path(profile, backup).ok()?)
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            let time = modified
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            Some((backup, time))
+        })
+        .collect()
+}
+
+/// Load the toml configuration of a backup of `profile` with the configuration fragments merged
+/// into it.
+pub fn load_backup(profile: &str, backup: usize) -> Result<Config, String> {
+    let backup_path = get_backup_path(profile, backup)?;
+    log!(
+        "[Configuration Loader] Loading backup \"{}\".",
+        backup_path.display()
+    );
+    match fs::read_to_string(&backup_path) {
+        Err(error) => Err(format!("Unable to read backup: {error}.")),
+        Ok(config_data) => toml::from_str(&config_data)
+            .map_err(|error| format!("Unable to deserialize backup: {error}."))
+            .and_then(|config| merge_config(config, &read_fragments()))
+            .map(|(config, _)| config),
+    }
+}
+
+/// Copy the configuration file of `profile` to backup 1, moving the older backups down and
+/// deleting the oldest. The backup keeps the time the file was saved.
+fn rotate_backups(profile: &str) -> Result<(), String> {
+    let config_file_path = get_profile_path(profile)?;
+    if !config_file_path.is_file() {
+        return Ok(());
+    }
+    let _ = fs::remove_file(get_backup_path(profile, ConfigSaver::BACKUPS)?);
+    for backup in (1..ConfigSaver::BACKUPS).rev() {
+        let backup_path = get_backup_path(profile, backup)?;
+        if backup_path.is_file() {
+            if let Err(error) = fs::rename(&backup_path, get_backup_path(profile, backup + 1)?) {
+                return Err(format!("Unable to rotate backups: {error}."));
+            }
+        }
+    }
+
+    let backup_path = get_backup_path(profile, 1)?;
+    let modified = fs::metadata(&config_file_path).and_then(|metadata| metadata.modified());
+    if let Err(error) = fs::copy(&config_file_path, &backup_path) {
+        return Err(format!("Unable to back up configuration file: {error}."));
+    }
+    if let Ok(modified) = modified {
+        let _ = fs::File::options()
+            .write(true)
+            .open(&backup_path)
+            .and_then(|file| file.set_modified(modified));
+    }
+    Ok(())
+}
+
+/// Check that `profile` can be used as a file name.
+pub fn validate_profile_name(profile: &str) -> Result<(), String> {
+    if profile.trim().is_empty() {
+        return Err("Profile names must not be empty.".to_string());
+    }
+    if profile.chars().any(|c| {
+        matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control()
+    }) {
+        return Err(format!(
+            "Profile name \"{profile}\" contains characters that are not allowed in file names."
+        ));
+    }
+    Ok(())
+}
+
+/// List the names of all profiles in [`get_profile_directory`], sorted alphabetically.
+pub fn list_profiles() -> Result<Vec<String>, String> {
+    let profile_directory = get_profile_directory()?;
+    let entries = match fs::read_dir(&profile_directory) {
+        Err(error) => {
+            log!(
+                Error,
+                "[Configuration Loader] Unable to read profile directory: {error}."
+            );
+            return Err(format!("Unable to read profile directory: {error}."));
+        }
+        Ok(entries) => entries,
+    };
+
+    let mut profiles: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    profiles.sort();
+    Ok(profiles)
+}
+
+/// Copy `from` to `to`, logging the copy.
+fn copy_config_file(from: &Path, to: &Path) -> Result<(), String> {
+    log!(
+        "[Configuration Loader] Copying \"{}\" to \"{}\".",
+        from.display(),
+        to.display()
+    );
+    if let Err(error) = fs::copy(from, to) {
+        log!(
+            Error,
+            "[Configuration Loader] Unable to copy configuration file: {error}."
+        );
+        return Err(format!("Unable to copy \"{}\": {error}.", from.display()));
+    }
+    Ok(())
+}
+
+/// Copy the profiles and statistics kept next to the executable by older versions into the
+/// configuration directory. The originals are left in place, since the executable's directory
+/// may be read-only, and files already copied are not replaced. A file that cannot be copied is
+/// logged and skipped. Returns true if every file was copied.
+fn migrate_executable_directory(config_directory: &Path) -> bool {
+    let (executable_directory, profile_directory, stats_path) = match (
+        get_executable_directory(),
+        get_profile_directory(),
+        get_stats_path(),
+    ) {
+        (Ok(executable), Ok(profiles), Ok(stats)) => (executable, profiles, stats),
+        (Err(error), _, _) | (_, Err(error), _) | (_, _, Err(error)) => {
+            log!(Error, "[Configuration Loader] {error}");
+            return false;
+        }
+    };
+    if executable_directory == config_directory {
+        return true;
+    }
+
+    let mut copies = Vec::new();
+    if let Ok(entries) = fs::read_dir(executable_directory.join("profiles")) {
+        for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                if let Some(file_name) = path.file_name() {
+                    copies.push((path.clone(), profile_directory.join(file_name)));
+                }
+            }
+        }
+    }
+    let old_stats_path = executable_directory.join("stats.jsonl");
+    if old_stats_path.is_file() {
+        copies.push((old_stats_path, stats_path));
+    }
+    let mut migrated = true;
+    for (from, to) in copies {
+        if !to.exists() && copy_config_file(&from, &to).is_err() {
+            migrated = false;
+        }
+    }
+    migrated
+}
+
+/// Create the profile directory if it does not exist and return the available profiles.
+/// On the first run with a new configuration directory, the profiles next to the executable are
+/// copied into it, again on later runs until every one was copied. A config.toml left next to
+/// the executable by an older version becomes the "Default" profile. If no profiles exist, a
+/// default one is installed.
+pub fn initialize_profiles() -> Result<Vec<String>, String> {
+    let config_directory = get_config_directory()?;
+    let profile_directory = get_profile_directory()?;
+    log!(
+        "[Configuration Loader] Using configuration directory \"{}\".",
+        config_directory.display()
+    );
+    // The migration is marked pending on the first run, and tried again on every start until
+    // it succeeds.
+    let migration_path = config_directory.join("migration_pending");
+    let first_run = !profile_directory.exists();
+    if let Err(error) = fs::create_dir_all(&profile_directory) {
+        log!(
+            Error,
+            "[Configuration Loader] Unable to create profile directory: {error}."
+        );
+        return Err(format!("Unable to create profile directory: {error}."));
+    }
+    if first_run {
+        if let Err(error) = fs::write(&migration_path, "") {
+            log!(
+                Error,
+                "[Configuration Loader] Unable to mark the migration pending: {error}."
+            );
+        }
+    }
+    if (first_run || migration_path.exists()) && migrate_executable_directory(&config_directory) {
+        let _ = fs::remove_file(&migration_path);
+    }
+    // Created so that it is easy to find, but the soundboard works without it.
+    if let Err(error) = fs::create_dir_all(get_fragment_directory()?) {
+        log!(
+            Error,
+            "[Configuration Loader] Unable to create fragment directory: {error}."
+        );
+    }
+
+    let mut profiles = list_profiles()?;
+    if profiles.is_empty() {
+        let legacy_config_file_path = get_executable_directory()?.join("config.toml");
+        let default_profile_path = get_profile_path("Default")?;
+        if legacy_config_file_path.is_file() {
+            copy_config_file(&legacy_config_file_path, &default_profile_path)?;
+        } else {
+            create_profile("Default")?;
+        }
+        profiles = list_profiles()?;
+    }
+    Ok(profiles)
+}
+
+/// Return the default configuration installed in new profiles, without reading or writing a file.
+pub fn default_config() -> Config {
+    toml::from_str(include_str!("default_config.toml")).expect("the default configuration is valid")
+}
+
+/// Install the default configuration as a new profile.
+pub fn create_profile(profile: &str) -> Result<(), String> {
+    validate_profile_name(profile)?;
+    let profile_path = get_profile_path(profile)?;
+    if profile_path.exists() {
+        return Err(format!("Profile \"{profile}\" already exists."));
+    }
+    log!(
+        "[Configuration Loader] Installing default configuration file \"{}\".",
+        profile_path.display()
+    );
+    if let Err(error) = fs::write(&profile_path, include_str!("default_config.toml")) {
+        log!(
+            Error,
+            "[Configuration Loader] Unable to install default configuration file: {error}."
+        );
+        return Err(format!(
+            "Unable to install default configuration file: {error}."
+        ));
+    }
+    Ok(())
+}
+
+/// The version of the configuration format written by this build. When a field is renamed or
+/// changes meaning, increase it and add a migration to [`MIGRATIONS`].
+pub const CONFIG_VERSION: u32 = 3;
+
+/// The migration at index `i` upgrades a configuration from version `i` to version `i + 1`.
+const MIGRATIONS: [fn(&mut toml::Table); CONFIG_VERSION as usize] =
+    [migrate_v0, migrate_v1, migrate_v2];
+
+/// Version 0 had no version field and required the top level fields of the default
+/// configuration. Fill in any that are missing.
+fn migrate_v0(config: &mut toml::Table) {
+    let default_config: toml::Table = match toml::from_str(include_str!("default_config.toml")) {
+        Err(_) => return,
+        Ok(default_config) => default_config,
+    };
+    for (key, value) in default_config {
+        config.entry(key).or_insert(value);
+    }
+}
+
+/// Version 1 stored whether a sound overlaps as `overlap = true`, which is now
+/// `retrigger = "overlap"`.
+fn migrate_v1(config: &mut toml::Table) {
+    let Some(toml::Value::Array(sounds)) = config.get_mut("sounds") else {
+        return;
+    };
+    for sound in sounds.iter_mut().filter_map(toml::Value::as_table_mut) {
+        if sound
+            .remove("overlap")
+            .and_then(|overlap| overlap.as_bool())
+            == Some(true)
+        {
+            sound.insert(
+                "retrigger".to_string(),
+                toml::Value::String("overlap".to_string()),
+            );
+        }
+    }
+}
+
+/// Version 2 stored the volumes in dB as `volume`, which is now `volume_db`.
+fn migrate_v2(config: &mut toml::Table) {
+    fn rename(table: &mut toml::Table) {
+        if let Some(volume) = table.remove("volume") {
+            table.entry("volume_db").or_insert(volume);
+        }
+    }
+    rename(config);
+    if let Some(toml::Value::Array(sounds)) = config.get_mut("sounds") {
+        sounds
+            .iter_mut()
+            .filter_map(toml::Value::as_table_mut)
+            .for_each(rename);
+    }
+    for key in ["outputs", "output_groups"] {
+        if let Some(toml::Value::Table(tables)) = config.get_mut(key) {
+            tables
+                .iter_mut()
+                .filter_map(|(_, table)| table.as_table_mut())
+                .for_each(rename);
+        }
+    }
+    if let Some(toml::Value::Table(microphone)) = config.get_mut("microphone") {
+        rename(microphone);
+    }
+}
+
+/// A configuration fragment: the file name of a partial toml configuration in
+/// [`get_fragment_directory`] and its contents.
+pub type Fragment = (String, String);
+
+/// Read the configuration fragments, sorted by file name. Fragments that cannot be read are
+/// logged and left out.
+pub fn read_fragments() -> Vec<Fragment> {
+    let Ok(entries) = get_fragment_directory()
+        .and_then(|directory| fs::read_dir(directory).map_err(|error| error.to_string()))
+    else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            match fs::read_to_string(&path) {
+                Err(error) => {
+                    log!(
+                        Error,
+                        "[Configuration Loader] Unable to read fragment {name}: {error}."
+                    );
+                    None
+                }
+                Ok(data) => Some((name, data)),
+            }
+        })
+        .collect()
+}
+
+/// Merge the `fragments` into a toml configuration, in order. Lists are extended and tables
+/// merged, and other values replace those of the profile. The sounds, cues and banks added are
+/// marked with the fragment's name, so that they are not saved to the profile. Fragments that
+/// are not valid toml are logged and left out. Returns the other values merged in, for
+/// [`unmerge_value`].
+fn merge_fragments(config: &mut toml::Table, fragments: &[Fragment]) -> toml::Table {
+    let mut merged = toml::Table::new();
+    for (name, data) in fragments {
+        let fragment: toml::Table = match toml::from_str(data) {
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Configuration Loader] Unable to deserialize fragment {name}: {error}"
+                );
+                continue;
+            }
+            Ok(fragment) => fragment,
+        };
+        let fragment = upgrade_fragment(fragment);
+        log!("[Configuration Loader] Merging fragment {name}.");
+        for (key, mut value) in fragment {
+            if key == "version" {
+                continue;
+            }
+            if ["sounds", "cues", "banks"].contains(&key.as_str()) {
+                if let toml::Value::Array(items) = &mut value {
+                    for item in items.iter_mut().filter_map(toml::Value::as_table_mut) {
+                        item.insert("fragment".to_string(), toml::Value::String(name.clone()));
+                    }
+                }
+            } else {
+                merge_value(merged.entry(key.clone()), value.clone());
+            }
+            merge_value(config.entry(key), value);
+        }
+    }
+    merged
+}
+
+/// Upgrade a fragment like a profile, from version 1 if it has no version, since a fragment holds
+/// only the keys it sets. A fragment newer than this build is merged as it is.
+fn upgrade_fragment(mut fragment: toml::Table) -> toml::Table {
+    let version = fragment
+        .get("version")
+        .and_then(toml::Value::as_integer)
+        .and_then(|version| usize::try_from(version).ok())
+        .unwrap_or(1)
+        .max(1);
+    for migration in MIGRATIONS.get(version..).unwrap_or_default() {
+        migration(&mut fragment);
+    }
+    fragment
+}
+
+fn merge_value(entry: toml::map::Entry, value: toml::Value) {
+    let existing = match entry {
+        toml::map::Entry::Vacant(entry) => {
+            entry.insert(value);
+            return;
+        }
+        toml::map::Entry::Occupied(entry) => entry.into_mut(),
+    };
+    match (existing, value) {
+        (toml::Value::Array(existing), toml::Value::Array(items)) => existing.extend(items),
+        (toml::Value::Table(existing), toml::Value::Table(table)) => {
+            for (key, value) in table {
+                merge_value(existing.entry(key), value);
+            }
+        }
+        (existing, value) => *existing = value,
+    }
+}
+
+/// Replace the `merged` values with those at the same keys of the `serialized` configuration.
+/// Items merged into lists are the last items of the lists.
+fn normalize_merged(merged: &mut toml::Table, serialized: &toml::Table) {
+    for (key, merged_value) in merged.iter_mut() {
+        match (merged_value, serialized.get(key)) {
+            (toml::Value::Array(items), Some(toml::Value::Array(serialized_items))) => {
+                if let Some(start) = serialized_items.len().checked_sub(items.len()) {
+                    *items = serialized_items[start..].to_vec();
+                }
+            }
+            (toml::Value::Table(table), Some(toml::Value::Table(serialized_table))) => {
+                normalize_merged(table, serialized_table)
+            }
+            (merged_value, Some(serialized_value)) => *merged_value = serialized_value.clone(),
+            (_, None) => {}
+        }
+    }
+}
+
+/// Take the `merged` values out of `config` again, putting back the values of `profile` they
+/// replaced. Items merged into lists are removed, and values that changed since they were
+/// merged are kept.
+fn unmerge_value(config: &mut toml::Table, merged: &toml::Table, profile: &toml::Table) {
+    let empty = toml::Table::new();
+    for (key, merged_value) in merged {
+        let original = profile.get(key);
+        let Some(value) = config.get_mut(key) else {
+            continue;
+        };
+        let unmerged = match (value, merged_value) {
+            (toml::Value::Array(items), toml::Value::Array(merged_items)) => {
+                // Merged items were added after the profile's own.
+                for merged_item in merged_items {
+                    if let Some(i) = items.iter().rposition(|item| item == merged_item) {
+                        items.remove(i);
+                    }
+                }
+                items.is_empty()
+            }
+            (toml::Value::Table(table), toml::Value::Table(merged_table)) => {
+                let original = original.and_then(toml::Value::as_table).unwrap_or(&empty);
+                unmerge_value(table, merged_table, original);
+                table.is_empty()
+            }
+            (value, merged_value) if value == merged_value => {
+                if let Some(original) = original {
+                    *value = original.clone();
+                }
+                true
+            }
+            _ => false,
+        };
+        // Leave out what only the fragments had.
+        if unmerged && original.is_none() {
+            config.remove(key);
+        }
+    }
+}
+
+/// Upgrade a toml configuration to [`CONFIG_VERSION`] and deserialize it.
+/// Returns the configuration and the version it was upgraded from.
+pub fn migrate_config(mut config: toml::Table) -> Result<(Config, u32), String> {
+    let version = upgrade_config(&mut config)?;
+    Ok((deserialize_config(config)?, version))
+}
+
+/// Upgrade a profile's toml configuration to [`CONFIG_VERSION`], merge the `fragments` into it
+/// and deserialize it. Returns the configuration and the version it was upgraded from.
+fn merge_config(mut profile: toml::Table, fragments: &[Fragment]) -> Result<(Config, u32), String> {
+    let version = upgrade_config(&mut profile)?;
+    let mut config = profile.clone();
+    let mut merged = merge_fragments(&mut config, fragments);
+    let mut config = deserialize_config(config)?;
+    // Compare with the merged values as they are serialized, e.g. rounded to an f32.
+    if let Ok(toml::Value::Table(serialized)) = toml::Value::try_from(&config) {
+        normalize_merged(&mut merged, &serialized);
+    }
+    config.fragment_changes = FragmentChanges { merged, profile };
+    Ok((config, version))
+}
+
+/// Upgrade a toml configuration to [`CONFIG_VERSION`] in place.
+/// Returns the version it was upgraded from.
+fn upgrade_config(config: &mut toml::Table) -> Result<u32, String> {
+    let version = match config.get("version") {
+        None => 0,
+        Some(version) => match version.as_integer().and_then(|v| u32::try_from(v).ok()) {
+            None => return Err(format!("Invalid configuration version {version}.")),
+            Some(version) => version,
+        },
+    };
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "The configuration is version {version}, which is newer than this soundboard \
+            (version {CONFIG_VERSION}). Update the soundboard to use it."
+        ));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(config);
+    }
+    config.insert(
+        "version".to_string(),
+        toml::Value::Integer(CONFIG_VERSION.into()),
+    );
+    Ok(version)
+}
+
+fn deserialize_config(config: toml::Table) -> Result<Config, String> {
+    match toml::Value::Table(config).try_into() {
+        Err(error) => Err(format!(
+            "Unable to deserialize configuration file: {error}."
+        )),
+        Ok(mut config) => {
+            Config::sanitize(&mut config);
+            Ok(config)
+        }
+    }
+}
+
+/// Parse and upgrade the toml configuration of `profile`, and merge the `fragments` into it.
+/// An upgraded configuration is backed up and written back to [`get_profile_path`] so the file
+/// matches what is loaded.
+fn parse_config(
+    profile: &str,
+    config_data: &str,
+    fragments: &[Fragment],
+) -> Result<Config, String> {
+    let (config, version) = match toml::from_str(config_data)
+        .map_err(|error| format!("Unable to deserialize configuration file: {error}."))
+        .and_then(|config| merge_config(config, fragments))
+    {
+        Err(error) => {
+            log!(Error, "[Configuration Loader] {error}");
+            return Err(error);
+        }
+        Ok(result) => result,
+    };
+    if version < CONFIG_VERSION {
+        log!(
+            "[Configuration Loader] Upgrading profile {profile} from version {version} to \
+            {CONFIG_VERSION}."
+        );
+        if let Err(error) = rotate_backups(profile) {
+            log!(
+                Error,
+                "[Configuration Loader] {error} Not upgrading the file."
+            );
+            return Ok(config);
+        }
+        let written = config.to_profile_string().and_then(|upgraded| {
+            fs::write(get_profile_path(profile)?, upgraded).map_err(|error| error.to_string())
+        });
+        if let Err(error) = written {
+            log!(
+                Error,
+                "[Configuration Loader] Unable to write upgraded configuration: {error}."
+            );
+        }
+    }
+    Ok(config)
+}
+
+/// Load the toml configuration of `profile` from [`get_profile_path`] with the configuration
+/// fragments merged into it.
+pub fn load_config(profile: &str) -> Result<Config, String> {
+    let config_file_path = get_profile_path(profile)?;
+    let fragments = read_fragments();
+    log!(
+        "[Configuration Loader] Loading configuration file \"{}\".",
+        config_file_path.display()
+    );
+
+    match fs::read_to_string(&config_file_path) {
+        Ok(config_data) => parse_config(profile, &config_data, &fragments),
+        Err(read_error) => {
+            log!(
+                Error,
+                "Unable to open configuration file: {read_error}. Installing default."
+            );
+            create_profile(profile)?;
+            match fs::read_to_string(&config_file_path) {
+                Err(read_error) => {
+                    log!(Error, "[Configuration Loader] Unable to open newly created configuration file: {read_error}.");
+                    Err(format!(
+                        "Unable to open newly created configuration file: {read_error}."
+                    ))
+                }
+                Ok(serialized_config) => parse_config(profile, &serialized_config, &fragments),
+            }
+        }
+    }
+}
+
+/// Read the configuration of `profile` like [`load_config`], without installing a missing
+/// profile or writing an upgraded one back. Returns the version the profile was saved as.
+pub fn read_config(profile: &str) -> Result<(Config, u32), String> {
+    let config_file_path = get_profile_path(profile)?;
+    let config_data = fs::read_to_string(&config_file_path).map_err(|error| {
+        format!(
+            "Unable to read configuration file \"{}\": {error}.",
+            config_file_path.display()
+        )
+    })?;
+    let config = toml::from_str(&config_data)
+        .map_err(|error| format!("Unable to deserialize configuration file: {error}."))?;
+    merge_config(config, &read_fragments())
+}
+
+pub struct ConfigSaver {
+    profile: String,
+    last_serialized: String,
+    /// The configuration fragments last loaded.
+    fragments: Vec<Fragment>,
+    last_saved: SystemTime,
+    last_backup: Option<SystemTime>,
+    autosave_interval: Duration,
+    /// Set when `save` was called inside the autosave interval, so a change may be unsaved.
+    autosave_pending: bool,
+    /// Never write the profile, e.g. because it could not be loaded.
+    read_only: bool,
+}
+
+impl ConfigSaver {
+    /// The number of backups kept of each profile.
+    pub const BACKUPS: usize = 5;
+    /// The shortest time between backups, so that autosaving does not replace every backup
+    /// within a few minutes. The first save of each session is always backed up.
+    const BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+    pub fn new(profile: String, autosave_interval: Duration) -> Self {
+        Self {
+            profile,
+            last_serialized: String::new(),
+            fragments: read_fragments(),
+            last_saved: SystemTime::now(),
+            last_backup: None,
+            autosave_interval,
+            autosave_pending: false,
+            read_only: false,
+        }
+    }
+
+    /// Create a saver that never writes `profile`, for a profile that could not be loaded, so
+    /// the file is left as it is.
+    pub fn read_only(profile: String, autosave_interval: Duration) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(profile, autosave_interval)
+        }
+    }
+
+    /// Return the name of the profile being saved.
+    #[inline]
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Load the configuration file again if another program changed it or the configuration
+    /// fragments since it was last saved. Returns None if nothing changed. A read-only profile
+    /// that loads is saved again from then on.
+    pub fn reload(&mut self) -> Result<Option<Config>, String> {
+        let config_file_path = get_profile_path(&self.profile)?;
+        let config_data = match fs::read_to_string(&config_file_path) {
+            Err(error) => return Err(format!("Unable to read configuration file: {error}.")),
+            Ok(config_data) => config_data,
+        };
+        let fragments = read_fragments();
+        if config_data == self.last_serialized && fragments == self.fragments {
+            return Ok(None);
+        }
+        let config = parse_config(&self.profile, &config_data, &fragments)?;
+        self.fragments = fragments;
+        self.read_only = false;
+        // Leave the file as it was written until the configuration is changed here.
+        self.last_serialized = config.to_profile_string().unwrap_or(config_data);
+        Ok(Some(config))
+    }
+
+    /// Check if `config` was changed since it was last saved or loaded. The changes of a
+    /// read-only profile are never saved, so they do not count.
+    pub fn unsaved(&self, config: &Config) -> bool {
+        !self.read_only
+            && config
+                .to_profile_string()
+                .is_ok_and(|serialized_config| serialized_config != self.last_serialized)
+    }
+
+    /// Save the toml configuration to [`get_profile_path`] if the autosave interval has elapsed.
+    /// Returns true if saved, false if not saved, or a string describing an error.
+    pub fn save(&mut self, config: &Config) -> Result<bool, String> {
+        if SystemTime::now() - self.autosave_interval < self.last_saved {
+            self.autosave_pending = true;
+            return Ok(false);
+        }
+        self.save_now(config)
+    }
+
+    /// Return how long until `save` saves again, if it was skipped since the last save.
+    pub fn autosave_pending(&self) -> Option<Duration> {
+        self.autosave_pending.then(|| {
+            (self.last_saved + self.autosave_interval)
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Save the toml configuration to [`get_profile_path`] regardless of the autosave interval.
+    /// Returns true if saved, false if not saved, or a string describing an error.
+    pub fn save_now(&mut self, config: &Config) -> Result<bool, String> {
+        self.autosave_pending = false;
+        if self.read_only {
+            return Ok(false);
+        }
+        match config.to_profile_string() {
+            Err(error) => {
+                log!(Error, "[Configuration Saver] {error}");
+                Err(error)
+            }
+            Ok(serialized_config) => {
+                if serialized_config == self.last_serialized {
+                    return Ok(false);
+                }
+                self.last_serialized = serialized_config;
+                self.last_saved = SystemTime::now();
+                let config_file_path = get_profile_path(&self.profile)?;
+                if self.last_backup.is_none_or(|last_backup| {
+                    last_backup.elapsed().unwrap_or_default() >= Self::BACKUP_INTERVAL
+                }) {
+                    // A failed backup is not a reason to lose the changes.
+                    match rotate_backups(&self.profile) {
+                        Err(error) => log!(Error, "[Configuration Saver] {error}"),
+                        Ok(()) => self.last_backup = Some(SystemTime::now()),
+                    }
+                }
+                log!(
+                    "[Configuration Saver] Saving configuration file \"{}\".",
+                    config_file_path.display()
+                );
+                match fs::write(&config_file_path, &self.last_serialized) {
+                    Err(error) => {
+                        log!(
+                            Error,
+                            "[Configuration Saver] Unable to write configuration file: {error}."
+                        );
+                        Err(format!("Unable to write configuration file: {error}."))
+                    }
+                    Ok(_) => Ok(true),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point the configuration directory at a new temporary directory, shared by every test.
+    fn use_test_config_directory() {
+        static DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+        let directory = DIRECTORY.get_or_init(|| {
+            let directory =
+                std::env::temp_dir().join(format!("soundboard-config-test-{}", std::process::id()));
+            fs::create_dir_all(directory.join("profiles")).unwrap();
+            directory
+        });
+        set_config_directory(directory.clone());
+    }
+
+    /// A sound named `name` in a `[[sounds]]` table, followed by `extra` keys.
+    fn sound_toml(name: &str, extra: &str) -> String {
+        format!(
+            "[[sounds]]\npath = \"{name}.wav\"\nname = \"{name}\"\nvolume = 0.0\n\
+            key = \"KEY_RESERVED\"\n{extra}\n"
+        )
+    }
+
+    /// A version 1 configuration: the defaults with a sound that overlaps and one that does not.
+    fn v1_config() -> String {
+        include_str!("default_config.toml")
+            .replace("version = 3", "version = 1")
+            .replace("volume_db = ", "volume = ")
+            .replace("sounds = []\n", "")
+            + &sound_toml("Overlapping", "overlap = true")
+            + &sound_toml("Restarting", "overlap = false")
+    }
+
+    fn retriggers(config: &Config) -> Vec<Retrigger> {
+        config.sounds.iter().map(|sound| sound.retrigger).collect()
+    }
+
+    /// An unversioned configuration holding a sound for each of `speeds`.
+    fn config_with_speeds(speeds: &[&str]) -> toml::Table {
+        let mut data = String::new();
+        for (i, speed) in speeds.iter().enumerate() {
+            data.push_str(&format!(
+                "[[sounds]]\npath = \"{i}.wav\"\nname = \"{i}\"\nvolume = 0.0\n\
+                key = \"KEY_RESERVED\"\nspeed = {speed}\n"
+            ));
+        }
+        toml::from_str(&data).unwrap()
+    }
+
+    #[test]
+    fn v0_is_migrated() {
+        // Version 0 had no version and only the keys that were set.
+        let data = sound_toml("Overlapping", "overlap = true");
+        let (config, version) = migrate_config(toml::from_str(&data).unwrap()).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.http.port, HttpConfig::default().port);
+        assert_eq!(retriggers(&config), [Retrigger::Overlap]);
+    }
+
+    #[test]
+    fn v1_is_migrated() {
+        let (config, version) = migrate_config(toml::from_str(&v1_config()).unwrap()).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(
+            retriggers(&config),
+            [Retrigger::Overlap, Retrigger::Restart]
+        );
+    }
+
+    #[test]
+    fn v2_volumes_are_renamed() {
+        let data = include_str!("default_config.toml")
+            .replace("version = 3", "version = 2")
+            .replace("volume_db = 1.0", "volume = -3.0")
+            .replace("outputs = {}\n", "")
+            .replace("sounds = []\n", "")
+            + "[outputs.Speakers]\nvolume = -6.0\nmute = \"KEY_RESERVED\"\n\
+            [microphone]\ndevice = \"\"\nvolume = 2.0\nmute = \"KEY_RESERVED\"\n"
+            + &sound_toml("Quiet", "");
+        let (config, version) = migrate_config(toml::from_str(&data).unwrap()).unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(config.volume_db, -3.0);
+        assert_eq!(config.outputs["Speakers"].volume_db, -6.0);
+        assert_eq!(config.microphone.volume_db, 2.0);
+        assert_eq!(config.sounds[0].volume_db, 0.0);
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let data = format!("version = {}", CONFIG_VERSION + 1);
+        assert!(migrate_config(toml::from_str(&data).unwrap()).is_err());
+    }
+
+    #[test]
+    fn newer_profiles_are_left_as_they_are() {
+        use_test_config_directory();
+        let profile = "Newer";
+        let data = format!("version = {}\nvolume_db = 2.0\n", CONFIG_VERSION + 1);
+        fs::write(get_profile_path(profile).unwrap(), &data).unwrap();
+
+        assert!(load_config(profile).is_err());
+        let mut saver = ConfigSaver::read_only(profile.to_string(), Duration::ZERO);
+        assert_eq!(saver.save_now(&default_config()), Ok(false));
+        assert_eq!(
+            fs::read_to_string(get_profile_path(profile).unwrap()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn unsaved_changes_are_found() {
+        use_test_config_directory();
+        let profile = "Unsaved";
+        let mut config = default_config();
+        let mut saver = ConfigSaver::new(profile.to_string(), Duration::ZERO);
+        assert!(saver.unsaved(&config));
+        assert_eq!(saver.save_now(&config), Ok(true));
+        assert!(!saver.unsaved(&config));
+
+        config.volume_db = -3.0;
+        assert!(saver.unsaved(&config));
+        // A reload from another program counts as saved, so only changes made after it count.
+        fs::write(
+            get_profile_path(profile).unwrap(),
+            config.to_profile_string().unwrap(),
+        )
+        .unwrap();
+        assert!(saver.reload().unwrap().is_some());
+        assert!(!saver.unsaved(&config));
+        assert!(!ConfigSaver::read_only(profile.to_string(), Duration::ZERO).unsaved(&config));
+    }
+
+    #[test]
+    fn upgrade_is_backed_up_first() {
+        use_test_config_directory();
+        let profile = "Upgrade";
+        let original = v1_config();
+        fs::write(get_profile_path(profile).unwrap(), &original).unwrap();
+
+        let config = load_config(profile).unwrap();
+        assert_eq!(
+            fs::read_to_string(get_backup_path(profile, 1).unwrap()).unwrap(),
+            original
+        );
+        let upgraded: toml::Table =
+            toml::from_str(&fs::read_to_string(get_profile_path(profile).unwrap()).unwrap())
+                .unwrap();
+        assert_eq!(
+            upgraded.get("version").and_then(toml::Value::as_integer),
+            Some(CONFIG_VERSION.into())
+        );
+        assert_eq!(
+            retriggers(&config),
+            [Retrigger::Overlap, Retrigger::Restart]
+        );
+    }
+
+    /// A profile with a remote device, and a fragment adding a remote device and a sound and
+    /// replacing the volume and an integration.
+    fn profile_and_fragment() -> (toml::Table, Vec<Fragment>) {
+        let profile = include_str!("default_config.toml")
+            .replace("remote_devices = []", "remote_devices = [\"Keyboard\"]");
+        let fragment = "remote_devices = [\"Pedal\"]\nvolume = -0.1\n[integrations]\nhttp = true\n"
+            .to_string()
+            + &sound_toml("Shared", "");
+        (
+            toml::from_str(&profile).unwrap(),
+            vec![("shared.toml".to_string(), fragment)],
+        )
+    }
+
+    #[test]
+    fn fragments_are_merged() {
+        let (profile, fragments) = profile_and_fragment();
+        let (config, _) = merge_config(profile, &fragments).unwrap();
+        assert_eq!(config.remote_devices, ["Keyboard", "Pedal"]);
+        assert_eq!(config.volume_db, -0.1);
+        assert!(config.integrations.http);
+        assert_eq!(config.sounds.len(), 1);
+    }
+
+    #[test]
+    fn fragments_are_not_saved() {
+        let (profile, fragments) = profile_and_fragment();
+        let (config, _) = merge_config(profile, &fragments).unwrap();
+        let saved = config.to_profile_string().unwrap();
+        let saved_profile: toml::Table = toml::from_str(&saved).unwrap();
+
+        // Loading and saving again changes nothing.
+        let (reloaded, _) = merge_config(saved_profile.clone(), &fragments).unwrap();
+        assert_eq!(reloaded.remote_devices, ["Keyboard", "Pedal"]);
+        assert_eq!(reloaded.to_profile_string().unwrap(), saved);
+
+        let (alone, _) = merge_config(saved_profile, &[]).unwrap();
+        assert_eq!(alone.remote_devices, ["Keyboard"]);
+        assert_eq!(alone.volume_db, 1.0);
+        assert!(!alone.integrations.http);
+        assert!(alone.sounds.is_empty());
+        // Without fragments, the profile is written as before.
+        assert_eq!(
+            alone.to_profile_string().unwrap(),
+            toml::to_string_pretty(&alone).unwrap()
+        );
+    }
+
+    #[test]
+    fn changed_fragment_values_are_saved() {
+        let (profile, fragments) = profile_and_fragment();
+        let (mut config, _) = merge_config(profile, &fragments).unwrap();
+        config.volume_db = -6.0;
+        config.remote_devices.push("Mouse".to_string());
+        let saved = toml::from_str(&config.to_profile_string().unwrap()).unwrap();
+        let (alone, _) = merge_config(saved, &[]).unwrap();
+        assert_eq!(alone.remote_devices, ["Keyboard", "Mouse"]);
+        assert_eq!(alone.volume_db, -6.0);
+    }
+
+    #[test]
+    fn unknown_shortcuts_are_kept() {
+        let shortcuts: ShortcutsConfig =
+            toml::from_str("pause = \"KEY_P\"\nfuture_action = \"KEY_F:TRIPLE_TAP\"").unwrap();
+        assert_eq!(
+            shortcuts.bindings.keys().collect::<Vec<_>>(),
+            [&ShortcutAction::Pause]
+        );
+        assert_eq!(
+            toml::to_string(&shortcuts).unwrap(),
+            "pause = \"KEY_P\"\nfuture_action = \"KEY_F:TRIPLE_TAP\"\n"
+        );
+    }
+
+    #[test]
+    fn key_buttons_round_trip() {
+        let parse = |name: &str| serde_json::from_str::<KeyButton>(&format!("\"{name}\""));
+        let round_trip = |name: &str| serde_json::to_string(&parse(name).unwrap()).unwrap();
+        assert_eq!(round_trip("KEY_F5"), "\"KEY_F5\"");
+        assert_eq!(round_trip("KEY_F5:LONG_PRESS"), "\"KEY_F5:LONG_PRESS\"");
+
+        // Modifiers are stored left-handed and in a fixed order.
+        let chord = parse("KEY_LEFTSHIFT+KEY_RIGHTCTRL+KEY_F5:DOUBLE_TAP").unwrap();
+        assert_eq!(chord.input, Input::Key(Key::KEY_F5));
+        assert_eq!(chord.modifiers, [Key::KEY_LEFTCTRL, Key::KEY_LEFTSHIFT]);
+        assert_eq!(chord.trigger, KeyTrigger::DoubleTap);
+        assert_eq!(
+            chord.to_string(),
+            "KEY_LEFTCTRL+KEY_LEFTSHIFT+KEY_F5:DOUBLE_TAP"
+        );
+
+        for wheel in Wheel::ALL {
+            assert_eq!(parse(wheel.name()).unwrap().input, Input::Wheel(wheel));
+            assert_eq!(round_trip(wheel.name()), format!("\"{}\"", wheel.name()));
+        }
+        for button in GamepadButton::ALL {
+            assert_eq!(parse(button.name()).unwrap().input, Input::Gamepad(button));
+            assert_eq!(round_trip(button.name()), format!("\"{}\"", button.name()));
+        }
+        assert_eq!(
+            round_trip("KEY_LEFTALT+WHEEL_UP"),
+            "\"KEY_LEFTALT+WHEEL_UP\""
+        );
+
+        assert!(parse("KEY_A+KEY_F5").is_err());
+        assert!(parse("KEY_F5:TRIPLE_TAP").is_err());
+        assert!(parse("KEY_NOT_A_KEY").is_err());
+    }
+
+    #[test]
+    fn invalid_speeds_are_replaced() {
+        let (config, _) = migrate_config(config_with_speeds(&[
+            "2.0", "0.0", "-1.0", "10.0", "nan", "inf",
+        ]))
+        .unwrap();
+        let speeds: Vec<f32> = config.sounds.iter().map(|sound| sound.speed).collect();
+        assert_eq!(speeds, [2.0, 0.25, 0.25, 4.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn long_output_delays_are_clamped() {
+        let mut data = String::new();
+        for (name, delay_ms) in [("Speakers", 150), ("Stream", 60_000)] {
+            data.push_str(&format!(
+                "[outputs.{name}]\nvolume_db = 0.0\nmute = \"KEY_RESERVED\"\ndelay_ms = {delay_ms}\n"
+            ));
+        }
+        let (config, _) = migrate_config(toml::from_str(&data).unwrap()).unwrap();
+        assert_eq!(config.outputs["Speakers"].delay_ms, 150);
+        assert_eq!(config.outputs["Stream"].delay_ms, MAX_OUTPUT_DELAY_MS);
+    }
+
+    #[test]
+    fn sounds_are_routed_to_groups() {
+        let everywhere = SoundConfig::default();
+        assert!(everywhere.plays_on("") && everywhere.plays_on("stream"));
+        let stream = SoundConfig {
+            groups: vec!["stream".to_string()],
+            ..SoundConfig::default()
+        };
+        assert!(stream.plays_on("stream"));
+        assert!(!stream.plays_on("local") && !stream.plays_on(""));
+    }
+
+    #[test]
+    fn choke_groups_cut_by_default() {
+        let mut config: Config = toml::from_str(include_str!("default_config.toml")).unwrap();
+        for group in ["callers", "", "callers"] {
+            config.sounds.push(SoundConfig {
+                choke_group: group.to_string(),
+                ..SoundConfig::default()
+            });
+        }
+        config.choke_groups.insert(
+            "radio".to_string(),
+            ChokeGroupConfig {
+                policy: ChokePolicy::Queue,
+            },
+        );
+        assert_eq!(config.choke_group_names(), ["callers"]);
+        assert_eq!(config.choke_policy("callers"), ChokePolicy::Cut);
+        assert_eq!(config.choke_policy("radio"), ChokePolicy::Queue);
+    }
+
+    #[test]
+    fn random_groups_pick_variants() {
+        let group = RandomGroupConfig::default();
+        assert_eq!(group.pick(&[3, 5, 8], None, 4), Some(5));
+        assert_eq!(group.pick(&[3], Some(3), 7), Some(3));
+        assert_eq!(group.pick::<usize>(&[], None, 0), None);
+
+        let group = RandomGroupConfig { no_repeat: true };
+        for random in 0..10 {
+            assert_ne!(group.pick(&[3, 5, 8], Some(5), random), Some(5));
+        }
+        // A single variant is played again.
+        assert_eq!(group.pick(&[5], Some(5), 1), Some(5));
+    }
+
+    #[test]
+    fn sequences_advance() {
+        let members = [2, 4, 7];
+        let sequence = SequenceConfig::default();
+        assert_eq!(sequence.triggered(&members, None), Some(2));
+        assert_eq!(sequence.triggered(&members, Some(2)), Some(4));
+        assert_eq!(sequence.triggered(&members, Some(7)), Some(2));
+        assert_eq!(sequence.after(&members, 2), None);
+
+        let sequence = SequenceConfig {
+            advance: SequenceAdvance::Finish,
+        };
+        assert_eq!(sequence.triggered(&members, Some(4)), Some(2));
+        assert_eq!(sequence.after(&members, 4), Some(7));
+        assert_eq!(sequence.after(&members, 7), None);
+        assert_eq!(sequence.triggered::<usize>(&[], None), None);
+    }
+
+    #[test]
+    fn startup_profile_is_chosen() {
+        let profiles = ["Default".to_string(), "Stream".to_string()];
+        assert_eq!(startup_profile(&profiles[..1], None).unwrap(), "Default");
+        assert_eq!(
+            startup_profile(&profiles, Some("Stream")).unwrap(),
+            "Stream"
+        );
+        assert_eq!(startup_profile(&profiles, Some("Deleted")), None);
+        assert_eq!(startup_profile(&profiles, None), None);
+    }
+
+    #[test]
+    fn themes_follow_the_system() {
+        let theme: ThemeConfig =
+            toml::from_str("mode = \"light\"\nreduced_motion = \"on\"").unwrap();
+        assert!(theme.high_contrast.enabled(true));
+        assert!(!theme.high_contrast.enabled(false));
+        assert!(theme.reduced_motion.enabled(false));
+        assert!(!SystemSetting::Off.enabled(true));
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex, MutexGuard, TryLockError,
+};
+
+/// The number of times [`lock`] had to wait for another thread.
+static CONTENDED_LOCKS: AtomicU64 = AtomicU64::new(0);
+
+/// Lock `mutex` like `mutex.lock().unwrap()`, counting the locks that had to wait for another
+/// thread. Used for the locks taken on the audio threads, where waiting can cause dropouts.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.try_lock() {
+        Err(TryLockError::WouldBlock) => {
+            CONTENDED_LOCKS.fetch_add(1, Ordering::Relaxed);
+            mutex.lock().unwrap()
+        }
+        result => result.unwrap(),
+    }
+}
+
+/// Return the number of locks taken with [`lock`] that had to wait since startup.
+pub fn contended_locks() -> u64 {
+    CONTENDED_LOCKS.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, thread, time::Duration};
+
+    #[test]
+    fn waiting_locks_are_counted() {
+        let mutex = Arc::new(Mutex::new(0));
+        let before = contended_locks();
+        let guard = lock(&mutex);
+        let waiting = {
+            let mutex = mutex.clone();
+            thread::spawn(move || *lock(&mutex) += 1)
+        };
+        thread::sleep(Duration::from_millis(50));
+        drop(guard);
+        waiting.join().unwrap();
+        assert_eq!(*lock(&mutex), 1);
+        assert!(contended_locks() > before);
+    }
+}
@@ -0,0 +1,970 @@
+use crate::atomic::{AtomicF32, AtomicF64, AtomicOptionF32};
+use rodio::Source;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Return the amplitude factor of a gain of `db` dB. Every volume is in dB until it is applied
+/// to the samples.
+pub fn db_to_amplitude(db: f32) -> f32 {
+    10_f32.powf(db / 20.0)
+}
+
+/// Measures the loudness of everything played on an output device.
+/// Sources are mixed after they are metered, so the level assumes they are uncorrelated.
+pub struct LevelMeter {
+    /// The sum of squared samples divided by samples per second since the last measurement.
+    energy: AtomicF64,
+    last_measured: Mutex<Instant>,
+}
+
+impl Default for LevelMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LevelMeter {
+    pub fn new() -> Self {
+        Self {
+            energy: AtomicF64::default(),
+            last_measured: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Return the RMS level in dBFS since the last call.
+    pub fn take_level(&self) -> f32 {
+        let energy = self.energy.take();
+        let mut last_measured = self.last_measured.lock().unwrap();
+        let elapsed = last_measured.elapsed().as_secs_f64().max(0.001);
+        *last_measured = Instant::now();
+        (10.0 * (energy / elapsed).max(1e-10).log10()) as f32
+    }
+
+    fn add(&self, energy: f64) {
+        self.energy.add(energy);
+    }
+}
+
+/// Passes samples through while adding their energy to a [`LevelMeter`].
+pub struct Metered<S> {
+    inner: S,
+    meter: Arc<LevelMeter>,
+    energy: f64,
+    samples: usize,
+}
+
+impl<S> Metered<S> {
+    /// The number of samples between updates of the meter.
+    const FLUSH_INTERVAL: usize = 1024;
+
+    pub fn new(inner: S, meter: Arc<LevelMeter>) -> Self {
+        Self {
+            inner,
+            meter,
+            energy: 0.0,
+            samples: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        self.meter.add(std::mem::take(&mut self.energy));
+        self.samples = 0;
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Metered<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = match self.inner.next() {
+            None => {
+                self.flush();
+                return None;
+            }
+            Some(sample) => sample,
+        };
+        let samples_per_second = self.inner.sample_rate() as f64 * self.inner.channels() as f64;
+        self.energy += (sample as f64).powi(2) / samples_per_second.max(1.0);
+        self.samples += 1;
+        if self.samples >= Self::FLUSH_INTERVAL {
+            self.flush();
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Metered<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// A biquad filter in direct form I.
+#[derive(Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    /// The last two inputs and outputs.
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// Measures the integrated loudness of a file in LUFS as in ITU-R BS.1770 and EBU R128: the
+/// K-weighted energy of 400 ms blocks overlapping by 75 %, gated at -70 LUFS and 10 LU below
+/// the loudness of the blocks above that. Every channel is weighted the same.
+pub struct LoudnessMeter {
+    /// The K-weighting filters of each channel: a high shelf and a high pass.
+    filters: Vec<(Biquad, Biquad)>,
+    channel: usize,
+    /// The frames in 100 ms, the frames and the energy of the current 100 ms so far.
+    step_frames: usize,
+    frames: usize,
+    energy: f64,
+    /// The mean energy of every 100 ms.
+    steps: Vec<f64>,
+}
+
+impl LoudnessMeter {
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        let rate = sample_rate.max(1) as f64;
+        let shelf = {
+            let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+            let k = (std::f64::consts::PI * f0 / rate).tan();
+            let vh = 10_f64.powf(gain_db / 20.0);
+            let vb = vh.powf(0.4996667741545416);
+            let a0 = 1.0 + k / q + k * k;
+            Biquad::new(
+                [
+                    (vh + vb * k / q + k * k) / a0,
+                    2.0 * (k * k - vh) / a0,
+                    (vh - vb * k / q + k * k) / a0,
+                ],
+                [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            )
+        };
+        let high_pass = {
+            let (f0, q) = (38.13547087602444, 0.5003270373238773);
+            let k = (std::f64::consts::PI * f0 / rate).tan();
+            let a0 = 1.0 + k / q + k * k;
+            Biquad::new(
+                [1.0, -2.0, 1.0],
+                [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            )
+        };
+        Self {
+            filters: vec![(shelf, high_pass); channels.max(1) as usize],
+            channel: 0,
+            step_frames: (sample_rate as usize / 10).max(1),
+            frames: 0,
+            energy: 0.0,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Add the next sample. Samples follow each other channel by channel.
+    pub fn add(&mut self, sample: f32) {
+        let (shelf, high_pass) = &mut self.filters[self.channel];
+        let weighted = high_pass.process(shelf.process(sample as f64));
+        self.energy += weighted * weighted;
+        self.channel += 1;
+        if self.channel == self.filters.len() {
+            self.channel = 0;
+            self.frames += 1;
+            if self.frames == self.step_frames {
+                self.steps.push(self.energy / self.frames as f64);
+                self.frames = 0;
+                self.energy = 0.0;
+            }
+        }
+    }
+
+    /// Return the integrated loudness in LUFS of the samples added, or None if they are silent.
+    /// A file shorter than a block is measured as one block.
+    pub fn integrated(&self) -> Option<f32> {
+        let mut steps = self.steps.clone();
+        if self.frames > 0 {
+            steps.push(self.energy / self.frames as f64);
+        }
+        let blocks: Vec<f64> = if steps.len() < 4 {
+            vec![steps.iter().sum::<f64>() / steps.len().max(1) as f64]
+        } else {
+            steps
+                .windows(4)
+                .map(|block| block.iter().sum::<f64>() / 4.0)
+                .collect()
+        };
+        let loudness = |energy: f64| -0.691 + 10.0 * energy.log10();
+        let gated_mean = |threshold: f64| {
+            let gated: Vec<f64> = blocks
+                .iter()
+                .copied()
+                .filter(|energy| loudness(*energy) > threshold)
+                .collect();
+            (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+        };
+        let relative_gate = loudness(gated_mean(-70.0)?) - 10.0;
+        Some(loudness(gated_mean(relative_gate.max(-70.0))?) as f32)
+    }
+}
+
+/// Work arounds for a miswired output, e.g. a venue feed, that can be changed while playing.
+/// They are applied to each frame in the order of the fields.
+#[derive(Default)]
+pub struct ChannelFixes {
+    /// Replace every channel with the average of the channels, e.g. for a venue PA fed from one
+    /// channel. The channel count is kept, so the same mono signal plays on every channel.
+    pub mono: AtomicBool,
+    /// Swap the first two channels.
+    pub swap: AtomicBool,
+    /// Invert the phase of the first channel.
+    pub invert_left: AtomicBool,
+    /// Invert the phase of the second channel.
+    pub invert_right: AtomicBool,
+}
+
+/// Applies [`ChannelFixes`] to a source.
+pub struct ChannelFix<S> {
+    inner: S,
+    fixes: Arc<ChannelFixes>,
+    /// The frame being played and the position in it.
+    frame: Vec<f32>,
+    position: usize,
+}
+
+impl<S> ChannelFix<S> {
+    pub fn new(inner: S, fixes: Arc<ChannelFixes>) -> Self {
+        Self {
+            inner,
+            fixes,
+            frame: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for ChannelFix<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.frame.len() {
+            // Read the next frame. A source that ends part way through a frame ends here too.
+            self.frame.clear();
+            self.position = 0;
+            let channels = self.inner.channels().max(1);
+            self.frame
+                .extend(self.inner.by_ref().take(channels as usize));
+            if self.fixes.mono.load(Ordering::Relaxed) && !self.frame.is_empty() {
+                let average = self.frame.iter().sum::<f32>() / self.frame.len() as f32;
+                self.frame.fill(average);
+            }
+            if self.frame.len() >= 2 {
+                if self.fixes.swap.load(Ordering::Relaxed) {
+                    self.frame.swap(0, 1);
+                }
+                if self.fixes.invert_left.load(Ordering::Relaxed) {
+                    self.frame[0] = -self.frame[0];
+                }
+                if self.fixes.invert_right.load(Ordering::Relaxed) {
+                    self.frame[1] = -self.frame[1];
+                }
+            }
+        }
+        let sample = self.frame.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl<S: Source<Item = f32>> Source for ChannelFix<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        // Include the samples read ahead from the inner source.
+        let buffered = self.frame.len().saturating_sub(self.position);
+        self.inner
+            .current_frame_len()
+            .map(|length| length + buffered)
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Plays channel `i` of a source on channel `map[i]` of an output with more channels, counting
+/// from 0, and silence on the other channels, e.g. a stereo mix on channels 3 and 4 of an audio
+/// interface. Channels mapped to a channel the output does not have are left out. With a single
+/// channel in `map`, the channels of the source are averaged onto it, so a stereo mix played on
+/// one channel keeps its right channel.
+pub struct ChannelRoute<S> {
+    inner: S,
+    map: Vec<u16>,
+    channels: u16,
+    /// The frame read from the inner source, and the routed frame being played with the
+    /// position in it.
+    input: Vec<f32>,
+    frame: Vec<f32>,
+    position: usize,
+}
+
+impl<S: Source<Item = f32>> ChannelRoute<S> {
+    pub fn new(inner: S, map: Vec<u16>, channels: u16) -> Self {
+        Self {
+            inner,
+            map,
+            channels: channels.max(1),
+            input: Vec::new(),
+            frame: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for ChannelRoute<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.frame.len() {
+            self.input.clear();
+            let channels = self.inner.channels().max(1);
+            self.input
+                .extend(self.inner.by_ref().take(channels as usize));
+            if self.input.is_empty() {
+                return None;
+            }
+            self.frame.clear();
+            self.frame.resize(self.channels as usize, 0.0);
+            if let [channel] = self.map[..] {
+                let sum: f32 = self.input.iter().sum();
+                if let Some(output) = self.frame.get_mut(channel as usize) {
+                    *output = sum / self.input.len() as f32;
+                }
+            } else {
+                for (sample, channel) in self.input.iter().zip(&self.map) {
+                    if let Some(output) = self.frame.get_mut(*channel as usize) {
+                        *output += sample;
+                    }
+                }
+            }
+            self.position = 0;
+        }
+        let sample = self.frame[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for ChannelRoute<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        let buffered = self.frame.len().saturating_sub(self.position);
+        self.inner.current_frame_len().map(|length| {
+            length / self.inner.channels().max(1) as usize * self.channels as usize + buffered
+        })
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Places a source between the left and right channels by lowering the other side, from -1 (left
+/// only) through 0 (unchanged) to 1 (right only). Only the first two channels are changed.
+pub struct Panned<S> {
+    inner: S,
+    pan: Arc<AtomicF32>,
+    /// The channel of the next sample and the gains of the current frame.
+    channel: u16,
+    gains: (f32, f32),
+}
+
+impl<S> Panned<S> {
+    pub fn new(inner: S, pan: Arc<AtomicF32>) -> Self {
+        Self {
+            inner,
+            pan,
+            channel: 0,
+            gains: (1.0, 1.0),
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Panned<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.channel == 0 {
+            let pan = self.pan.load().clamp(-1.0, 1.0);
+            self.gains = ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0));
+        }
+        let sample = self.inner.next()?;
+        let channels = self.inner.channels().max(1);
+        let gain = match (channels, self.channel) {
+            (1, _) => 1.0,
+            (_, 0) => self.gains.0,
+            (_, 1) => self.gains.1,
+            _ => 1.0,
+        };
+        self.channel = (self.channel + 1) % channels;
+        Some(sample * gain)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Panned<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Keeps the mix of an output below a ceiling, so stacked sounds do not clip. The gain drops at
+/// once on a peak above the ceiling and recovers over [`Limiter::RELEASE`]. The ceiling is shared
+/// with the output, in dBFS, or None to pass the mix through.
+pub struct Limiter<S> {
+    inner: S,
+    ceiling_db: Arc<AtomicOptionF32>,
+    /// The ceiling as an amplitude, or None for no limit.
+    ceiling: Option<f32>,
+    /// The peak the gain is following, decaying towards the ceiling.
+    envelope: f32,
+    /// The factor the envelope decays by each sample.
+    decay: f32,
+    /// The samples left until the ceiling is read again.
+    countdown: usize,
+}
+
+impl<S: Source<Item = f32>> Limiter<S> {
+    /// How long the gain takes to recover by 60 dB after a peak.
+    const RELEASE: Duration = Duration::from_millis(200);
+    /// The number of samples played between reads of the ceiling.
+    const BLOCK: usize = 64;
+
+    pub fn new(inner: S, ceiling_db: Arc<AtomicOptionF32>) -> Self {
+        let samples_per_second = inner.sample_rate() as f32 * inner.channels() as f32;
+        Self {
+            decay: 0.001_f32.powf(1.0 / (Self::RELEASE.as_secs_f32() * samples_per_second)),
+            inner,
+            ceiling_db,
+            ceiling: None,
+            envelope: 0.0,
+            countdown: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Limiter<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.countdown == 0 {
+            self.countdown = Self::BLOCK;
+            self.ceiling = self.ceiling_db.load().map(db_to_amplitude);
+        }
+        self.countdown -= 1;
+        let sample = self.inner.next()?;
+        let Some(ceiling) = self.ceiling else {
+            return Some(sample);
+        };
+        self.envelope = (self.envelope * self.decay).max(sample.abs()).max(ceiling);
+        Some(sample * ceiling / self.envelope)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Limiter<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// Sets a flag shared with the output when a sample of the mix is above full scale, so the
+/// window can show that the output clips.
+pub struct ClipDetector<S> {
+    inner: S,
+    clipped: Arc<AtomicBool>,
+}
+
+impl<S: Source<Item = f32>> ClipDetector<S> {
+    pub fn new(inner: S, clipped: Arc<AtomicBool>) -> Self {
+        Self { inner, clipped }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for ClipDetector<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.inner.next()?;
+        if sample.abs() > 1.0 {
+            self.clipped.store(true, Ordering::Relaxed);
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for ClipDetector<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+/// How far and how fast [`Ducker`] lowers the other sounds.
+#[derive(Clone, Copy, Default)]
+pub struct DuckingSettings {
+    pub depth_db: f32,
+    /// How long lowering the sounds takes.
+    pub attack: Duration,
+    /// How long raising them back takes.
+    pub release: Duration,
+    /// How far every sound is lowered during talk-over, see [`Ducker::set_talk_over`].
+    pub talk_over_db: f32,
+}
+
+/// Lowers the sounds that are not a priority while a priority sound plays, so alerts cut
+/// through background loops. Shared by every output device.
+#[derive(Default)]
+pub struct Ducker {
+    /// The number of [`DuckingHold`]s held by playing priority sources.
+    holds: AtomicUsize,
+    /// Set while every sound, priority sounds included, is lowered by hand.
+    talk_over: AtomicBool,
+    /// The [`DuckingSettings`], read by the audio threads without a lock, with the times in
+    /// seconds.
+    depth_db: AtomicF32,
+    attack: AtomicF32,
+    release: AtomicF32,
+    talk_over_db: AtomicF32,
+}
+
+impl Ducker {
+    /// Return true while a priority source plays.
+    pub fn active(&self) -> bool {
+        self.holds.load(Ordering::SeqCst) > 0
+    }
+
+    pub fn set_settings(&self, settings: DuckingSettings) {
+        self.depth_db.store(settings.depth_db);
+        self.attack.store(settings.attack.as_secs_f32());
+        self.release.store(settings.release.as_secs_f32());
+        self.talk_over_db.store(settings.talk_over_db);
+    }
+
+    /// Lower every sound by the talk-over depth, e.g. while a key is held to talk over them, or
+    /// raise them back.
+    pub fn set_talk_over(&self, talk_over: bool) {
+        self.talk_over.store(talk_over, Ordering::SeqCst);
+    }
+
+    pub fn talking_over(&self) -> bool {
+        self.talk_over.load(Ordering::SeqCst)
+    }
+
+    /// Return the attenuation in dB of a source `elapsed` seconds after it was at `attenuation`.
+    /// It moves towards the depth while a priority sound plays (unless the source is one) or
+    /// the talk-over depth during talk-over, whichever is deeper, and back to none otherwise,
+    /// covering the depth in the attack or release time.
+    fn attenuation(&self, attenuation: f32, elapsed: f32, priority: bool) -> f32 {
+        let depth = if priority {
+            0.0
+        } else {
+            self.depth_db.load().max(0.0)
+        };
+        let mut target = if self.active() { depth } else { 0.0 };
+        if self.talking_over() {
+            target = target.max(self.talk_over_db.load().max(0.0));
+        }
+        let time = if target >= attenuation {
+            self.attack.load()
+        } else {
+            self.release.load()
+        };
+        let step = attenuation.max(target).max(depth) * elapsed / time;
+        if !step.is_finite() || (target - attenuation).abs() <= step {
+            target
+        } else if target > attenuation {
+            attenuation + step
+        } else {
+            attenuation - step
+        }
+    }
+}
+
+/// Keeps a [`Ducker`] active while held, kept by a priority source on the audio thread so the
+/// other sounds are lowered however often the window is drawn. Released when dropped.
+pub struct DuckingHold {
+    ducker: Arc<Ducker>,
+    held: bool,
+}
+
+impl DuckingHold {
+    /// Create a hold that is already held, so the other sounds are lowered from the first sample.
+    pub fn new(ducker: Arc<Ducker>) -> Self {
+        ducker.holds.fetch_add(1, Ordering::SeqCst);
+        Self { ducker, held: true }
+    }
+
+    /// Hold or release the ducker, e.g. while the source is paused.
+    pub fn set_held(&mut self, held: bool) {
+        if held != self.held {
+            self.held = held;
+            if held {
+                self.ducker.holds.fetch_add(1, Ordering::SeqCst);
+            } else {
+                self.ducker.holds.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+impl Drop for DuckingHold {
+    fn drop(&mut self) {
+        self.set_held(false);
+    }
+}
+
+/// Lowers a source while its [`Ducker`] is active, ramping the attenuation so the change is not
+/// heard as a click.
+pub struct Ducked<S> {
+    inner: S,
+    ducker: Option<Arc<Ducker>>,
+    /// Priority sources are only lowered during talk-over.
+    priority: bool,
+    /// The current attenuation in dB and its amplitude factor.
+    attenuation: f32,
+    factor: f32,
+    /// The samples left until the attenuation is updated.
+    countdown: usize,
+}
+
+impl<S> Ducked<S> {
+    /// The number of samples played between updates of the attenuation.
+    const BLOCK: usize = 64;
+
+    pub fn new(inner: S, ducker: Option<Arc<Ducker>>, priority: bool) -> Self {
+        Self {
+            inner,
+            ducker,
+            priority,
+            attenuation: 0.0,
+            factor: 1.0,
+            countdown: 0,
+        }
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Ducked<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.countdown == 0 {
+            self.countdown = Self::BLOCK;
+            if let Some(ducker) = &self.ducker {
+                let samples_per_second =
+                    self.inner.sample_rate() as f32 * self.inner.channels() as f32;
+                let elapsed = Self::BLOCK as f32 / samples_per_second;
+                self.attenuation = ducker.attenuation(self.attenuation, elapsed, self.priority);
+                self.factor = db_to_amplitude(-self.attenuation);
+            }
+        }
+        self.countdown -= 1;
+        self.inner.next().map(|sample| sample * self.factor)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Ducked<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+    use rodio::source::UniformSourceIterator;
+
+    #[test]
+    fn ducker_covers_depth_in_attack_and_release_time() {
+        let ducker = Arc::new(Ducker::default());
+        ducker.set_settings(DuckingSettings {
+            depth_db: 12.0,
+            attack: Duration::from_millis(50),
+            release: Duration::from_millis(500),
+            talk_over_db: 0.0,
+        });
+        let hold = DuckingHold::new(ducker.clone());
+        assert_eq!(ducker.attenuation(0.0, 0.025, false), 6.0);
+        assert_eq!(ducker.attenuation(6.0, 0.025, false), 12.0);
+        assert_eq!(ducker.attenuation(12.0, 1.0, false), 12.0);
+        drop(hold);
+        assert_eq!(ducker.attenuation(12.0, 0.25, false), 6.0);
+        assert_eq!(ducker.attenuation(6.0, 1.0, false), 0.0);
+
+        // Without a ramp, the attenuation changes at once.
+        ducker.set_settings(DuckingSettings {
+            depth_db: 12.0,
+            attack: Duration::ZERO,
+            release: Duration::ZERO,
+            talk_over_db: 0.0,
+        });
+        let _hold = DuckingHold::new(ducker.clone());
+        assert_eq!(ducker.attenuation(0.0, 0.001, false), 12.0);
+    }
+
+    #[test]
+    fn ducker_is_active_while_a_hold_is_held() {
+        let ducker = Arc::new(Ducker::default());
+        let mut first = DuckingHold::new(ducker.clone());
+        let second = DuckingHold::new(ducker.clone());
+        first.set_held(false);
+        assert!(ducker.active());
+        drop(second);
+        assert!(!ducker.active());
+        first.set_held(true);
+        assert!(ducker.active());
+        drop(first);
+        assert!(!ducker.active());
+    }
+
+    #[test]
+    fn priority_sounds_are_not_ducked() {
+        let ducker = Arc::new(Ducker::default());
+        ducker.set_settings(DuckingSettings {
+            depth_db: 20.0,
+            attack: Duration::ZERO,
+            release: Duration::ZERO,
+            talk_over_db: 0.0,
+        });
+        let _hold = DuckingHold::new(ducker.clone());
+        let samples = || SamplesBuffer::new(1, 48000, vec![0.5; 128]);
+        let ducked: Vec<f32> = Ducked::new(samples(), Some(ducker.clone()), false).collect();
+        let priority: Vec<f32> = Ducked::new(samples(), Some(ducker), true).collect();
+        assert!(ducked.iter().all(|sample| (sample - 0.05).abs() < 1e-6));
+        assert!(priority.iter().all(|sample| *sample == 0.5));
+    }
+
+    #[test]
+    fn talk_over_lowers_every_sound() {
+        let ducker = Arc::new(Ducker::default());
+        ducker.set_settings(DuckingSettings {
+            depth_db: 6.0,
+            attack: Duration::from_millis(100),
+            release: Duration::from_millis(1000),
+            talk_over_db: 20.0,
+        });
+        ducker.set_talk_over(true);
+        assert_eq!(ducker.attenuation(0.0, 0.05, false), 10.0);
+        assert_eq!(ducker.attenuation(10.0, 0.05, true), 20.0);
+
+        // The deeper of the two applies, and the release ramps back to the ducking depth.
+        let _hold = DuckingHold::new(ducker.clone());
+        assert_eq!(ducker.attenuation(20.0, 1.0, false), 20.0);
+        ducker.set_talk_over(false);
+        assert_eq!(ducker.attenuation(20.0, 0.5, false), 10.0);
+        assert_eq!(ducker.attenuation(10.0, 1.0, false), 6.0);
+        assert_eq!(ducker.attenuation(20.0, 1.0, true), 0.0);
+    }
+
+    #[test]
+    fn loudness_of_a_sine() {
+        // A 1 kHz sine at -20 dBFS on one channel is -23 LUFS, on two channels 3 LU louder.
+        for (channels, expected) in [(1, -23.0), (2, -20.0)] {
+            let mut meter = LoudnessMeter::new(channels, 48000);
+            for i in 0..48000 * 2 {
+                let sample = 0.1 * (std::f32::consts::TAU * 1000.0 * i as f32 / 48000.0).sin();
+                for _ in 0..channels {
+                    meter.add(sample);
+                }
+            }
+            let loudness = meter.integrated().unwrap();
+            assert!((loudness - expected).abs() < 0.1, "{loudness} LUFS");
+        }
+        assert_eq!(LoudnessMeter::new(2, 48000).integrated(), None);
+    }
+
+    #[test]
+    fn pan_lowers_the_other_side() {
+        let pan = Arc::new(AtomicF32::new(-0.5));
+        let source = SamplesBuffer::new(2, 48000, vec![1.0; 4]);
+        let samples: Vec<f32> = Panned::new(source, pan.clone()).collect();
+        assert_eq!(samples, [1.0, 0.5, 1.0, 0.5]);
+
+        pan.store(1.0);
+        let source = SamplesBuffer::new(3, 48000, vec![1.0; 3]);
+        let samples: Vec<f32> = Panned::new(source, pan.clone()).collect();
+        assert_eq!(samples, [0.0, 1.0, 1.0]);
+
+        // A mono source has no sides.
+        let source = SamplesBuffer::new(1, 48000, vec![1.0; 2]);
+        let samples: Vec<f32> = Panned::new(source, pan).collect();
+        assert_eq!(samples, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn limiter_keeps_peaks_below_the_ceiling() {
+        let ceiling_db = Arc::new(AtomicOptionF32::default());
+        ceiling_db.store(Some(-6.0));
+        let ceiling = 10_f32.powf(-6.0 / 20.0);
+        let loud = SamplesBuffer::new(2, 48000, vec![1.5; 9600]);
+        let limited: Vec<f32> = Limiter::new(loud, ceiling_db.clone()).collect();
+        assert!(limited.iter().all(|sample| *sample <= ceiling + 1e-6));
+        assert!((limited[9599] - ceiling).abs() < 1e-4);
+
+        // Quiet samples after the peak come back up to their level.
+        let mut samples = vec![1.5; 64];
+        samples.extend([0.1; 48000]);
+        let limited: Vec<f32> =
+            Limiter::new(SamplesBuffer::new(1, 48000, samples), ceiling_db.clone()).collect();
+        assert!(limited[64] < 0.05);
+        assert!((limited[48063] - 0.1).abs() < 1e-6);
+
+        ceiling_db.store(None);
+        let loud = SamplesBuffer::new(1, 48000, vec![1.5; 128]);
+        assert!(Limiter::new(loud, ceiling_db).all(|sample| sample == 1.5));
+    }
+
+    #[test]
+    fn clipping_is_detected() {
+        let clipped = Arc::new(AtomicBool::new(false));
+        let full_scale = SamplesBuffer::new(1, 48000, vec![1.0, -1.0, 0.5]);
+        ClipDetector::new(full_scale, clipped.clone()).for_each(drop);
+        assert!(!clipped.load(Ordering::Relaxed));
+        let over = SamplesBuffer::new(1, 48000, vec![0.5, -1.01, 0.5]);
+        ClipDetector::new(over, clipped.clone()).for_each(drop);
+        assert!(clipped.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn channel_fix_inverts_after_swapping() {
+        let fixes = Arc::new(ChannelFixes::default());
+        fixes.swap.store(true, Ordering::SeqCst);
+        fixes.invert_left.store(true, Ordering::SeqCst);
+        let source = SamplesBuffer::new(2, 48000, vec![0.25, 0.5, 0.25, 0.5]);
+        let samples: Vec<f32> = ChannelFix::new(source, fixes.clone()).collect();
+        assert_eq!(samples, [-0.5, 0.25, -0.5, 0.25]);
+
+        fixes.swap.store(false, Ordering::SeqCst);
+        fixes.invert_left.store(false, Ordering::SeqCst);
+        fixes.invert_right.store(true, Ordering::SeqCst);
+        // A mono file played on a stereo output has a right channel to invert.
+        let file = SamplesBuffer::new(1, 48000, vec![0.25, 0.5]);
+        let source = UniformSourceIterator::<_, f32>::new(file, 2, 48000);
+        let samples: Vec<f32> = ChannelFix::new(source, fixes).collect();
+        assert_eq!(samples, [0.25, -0.25, 0.5, -0.5]);
+    }
+
+    #[test]
+    fn channel_route_plays_on_the_mapped_channels() {
+        let source = SamplesBuffer::new(2, 48000, vec![0.25, 0.5, -0.25, -0.5]);
+        let route = ChannelRoute::new(source, vec![2, 3], 4);
+        assert_eq!(route.channels(), 4);
+        let samples: Vec<f32> = route.collect();
+        assert_eq!(samples, [0.0, 0.0, 0.25, 0.5, 0.0, 0.0, -0.25, -0.5]);
+
+        // A channel the output does not have is left out.
+        let source = SamplesBuffer::new(2, 48000, vec![0.25, 0.5]);
+        let samples: Vec<f32> = ChannelRoute::new(source, vec![0, 7], 2).collect();
+        assert_eq!(samples, [0.25, 0.0]);
+
+        // A single channel gets both channels of a stereo source.
+        let source = SamplesBuffer::new(2, 48000, vec![0.25, 0.5]);
+        let samples: Vec<f32> = ChannelRoute::new(source, vec![1], 3).collect();
+        assert_eq!(samples, [0.0, 0.375, 0.0]);
+    }
+}
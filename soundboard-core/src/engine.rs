@@ -0,0 +1,345 @@
+use crate::audio::{
+    audio_host, play_routed, AudioControls, OutputDevice, SoundPlayback, SoundStart,
+    SYSTEM_DEFAULT_OUTPUT,
+};
+use crate::config::{Config, DuckingConfig, OutputConfig, SoundConfig};
+use crate::dsp::{Ducker, DuckingSettings};
+use rodio::cpal::traits::HostTrait;
+use rodio::DeviceTrait;
+use std::{sync::Arc, time::Duration};
+
+/// A soundboard without the user interface, for programs that play sounds on their own, such
+/// as chat bots and kiosks. Build one with [`SoundboardEngine::builder`].
+///
+/// Sounds play on every output, or on the outputs of their [`SoundConfig::groups`], with their
+/// volume, trim points, speed, pan, loop file and ducking. Play modes other than
+/// [`Retrigger::Overlap`](crate::config::Retrigger::Overlap) restart the sound, and
+/// normalization is not applied.
+///
+/// # Examples
+///
+/// ```no_run
+/// use soundboard_core::config::SoundConfig;
+/// use soundboard_core::engine::SoundboardEngine;
+///
+/// let mut engine = SoundboardEngine::builder()
+///     .output("System Default")
+///     .volume_db(-6.0)
+///     .sound(SoundConfig {
+///         name: "Airhorn".to_string(),
+///         path: "sounds/airhorn.mp3".to_string(),
+///         ..SoundConfig::default()
+///     })
+///     .build()?;
+/// let controls = engine.play("Airhorn")?;
+/// while !controls.finished() {
+///     std::thread::sleep(std::time::Duration::from_millis(100));
+/// }
+/// # Ok::<(), String>(())
+/// ```
+///
+/// A profile saved by the soundboard can be played as it is:
+///
+/// ```no_run
+/// use soundboard_core::config::load_config;
+/// use soundboard_core::engine::SoundboardEngine;
+///
+/// let engine = SoundboardEngine::builder()
+///     .profile(load_config("Stream")?)
+///     .build()?;
+/// for sound in engine.sounds() {
+///     println!("{}", sound.name);
+/// }
+/// # Ok::<(), String>(())
+/// ```
+pub struct SoundboardEngine {
+    /// The open outputs by name, each with the name of its output group.
+    outputs: Vec<(String, String, OutputDevice)>,
+    sounds: Vec<SoundConfig>,
+    playback: Vec<SoundPlayback>,
+    volume_db: f32,
+    ducker: Arc<Ducker>,
+}
+
+impl SoundboardEngine {
+    /// Start building an engine without outputs or sounds.
+    pub fn builder() -> SoundboardEngineBuilder {
+        SoundboardEngineBuilder {
+            outputs: Vec::new(),
+            sounds: Vec::new(),
+            volume_db: 0.0,
+            ducking: DuckingConfig::default(),
+        }
+    }
+
+    pub fn sounds(&self) -> &[SoundConfig] {
+        &self.sounds
+    }
+
+    /// Return the names of the open outputs.
+    pub fn outputs(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|(name, _, _)| name.as_str())
+    }
+
+    /// Play the first sound named `name` and return its controls.
+    pub fn play(&mut self, name: &str) -> Result<Arc<AudioControls>, String> {
+        let i = self.index(name)?;
+        self.play_index(i)
+    }
+
+    /// Play the sound at index `i` of [`SoundboardEngine::sounds`] and return its controls.
+    pub fn play_index(&mut self, i: usize) -> Result<Arc<AudioControls>, String> {
+        let Some(sound) = self.sounds.get(i) else {
+            return Err(format!("There is no sound {i}."));
+        };
+        let start = SoundStart::new(
+            sound,
+            self.volume_db + sound.volume_db,
+            Duration::ZERO,
+            Some(self.ducker.clone()),
+        );
+        let played = play_routed(
+            self.outputs
+                .iter_mut()
+                .map(|(_, group, device)| (group.as_str(), device)),
+            std::slice::from_ref(&start),
+            |_, group| sound.plays_on(group),
+        );
+        if !played[0] {
+            return Err(format!("No output could play {}.", sound.name));
+        }
+        self.playback[i].add_start(&start, sound.instance_limit());
+        Ok(start.controls)
+    }
+
+    /// Stop every instance of the first sound named `name`.
+    pub fn stop(&mut self, name: &str) -> Result<(), String> {
+        let i = self.index(name)?;
+        self.playback[i].stop(None);
+        Ok(())
+    }
+
+    pub fn stop_all(&self) {
+        for playback in &self.playback {
+            playback.stop(None);
+        }
+    }
+
+    /// Pause or resume every sound playing.
+    pub fn set_playing(&self, playing: bool) {
+        for playback in &self.playback {
+            playback.set_playing(playing);
+        }
+    }
+
+    /// Check if the first sound named `name` is playing or paused.
+    pub fn is_playing(&self, name: &str) -> bool {
+        self.index(name).is_ok_and(|i| !self.playback[i].idle())
+    }
+
+    /// Set the volume added to every sound in dB, including the sounds playing.
+    pub fn set_volume_db(&mut self, volume_db: f32) {
+        self.volume_db = volume_db;
+        for (sound, playback) in self.sounds.iter().zip(&self.playback) {
+            playback.set_volume_db(volume_db + sound.volume_db);
+        }
+    }
+
+    /// Mute or unmute the output `name`.
+    pub fn set_output_muted(&self, name: &str, muted: bool) -> Result<(), String> {
+        match self.outputs.iter().find(|(output, _, _)| output == name) {
+            None => Err(format!("There is no output {name}.")),
+            Some((_, _, device)) => {
+                device.set_muted(muted);
+                Ok(())
+            }
+        }
+    }
+
+    /// Lower every sound by the talk-over depth of the ducking settings, or raise them back.
+    pub fn set_talk_over(&self, talk_over: bool) {
+        self.ducker.set_talk_over(talk_over);
+    }
+
+    fn index(&self, name: &str) -> Result<usize, String> {
+        self.sounds
+            .iter()
+            .position(|sound| sound.name == name)
+            .ok_or_else(|| format!("There is no sound named {name}."))
+    }
+}
+
+/// Collects the outputs and sounds of a [`SoundboardEngine`]. Created by
+/// [`SoundboardEngine::builder`].
+pub struct SoundboardEngineBuilder {
+    outputs: Vec<(String, OutputConfig)>,
+    sounds: Vec<SoundConfig>,
+    volume_db: f32,
+    ducking: DuckingConfig,
+}
+
+impl SoundboardEngineBuilder {
+    /// Play on the output device `name` with the default settings. [`SYSTEM_DEFAULT_OUTPUT`]
+    /// plays on the default output device of the system.
+    pub fn output(self, name: impl Into<String>) -> Self {
+        self.output_with(name, OutputConfig::default())
+    }
+
+    /// Play on the output device `name` with the volume, delay, limiter, channel fixes, stream
+    /// format, channel map and group of `config`. Its mute key bind is not used.
+    pub fn output_with(mut self, name: impl Into<String>, config: OutputConfig) -> Self {
+        self.outputs.push((name.into(), config));
+        self
+    }
+
+    /// Add `sound`, played by [`SoundboardEngine::play`] with its name.
+    pub fn sound(mut self, sound: SoundConfig) -> Self {
+        self.sounds.push(sound);
+        self
+    }
+
+    /// Set the volume added to every sound in dB.
+    pub fn volume_db(mut self, volume_db: f32) -> Self {
+        self.volume_db = volume_db;
+        self
+    }
+
+    /// Set how far and how fast priority sounds lower the other sounds.
+    pub fn ducking(mut self, ducking: DuckingConfig) -> Self {
+        self.ducking = ducking;
+        self
+    }
+
+    /// Add the outputs and the sounds of a profile, and use its volume and ducking settings.
+    /// Hidden sounds and the outputs of disabled groups are left out, and the volume of each
+    /// output group is added to its outputs.
+    pub fn profile(mut self, mut config: Config) -> Self {
+        for (name, mut output) in std::mem::take(&mut config.outputs) {
+            let group = config
+                .output_groups
+                .get(&output.group)
+                .cloned()
+                .unwrap_or_default();
+            if !output.group.is_empty() {
+                if !group.enabled {
+                    continue;
+                }
+                output.volume_db += group.volume_db;
+            }
+            self.outputs.push((name, output));
+        }
+        self.sounds
+            .extend(config.sounds.into_iter().filter(|sound| !sound.hidden()));
+        self.volume_db = config.volume_db;
+        self.ducking = config.ducking;
+        self
+    }
+
+    /// Open the outputs. Fails if there are none or one is not connected or cannot be opened.
+    pub fn build(self) -> Result<SoundboardEngine, String> {
+        if self.outputs.is_empty() {
+            return Err("No outputs were added.".to_string());
+        }
+        let host = audio_host();
+        let mut devices: Vec<rodio::Device> = host
+            .output_devices()
+            .map_err(|error| format!("Unable to list the output devices: {error}."))?
+            .collect();
+        let mut outputs = Vec::with_capacity(self.outputs.len());
+        for (name, config) in self.outputs {
+            let device = if name == SYSTEM_DEFAULT_OUTPUT {
+                host.default_output_device()
+            } else {
+                devices
+                    .iter()
+                    .position(|device| device.name().is_ok_and(|device| device == name))
+                    .map(|i| devices.remove(i))
+            };
+            let Some(device) = device else {
+                return Err(format!("Output device \"{name}\" is not connected."));
+            };
+            let mut output = OutputDevice::new(device);
+            output.set_stream_format(config.sample_rate, config.buffer_frames);
+            output.set_channel_map(&config.channel_map);
+            output.set_delay(Duration::from_millis(config.delay_ms));
+            output.set_max_gain(config.max_gain_db);
+            output.set_limiter(config.limiter_db);
+            output.set_pan(config.pan);
+            output.set_mono(config.mono);
+            output.set_swap_channels(config.swap_channels);
+            output.set_inverted(config.invert_left, config.invert_right);
+            output.set_volume_db(config.volume_db);
+            output.enable();
+            if !output.enabled() {
+                return Err(format!("Unable to open output device \"{name}\"."));
+            }
+            outputs.push((name, config.group, output));
+        }
+
+        let ducker = Arc::new(Ducker::default());
+        ducker.set_settings(DuckingSettings {
+            depth_db: self.ducking.depth_db,
+            attack: Duration::from_millis(self.ducking.attack_ms),
+            release: Duration::from_millis(self.ducking.release_ms),
+            talk_over_db: self.ducking.talk_over_db,
+        });
+        let mut playback = Vec::new();
+        playback.resize_with(self.sounds.len(), SoundPlayback::default);
+        Ok(SoundboardEngine {
+            outputs,
+            sounds: self.sounds,
+            playback,
+            volume_db: self.volume_db,
+            ducker,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputGroupConfig;
+
+    #[test]
+    fn profiles_are_added() {
+        let mut config: Config = toml::from_str(include_str!("default_config.toml")).unwrap();
+        for (name, archived) in [("Airhorn", false), ("Old", true)] {
+            config.sounds.push(SoundConfig {
+                name: name.to_string(),
+                archived,
+                ..SoundConfig::default()
+            });
+        }
+        for (name, group) in [("Speakers", "local"), ("Cable", "stream")] {
+            config.outputs.insert(
+                name.to_string(),
+                OutputConfig {
+                    volume_db: -3.0,
+                    group: group.to_string(),
+                    ..OutputConfig::default()
+                },
+            );
+        }
+        config.output_groups.insert(
+            "local".to_string(),
+            OutputGroupConfig {
+                volume_db: -6.0,
+                ..OutputGroupConfig::default()
+            },
+        );
+        config.output_groups.insert(
+            "stream".to_string(),
+            OutputGroupConfig {
+                enabled: false,
+                ..OutputGroupConfig::default()
+            },
+        );
+
+        let builder = SoundboardEngine::builder().profile(config);
+        assert_eq!(builder.sounds.len(), 1);
+        assert_eq!(builder.outputs.len(), 1);
+        assert_eq!(builder.outputs[0].0, "Speakers");
+        assert_eq!(builder.outputs[0].1.volume_db, -9.0);
+        assert!(SoundboardEngine::builder().build().is_err());
+    }
+}
@@ -1,8 +1,15 @@
 //! Enum names and values were obtained from <https://github.com/torvalds/linux/blob/master/include/uapi/linux/input-event-codes.h>.
-use serde::{Deserialize, Serialize};
+//!
+//! Every enum converts to and from its Linux name (e.g. `KEY_A`) with
+//! [`Display`](std::fmt::Display), [`FromStr`] and serde, and to and from its value with `as u16`
+//! and `from_repr`.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 use strum_macros::AsRefStr;
-use strum_macros::FromRepr;
+use strum_macros::{Display, EnumString, FromRepr};
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
 pub enum Event {
     Synchronization(Synchronization),
@@ -19,6 +26,60 @@ pub enum Event {
 }
 
 impl Event {
+    /// Return the event with the raw `event_type` and `code` if both exist.
+    pub fn from_raw(event_type: u16, code: u16) -> Option<Event> {
+        Some(match EventType::from_repr(event_type)? {
+            EventType::EV_SYN => Event::Synchronization(Synchronization::from_repr(code)?),
+            EventType::EV_KEY => Event::Key(Key::from_repr(code)?),
+            EventType::EV_REL => Event::RelativeAxis(RelativeAxis::from_repr(code)?),
+            EventType::EV_ABS => Event::AbsoluteAxis(AbsoluteAxis::from_repr(code)?),
+            EventType::EV_MSC => Event::Miscellaneous(Miscellaneous::from_repr(code)?),
+            EventType::EV_SW => Event::Switch(Switch::from_repr(code)?),
+            EventType::EV_LED => Event::LED(LED::from_repr(code)?),
+            EventType::EV_SND => Event::Sound(Sound::from_repr(code)?),
+            EventType::EV_REP => Event::AutoRepeat(AutoRepeat::from_repr(code)?),
+            EventType::EV_FF => Event::ForceFeedback(ForceFeedback::from_repr(code)?),
+            EventType::EV_PWR => return None,
+            EventType::EV_FF_STATUS => {
+                Event::ForceFeedbackStatus(ForceFeedbackStatus::from_repr(code)?)
+            }
+        })
+    }
+
+    /// Return the type of this event.
+    pub fn event_type(&self) -> EventType {
+        match self {
+            Event::Synchronization(_) => EventType::EV_SYN,
+            Event::Key(_) => EventType::EV_KEY,
+            Event::RelativeAxis(_) => EventType::EV_REL,
+            Event::AbsoluteAxis(_) => EventType::EV_ABS,
+            Event::Miscellaneous(_) => EventType::EV_MSC,
+            Event::Switch(_) => EventType::EV_SW,
+            Event::LED(_) => EventType::EV_LED,
+            Event::Sound(_) => EventType::EV_SND,
+            Event::AutoRepeat(_) => EventType::EV_REP,
+            Event::ForceFeedback(_) => EventType::EV_FF,
+            Event::ForceFeedbackStatus(_) => EventType::EV_FF_STATUS,
+        }
+    }
+
+    /// Return the raw code of this event.
+    pub fn code(&self) -> u16 {
+        match *self {
+            Event::Synchronization(code) => code as u16,
+            Event::Key(code) => code as u16,
+            Event::RelativeAxis(code) => code as u16,
+            Event::AbsoluteAxis(code) => code as u16,
+            Event::Miscellaneous(code) => code as u16,
+            Event::Switch(code) => code as u16,
+            Event::LED(code) => code as u16,
+            Event::Sound(code) => code as u16,
+            Event::AutoRepeat(code) => code as u16,
+            Event::ForceFeedback(code) => code as u16,
+            Event::ForceFeedbackStatus(code) => code as u16,
+        }
+    }
+
     /// Return the string representing this event's code.
     pub fn code_as_ref(&self) -> &str {
         match self {
@@ -37,10 +98,64 @@ impl Event {
     }
 }
 
+/// Shows the name of the event's code, e.g. `KEY_A`.
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code_as_ref())
+    }
+}
+
+/// Parses the name of a code of any event type. The names are unique across event types.
+impl FromStr for Event {
+    type Err = strum::ParseError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        name.parse()
+            .map(Event::Key)
+            .or_else(|_| name.parse().map(Event::Synchronization))
+            .or_else(|_| name.parse().map(Event::RelativeAxis))
+            .or_else(|_| name.parse().map(Event::AbsoluteAxis))
+            .or_else(|_| name.parse().map(Event::Miscellaneous))
+            .or_else(|_| name.parse().map(Event::Switch))
+            .or_else(|_| name.parse().map(Event::LED))
+            .or_else(|_| name.parse().map(Event::Sound))
+            .or_else(|_| name.parse().map(Event::AutoRepeat))
+            .or_else(|_| name.parse().map(Event::ForceFeedback))
+            .or_else(|_| name.parse().map(Event::ForceFeedbackStatus))
+    }
+}
+
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code_as_ref())
+    }
+}
+
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| serde::de::Error::custom(format!("unknown event code {name}")))
+    }
+}
+
 // cSpell:disable
 /// Event types
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum EventType {
     EV_SYN = 0x00,
@@ -59,7 +174,20 @@ pub enum EventType {
 
 /// Synchronization events
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum Synchronization {
     SYN_REPORT = 0,
@@ -70,7 +198,19 @@ pub enum Synchronization {
 
 #[allow(non_camel_case_types, dead_code)]
 #[derive(
-    Clone, Copy, Eq, PartialEq, Hash, Debug, Default, Serialize, Deserialize, FromRepr, AsRefStr,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
 )]
 #[repr(u16)]
 /// Keys and buttons
@@ -696,7 +836,20 @@ pub enum Key {
 
 /// Relative axes
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum RelativeAxis {
     REL_X = 0x00,
@@ -716,7 +869,20 @@ pub enum RelativeAxis {
 
 /// Absolute axes
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum AbsoluteAxis {
     ABS_X = 0x00,
@@ -766,7 +932,20 @@ pub enum AbsoluteAxis {
 
 /// Switch events
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum Switch {
     SW_LID = 0x00,
@@ -790,7 +969,20 @@ pub enum Switch {
 
 /// Misc events
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum Miscellaneous {
     MSC_SERIAL = 0x00,
@@ -802,8 +994,26 @@ pub enum Miscellaneous {
 }
 
 /// LEDs
-#[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[allow(
+    non_camel_case_types,
+    dead_code,
+    clippy::upper_case_acronyms,
+    clippy::enum_variant_names
+)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum LED {
     LED_NUML = 0x00,
@@ -821,7 +1031,20 @@ pub enum LED {
 
 /// Autorepeat values
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum AutoRepeat {
     REP_DELAY = 0x00,
@@ -830,7 +1053,20 @@ pub enum AutoRepeat {
 
 /// Sounds
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum Sound {
     SND_CLICK = 0x00,
@@ -840,7 +1076,20 @@ pub enum Sound {
 
 /// Force feedback
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum ForceFeedback {
     FF_RUMBLE = 0x50,
@@ -863,9 +1112,82 @@ pub enum ForceFeedback {
 
 /// Force feedback status
 #[allow(non_camel_case_types, dead_code)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, FromRepr, AsRefStr)]
+#[derive(
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Debug,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    AsRefStr,
+    Display,
+    EnumString,
+)]
 #[repr(u16)]
 pub enum ForceFeedbackStatus {
     FF_STATUS_STOPPED = 0x00,
     FF_STATUS_PLAYING = 0x01,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every event of every type, by trying each raw code.
+    fn all_events() -> Vec<Event> {
+        (0..=EventType::EV_FF_STATUS as u16)
+            .flat_map(|event_type| (0..=0x2ff).map(move |code| (event_type, code)))
+            .filter_map(|(event_type, code)| Event::from_raw(event_type, code))
+            .collect()
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let events = all_events();
+        assert!(events.contains(&Event::Key(Key::KEY_A)));
+        assert!(events.contains(&Event::ForceFeedbackStatus(
+            ForceFeedbackStatus::FF_STATUS_PLAYING
+        )));
+        for event in events {
+            let json = serde_json::to_string(&event).unwrap();
+            assert_eq!(json, format!("\"{event}\""));
+            // The names are unique across event types, so each parses back to the same event.
+            assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+            assert_eq!(event.to_string().parse::<Event>().unwrap(), event);
+            assert_eq!(
+                Event::from_raw(event.event_type() as u16, event.code()),
+                Some(event)
+            );
+        }
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        let json = serde_json::to_string(&[Key::KEY_F5, Key::BTN_LEFT]).unwrap();
+        assert_eq!(json, "[\"KEY_F5\",\"BTN_LEFT\"]");
+        assert_eq!(
+            serde_json::from_str::<Vec<Key>>(&json).unwrap(),
+            [Key::KEY_F5, Key::BTN_LEFT]
+        );
+        assert_eq!(
+            serde_json::from_str::<RelativeAxis>("\"REL_WHEEL\"").unwrap(),
+            RelativeAxis::REL_WHEEL
+        );
+        assert_eq!(
+            serde_json::from_str::<EventType>("\"EV_KEY\"").unwrap(),
+            EventType::EV_KEY
+        );
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert!(serde_json::from_str::<Event>("\"KEY_NOT_A_KEY\"").is_err());
+        assert!(serde_json::from_str::<Key>("\"REL_WHEEL\"").is_err());
+        assert!(serde_json::from_str::<Event>("5").is_err());
+        assert_eq!(Event::from_raw(EventType::EV_PWR as u16, 0), None);
+        assert_eq!(Event::from_raw(0x1f, 0), None);
+    }
+}
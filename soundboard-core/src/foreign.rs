@@ -0,0 +1,285 @@
+use crate::config::SoundConfig;
+use serde_json::Value;
+use std::path::Path;
+use xml::reader::{EventReader, XmlEvent};
+
+/// A soundboard app whose sound lists can be imported. Key binds are not converted, since each
+/// app stores keys in its own way.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ForeignApp {
+    /// A sound list (.spl), saved from its File menu.
+    Soundpad,
+    /// A saved soundboard (.json).
+    ExpSoundboard,
+    /// A settings or profile file (.json or .xml).
+    Resanance,
+}
+
+impl ForeignApp {
+    pub const ALL: [ForeignApp; 3] = [
+        ForeignApp::Soundpad,
+        ForeignApp::ExpSoundboard,
+        ForeignApp::Resanance,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ForeignApp::Soundpad => "Soundpad",
+            ForeignApp::ExpSoundboard => "EXP Soundboard",
+            ForeignApp::Resanance => "Resanance",
+        }
+    }
+
+    /// The extensions of the files the app saves its sounds in.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            ForeignApp::Soundpad => &["spl", "xml"],
+            ForeignApp::ExpSoundboard => &["json"],
+            ForeignApp::Resanance => &["json", "xml"],
+        }
+    }
+
+    /// Return the sounds of the file `text` saved by the app, named after their titles or
+    /// files.
+    pub fn read(self, text: &str) -> Result<Vec<SoundConfig>, String> {
+        let sounds = match self {
+            ForeignApp::Soundpad => read_soundpad(text)?,
+            ForeignApp::ExpSoundboard => read_exp_soundboard(text)?,
+            ForeignApp::Resanance => read_resanance(text)?,
+        };
+        if sounds.is_empty() {
+            return Err(format!(
+                "No sounds were found. Is this a file saved by {}?",
+                self.label()
+            ));
+        }
+        Ok(sounds)
+    }
+}
+
+/// The audio file extensions looked for in files without a known layout.
+const AUDIO_EXTENSIONS: [&str; 8] = ["wav", "mp3", "ogg", "flac", "m4a", "aac", "wma", "opus"];
+
+/// The names of the fields holding the name of a sound, in order of preference.
+const NAME_KEYS: [&str; 3] = ["name", "title", "label"];
+
+/// Return a sound playing `path`, named `name` or after the file if it is empty.
+fn sound(path: &str, name: Option<&str>) -> SoundConfig {
+    let name = name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            // Windows paths are split on backslashes on every platform.
+            let file = path.rsplit(['/', '\\']).next().unwrap_or(path);
+            Path::new(file)
+                .file_stem()
+                .map_or(file.to_string(), |stem| stem.to_string_lossy().to_string())
+        });
+    SoundConfig {
+        name,
+        path: path.to_string(),
+        ..SoundConfig::default()
+    }
+}
+
+/// Check if `value` is the path of an audio file.
+fn is_audio_path(value: &str) -> bool {
+    let value = value.to_lowercase();
+    AUDIO_EXTENSIONS
+        .iter()
+        .any(|extension| value.ends_with(&format!(".{extension}")))
+}
+
+/// Read every element of the XML document `text` with its attributes and parent.
+fn xml_elements(text: &str) -> Result<Vec<XmlElement>, String> {
+    let mut elements = Vec::new();
+    let mut parents: Vec<XmlElement> = Vec::new();
+    for event in EventReader::from_str(text) {
+        match event.map_err(|error| format!("The file is not valid XML: {error}."))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let element = XmlElement {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, attribute.value))
+                        .collect(),
+                    parent: parents.last().map(|parent| {
+                        (
+                            parent.name.clone(),
+                            parent.attribute("name").unwrap_or_default(),
+                        )
+                    }),
+                };
+                parents.push(element.clone());
+                elements.push(element);
+            }
+            XmlEvent::EndElement { .. } => {
+                parents.pop();
+            }
+            _ => {}
+        }
+    }
+    Ok(elements)
+}
+
+/// An element read by [`xml_elements`].
+#[derive(Clone)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    /// The name of the parent element and its "name" attribute.
+    parent: Option<(String, String)>,
+}
+
+impl XmlElement {
+    fn attribute(&self, key: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// Read a Soundpad sound list: `<Sound url=".." title=".."/>` elements, and categories holding
+/// `<Sound id=".."/>` elements that refer to the sounds by their position, counting from 1. The
+/// first category of a sound becomes its bank.
+fn read_soundpad(text: &str) -> Result<Vec<SoundConfig>, String> {
+    let elements = xml_elements(text)?;
+    let mut sounds: Vec<SoundConfig> = elements
+        .iter()
+        .filter(|element| element.name == "Sound")
+        .filter_map(|element| {
+            let url = element.attribute("url")?;
+            let title = element.attribute("title");
+            let artist = element.attribute("artist").filter(|a| !a.is_empty());
+            let name = match (artist, title) {
+                (Some(artist), Some(title)) if !title.is_empty() => {
+                    Some(format!("{artist} - {title}"))
+                }
+                (_, title) => title,
+            };
+            Some(sound(&url, name.as_deref()))
+        })
+        .collect();
+    for element in &elements {
+        let Some((parent, category)) = &element.parent else {
+            continue;
+        };
+        let index = element
+            .attribute("id")
+            .and_then(|id| id.parse::<usize>().ok())
+            .and_then(|id| id.checked_sub(1));
+        if let (true, Some(sound)) = (
+            element.name == "Sound" && parent == "Category" && !category.is_empty(),
+            index.and_then(|index| sounds.get_mut(index)),
+        ) {
+            if sound.bank.is_empty() {
+                sound.bank = category.clone();
+            }
+        }
+    }
+    Ok(sounds)
+}
+
+/// Read a saved EXP Soundboard: `{"soundboardEntries": [{"fileString": ".."}]}`.
+fn read_exp_soundboard(text: &str) -> Result<Vec<SoundConfig>, String> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|error| format!("The file is not valid JSON: {error}."))?;
+    Ok(value["soundboardEntries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry["fileString"].as_str())
+        .map(|path| sound(path, None))
+        .collect())
+}
+
+/// Read a Resanance file. Its layout is not documented, so every value in it that is the path
+/// of an audio file becomes a sound, named by a name field next to it if there is one.
+fn read_resanance(text: &str) -> Result<Vec<SoundConfig>, String> {
+    if text.trim_start().starts_with('<') {
+        return Ok(xml_elements(text)?
+            .iter()
+            .filter_map(|element| {
+                let (_, path) = element
+                    .attributes
+                    .iter()
+                    .find(|(_, value)| is_audio_path(value))?;
+                let name = NAME_KEYS.iter().find_map(|key| element.attribute(key));
+                Some(sound(path, name.as_deref()))
+            })
+            .collect());
+    }
+    let value: Value = serde_json::from_str(text)
+        .map_err(|error| format!("The file is neither valid JSON nor XML: {error}."))?;
+    let mut sounds = Vec::new();
+    find_json_sounds(&value, &mut sounds);
+    Ok(sounds)
+}
+
+/// Add a sound to `sounds` for every audio file path in `value`.
+fn find_json_sounds(value: &Value, sounds: &mut Vec<SoundConfig>) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                find_json_sounds(value, sounds);
+            }
+        }
+        Value::Object(object) => {
+            let name = NAME_KEYS
+                .iter()
+                .find_map(|key| object.get(*key).and_then(Value::as_str))
+                .filter(|name| !is_audio_path(name));
+            for value in object.values() {
+                match value {
+                    Value::String(path) if is_audio_path(path) => sounds.push(sound(path, name)),
+                    _ => find_json_sounds(value, sounds),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_apps_are_read() {
+        let soundpad = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <Soundlist>
+                <Sound url="C:\Sounds\airhorn.mp3" artist="" title="Airhorn" duration="0:02"/>
+                <Sound url="C:\Sounds\Rimshot &amp; Cymbal.wav" artist="Drums" title="Rimshot"/>
+                <Categories>
+                    <Category name="Memes"><Sound id="2"/></Category>
+                </Categories>
+            </Soundlist>"#;
+        let sounds = ForeignApp::Soundpad.read(soundpad).unwrap();
+        assert_eq!(sounds.len(), 2);
+        assert_eq!(sounds[0].name, "Airhorn");
+        assert_eq!(sounds[1].name, "Drums - Rimshot");
+        assert_eq!(sounds[1].path, r"C:\Sounds\Rimshot & Cymbal.wav");
+        assert_eq!(sounds[1].bank, "Memes");
+
+        let exp = r#"{"soundboardEntries": [
+            {"fileString": "C:\\Sounds\\applause.ogg", "activationKeysNumbers": [29, 2]}
+        ]}"#;
+        let sounds = ForeignApp::ExpSoundboard.read(exp).unwrap();
+        assert_eq!(sounds[0].name, "applause");
+        assert_eq!(sounds[0].path, r"C:\Sounds\applause.ogg");
+
+        let resanance = r#"{"profiles": [{"sounds": [
+            {"name": "Sad Trombone", "file": "/home/me/trombone.flac", "volume": 80}
+        ]}]}"#;
+        let sounds = ForeignApp::Resanance.read(resanance).unwrap();
+        assert_eq!(sounds[0].name, "Sad Trombone");
+        assert_eq!(sounds[0].path, "/home/me/trombone.flac");
+
+        assert!(ForeignApp::ExpSoundboard.read("{}").is_err());
+        assert!(ForeignApp::Soundpad.read("not xml").is_err());
+    }
+}
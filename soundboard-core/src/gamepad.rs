@@ -0,0 +1,205 @@
+use crate::retry::ConnectionStatus;
+use crate::supervisor::spawn_supervised;
+use crate::waker::{Waker, WakingSender};
+use gilrs::{Button, EventType, Gilrs};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// A button of a gamepad connected to this computer, bound like a key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    LeftTrigger,
+    RightBumper,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 17] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::North,
+        GamepadButton::West,
+        GamepadButton::LeftBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightBumper,
+        GamepadButton::RightTrigger,
+        GamepadButton::Select,
+        GamepadButton::Start,
+        GamepadButton::Mode,
+        GamepadButton::LeftStick,
+        GamepadButton::RightStick,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+    ];
+
+    /// The name stored for a key bind, e.g. "GAMEPAD_SOUTH" for the bottom face button (A on
+    /// Xbox controllers).
+    pub fn name(self) -> &'static str {
+        match self {
+            GamepadButton::South => "GAMEPAD_SOUTH",
+            GamepadButton::East => "GAMEPAD_EAST",
+            GamepadButton::North => "GAMEPAD_NORTH",
+            GamepadButton::West => "GAMEPAD_WEST",
+            GamepadButton::LeftBumper => "GAMEPAD_LB",
+            GamepadButton::LeftTrigger => "GAMEPAD_LT",
+            GamepadButton::RightBumper => "GAMEPAD_RB",
+            GamepadButton::RightTrigger => "GAMEPAD_RT",
+            GamepadButton::Select => "GAMEPAD_SELECT",
+            GamepadButton::Start => "GAMEPAD_START",
+            GamepadButton::Mode => "GAMEPAD_MODE",
+            GamepadButton::LeftStick => "GAMEPAD_LEFT_STICK",
+            GamepadButton::RightStick => "GAMEPAD_RIGHT_STICK",
+            GamepadButton::DPadUp => "GAMEPAD_DPAD_UP",
+            GamepadButton::DPadDown => "GAMEPAD_DPAD_DOWN",
+            GamepadButton::DPadLeft => "GAMEPAD_DPAD_LEFT",
+            GamepadButton::DPadRight => "GAMEPAD_DPAD_RIGHT",
+        }
+    }
+
+    fn from_gilrs(button: Button) -> Option<Self> {
+        Some(match button {
+            Button::South => GamepadButton::South,
+            Button::East => GamepadButton::East,
+            Button::North => GamepadButton::North,
+            Button::West => GamepadButton::West,
+            Button::LeftTrigger => GamepadButton::LeftBumper,
+            Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+            Button::RightTrigger => GamepadButton::RightBumper,
+            Button::RightTrigger2 => GamepadButton::RightTrigger,
+            Button::Select => GamepadButton::Select,
+            Button::Start => GamepadButton::Start,
+            Button::Mode => GamepadButton::Mode,
+            Button::LeftThumb => GamepadButton::LeftStick,
+            Button::RightThumb => GamepadButton::RightStick,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            Button::C | Button::Z | Button::Unknown => return None,
+        })
+    }
+}
+
+/// Reads the buttons of the gamepads connected to this computer on a background thread until
+/// dropped.
+pub struct GamepadInput {
+    /// Each button pressed (true) or released (false).
+    receiver: Receiver<(GamepadButton, bool)>,
+    /// The names of the connected gamepads.
+    names: Arc<Mutex<Vec<String>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl GamepadInput {
+    /// How long the thread waits for an event before checking if it should stop.
+    const POLL_TIMEOUT: Duration = Duration::from_millis(250);
+
+    /// Start reading gamepads. `waker` is called whenever a button is pressed or released or a
+    /// gamepad is connected or disconnected.
+    pub fn start(waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let sender = WakingSender::new(sender, waker.clone());
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_names = names.clone();
+        let thread_stop = stop.clone();
+        spawn_supervised(
+            "Gamepads".to_string(),
+            Arc::new(Mutex::new(ConnectionStatus::Connected)),
+            stop.clone(),
+            move || {
+                // Created on this thread, since it cannot be sent between threads on every
+                // platform.
+                let mut gilrs = match Gilrs::new() {
+                    Err(error) => {
+                        log!(Error, "[Gamepads] Unable to read gamepads: {error}");
+                        return;
+                    }
+                    Ok(gilrs) => gilrs,
+                };
+                let update_names = |gilrs: &Gilrs| {
+                    *thread_names.lock().unwrap() = gilrs
+                        .gamepads()
+                        .map(|(_, gamepad)| gamepad.name().to_string())
+                        .collect();
+                    waker.wake();
+                };
+                update_names(&gilrs);
+                log!("[Gamepads] Reading gamepads.");
+
+                while !thread_stop.load(Ordering::SeqCst) {
+                    let event = match gilrs.next_event_blocking(Some(Self::POLL_TIMEOUT)) {
+                        None => continue,
+                        Some(event) => event,
+                    };
+                    match event.event {
+                        EventType::ButtonPressed(button, _)
+                        | EventType::ButtonReleased(button, _) => {
+                            let pressed = matches!(event.event, EventType::ButtonPressed(..));
+                            if let Some(button) = GamepadButton::from_gilrs(button) {
+                                if sender.send((button, pressed)).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        EventType::Connected => {
+                            log!("[Gamepads] Connected {}.", gilrs.gamepad(event.id).name());
+                            update_names(&gilrs);
+                        }
+                        EventType::Disconnected => {
+                            log!(
+                                "[Gamepads] Disconnected {}.",
+                                gilrs.gamepad(event.id).name()
+                            );
+                            update_names(&gilrs);
+                        }
+                        _ => (),
+                    }
+                }
+            },
+        );
+        Self {
+            receiver,
+            names,
+            stop,
+        }
+    }
+
+    /// Retrieve the buttons pressed (true) or released (false) since this was last called.
+    pub fn events(&self) -> Vec<(GamepadButton, bool)> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Return the names of the connected gamepads.
+    pub fn names(&self) -> Vec<String> {
+        self.names.lock().unwrap().clone()
+    }
+}
+
+impl Drop for GamepadInput {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,182 @@
+//! Translations of the user interface. A language is a TOML file holding its name, the
+//! translations of English strings and optionally a translated manual:
+//!
+//! ```toml
+//! name = "Deutsch"
+//!
+//! [strings]
+//! "Settings" = "Einstellungen"
+//! ```
+//!
+//! Strings without a translation are shown in English.
+
+use crate::config::get_language_directory;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{Mutex, RwLock},
+};
+
+/// The code of English, the language the soundboard is written in.
+pub const ENGLISH: &str = "en";
+
+/// The languages built into the soundboard, by code. Files in the language directory with the
+/// same code replace them.
+const BUILT_IN: [(&str, &str); 1] = [("de", include_str!("languages/de.toml"))];
+
+/// A language file.
+#[derive(Deserialize)]
+pub struct Language {
+    /// The name of the language in the language itself.
+    pub name: String,
+    /// The whole manual, in place of the English one.
+    #[serde(default)]
+    pub manual: Option<String>,
+    /// The translation of each English string.
+    #[serde(default)]
+    pub strings: HashMap<String, String>,
+}
+
+/// A language switched to, with its strings kept for the rest of the run so [`tr`] can lend them
+/// out. A few kilobytes are kept for each language, once however often it is switched to.
+struct Current {
+    name: &'static str,
+    manual: Option<&'static str>,
+    strings: HashMap<String, &'static str>,
+}
+
+static CURRENT: RwLock<Option<&'static Current>> = RwLock::new(None);
+/// The languages switched to so far, by code.
+static LOADED: Mutex<Option<HashMap<String, &'static Current>>> = Mutex::new(None);
+
+/// Read the language `code` from the language directory, or the built-in one.
+pub fn load_language(code: &str) -> Result<Language, String> {
+    let file = get_language_directory()
+        .ok()
+        .and_then(|directory| fs::read_to_string(directory.join(format!("{code}.toml"))).ok());
+    let text = match file {
+        Some(text) => text,
+        None => match BUILT_IN.iter().find(|(built_in, _)| *built_in == code) {
+            Some((_, text)) => text.to_string(),
+            None => return Err(format!("There is no language \"{code}\".")),
+        },
+    };
+    toml::from_str(&text).map_err(|error| format!("Unable to read language \"{code}\": {error}"))
+}
+
+/// Return the code and name of every language, starting with English. Language files that
+/// cannot be read are left out.
+pub fn list_languages() -> Vec<(String, String)> {
+    let mut codes: Vec<String> = BUILT_IN.iter().map(|(code, _)| code.to_string()).collect();
+    if let Ok(entries) = get_language_directory()
+        .and_then(|directory| fs::read_dir(directory).map_err(|error| error.to_string()))
+    {
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                if let Some(code) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    codes.push(code.to_string());
+                }
+            }
+        }
+    }
+    codes.sort();
+    codes.dedup();
+
+    let mut languages = vec![(ENGLISH.to_string(), "English".to_string())];
+    for code in codes.into_iter().filter(|code| code != ENGLISH) {
+        match load_language(&code) {
+            Ok(language) => languages.push((code, language.name)),
+            Err(error) => log!(Warning, "[Languages] {error}"),
+        }
+    }
+    languages
+}
+
+/// Show the user interface in the language `code`. English and the empty code turn
+/// translations off. A language is read once, so changes to its file apply after a restart.
+pub fn set_language(code: &str) -> Result<(), String> {
+    let current = if code.is_empty() || code == ENGLISH {
+        None
+    } else {
+        let mut loaded = LOADED.lock().unwrap();
+        let loaded = loaded.get_or_insert_with(HashMap::new);
+        match loaded.get(code) {
+            Some(current) => Some(*current),
+            None => {
+                let language = load_language(code)?;
+                let leak = |text: String| -> &'static str { Box::leak(text.into_boxed_str()) };
+                let current: &'static Current = Box::leak(Box::new(Current {
+                    name: leak(language.name),
+                    manual: language.manual.map(leak),
+                    strings: language
+                        .strings
+                        .into_iter()
+                        .map(|(english, translated)| (english, leak(translated)))
+                        .collect(),
+                }));
+                loaded.insert(code.to_string(), current);
+                Some(current)
+            }
+        }
+    };
+    *CURRENT.write().unwrap() = current;
+    Ok(())
+}
+
+/// Translate the English string `text` into the language in use.
+pub fn tr(text: &'static str) -> &'static str {
+    match &*CURRENT.read().unwrap() {
+        Some(current) => current.strings.get(text).copied().unwrap_or(text),
+        None => text,
+    }
+}
+
+/// Return the manual of the language in use, or `english` if it has none.
+pub fn manual(english: &'static str) -> &'static str {
+    match &*CURRENT.read().unwrap() {
+        Some(current) => current.manual.unwrap_or(english),
+        None => english,
+    }
+}
+
+/// Return the name of the language in use.
+pub fn language_name() -> &'static str {
+    match &*CURRENT.read().unwrap() {
+        Some(current) => current.name,
+        None => "English",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_translated() {
+        for (code, text) in BUILT_IN {
+            let language: Language = toml::from_str(text).unwrap();
+            assert!(!language.strings.is_empty(), "{code} has no strings");
+        }
+
+        set_language("de").unwrap();
+        assert_eq!(language_name(), "Deutsch");
+        assert_eq!(tr("Settings"), "Einstellungen");
+        assert_eq!(tr("Not translated"), "Not translated");
+        assert_eq!(manual("English manual"), "English manual");
+        set_language(ENGLISH).unwrap();
+        assert_eq!(tr("Settings"), "Settings");
+        assert!(set_language("xx").is_err());
+
+        // Switching back reuses the strings read the first time.
+        set_language("de").unwrap();
+        let first = tr("Settings").as_ptr();
+        set_language(ENGLISH).unwrap();
+        set_language("de").unwrap();
+        assert_eq!(tr("Settings").as_ptr(), first);
+        set_language(ENGLISH).unwrap();
+    }
+}
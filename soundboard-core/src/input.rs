@@ -0,0 +1,370 @@
+use crate::as_hex::as_hex;
+use crate::event::*;
+use crate::retry::*;
+use crate::supervisor::*;
+use crate::waker::*;
+use serde::Deserialize;
+use std::io::{prelude::*, BufReader};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Instant, SystemTime};
+
+/// Format a [`SystemTime`] as T+{ms} or T-{ms} relative to the current system time.
+fn format_timestamp(timestamp: SystemTime) -> String {
+    match timestamp.elapsed() {
+        Ok(duration) => format!("T-{}ms", duration.as_millis()),
+        Err(system_time_error) => format!("T+{}ms", system_time_error.duration().as_millis()),
+    }
+}
+
+/// Holds information about an input event. Serialized using postcard and sent to clients.
+/// Enum values can be found in
+/// <https://github.com/torvalds/linux/blob/master/include/uapi/linux/input-event-codes.h>
+/// Fields:
+/// - `timestamp`: a `std::time::SystemTime` associated with the event
+/// - `event_type`: the raw type (e.g., a key press)
+/// - `code`: the raw code (e.g., corresponding to a certain key)
+/// - `value`: the raw value (e.g., 1 for a key press and 0 for a key release)
+#[derive(Deserialize)]
+pub struct InputEventWrapper {
+    pub timestamp: SystemTime,
+    pub event_type: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl InputEventWrapper {
+    /// Returns the [`EventType`] of this [`InputEventWrapper`] if it exists.
+    pub fn as_event_type(&self) -> Option<EventType> {
+        EventType::from_repr(self.event_type)
+    }
+
+    /// Returns the [`Event`] of this [`InputEventWrapper`] if it exists.
+    pub fn as_event(&self) -> Option<Event> {
+        Event::from_raw(self.event_type, self.code)
+    }
+}
+
+/// What the remote input client is doing, as shown on the Connect button.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteInputState {
+    Disconnected,
+    Connecting,
+    /// Connected and the API key sent, but no event received yet.
+    Authenticated,
+    /// Events are being received. Holds when the last one was.
+    Receiving {
+        last_event: Instant,
+    },
+    /// The last attempt failed or the server disconnected for `reason`. `status` tells when the
+    /// next attempt is made.
+    Error {
+        reason: String,
+        status: ConnectionStatus,
+    },
+}
+
+/// Why the connection to the remote input server last failed and when the last event was
+/// received on it, shared with the client thread.
+#[derive(Default)]
+struct ConnectionState {
+    error: Option<String>,
+    last_event: Option<Instant>,
+}
+
+pub struct RemoteInputClientManager {
+    remote_input_thread: Option<thread::JoinHandle<()>>,
+    event_receiver: Option<Receiver<InputEventWrapper>>,
+    status: Arc<Mutex<ConnectionStatus>>,
+    state: Arc<Mutex<ConnectionState>>,
+    stop: Arc<AtomicBool>,
+    /// The number of connections made to a server, so a dropped connection is noticed even if
+    /// the next one is made at once.
+    connections: Arc<AtomicU64>,
+    waker: Waker,
+}
+
+impl RemoteInputClientManager {
+    /// Create a new remote input client manager. Nothing will be done until `connect` is called.
+    /// `waker` is called whenever an event is received.
+    pub fn new(waker: Waker) -> Self {
+        Self {
+            remote_input_thread: None,
+            event_receiver: None,
+            status: Arc::new(Mutex::new(ConnectionStatus::Disconnected)),
+            state: Arc::default(),
+            stop: Arc::new(AtomicBool::new(false)),
+            connections: Arc::default(),
+            waker,
+        }
+    }
+
+    /// Connect to the remote input server in a new thread, receiving events only from the
+    /// server's `devices` (or every device if empty).
+    /// Failed connections and disconnections are retried with backoff until `disconnect` is called.
+    /// The thread is restarted if it panics, e.g. while handling a malformed event.
+    pub fn connect(&mut self, server_address: String, api_key: String, devices: Vec<String>) {
+        self.disconnect();
+        let (event_sender, event_receiver) = mpsc::channel();
+        let event_sender = WakingSender::new(event_sender, self.waker.clone());
+        let waker = self.waker.clone();
+        let status = Arc::new(Mutex::new(ConnectionStatus::Connecting));
+        let state: Arc<Mutex<ConnectionState>> = Arc::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let connections = self.connections.clone();
+        self.event_receiver = Some(event_receiver);
+        self.status = status.clone();
+        self.state = state.clone();
+        self.stop = stop.clone();
+        self.remote_input_thread = Some(spawn_supervised(
+            format!("Remote Input Client {server_address}"),
+            status.clone(),
+            stop.clone(),
+            move || {
+                let mut circuit_breaker = CircuitBreaker::new(RetryPolicy::default());
+                loop {
+                    let mut remote_input_client =
+                        match circuit_breaker.retry(&status, &stop, || {
+                            let result = RemoteInputClient::connect(
+                                server_address.clone(),
+                                api_key.clone(),
+                                &devices,
+                            );
+                            if result.is_ok() {
+                                connections.fetch_add(1, Ordering::SeqCst);
+                            }
+                            let mut state = state.lock().unwrap();
+                            state.last_event = None;
+                            state.error = result.as_ref().err().cloned();
+                            drop(state);
+                            waker.wake();
+                            result.ok()
+                        }) {
+                            Some(r) => r,
+                            None => break,
+                        };
+                    while let Some(event) = remote_input_client.process_event() {
+                        state.lock().unwrap().last_event = Some(Instant::now());
+                        if stop.load(Ordering::SeqCst) || event_sender.send(event).is_err() {
+                            log!(
+                                Warning, "[Remote Input Client {server_address}] Local channel disconnected."
+                            );
+                            *status.lock().unwrap() = ConnectionStatus::Disconnected;
+                            return;
+                        }
+                    }
+                    log!(
+                        Warning,
+                        "[Remote Input Client {server_address}] Server disconnected."
+                    );
+                    let mut state = state.lock().unwrap();
+                    // The server closes the connection at once if the API key is wrong.
+                    state.error = Some(if state.last_event.is_some() {
+                        "The server disconnected.".to_string()
+                    } else {
+                        "The server closed the connection before sending an event. Check the \
+                        API key."
+                            .to_string()
+                    });
+                    drop(state);
+                    waker.wake();
+
+                    // Back off before reconnecting so a server that drops connections immediately
+                    // is not flooded with attempts.
+                    if !circuit_breaker.fail_and_wait(&status, &stop) {
+                        break;
+                    }
+                }
+                *status.lock().unwrap() = ConnectionStatus::Disconnected;
+            },
+        ));
+    }
+
+    /// Disconnect the [`RemoteInputClient`] and stop reconnecting.
+    pub fn disconnect(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+        self.event_receiver = None;
+        self.remote_input_thread = None;
+    }
+
+    /// Check if the [`RemoteInputClient`] is connected or trying to connect.
+    pub fn connected(&self) -> bool {
+        self.event_receiver.is_some()
+            && self
+                .remote_input_thread
+                .as_ref()
+                .is_some_and(|h| !h.is_finished())
+    }
+
+    /// Return the status of the connection to the remote input server.
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Return a number identifying the current connection to the remote input server, or None
+    /// while not connected. It changes whenever a connection ends.
+    pub fn connection(&self) -> Option<u64> {
+        (self.status() == ConnectionStatus::Connected)
+            .then(|| self.connections.load(Ordering::SeqCst))
+    }
+
+    /// Return what the client is doing, with why the connection last failed.
+    pub fn state(&self) -> RemoteInputState {
+        let state = self.state.lock().unwrap();
+        match self.status() {
+            ConnectionStatus::Disconnected => RemoteInputState::Disconnected,
+            ConnectionStatus::Connecting => RemoteInputState::Connecting,
+            ConnectionStatus::Connected => match state.last_event {
+                None => RemoteInputState::Authenticated,
+                Some(last_event) => RemoteInputState::Receiving { last_event },
+            },
+            status => RemoteInputState::Error {
+                reason: state
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| "The connection failed.".to_string()),
+                status,
+            },
+        }
+    }
+
+    /// Replace the contents of `events` with the input events received since this was last
+    /// called. Reusing the same buffer every frame avoids allocating once it has grown.
+    /// No events are received while disconnected.
+    pub fn poll_events(&self, events: &mut Vec<InputEventWrapper>) {
+        events.clear();
+        if let Some(receiver) = self.event_receiver.as_ref() {
+            events.extend(receiver.try_iter());
+        }
+    }
+}
+
+pub struct RemoteInputClient {
+    buffer_reader: BufReader<TcpStream>,
+    event_buffer: Vec<u8>,
+    server_address: String,
+}
+
+impl RemoteInputClient {
+    pub fn connect(
+        server_address: String,
+        api_key: String,
+        devices: &[String],
+    ) -> Result<RemoteInputClient, String> {
+        log!(
+            "[Remote Input Client {server_address}] Connecting to remote input server {}.",
+            server_address
+        );
+
+        // Connect to the remote input server.
+        let mut stream = match std::net::TcpStream::connect(server_address.clone()) {
+            Err(error) => {
+                log!(Error, "[Remote Input Client {server_address}] Error connecting to remote input server {server_address}: {error}");
+                return Err(format!("Unable to connect to {server_address}: {error}."));
+            }
+            Ok(stream) => stream,
+        };
+        log!("[Remote Input Client {server_address}] Connected to remote input server {server_address}.");
+
+        // Send the API key to the remote input server, followed by the names of the devices to
+        // receive events from. Each is terminated by a zero byte, and an empty name ends the list.
+        // Servers that do not filter devices ignore the list. Without devices, only the API key
+        // is sent, as before device lists existed.
+        let mut request = [api_key.as_bytes(), &[0x00u8]].concat();
+        let mut devices = devices
+            .iter()
+            .filter(|device| !device.is_empty())
+            .peekable();
+        if devices.peek().is_some() {
+            for device in devices {
+                request.extend_from_slice(device.as_bytes());
+                request.push(0x00);
+            }
+            request.push(0x00);
+        }
+        match stream.write(&request) {
+            Ok(0) => {
+                log!(
+                    Warning, "[Remote Input Client {server_address}] Sent 0 bytes of API key. Connection is likely closed."
+                );
+                return Err("The server closed the connection.".to_string());
+            }
+            Ok(n) => log!(
+                "[Remote Input Client {server_address}] Sent {n} bytes of {} byte API key and device list.",
+                request.len()
+            ),
+            Err(error) => {
+                log!(Error, "[Remote Input Client {server_address}] Unable to send API key: {error}");
+                return Err(format!("Unable to send the API key: {error}."));
+            }
+        }
+
+        // Receive events from the remote input server.
+        // Events are [`InputEventWrapper`] serialized by [`postcard`] and encoded by COBS.
+        let buffer_reader = BufReader::new(stream);
+        let event_buffer = Vec::new();
+
+        Ok(RemoteInputClient {
+            buffer_reader,
+            event_buffer,
+            server_address,
+        })
+    }
+
+    pub fn process_event(&mut self) -> Option<InputEventWrapper> {
+        let address = &self.server_address;
+
+        // Receive data.
+        self.event_buffer.clear();
+        match self.buffer_reader.read_until(0x00, &mut self.event_buffer) {
+            Ok(0) => {
+                log!(
+                    Warning, "[Remote Input Client {address}] Read 0 bytes of data. Connection is likely closed."
+                );
+                return None;
+            }
+            Ok(_) => {}
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Remote Input Client {address}] Unable to read event: {error}."
+                );
+            }
+        }
+
+        // Deserialize event.
+        let event_data = self.event_buffer.as_mut_slice();
+        log!(
+            Debug,
+            "[Remote Input Client {address}] Received event: {}.",
+            as_hex(event_data)
+        );
+        match postcard::from_bytes_cobs::<InputEventWrapper>(event_data) {
+            Err(deserialize_error) => {
+                log!(Error, "[Remote Input Client {address}] Failed to deserialize event: {deserialize_error}.");
+                None
+            }
+            Ok(event_wrapper) => {
+                match event_wrapper.as_event() {
+                    Some(enumerated_event) => {
+                        log!(
+                            Debug, "[Remote Input Client {address}] Deserialized enumerated event: timestamp: {}, event_type: {}, code: {}, value: {}.",
+                            format_timestamp(event_wrapper.timestamp), event_wrapper.as_event_type().unwrap().as_ref(), enumerated_event.code_as_ref(), event_wrapper.value
+                        );
+                    }
+                    None => {
+                        log!(
+                            Debug, "[Remote Input Client {address}] Deserialized undefined event: timestamp: {}, event_type: {}, code: {}, value: {}.",
+                            format_timestamp(event_wrapper.timestamp), event_wrapper.event_type, event_wrapper.code, event_wrapper.value
+                        );
+                    }
+                };
+                Some(event_wrapper)
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! The engine of the soundboard: profiles and their configuration, audio playback on output
+//! devices, the remote input client and gamepad input. The user interface is built on top of
+//! this crate. Other programs can play sounds without it with [`engine::SoundboardEngine`].
+
+// First, so that `log!` can be used by every other module.
+#[macro_use]
+pub mod log;
+
+pub mod as_hex;
+pub mod atomic;
+pub mod audio;
+pub mod channel_test;
+pub mod check;
+pub mod config;
+pub mod contention;
+pub mod dsp;
+pub mod engine;
+pub mod event;
+pub mod foreign;
+pub mod gamepad;
+pub mod i18n;
+pub mod input;
+pub mod microphone;
+pub mod retry;
+pub mod supervisor;
+pub mod trigger;
+pub mod waker;
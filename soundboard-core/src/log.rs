@@ -0,0 +1,135 @@
+use crate::waker::Waker;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+/// The number of recent lines kept for support bundles and the log window.
+const RECENT_LINES: usize = 2000;
+
+static RECENT: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+
+/// Called after every line logged at [`Level::Error`].
+static ERROR_WAKER: OnceLock<Waker> = OnceLock::new();
+
+/// Set to print the lines to the standard error instead of the standard output.
+static TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// How important a log line is.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Level {
+    /// Details such as every received input event.
+    Debug,
+    Info,
+    /// Something unexpected that the soundboard worked around.
+    Warning,
+    /// Something that failed.
+    Error,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Debug, Level::Info, Level::Warning, Level::Error];
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Pad, so that a width such as {:<7} lines up the levels.
+        f.pad(match self {
+            Level::Debug => "Debug",
+            Level::Info => "Info",
+            Level::Warning => "Warning",
+            Level::Error => "Error",
+        })
+    }
+}
+
+/// A line in the recent log.
+#[derive(Clone)]
+pub struct LogLine {
+    /// Counts the lines logged, starting from 1.
+    pub number: u64,
+    pub time: chrono::DateTime<chrono::Local>,
+    pub level: Level,
+    pub text: String,
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<7} {}",
+            self.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.level,
+            self.text
+        )
+    }
+}
+
+/// Print a line to the standard output and keep it with its time in the recent log.
+/// The line is logged at [`Level::Info`] unless a level is given first, as in
+/// `log!(Error, "[Audio] ...")`.
+#[macro_export]
+macro_rules! log {
+    ($level:ident, $($arg:tt)*) => {
+        $crate::log::write_line($crate::log::Level::$level, format!($($arg)*))
+    };
+    ($($arg:tt)*) => {
+        $crate::log::write_line($crate::log::Level::Info, format!($($arg)*))
+    };
+}
+
+pub fn write_line(level: Level, text: String) {
+    if TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{text}");
+    } else {
+        println!("{text}");
+    }
+    let mut recent = RECENT.lock().unwrap_or_else(|error| error.into_inner());
+    let number = recent.back().map_or(0, |line| line.number) + 1;
+    if recent.len() >= RECENT_LINES {
+        recent.pop_front();
+    }
+    recent.push_back(LogLine {
+        number,
+        time: chrono::Local::now(),
+        level,
+        text,
+    });
+    drop(recent);
+    if level == Level::Error {
+        if let Some(waker) = ERROR_WAKER.get() {
+            waker.wake();
+        }
+    }
+}
+
+/// Call `waker` whenever an error is logged from now on. Only the first waker set is kept.
+pub fn wake_on_error(waker: Waker) {
+    let _ = ERROR_WAKER.set(waker);
+}
+
+/// Print the lines logged from now on to the standard error, so that the standard output only
+/// holds a report such as the one of `soundboard --check`.
+pub fn log_to_stderr() {
+    TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Return the most recent log lines, oldest first.
+pub fn recent_lines() -> Vec<LogLine> {
+    let recent = RECENT.lock().unwrap_or_else(|error| error.into_inner());
+    recent.iter().cloned().collect()
+}
+
+/// Return the recent lines logged at `level` after the line numbered `number`, oldest first.
+pub fn lines_after(number: u64, level: Level) -> Vec<LogLine> {
+    let recent = RECENT.lock().unwrap_or_else(|error| error.into_inner());
+    recent
+        .iter()
+        .filter(|line| line.number > number && line.level == level)
+        .cloned()
+        .collect()
+}
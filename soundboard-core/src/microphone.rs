@@ -0,0 +1,281 @@
+use crate::atomic::AtomicF32;
+use crate::contention::lock;
+use crate::dsp::db_to_amplitude;
+use rodio::cpal::traits::{DeviceTrait, StreamTrait};
+use rodio::cpal::{self, SampleFormat, SizedSample};
+use rodio::Source;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// Samples captured from the microphone waiting to be played on one output.
+struct SampleQueue {
+    samples: Mutex<VecDeque<f32>>,
+    closed: AtomicBool,
+}
+
+/// Captures audio from an input device so it can be mixed into [`crate::audio::OutputDevice`]s.
+pub struct Microphone {
+    name: String,
+    _stream: cpal::Stream,
+    channels: u16,
+    sample_rate: u32,
+    queues: Arc<Mutex<Vec<Arc<SampleQueue>>>>,
+    volume_db: Arc<AtomicF32>,
+    muted: Arc<AtomicBool>,
+}
+
+impl Microphone {
+    /// Samples buffered per output before the oldest are dropped, in seconds. This keeps the
+    /// passthrough latency bounded when the input and output clocks drift apart.
+    const MAX_LATENCY: f32 = 0.1;
+
+    /// Start capturing from `device` with its default input configuration.
+    pub fn open(device: &cpal::Device) -> Result<Self, String> {
+        let name = device.name().unwrap_or_else(|_| "[Unknown]".to_string());
+        let supported_config = match device.default_input_config() {
+            Err(error) => {
+                return Err(format!(
+                    "Unable to obtain an input configuration for device {name}: {error}."
+                ))
+            }
+            Ok(supported_config) => supported_config,
+        };
+        let sample_format = supported_config.sample_format();
+        let config: cpal::StreamConfig = supported_config.into();
+        let max_samples =
+            (config.sample_rate.0 as f32 * config.channels as f32 * Self::MAX_LATENCY) as usize;
+
+        let queues: Arc<Mutex<Vec<Arc<SampleQueue>>>> = Arc::new(Mutex::new(Vec::new()));
+        let stream = match sample_format {
+            SampleFormat::F32 => Self::build_stream::<f32>(device, &config, &queues, max_samples),
+            SampleFormat::I16 => Self::build_stream::<i16>(device, &config, &queues, max_samples),
+            SampleFormat::U16 => Self::build_stream::<u16>(device, &config, &queues, max_samples),
+            sample_format => {
+                return Err(format!(
+                    "Unsupported sample format {sample_format} on input device {name}."
+                ))
+            }
+        };
+        let stream = match stream {
+            Err(error) => {
+                return Err(format!(
+                    "Unable to build an input stream from device {name}: {error}."
+                ))
+            }
+            Ok(stream) => stream,
+        };
+        if let Err(error) = stream.play() {
+            return Err(format!(
+                "Unable to start the input stream from device {name}: {error}."
+            ));
+        }
+
+        log!("[Microphone] Capturing from input device {name}.");
+        Ok(Self {
+            name,
+            _stream: stream,
+            channels: config.channels,
+            sample_rate: config.sample_rate.0,
+            queues,
+            volume_db: Arc::new(AtomicF32::default()),
+            muted: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    fn build_stream<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        queues: &Arc<Mutex<Vec<Arc<SampleQueue>>>>,
+        max_samples: usize,
+    ) -> Result<cpal::Stream, cpal::BuildStreamError>
+    where
+        T: SizedSample,
+        f32: cpal::FromSample<T>,
+    {
+        let queues = queues.clone();
+        let channels = config.channels as usize;
+        device.build_input_stream(
+            config,
+            move |data: &[T], _| {
+                let mut queues = lock(&queues);
+                // Forget queues whose source has been dropped by its output stream.
+                queues.retain(|queue| Arc::strong_count(queue) > 1);
+                for queue in queues.iter() {
+                    let mut samples = lock(&queue.samples);
+                    samples.extend(data.iter().map(|sample| sample.to_sample::<f32>()));
+                    // Drop whole frames so the channels stay aligned.
+                    let excess = samples.len().saturating_sub(max_samples);
+                    let excess = (excess.div_ceil(channels) * channels).min(samples.len());
+                    samples.drain(..excess);
+                }
+            },
+            |error| log!(Error, "[Microphone] Input stream error: {error}."),
+            None,
+        )
+    }
+
+    /// Return &self.name.
+    #[inline]
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Create a new [`Source`] playing the captured audio. The source ends when this
+    /// [`Microphone`] is dropped.
+    pub fn source(&self) -> impl Source<Item = f32> + Send {
+        let queue = Arc::new(SampleQueue {
+            samples: Mutex::new(VecDeque::new()),
+            closed: AtomicBool::new(false),
+        });
+        self.queues.lock().unwrap().push(queue.clone());
+
+        let volume_db = self.volume_db.clone();
+        let muted = self.muted.clone();
+        MicrophoneSource::new(queue, self.channels, self.sample_rate)
+            .amplify(1.0)
+            .periodic_access(Duration::from_millis(200), move |src| {
+                if muted.load(Ordering::SeqCst) {
+                    src.set_factor(0.0);
+                } else {
+                    src.set_factor(db_to_amplitude(volume_db.load()));
+                }
+            })
+    }
+
+    /// Set the volume in dB.
+    pub fn set_volume_db(&self, volume_db: f32) {
+        self.volume_db.store(volume_db)
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::SeqCst);
+    }
+
+    /// Toggle muted.
+    pub fn toggle_muted(&self) {
+        self.muted.fetch_xor(true, Ordering::AcqRel);
+    }
+
+    /// Get muted.
+    #[inline]
+    pub fn muted(&self) -> bool {
+        self.muted.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for Microphone {
+    fn drop(&mut self) {
+        for queue in self.queues.lock().unwrap().iter() {
+            queue.closed.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Plays the samples of one [`SampleQueue`], filling gaps with silence.
+struct MicrophoneSource {
+    queue: Arc<SampleQueue>,
+    channels: u16,
+    sample_rate: u32,
+    /// The samples taken from the queue and the position in them. The queue is locked once
+    /// per captured buffer instead of once per sample, since this runs in the output callback.
+    buffer: Vec<f32>,
+    position: usize,
+    /// Silent samples left to play in the current frame.
+    silence: u16,
+}
+
+impl MicrophoneSource {
+    fn new(queue: Arc<SampleQueue>, channels: u16, sample_rate: u32) -> Self {
+        Self {
+            queue,
+            channels,
+            sample_rate,
+            buffer: Vec::new(),
+            position: 0,
+            silence: 0,
+        }
+    }
+}
+
+impl Iterator for MicrophoneSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.queue.closed.load(Ordering::Relaxed) {
+            return None;
+        }
+        if self.silence > 0 {
+            self.silence -= 1;
+            return Some(0.0);
+        }
+        if self.position >= self.buffer.len() {
+            self.buffer.clear();
+            self.position = 0;
+            self.buffer.extend(lock(&self.queue.samples).drain(..));
+        }
+        match self.buffer.get(self.position) {
+            Some(&sample) => {
+                self.position += 1;
+                Some(sample)
+            }
+            None => {
+                // The queue only holds whole frames, so it can only run dry at the start of a
+                // frame. Fill that entire frame with silence.
+                self.silence = self.channels - 1;
+                Some(0.0)
+            }
+        }
+    }
+}
+
+impl Source for MicrophoneSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(samples: &[f32]) -> Arc<SampleQueue> {
+        Arc::new(SampleQueue {
+            samples: Mutex::new(samples.iter().copied().collect()),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    #[test]
+    fn plays_queued_frames_then_silent_frames() {
+        let queue = queue(&[0.1, 0.2, 0.3, 0.4]);
+        let mut source = MicrophoneSource::new(queue.clone(), 2, 48000);
+        let samples: Vec<f32> = source.by_ref().take(6).collect();
+        assert_eq!(samples, [0.1, 0.2, 0.3, 0.4, 0.0, 0.0]);
+
+        // Samples captured later are played from the start of the next frame.
+        queue.samples.lock().unwrap().extend([0.5, 0.6]);
+        let samples: Vec<f32> = source.by_ref().take(2).collect();
+        assert_eq!(samples, [0.5, 0.6]);
+
+        queue.closed.store(true, Ordering::SeqCst);
+        assert_eq!(source.next(), None);
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Backoff and circuit breaker settings shared by network integrations.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// The delay after the first failure. It doubles with each consecutive failure.
+    pub initial_delay: Duration,
+    /// The longest delay between two attempts while the circuit is closed.
+    pub max_delay: Duration,
+    /// The number of consecutive failures after which the circuit opens.
+    pub failure_threshold: u32,
+    /// How long an open circuit waits before allowing a trial attempt.
+    pub open_duration: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The state of a network integration's connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Disconnected,
+    Connecting,
+    Connected,
+    /// The last `failures` attempts failed. The next attempt is made at `next_attempt`.
+    Retrying {
+        failures: u32,
+        next_attempt: Instant,
+    },
+    /// Too many attempts failed. A trial attempt is made at `next_attempt`.
+    CircuitOpen {
+        next_attempt: Instant,
+    },
+}
+
+impl fmt::Display for ConnectionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionStatus::Disconnected => write!(f, "Disconnected"),
+            ConnectionStatus::Connecting => write!(f, "Connecting"),
+            ConnectionStatus::Connected => write!(f, "Connected"),
+            ConnectionStatus::Retrying {
+                failures,
+                next_attempt,
+            } => write!(
+                f,
+                "{failures} failed attempt(s), retrying in {}s",
+                next_attempt
+                    .saturating_duration_since(Instant::now())
+                    .as_secs()
+            ),
+            ConnectionStatus::CircuitOpen { next_attempt } => write!(
+                f,
+                "Paused after repeated failures, retrying in {}s",
+                next_attempt
+                    .saturating_duration_since(Instant::now())
+                    .as_secs()
+            ),
+        }
+    }
+}
+
+/// Counts consecutive failures of a connection and decides when to try again.
+pub struct CircuitBreaker {
+    policy: RetryPolicy,
+    failures: u32,
+}
+
+impl CircuitBreaker {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            failures: 0,
+        }
+    }
+
+    /// Close the circuit after a successful attempt.
+    pub fn record_success(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Record a failed attempt and return the status describing when to try again.
+    pub fn record_failure(&mut self) -> ConnectionStatus {
+        self.failures = self.failures.saturating_add(1);
+        let next_attempt = Instant::now() + self.delay();
+        if self.open() {
+            ConnectionStatus::CircuitOpen { next_attempt }
+        } else {
+            ConnectionStatus::Retrying {
+                failures: self.failures,
+                next_attempt,
+            }
+        }
+    }
+
+    /// Check if so many attempts failed in a row that the circuit is open.
+    fn open(&self) -> bool {
+        self.failures >= self.policy.failure_threshold
+    }
+
+    /// Return how long to wait after the failures so far before the next attempt.
+    fn delay(&self) -> Duration {
+        if self.failures == 0 {
+            Duration::ZERO
+        } else if self.open() {
+            self.policy.open_duration
+        } else {
+            self.policy
+                .initial_delay
+                .saturating_mul(1 << (self.failures - 1).min(16))
+                .min(self.policy.max_delay)
+        }
+    }
+
+    /// Record a failed attempt, publish the new status and sleep until the next attempt is due.
+    /// Returns false if `stop` was set while waiting.
+    pub fn fail_and_wait(&mut self, status: &Mutex<ConnectionStatus>, stop: &AtomicBool) -> bool {
+        let failed_status = self.record_failure();
+        let next_attempt = match failed_status {
+            ConnectionStatus::Retrying { next_attempt, .. }
+            | ConnectionStatus::CircuitOpen { next_attempt } => next_attempt,
+            _ => Instant::now(),
+        };
+        *status.lock().unwrap() = failed_status;
+
+        while Instant::now() < next_attempt {
+            if stop.load(Ordering::SeqCst) {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        !stop.load(Ordering::SeqCst)
+    }
+
+    /// Call `attempt` until it succeeds or `stop` is set, backing off between failures.
+    pub fn retry<T>(
+        &mut self,
+        status: &Mutex<ConnectionStatus>,
+        stop: &AtomicBool,
+        mut attempt: impl FnMut() -> Option<T>,
+    ) -> Option<T> {
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                return None;
+            }
+            *status.lock().unwrap() = ConnectionStatus::Connecting;
+            if let Some(value) = attempt() {
+                self.record_success();
+                *status.lock().unwrap() = ConnectionStatus::Connected;
+                return Some(value);
+            }
+            if !self.fail_and_wait(status, stop) {
+                return None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: RetryPolicy = RetryPolicy {
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(4),
+        failure_threshold: 3,
+        open_duration: Duration::from_millis(10),
+    };
+
+    #[test]
+    fn delays_double_up_to_the_limit() {
+        let mut circuit_breaker = CircuitBreaker::new(RetryPolicy {
+            failure_threshold: 10,
+            ..RetryPolicy::default()
+        });
+        let delays: Vec<u64> = (0..8)
+            .map(|_| {
+                circuit_breaker.record_failure();
+                circuit_breaker.delay().as_secs()
+            })
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30, 30]);
+
+        circuit_breaker.record_success();
+        assert_eq!(circuit_breaker.delay(), Duration::ZERO);
+        assert!(matches!(
+            circuit_breaker.record_failure(),
+            ConnectionStatus::Retrying { failures: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn many_failures_open_the_circuit() {
+        let mut circuit_breaker = CircuitBreaker::new(RetryPolicy::default());
+        for failures in 1..5 {
+            let status = circuit_breaker.record_failure();
+            assert!(
+                matches!(status, ConnectionStatus::Retrying { failures: f, .. } if f == failures)
+            );
+        }
+        let before = Instant::now();
+        match circuit_breaker.record_failure() {
+            ConnectionStatus::CircuitOpen { next_attempt } => {
+                assert!(next_attempt >= before + Duration::from_secs(60))
+            }
+            status => panic!("expected an open circuit, got {status:?}"),
+        }
+        // It stays open until an attempt succeeds.
+        assert!(matches!(
+            circuit_breaker.record_failure(),
+            ConnectionStatus::CircuitOpen { .. }
+        ));
+    }
+
+    #[test]
+    fn retrying_stops_after_success() {
+        let status = Mutex::new(ConnectionStatus::Disconnected);
+        let stop = AtomicBool::new(false);
+        let mut attempts = 0;
+        let mut circuit_breaker = CircuitBreaker::new(FAST);
+        let result = circuit_breaker.retry(&status, &stop, || {
+            attempts += 1;
+            (attempts == 5).then_some(attempts)
+        });
+        // The circuit opened after 3 failures, and the trial attempts went on.
+        assert_eq!(result, Some(5));
+        assert_eq!(*status.lock().unwrap(), ConnectionStatus::Connected);
+        assert_eq!(circuit_breaker.delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn retrying_gives_up_when_stopped() {
+        let status = Mutex::new(ConnectionStatus::Disconnected);
+        let stop = AtomicBool::new(false);
+        let mut attempts = 0;
+        let result = CircuitBreaker::new(FAST).retry(&status, &stop, || {
+            attempts += 1;
+            if attempts == 4 {
+                stop.store(true, Ordering::SeqCst);
+            }
+            None::<()>
+        });
+        assert_eq!(result, None);
+        assert_eq!(attempts, 4);
+        assert!(matches!(
+            *status.lock().unwrap(),
+            ConnectionStatus::CircuitOpen { .. }
+        ));
+
+        // A stopped retry makes no attempt at all.
+        let result = CircuitBreaker::new(FAST).retry(&status, &stop, || Some(()));
+        assert_eq!(result, None);
+    }
+}
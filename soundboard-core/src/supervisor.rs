@@ -0,0 +1,53 @@
+use crate::retry::*;
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    thread,
+};
+
+/// Describe the payload of a panic.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Run `f`, returning an error describing the panic if it panics.
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(payload.as_ref()))
+}
+
+/// Spawn a thread running `body` until it returns. If `body` panics, the panic is logged and
+/// `body` is restarted, backing off with a [`CircuitBreaker`] until `stop` is set.
+/// `status` shows the wait between restarts.
+pub fn spawn_supervised(
+    name: String,
+    status: Arc<Mutex<ConnectionStatus>>,
+    stop: Arc<AtomicBool>,
+    mut body: impl FnMut() + Send + 'static,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut circuit_breaker = CircuitBreaker::new(RetryPolicy::default());
+        loop {
+            match catch_panic(&mut body) {
+                Ok(()) => return,
+                Err(error) => {
+                    log!(Error, "[Supervisor] {name} panicked: {error}. Restarting.");
+                    if !circuit_breaker.fail_and_wait(&status, &stop) {
+                        log!(
+                            Warning,
+                            "[Supervisor] {name} stopped while waiting to restart."
+                        );
+                        *status.lock().unwrap() = ConnectionStatus::Disconnected;
+                        return;
+                    }
+                }
+            }
+        }
+    })
+}
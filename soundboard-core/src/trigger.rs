@@ -0,0 +1,235 @@
+use crate::config::{Input, KeyRelease, KeyTrigger, TriggerConfig};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// An input held down.
+struct Press {
+    /// The long press of the input with the modifier keys held when it was pressed.
+    long_press: KeyRelease,
+    time: Instant,
+    /// Set once the input was held for the long press time.
+    held: bool,
+    /// Set if its long press was bound then and has triggered, so the release does nothing.
+    fired: bool,
+}
+
+/// Turns presses and releases into single press, double tap and long press [`KeyRelease`]s.
+/// A release is only held back while a double tap of it is bound, so other key binds trigger
+/// without delay. A long press triggers as soon as the key has been held long enough.
+#[derive(Default)]
+pub struct TriggerDetector {
+    /// The inputs held down.
+    pressed: HashMap<Input, Press>,
+    /// Releases waiting for a second tap, and when they were released.
+    taps: Vec<(KeyRelease, Instant)>,
+}
+
+impl TriggerDetector {
+    /// Start timing the long press of `press`, the input pressed with the modifier keys held.
+    pub fn press(&mut self, press: KeyRelease) {
+        self.pressed.insert(
+            press.input,
+            Press {
+                long_press: KeyRelease {
+                    trigger: KeyTrigger::LongPress,
+                    ..press
+                },
+                time: Instant::now(),
+                held: false,
+                fired: false,
+            },
+        );
+    }
+
+    /// Return the long presses of the inputs that have just been held for the long press time
+    /// and whose long press is `bound`.
+    pub fn held(
+        &mut self,
+        config: &TriggerConfig,
+        bound: impl Fn(&KeyRelease) -> bool,
+    ) -> Vec<KeyRelease> {
+        let threshold = Duration::from_millis(config.long_press_ms);
+        let mut long_presses = Vec::new();
+        for press in self.pressed.values_mut() {
+            if press.held || press.time.elapsed() < threshold {
+                continue;
+            }
+            press.held = true;
+            if bound(&press.long_press) {
+                press.fired = true;
+                long_presses.push(press.long_press.clone());
+            }
+        }
+        long_presses
+    }
+
+    /// Return how `release` triggers, if it does so now. `bound` checks if a key bind matches a
+    /// release with another trigger. The release of a long press that triggered while held does
+    /// nothing, a long press falls back to a single press when no long press is bound, and a
+    /// first tap is held back when a double tap is bound.
+    pub fn release(
+        &mut self,
+        mut release: KeyRelease,
+        config: &TriggerConfig,
+        bound: impl Fn(&KeyRelease) -> bool,
+    ) -> Option<KeyRelease> {
+        if let Some(press) = self.pressed.remove(&release.input) {
+            if press.fired {
+                return None;
+            }
+            // The long press time may pass between two checks of `held`.
+            let long_press = KeyRelease {
+                trigger: KeyTrigger::LongPress,
+                ..release.clone()
+            };
+            if press.time.elapsed() >= Duration::from_millis(config.long_press_ms)
+                && bound(&long_press)
+            {
+                return Some(long_press);
+            }
+        }
+
+        if let Some(i) = self.taps.iter().position(|(tap, time)| {
+            tap.input == release.input
+                && tap.modifiers == release.modifiers
+                && time.elapsed() <= Duration::from_millis(config.double_tap_ms)
+        }) {
+            self.taps.remove(i);
+            release.trigger = KeyTrigger::DoubleTap;
+            return Some(release);
+        }
+        let double_tap = KeyRelease {
+            trigger: KeyTrigger::DoubleTap,
+            ..release.clone()
+        };
+        if bound(&double_tap) {
+            self.taps.push((release, Instant::now()));
+            return None;
+        }
+        Some(release)
+    }
+
+    /// Return the held back taps that were not followed by a second tap in time, as single
+    /// presses.
+    pub fn expired(&mut self, config: &TriggerConfig) -> Vec<KeyRelease> {
+        let window = Duration::from_millis(config.double_tap_ms);
+        let mut expired = Vec::new();
+        self.taps.retain(|(tap, time)| {
+            if time.elapsed() <= window {
+                return true;
+            }
+            expired.push(tap.clone());
+            false
+        });
+        expired
+    }
+
+    /// Return how long until the next held back tap expires or the next input held down
+    /// reaches the long press time.
+    pub fn next_expiry(&self, config: &TriggerConfig) -> Option<Duration> {
+        let window = Duration::from_millis(config.double_tap_ms);
+        let threshold = Duration::from_millis(config.long_press_ms);
+        let taps = self
+            .taps
+            .iter()
+            .map(|(_, time)| window.saturating_sub(time.elapsed()));
+        let presses = self
+            .pressed
+            .values()
+            .filter(|press| !press.held)
+            .map(|press| threshold.saturating_sub(press.time.elapsed()));
+        taps.chain(presses).min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Key;
+    use std::collections::HashSet;
+    use std::thread;
+
+    const CONFIG: TriggerConfig = TriggerConfig {
+        double_tap_ms: 40,
+        long_press_ms: 40,
+    };
+
+    fn f5() -> KeyRelease {
+        KeyRelease::new(Input::Key(Key::KEY_F5), &HashSet::new())
+    }
+
+    fn sleep_past(ms: u64) {
+        thread::sleep(Duration::from_millis(ms + 20));
+    }
+
+    /// Check if a bind with `trigger` is bound, for key binds of F5 with `triggers`.
+    fn bound(triggers: &[KeyTrigger]) -> impl Fn(&KeyRelease) -> bool + '_ {
+        |release| triggers.contains(&release.trigger)
+    }
+
+    fn trigger(release: Option<KeyRelease>) -> Option<KeyTrigger> {
+        release.map(|release| release.trigger)
+    }
+
+    #[test]
+    fn presses_trigger_on_release() {
+        let mut detector = TriggerDetector::default();
+        detector.press(f5());
+        assert!(detector.held(&CONFIG, bound(&[])).is_empty());
+        let release = detector.release(f5(), &CONFIG, bound(&[KeyTrigger::Press]));
+        assert_eq!(trigger(release), Some(KeyTrigger::Press));
+        assert_eq!(detector.next_expiry(&CONFIG), None);
+    }
+
+    #[test]
+    fn double_taps_hold_back_the_first_tap() {
+        let triggers = [KeyTrigger::Press, KeyTrigger::DoubleTap];
+        let mut detector = TriggerDetector::default();
+        detector.press(f5());
+        assert!(detector.release(f5(), &CONFIG, bound(&triggers)).is_none());
+        assert!(detector.next_expiry(&CONFIG).is_some());
+        detector.press(f5());
+        let release = detector.release(f5(), &CONFIG, bound(&triggers));
+        assert_eq!(trigger(release), Some(KeyTrigger::DoubleTap));
+        assert!(detector.expired(&CONFIG).is_empty());
+
+        // A single tap is let through once the double tap time has passed.
+        detector.press(f5());
+        assert!(detector.release(f5(), &CONFIG, bound(&triggers)).is_none());
+        assert!(detector.expired(&CONFIG).is_empty());
+        sleep_past(CONFIG.double_tap_ms);
+        let expired = detector.expired(&CONFIG);
+        assert_eq!(trigger(expired.into_iter().next()), Some(KeyTrigger::Press));
+        assert_eq!(detector.next_expiry(&CONFIG), None);
+    }
+
+    #[test]
+    fn long_presses_trigger_while_held() {
+        let triggers = [KeyTrigger::Press, KeyTrigger::LongPress];
+        let mut detector = TriggerDetector::default();
+        detector.press(f5());
+        assert!(detector.held(&CONFIG, bound(&triggers)).is_empty());
+        sleep_past(CONFIG.long_press_ms);
+        assert_eq!(detector.next_expiry(&CONFIG), Some(Duration::ZERO));
+        let held = detector.held(&CONFIG, bound(&triggers));
+        assert_eq!(
+            trigger(held.into_iter().next()),
+            Some(KeyTrigger::LongPress)
+        );
+        // It triggers once, and its release does nothing.
+        assert!(detector.held(&CONFIG, bound(&triggers)).is_empty());
+        assert_eq!(detector.next_expiry(&CONFIG), None);
+        assert!(detector.release(f5(), &CONFIG, bound(&triggers)).is_none());
+    }
+
+    #[test]
+    fn unbound_long_presses_are_single_presses() {
+        let triggers = [KeyTrigger::Press];
+        let mut detector = TriggerDetector::default();
+        detector.press(f5());
+        sleep_past(CONFIG.long_press_ms);
+        assert!(detector.held(&CONFIG, bound(&triggers)).is_empty());
+        let release = detector.release(f5(), &CONFIG, bound(&triggers));
+        assert_eq!(trigger(release), Some(KeyTrigger::Press));
+    }
+}
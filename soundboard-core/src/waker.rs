@@ -0,0 +1,61 @@
+use std::{
+    sync::{
+        mpsc::{SendError, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Wakes the UI thread when a background thread has something for it, so the window does not
+/// have to repaint on a timer to notice.
+#[derive(Clone, Default)]
+pub struct Waker(Option<Arc<dyn Fn() + Send + Sync>>);
+
+impl Waker {
+    pub fn new(wake: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(wake)))
+    }
+
+    pub fn wake(&self) {
+        if let Some(wake) = &self.0 {
+            wake();
+        }
+    }
+
+    /// Wake the UI thread once `delay` has passed, from a new thread.
+    pub fn wake_after(&self, delay: Duration) {
+        let waker = self.clone();
+        thread::spawn(move || {
+            thread::sleep(delay);
+            waker.wake();
+        });
+    }
+}
+
+/// A [`Sender`] that wakes the UI thread after every message.
+pub struct WakingSender<T> {
+    sender: Sender<T>,
+    waker: Waker,
+}
+
+impl<T> Clone for WakingSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+impl<T> WakingSender<T> {
+    pub fn new(sender: Sender<T>, waker: Waker) -> Self {
+        Self { sender, waker }
+    }
+
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.sender.send(value)?;
+        self.waker.wake();
+        Ok(())
+    }
+}
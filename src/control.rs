@@ -0,0 +1,254 @@
+use crate::http::{constant_time_eq, send_command, ApiCommand, ApiRequest, ConnectionLimit};
+use soundboard_core::config::get_config_directory;
+use soundboard_core::retry::ConnectionStatus;
+use soundboard_core::supervisor::spawn_supervised;
+use soundboard_core::waker::*;
+use std::{
+    fs,
+    io::{self, prelude::*, BufReader},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How long either side waits for the other.
+const TIMEOUT: Duration = Duration::from_secs(3);
+/// The most commands handled at once. Further connections are closed right away.
+const MAX_CONNECTIONS: usize = 8;
+
+/// The file in the configuration directory telling command line invocations how to reach the
+/// running soundboard. It holds the port and the token on separate lines.
+fn get_control_path() -> Result<PathBuf, String> {
+    Ok(get_config_directory()?.join("control"))
+}
+
+/// Return a token that other users on the machine cannot guess, from the operating system's
+/// random number generator.
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|error| format!("Unable to generate a token: {error}."))?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Write `contents` to `path`, readable only by the current user where supported.
+fn write_private(path: &PathBuf, contents: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // The mode only applies to new files, so also restrict a file left by an earlier run.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+/// Listens on a loopback port for commands from `soundboard play`, `soundboard stop-all` and
+/// `soundboard list` until it is dropped.
+pub struct ControlServer {
+    request_receiver: Receiver<ApiRequest>,
+    /// What was written to the control file, so another instance's file is left alone.
+    contents: String,
+    status: Arc<Mutex<ConnectionStatus>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    /// Listen on a free loopback port and write the control file. `waker` is called whenever a
+    /// command is received.
+    pub fn start(waker: Waker) -> Result<Self, String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .map_err(|error| format!("Unable to listen: {error}."))?;
+        // Poll so the stop flag is noticed without waiting for a connection.
+        listener
+            .set_nonblocking(true)
+            .map_err(|error| format!("Unable to set listener to non-blocking: {error}."))?;
+        let port = listener
+            .local_addr()
+            .map_err(|error| format!("Unable to get the port: {error}."))?
+            .port();
+        let token = random_token()?;
+        let contents = format!("{port}\n{token}\n");
+        let path = get_control_path()?;
+        write_private(&path, &contents)
+            .map_err(|error| format!("Unable to write \"{}\": {error}.", path.display()))?;
+
+        let (request_sender, request_receiver) = mpsc::channel();
+        let request_sender = WakingSender::new(request_sender, waker);
+        let status = Arc::new(Mutex::new(ConnectionStatus::Connected));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let limit = ConnectionLimit::default();
+        let token = Arc::new(token);
+        spawn_supervised(
+            "Control".to_string(),
+            status.clone(),
+            stop.clone(),
+            move || {
+                while !thread_stop.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        // Each command gets its own thread so a client that never sends its line
+                        // cannot hold up the others.
+                        Ok((stream, _)) => {
+                            let Some(slot) = limit.acquire(MAX_CONNECTIONS) else {
+                                log!(Warning, "[Control] Too many commands at once. Closing one.");
+                                continue;
+                            };
+                            let token = token.clone();
+                            let request_sender = request_sender.clone();
+                            thread::spawn(move || {
+                                let _slot = slot;
+                                if let Err(error) = Self::handle(stream, &token, &request_sender) {
+                                    log!(Error, "[Control] Error handling command: {error}.");
+                                }
+                            });
+                        }
+                        Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(50));
+                        }
+                        Err(error) => {
+                            log!(Error, "[Control] Unable to accept connection: {error}.")
+                        }
+                    }
+                }
+                log!("[Control] Stopped listening on port {port}.");
+            },
+        );
+        log!("[Control] Listening on port {port}.");
+        Ok(Self {
+            request_receiver,
+            contents,
+            status,
+            stop,
+        })
+    }
+
+    /// Return whether commands are being received.
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Retrieve a list of new requests since this was last called.
+    pub fn requests(&self) -> Vec<ApiRequest> {
+        self.request_receiver.try_iter().collect()
+    }
+
+    /// Read one command line from `stream`, pass it to the soundboard and write the response as
+    /// a line of JSON.
+    fn handle(
+        stream: TcpStream,
+        token: &str,
+        request_sender: &WakingSender<ApiRequest>,
+    ) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let mut line = String::new();
+        BufReader::new(stream.take(4096)).read_line(&mut line)?;
+
+        let (status, body) = match line.trim_end().split_once(' ') {
+            Some((client_token, command))
+                if constant_time_eq(client_token.as_bytes(), token.as_bytes()) =>
+            {
+                match parse_command(command) {
+                    Some(command) => send_command(command, request_sender),
+                    None => (400, serde_json::json!({ "error": "Unknown command." })),
+                }
+            }
+            _ => (401, serde_json::json!({ "error": "Wrong token." })),
+        };
+        writeln!(
+            writer,
+            "{}",
+            serde_json::json!({ "status": status, "body": body })
+        )?;
+        writer.flush()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+        if let Ok(path) = get_control_path() {
+            if fs::read_to_string(&path).is_ok_and(|contents| contents == self.contents) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+fn parse_command(command: &str) -> Option<ApiCommand> {
+    let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+    match name {
+        "play" if !argument.is_empty() => Some(ApiCommand::PlayName(argument.to_string())),
+        "stop-all" => Some(ApiCommand::StopAll),
+        "list" => Some(ApiCommand::ListSounds),
+        _ => None,
+    }
+}
+
+/// Send `command` to the running soundboard and return its response.
+fn send(command: &str) -> Result<serde_json::Value, String> {
+    let path = get_control_path()?;
+    let contents =
+        fs::read_to_string(&path).map_err(|_| "The soundboard is not running.".to_string())?;
+    let mut lines = contents.lines();
+    let (port, token) = match (lines.next().map(str::parse::<u16>), lines.next()) {
+        (Some(Ok(port)), Some(token)) => (port, token),
+        _ => return Err(format!("\"{}\" is malformed.", path.display())),
+    };
+
+    let stream = TcpStream::connect_timeout(&(Ipv4Addr::LOCALHOST, port).into(), TIMEOUT)
+        .map_err(|_| "The soundboard is not running.".to_string())?;
+    let result = (|| {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        writeln!(writer, "{token} {command}")?;
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line)?;
+        Ok::<_, io::Error>(line)
+    })();
+    let line = result.map_err(|error| format!("Unable to reach the soundboard: {error}."))?;
+    let response: serde_json::Value = serde_json::from_str(&line)
+        .map_err(|_| "The soundboard sent a malformed response.".to_string())?;
+    match response["status"].as_u64() {
+        Some(200) => Ok(response["body"].clone()),
+        _ => Err(response["body"]["error"]
+            .as_str()
+            .unwrap_or("The command failed.")
+            .to_string()),
+    }
+}
+
+/// Run a command line subcommand against the running soundboard and return the exit code.
+pub fn run_subcommand(arguments: &[String]) -> i32 {
+    let command = match arguments {
+        [play, name] if play == "play" => format!("play {}", name.replace('\n', " ")),
+        [command] if command == "stop-all" || command == "list" => command.clone(),
+        _ => {
+            eprintln!("Unknown command \"{}\". See --help.", arguments.join(" "));
+            return 2;
+        }
+    };
+    match send(&command) {
+        Err(error) => {
+            eprintln!("{error}");
+            1
+        }
+        Ok(body) => {
+            for sound in body.as_array().into_iter().flatten() {
+                println!("{}", sound["name"].as_str().unwrap_or_default());
+            }
+            0
+        }
+    }
+}
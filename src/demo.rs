@@ -0,0 +1,141 @@
+use soundboard_core::config::{get_demo_directory, SoundConfig};
+use std::{f32::consts::TAU, fs, path::Path};
+
+/// The bank the demo sounds are added to.
+pub const DEMO_BANK: &str = "Demo";
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// A demo sound: its name, file name and a function generating its samples.
+type DemoSound = (&'static str, &'static str, fn() -> Vec<f32>);
+
+/// The demo sounds are generated rather than shipped, so they are free of any license.
+const DEMO_SOUNDS: [DemoSound; 4] = [
+    ("Beep", "beep.wav", beep),
+    ("Chime", "chime.wav", chime),
+    ("Sweep", "sweep.wav", sweep),
+    ("Noise Burst", "noise.wav", noise_burst),
+];
+
+/// Write the demo sounds to [`get_demo_directory`] and return their configurations.
+pub fn install_demo_sounds() -> Result<Vec<SoundConfig>, String> {
+    let directory = get_demo_directory()?;
+    if let Err(error) = fs::create_dir_all(&directory) {
+        return Err(format!("Unable to create demo directory: {error}."));
+    }
+
+    let mut sounds = Vec::new();
+    for (name, file_name, generate) in DEMO_SOUNDS {
+        let path = directory.join(file_name);
+        write_wav(&path, &generate())?;
+        sounds.push(SoundConfig {
+            path: path.display().to_string(),
+            name: name.to_string(),
+            volume_db: -10.0,
+            bank: DEMO_BANK.to_string(),
+            ..SoundConfig::default()
+        });
+    }
+    log!(
+        "[Demo] Installed {} demo sounds in \"{}\".",
+        sounds.len(),
+        directory.display()
+    );
+    Ok(sounds)
+}
+
+/// Write mono samples from -1.0 to 1.0 as a 16-bit PCM WAV file.
+fn write_wav(path: &Path, samples: &[f32]) -> Result<(), String> {
+    let data_length = samples.len() as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_length as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_length).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes()); // Format chunk length
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // Channels
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // Bytes per second
+    wav.extend_from_slice(&2u16.to_le_bytes()); // Bytes per frame
+    wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_length.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    fs::write(path, wav)
+        .map_err(|error| format!("Unable to write \"{}\": {error}.", path.display()))
+}
+
+/// Return the time of each sample in a sound `seconds` long.
+fn times(seconds: f32) -> impl Iterator<Item = f32> {
+    (0..(seconds * SAMPLE_RATE as f32) as u32).map(|i| i as f32 / SAMPLE_RATE as f32)
+}
+
+/// Fade the first and last 5 ms of `samples` to avoid clicks.
+fn fade_edges(mut samples: Vec<f32>) -> Vec<f32> {
+    let fade = (SAMPLE_RATE / 200) as usize;
+    let length = samples.len();
+    for i in 0..fade.min(length / 2) {
+        let gain = i as f32 / fade as f32;
+        samples[i] *= gain;
+        samples[length - 1 - i] *= gain;
+    }
+    samples
+}
+
+fn beep() -> Vec<f32> {
+    fade_edges(times(0.3).map(|t| 0.5 * (TAU * 880.0 * t).sin()).collect())
+}
+
+fn chime() -> Vec<f32> {
+    // A rising C major arpeggio with each note ringing out.
+    let notes = [523.25, 659.25, 783.99];
+    fade_edges(
+        times(1.5)
+            .map(|t| {
+                notes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, frequency)| {
+                        let t = t - i as f32 * 0.15;
+                        if t < 0.0 {
+                            0.0
+                        } else {
+                            0.3 * (TAU * frequency * t).sin() * (-3.0 * t).exp()
+                        }
+                    })
+                    .sum()
+            })
+            .collect(),
+    )
+}
+
+fn sweep() -> Vec<f32> {
+    // An exponential sweep from 200 Hz to 2000 Hz over one and a half seconds.
+    let (start, end, seconds) = (200.0f32, 2000.0f32, 1.5);
+    let k = (end / start).ln() / seconds;
+    fade_edges(
+        times(seconds)
+            .map(|t| 0.4 * (TAU * start * ((k * t).exp() - 1.0) / k).sin())
+            .collect(),
+    )
+}
+
+fn noise_burst() -> Vec<f32> {
+    // Decaying white noise from a xorshift generator.
+    let mut state: u32 = 0x1234_5678;
+    fade_edges(
+        times(0.8)
+            .map(|t| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let noise = state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                0.4 * noise * (-5.0 * t).exp()
+            })
+            .collect(),
+    )
+}
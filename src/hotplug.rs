@@ -0,0 +1,91 @@
+use rodio::cpal::traits::HostTrait;
+use rodio::DeviceTrait;
+use soundboard_core::audio::audio_host;
+use soundboard_core::retry::ConnectionStatus;
+use soundboard_core::supervisor::spawn_supervised;
+use soundboard_core::waker::{Waker, WakingSender};
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// The output devices listed by a [`DeviceWatcher`].
+#[derive(PartialEq, Eq, Clone)]
+pub struct ListedOutputs {
+    pub names: BTreeSet<String>,
+    /// The name of the default output device of the system.
+    pub default: Option<String>,
+}
+
+/// How often the output devices are listed.
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Lists the output devices on a background thread every [`POLL_INTERVAL`], since cpal does not
+/// report devices being plugged in or removed, e.g. a USB interface or a virtual cable created
+/// after the soundboard started. Listing can take a while, so it is kept off the UI thread.
+pub struct DeviceWatcher {
+    receiver: Receiver<ListedOutputs>,
+}
+
+impl DeviceWatcher {
+    /// `waker` is called whenever the names of the output devices or the default device change.
+    pub fn new(waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let sender = WakingSender::new(sender, waker);
+        // The thread stops once the watcher is dropped.
+        spawn_supervised(
+            "Device Watcher".to_string(),
+            Arc::new(Mutex::new(ConnectionStatus::Connected)),
+            Arc::new(AtomicBool::new(false)),
+            move || {
+                let mut last = None;
+                // Set after an error, so that an error that persists is logged once.
+                let mut failing = false;
+                loop {
+                    thread::sleep(POLL_INTERVAL);
+                    let host = audio_host();
+                    let names = match host.output_devices() {
+                        Err(error) => {
+                            if !failing {
+                                log!(
+                                    Error,
+                                    "[Device Watcher] Error finding output devices: {error}."
+                                );
+                            }
+                            failing = true;
+                            continue;
+                        }
+                        Ok(devices) => devices
+                            .filter_map(|device| device.name().ok())
+                            .collect::<BTreeSet<_>>(),
+                    };
+                    failing = false;
+                    let listed = ListedOutputs {
+                        names,
+                        default: host
+                            .default_output_device()
+                            .and_then(|device| device.name().ok()),
+                    };
+                    if last.as_ref() != Some(&listed) {
+                        last = Some(listed.clone());
+                        if sender.send(listed).is_err() {
+                            return;
+                        }
+                    }
+                }
+            },
+        );
+        Self { receiver }
+    }
+
+    /// Return the output devices if they changed since the last call.
+    pub fn changed(&self) -> Option<ListedOutputs> {
+        self.receiver.try_iter().last()
+    }
+}
@@ -0,0 +1,677 @@
+use crate::sync::{build_manifest, manifest_file, Manifest};
+use crate::websocket;
+use serde::Serialize;
+use soundboard_core::retry::*;
+use soundboard_core::supervisor::*;
+use soundboard_core::waker::*;
+use std::{
+    collections::HashMap,
+    io::{self, prelude::*, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// A command sent to the soundboard by an HTTP client.
+pub enum ApiCommand {
+    ListSounds,
+    PlayIndex(usize),
+    PlayName(String),
+    MissedTriggers,
+    /// The profile name and sounds, for `GET /manifest` and `GET /file`.
+    Sounds,
+    StopAll,
+    GetVolume,
+    SetVolume(f32),
+}
+
+/// A sound as listed by `GET /sounds`.
+#[derive(Serialize)]
+pub struct SoundInfo {
+    pub index: usize,
+    pub name: String,
+    pub key: String,
+    pub volume: f32,
+    pub playing: bool,
+}
+
+/// An [`ApiCommand`] waiting to be handled by the soundboard.
+pub struct ApiRequest {
+    pub command: ApiCommand,
+    response_sender: Sender<(u16, serde_json::Value)>,
+}
+
+impl ApiRequest {
+    /// Send a successful response with `body` to the HTTP client.
+    pub fn respond(self, body: serde_json::Value) {
+        let _ = self.response_sender.send((200, body));
+    }
+
+    /// Send an error response to the HTTP client.
+    pub fn fail(self, status: u16, error: &str) {
+        self.respond_with_status(status, serde_json::json!({ "error": error }));
+    }
+
+    /// Send a response with `status` and `body` to the HTTP client.
+    pub fn respond_with_status(self, status: u16, body: serde_json::Value) {
+        let _ = self.response_sender.send((status, body));
+    }
+}
+
+/// What `GET /manifest` and `GET /file` answer with.
+enum SyncResponse {
+    Manifest(Manifest),
+    /// The path of the file to send.
+    File(String),
+}
+
+/// State shared between the [`HttpServerManager`] and its server thread.
+struct Shared {
+    token: Mutex<String>,
+    /// Senders pushing state to each WebSocket client.
+    clients: Mutex<Vec<Sender<String>>>,
+    /// The last state pushed to WebSocket clients, sent to new clients when they connect.
+    state: Mutex<String>,
+}
+
+/// A request line, query string and headers read from an HTTP client.
+struct HttpRequest {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+}
+
+pub struct HttpServerManager {
+    server_thread: Option<thread::JoinHandle<()>>,
+    request_receiver: Option<Receiver<ApiRequest>>,
+    shared: Arc<Shared>,
+    status: Arc<Mutex<ConnectionStatus>>,
+    stop: Arc<AtomicBool>,
+    waker: Waker,
+}
+
+impl HttpServerManager {
+    /// How long a client may take to send its request and how long it waits for the soundboard.
+    const TIMEOUT: Duration = Duration::from_secs(2);
+    /// The longest request head that is read from a client.
+    const MAX_REQUEST_SIZE: u64 = 8192;
+    /// The most requests handled at once. Further connections are closed right away.
+    const MAX_CONNECTIONS: usize = 32;
+    /// How long a client downloading a file may go without reading any of it.
+    const FILE_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Create a new HTTP server manager. Nothing will be done until `start` is called.
+    /// `waker` is called whenever a request is received.
+    pub fn new(waker: Waker) -> Self {
+        Self {
+            server_thread: None,
+            request_receiver: None,
+            shared: Arc::new(Shared {
+                token: Mutex::new(String::new()),
+                clients: Mutex::new(Vec::new()),
+                state: Mutex::new(String::new()),
+            }),
+            status: Arc::new(Mutex::new(ConnectionStatus::Disconnected)),
+            stop: Arc::new(AtomicBool::new(false)),
+            waker,
+        }
+    }
+
+    /// Listen for HTTP requests on `address` and `port` in a new thread. Failures to bind are
+    /// retried with backoff until `stop` is called.
+    pub fn start(&mut self, address: String, port: u16, token: String) {
+        self.stop();
+        let (request_sender, request_receiver) = mpsc::channel();
+        let request_sender = WakingSender::new(request_sender, self.waker.clone());
+        let status = Arc::new(Mutex::new(ConnectionStatus::Connecting));
+        let stop = Arc::new(AtomicBool::new(false));
+        self.request_receiver = Some(request_receiver);
+        self.status = status.clone();
+        self.stop = stop.clone();
+        self.set_token(token);
+        let shared = self.shared.clone();
+        let limit = ConnectionLimit::default();
+        self.server_thread = Some(spawn_supervised(
+            "HTTP API".to_string(),
+            status.clone(),
+            stop.clone(),
+            move || {
+                let mut circuit_breaker = CircuitBreaker::new(RetryPolicy::default());
+                let listener =
+                    match circuit_breaker.retry(&status, &stop, || Self::bind(&address, port)) {
+                        Some(listener) => listener,
+                        None => return,
+                    };
+                while !stop.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        // Each connection gets its own thread so a slow client cannot hold up
+                        // the others.
+                        Ok((stream, address)) => {
+                            let Some(slot) = limit.acquire(Self::MAX_CONNECTIONS) else {
+                                log!(
+                                    Warning,
+                                    "[HTTP API] Too many requests at once. Closing the connection from {address}."
+                                );
+                                continue;
+                            };
+                            let shared = shared.clone();
+                            let request_sender = request_sender.clone();
+                            let stop = stop.clone();
+                            thread::spawn(move || {
+                                let _slot = slot;
+                                if let Err(error) =
+                                    Self::handle(stream, &shared, &request_sender, &stop)
+                                {
+                                    log!(
+                                        Error,
+                                        "[HTTP API] Error handling request from {address}: {error}."
+                                    );
+                                }
+                            });
+                        }
+                        Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(Duration::from_millis(50));
+                        }
+                        Err(error) => {
+                            log!(Error, "[HTTP API] Unable to accept connection: {error}.")
+                        }
+                    }
+                }
+                log!("[HTTP API] Stopped listening on port {port}.");
+                *status.lock().unwrap() = ConnectionStatus::Disconnected;
+            },
+        ));
+    }
+
+    /// Stop listening for HTTP requests.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+        self.request_receiver = None;
+        self.server_thread = None;
+    }
+
+    /// Check if the server is listening or trying to listen.
+    pub fn running(&self) -> bool {
+        self.request_receiver.is_some()
+            && self
+                .server_thread
+                .as_ref()
+                .is_some_and(|h| !h.is_finished())
+    }
+
+    /// Set the token clients must send. Takes effect for the next request.
+    pub fn set_token(&self, token: String) {
+        *self.shared.token.lock().unwrap() = token;
+    }
+
+    /// Push `state` to every WebSocket client if it changed since it was last published.
+    pub fn publish_state(&self, state: String) {
+        let mut last_state = self.shared.state.lock().unwrap();
+        if *last_state == state {
+            return;
+        }
+        self.shared
+            .clients
+            .lock()
+            .unwrap()
+            .retain(|client| client.send(state.clone()).is_ok());
+        *last_state = state;
+    }
+
+    /// Push `event` to every WebSocket client. Unlike the state, events are not sent to clients
+    /// that connect later.
+    pub fn publish_event(&self, event: String) {
+        self.shared
+            .clients
+            .lock()
+            .unwrap()
+            .retain(|client| client.send(event.clone()).is_ok());
+    }
+
+    /// Return the status of the server.
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Retrieve a list of new requests since this was last called.
+    /// This will be emptied when stopped.
+    pub fn requests(&self) -> Vec<ApiRequest> {
+        match self.request_receiver.as_ref() {
+            Some(r) => r.try_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn bind(address: &str, port: u16) -> Option<TcpListener> {
+        let listener = match TcpListener::bind((address, port)) {
+            Err(error) => {
+                log!(
+                    Error,
+                    "[HTTP API] Unable to listen on {address} port {port}: {error}."
+                );
+                return None;
+            }
+            Ok(listener) => listener,
+        };
+        // Poll so the stop flag is noticed without waiting for a connection.
+        if let Err(error) = listener.set_nonblocking(true) {
+            log!(
+                Error,
+                "[HTTP API] Unable to set listener to non-blocking: {error}."
+            );
+            return None;
+        }
+        log!("[HTTP API] Listening on {address} port {port}.");
+        Some(listener)
+    }
+
+    /// Read one request from `stream`, pass it to the soundboard and write the response.
+    /// Requests to `/ws` are upgraded to a WebSocket served by its own thread.
+    fn handle(
+        stream: TcpStream,
+        shared: &Shared,
+        request_sender: &WakingSender<ApiRequest>,
+        stop: &Arc<AtomicBool>,
+    ) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Self::TIMEOUT))?;
+        stream.set_write_timeout(Some(Self::TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+
+        let (status, body) = match Self::read_request(stream) {
+            None => (400, serde_json::json!({ "error": "Malformed request." })),
+            Some(request) => {
+                let token = shared.token.lock().unwrap().clone();
+                if !Self::authorized(&request, &token) {
+                    (
+                        401,
+                        serde_json::json!({ "error": "Missing or wrong token." }),
+                    )
+                } else if request.path.trim_end_matches('/') == "/ws" {
+                    match request.headers.get("sec-websocket-key") {
+                        Some(key)
+                            if request.headers.get("upgrade").is_some_and(|upgrade| {
+                                upgrade.eq_ignore_ascii_case("websocket")
+                            }) =>
+                        {
+                            let client = websocket::accept(
+                                writer,
+                                key,
+                                request_sender.clone(),
+                                stop.clone(),
+                            )?;
+                            let mut clients = shared.clients.lock().unwrap();
+                            let state = shared.state.lock().unwrap();
+                            if !state.is_empty() {
+                                let _ = client.send(state.clone());
+                            }
+                            clients.push(client);
+                            return Ok(());
+                        }
+                        _ => (
+                            400,
+                            serde_json::json!({ "error": "Expected a WebSocket upgrade." }),
+                        ),
+                    }
+                } else if matches!(request.path.trim_end_matches('/'), "/manifest" | "/file") {
+                    match Self::sync_request(&request, request_sender) {
+                        Err((status, error)) => (status, serde_json::json!({ "error": error })),
+                        Ok(SyncResponse::Manifest(manifest)) => {
+                            (200, serde_json::to_value(manifest).unwrap_or_default())
+                        }
+                        Ok(SyncResponse::File(path)) => return Self::write_file(writer, &path),
+                    }
+                } else {
+                    match Self::route(&request) {
+                        Err((status, error)) => (status, serde_json::json!({ "error": error })),
+                        Ok(command) => send_command(command, request_sender),
+                    }
+                }
+            }
+        };
+
+        let body = body.to_string();
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            _ => "Service Unavailable",
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )?;
+        writer.flush()
+    }
+
+    /// Answer `GET /manifest` with the manifest of the sounds, or find the file of a sound for
+    /// `GET /file?hash=`. The files are hashed on this thread, so the soundboard is not held up.
+    fn sync_request(
+        request: &HttpRequest,
+        request_sender: &WakingSender<ApiRequest>,
+    ) -> Result<SyncResponse, (u16, String)> {
+        if request.method != "GET" {
+            return Err((405, "Use GET.".to_string()));
+        }
+        let (status, body) = send_command(ApiCommand::Sounds, request_sender);
+        if status != 200 {
+            let error = body["error"]
+                .as_str()
+                .unwrap_or("The soundboard did not respond.");
+            return Err((status, error.to_string()));
+        }
+        let profile = body["profile"].as_str().unwrap_or_default().to_string();
+        let sounds = serde_json::from_value(body["sounds"].clone())
+            .map_err(|error| (503, format!("Unable to read the sounds: {error}.")))?;
+        let manifest = build_manifest(profile, sounds);
+        if request.path.trim_end_matches('/') == "/manifest" {
+            return Ok(SyncResponse::Manifest(manifest));
+        }
+        let hash = request
+            .query
+            .get("hash")
+            .ok_or((400, "Give the hash of the file as hash.".to_string()))?;
+        manifest_file(&manifest, hash)
+            .map(SyncResponse::File)
+            .ok_or((404, format!("No sound file with hash {hash}.")))
+    }
+
+    /// Stream the file at `path` to the client.
+    fn write_file(mut writer: TcpStream, path: &str) -> io::Result<()> {
+        let file = std::fs::File::open(path)?;
+        let size = file.metadata()?.len();
+        // The timeout applies to each write, so a slow client still gets large files, but one
+        // that stops reading is given up on.
+        writer.set_write_timeout(Some(Self::FILE_WRITE_TIMEOUT))?;
+        write!(
+            writer,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {size}\r\nConnection: close\r\n\r\n"
+        )?;
+        io::copy(&mut file.take(size), &mut writer)?;
+        writer.flush()
+    }
+
+    /// Read the request line and headers. Returns None if they are malformed or the client
+    /// closed the connection before the end of the headers.
+    fn read_request(stream: impl Read) -> Option<HttpRequest> {
+        let mut reader = BufReader::new(stream.take(Self::MAX_REQUEST_SIZE));
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let (method, target) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [method, target, version]
+                if version.starts_with("HTTP/") && target.starts_with('/') =>
+            {
+                (method.to_string(), target)
+            }
+            _ => return None,
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let (path, query) = (percent_decode(path), parse_query(query));
+
+        let mut headers = HashMap::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).ok()? == 0 {
+                return None;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(':')?;
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return None;
+            }
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+
+        Some(HttpRequest {
+            method,
+            path,
+            query,
+            headers,
+        })
+    }
+
+    /// Check the token given as a bearer token or a `token` query parameter.
+    /// An empty token rejects every request.
+    fn authorized(request: &HttpRequest, token: &str) -> bool {
+        if token.is_empty() {
+            return false;
+        }
+        let bearer = request
+            .headers
+            .get("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+        [bearer, request.query.get("token").map(String::as_str)]
+            .into_iter()
+            .flatten()
+            .fold(false, |authorized, given| {
+                constant_time_eq(given.as_bytes(), token.as_bytes()) | authorized
+            })
+    }
+
+    fn route(request: &HttpRequest) -> Result<ApiCommand, (u16, String)> {
+        let method = request.method.as_str();
+        let command = match request.path.trim_end_matches('/') {
+            "/sounds" => match method {
+                "GET" => ApiCommand::ListSounds,
+                _ => return Err((405, "Use GET.".to_string())),
+            },
+            "/missed" => match method {
+                "GET" => ApiCommand::MissedTriggers,
+                _ => return Err((405, "Use GET.".to_string())),
+            },
+            "/play" => {
+                if method != "POST" {
+                    return Err((405, "Use POST.".to_string()));
+                }
+                if let Some(index) = request.query.get("index") {
+                    match index.parse() {
+                        Ok(index) => ApiCommand::PlayIndex(index),
+                        Err(_) => return Err((400, format!("Invalid index {index}."))),
+                    }
+                } else if let Some(name) = request.query.get("name") {
+                    ApiCommand::PlayName(name.clone())
+                } else {
+                    return Err((400, "Give an index or name.".to_string()));
+                }
+            }
+            "/stop" => match method {
+                "POST" => ApiCommand::StopAll,
+                _ => return Err((405, "Use POST.".to_string())),
+            },
+            "/volume" => match method {
+                "GET" => ApiCommand::GetVolume,
+                "POST" => match request.query.get("db").map(|db| db.parse::<f32>()) {
+                    Some(Ok(db)) if db.is_finite() => ApiCommand::SetVolume(db),
+                    _ => return Err((400, "Give a volume in dB as db.".to_string())),
+                },
+                _ => return Err((405, "Use GET or POST.".to_string())),
+            },
+            path => return Err((404, format!("No endpoint {path}."))),
+        };
+        Ok(command)
+    }
+}
+
+/// Pass `command` to the soundboard and wait for its response.
+pub fn send_command(
+    command: ApiCommand,
+    request_sender: &WakingSender<ApiRequest>,
+) -> (u16, serde_json::Value) {
+    let (response_sender, response_receiver) = mpsc::channel();
+    let request = ApiRequest {
+        command,
+        response_sender,
+    };
+    if request_sender.send(request).is_err() {
+        return (503, serde_json::json!({ "error": "Soundboard stopped." }));
+    }
+    match response_receiver.recv_timeout(HttpServerManager::TIMEOUT) {
+        Ok(response) => response,
+        Err(_) => (
+            503,
+            serde_json::json!({ "error": "Soundboard did not respond." }),
+        ),
+    }
+}
+
+/// Compare `a` and `b` in a time that depends only on their lengths, so the token cannot be
+/// guessed one byte at a time.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Counts the connections being handled by a server, so a flood of clients cannot start an
+/// unlimited number of threads.
+#[derive(Clone, Default)]
+pub struct ConnectionLimit(Arc<AtomicUsize>);
+
+impl ConnectionLimit {
+    /// Take a slot for a new connection if fewer than `max` are being handled. The slot is freed
+    /// when the returned guard is dropped.
+    pub fn acquire(&self, max: usize) -> Option<ConnectionSlot> {
+        self.0
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < max).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| ConnectionSlot(self.0.clone()))
+    }
+}
+
+/// A connection counted by a [`ConnectionLimit`].
+pub struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Split a query string into decoded names and values.
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` in a URL component.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(request: &str) -> Option<HttpRequest> {
+        HttpServerManager::read_request(request.as_bytes())
+    }
+
+    #[test]
+    fn requests_are_parsed() {
+        let request = parse(
+            "POST /play%20name?name=Air+horn&token=a%26b HTTP/1.1\r\nHost: localhost\r\nAuthorization:  Bearer secret \r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/play name");
+        assert_eq!(request.query["name"], "Air horn");
+        assert_eq!(request.query["token"], "a&b");
+        assert_eq!(request.headers["host"], "localhost");
+        assert_eq!(request.headers["authorization"], "Bearer secret");
+        assert!(HttpServerManager::authorized(&request, "secret"));
+        assert!(!HttpServerManager::authorized(&request, "other"));
+        assert!(!HttpServerManager::authorized(&request, ""));
+    }
+
+    #[test]
+    fn malformed_request_lines_are_rejected() {
+        for line in [
+            "",
+            "\r\n",
+            "GET\r\n",
+            "GET /sounds\r\n",
+            "GET sounds HTTP/1.1\r\n",
+            "GET /sounds FTP/1.0\r\n",
+            "GET /sounds HTTP/1.1 extra\r\n",
+        ] {
+            assert!(parse(&format!("{line}\r\n")).is_none(), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn malformed_headers_are_rejected() {
+        for header in ["Host localhost", ": localhost", "Bad Name: value"] {
+            let request = format!("GET /sounds HTTP/1.1\r\n{header}\r\n\r\n");
+            assert!(parse(&request).is_none(), "{header:?}");
+        }
+        // The headers must end with an empty line.
+        assert!(parse("GET /sounds HTTP/1.1\r\nHost: localhost\r\n").is_none());
+        // They must also fit in the request size limit.
+        let long = "a".repeat(HttpServerManager::MAX_REQUEST_SIZE as usize);
+        assert!(parse(&format!("GET /sounds HTTP/1.1\r\nX-Long: {long}\r\n\r\n")).is_none());
+    }
+
+    #[test]
+    fn percent_escapes_are_decoded() {
+        assert_eq!(percent_decode("a%2Fb+c"), "a/b c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn connections_are_limited() {
+        let limit = ConnectionLimit::default();
+        let first = limit.acquire(2).unwrap();
+        let _second = limit.acquire(2).unwrap();
+        assert!(limit.acquire(2).is_none());
+        drop(first);
+        assert!(limit.acquire(2).is_some());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::worker::WorkerPool;
+use rodio::source::UniformSourceIterator;
+use rodio::{Decoder, Source};
+use sha2::{Digest, Sha256};
+use soundboard_core::as_hex::as_hex;
+use soundboard_core::config::get_library_directory;
+use soundboard_core::supervisor::catch_panic;
+use soundboard_core::waker::{Waker, WakingSender};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+/// The sample rate of the files in the media library.
+const SAMPLE_RATE: u32 = 48000;
+
+/// Transcodes audio files into the media library on a background thread, so that every sound
+/// is a 48 kHz 16-bit WAV file that is cheap to decode and plays the same on every computer.
+pub struct Importer {
+    workers: WorkerPool,
+    sender: WakingSender<(String, Result<String, String>)>,
+    receiver: Receiver<(String, Result<String, String>)>,
+    /// The paths being transcoded.
+    pending: HashSet<String>,
+}
+
+impl Importer {
+    /// `waker` is called whenever a file is transcoded.
+    pub fn new(waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            workers: WorkerPool::new("Import Worker", 1),
+            sender: WakingSender::new(sender, waker),
+            receiver,
+            pending: HashSet::new(),
+        }
+    }
+
+    /// Queue the file at `path` to be transcoded, unless it already is in the library or queued.
+    pub fn import(&mut self, path: &str) {
+        if path.is_empty() || in_library(path) || !self.pending.insert(path.to_string()) {
+            return;
+        }
+        let sender = self.sender.clone();
+        let path = path.to_string();
+        self.workers.spawn(move || {
+            // Some decoders panic on malformed files.
+            let result = catch_panic(|| transcode(&path))
+                .unwrap_or_else(|error| Err(format!("Decoding file {path} panicked: {error}.")));
+            let _ = sender.send((path, result));
+        });
+    }
+
+    /// Return the number of files waiting to be transcoded.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Return the paths transcoded since the last call with the path of their copy in the
+    /// library, or why they could not be transcoded.
+    pub fn finished(&mut self) -> Vec<(String, Result<String, String>)> {
+        let finished: Vec<_> = self.receiver.try_iter().collect();
+        for (path, _) in &finished {
+            self.pending.remove(path);
+        }
+        finished
+    }
+}
+
+/// Return true if `path` is in the media library.
+fn in_library(path: &str) -> bool {
+    get_library_directory().is_ok_and(|directory| Path::new(path).starts_with(directory))
+}
+
+/// Return the path of the copy of `path` in the library. The name keeps the file's name and adds
+/// a hash of its path, so files with the same name in different folders do not clash.
+fn library_path(path: &str) -> Result<PathBuf, String> {
+    let stem = Path::new(path)
+        .file_stem()
+        .map_or("sound".into(), |stem| stem.to_string_lossy());
+    let hash = as_hex(&Sha256::digest(path.as_bytes())[..4]).to_lowercase();
+    Ok(get_library_directory()?.join(format!("{stem}-{hash}.wav")))
+}
+
+/// Decode the audio file at `path` and write it to the library at [`SAMPLE_RATE`], keeping its
+/// channels. Returns the path written.
+fn transcode(path: &str) -> Result<String, String> {
+    let file = BufReader::new(match File::open(path) {
+        Err(error) => return Err(format!("Unable to read file {path}: {error}.")),
+        Ok(file) => file,
+    });
+    let source = match Decoder::new(file) {
+        Err(error) => return Err(format!("Unable to decode file {path}: {error}.")),
+        Ok(source) => source,
+    };
+    let channels = source.channels().max(1);
+    let destination = library_path(path)?;
+    if let Some(directory) = destination.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("Unable to create the media library: {error}."))?;
+    }
+
+    // Write to a temporary file first, so a failed import never leaves a partial copy in use.
+    let temporary = destination.with_extension("wav.tmp");
+    let write_error =
+        |error: hound::Error| format!("Unable to write \"{}\": {error}.", temporary.display());
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&temporary, spec).map_err(write_error)?;
+    for sample in UniformSourceIterator::<_, f32>::new(source, channels, SAMPLE_RATE) {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        writer.write_sample(sample).map_err(write_error)?;
+    }
+    writer.finalize().map_err(write_error)?;
+    fs::rename(&temporary, &destination).map_err(|error| {
+        format!(
+            "Unable to move \"{}\" into the media library: {error}.",
+            temporary.display()
+        )
+    })?;
+    Ok(destination.display().to_string())
+}
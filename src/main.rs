@@ -1,280 +1,138 @@
+use chrono::Datelike;
 use eframe::egui;
-use egui::{Button, Color32, RichText, Slider, TextEdit, TextStyle, Vec2};
+use egui::plot::{Bar, BarChart, Plot};
+use egui::{
+    Button, Color32, RichText, SelectableLabel, Slider, TextEdit, TextStyle, Vec2, WidgetInfo,
+    WidgetType,
+};
 use rodio::cpal;
 use rodio::cpal::traits::HostTrait;
 use rodio::DeviceTrait;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::convert::AsRef;
-use std::fs;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::io::Write;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
-mod as_hex;
-mod event;
-use event::*;
-mod input;
-use input::*;
-mod audio;
-use audio::*;
-
-/// Holds configuration values read from config.toml.
-#[derive(Serialize, Deserialize)]
-struct Config {
-    server_address: String,
-    api_key: String,
-    volume: f32,
-    outputs: HashMap<String, OutputConfig>,
-    sounds: Vec<SoundConfig>,
-    shortcuts: ShortcutsConfig,
-}
-
-/// Holds audio output configuration
-#[derive(Serialize, Deserialize)]
-struct OutputConfig {
-    volume: f32,
-    mute: KeyButton,
-}
-
-/// Holds shortcut configuration.
-#[derive(Serialize, Deserialize)]
-struct ShortcutsConfig {
-    pause: KeyButton,
-    stop: KeyButton,
-    modifier: KeyButton,
-}
-
-/// Holds a sound configuration.
-#[derive(Serialize, Deserialize, Clone)]
-pub struct SoundConfig {
-    path: String,
-    name: String,
-    volume: f32,
-    key: KeyButton,
-}
-
-impl Default for SoundConfig {
-    fn default() -> Self {
-        Self {
-            path: String::new(),
-            name: String::new(),
-            volume: 0.0,
-            key: KeyButton::default(),
-        }
-    }
-}
-
-/// Get the path of the configuration file path.
-/// [this executable's directory]/config.toml
-fn get_config_file_path() -> Result<std::path::PathBuf, String> {
-    match std::env::current_exe() {
-        Err(exe_path_error) => {
-            return Err(format!(
-                "Unable to obtain executable directory: {exe_path_error}."
-            ))
-        }
-        Ok(exe_path) => match exe_path.parent() {
-            None => return Err("Unable to obtain executable directory.".to_string()),
-            Some(parent_dir) => Ok(parent_dir.join("config.toml")),
-        },
-    }
-}
-
-/// Load the toml configuration from [`get_config_file_path`].
-fn load_config() -> Result<Config, String> {
-    let config_file_path = get_config_file_path()?;
-    println!(
-        "[Configuration Loader] Loading configuration file \"{}\".",
-        config_file_path.display()
-    );
-
-    match fs::read_to_string(&config_file_path) {
-        Ok(config_data) => match toml::from_str(&config_data) {
-            Err(error) => {
-                println!(
-                    "[Configuration Loader] Unable to deserialize configuration file: {error}."
-                );
-                Err(format!(
-                    "Unable to deserialize configuration file: {error}."
-                ))
-            }
-            Ok(config) => Ok(config),
-        },
-        Err(read_error) => {
-            println!("Unable to open configuration file: {read_error}. Installing default.");
-            if let Err(write_error) =
-                fs::write(&config_file_path, include_str!("default_config.toml"))
-            {
-                println!("[Configuration Loader] Unable to install default configuration file: {write_error}.");
-                return Err(format!(
-                    "Unable to install default configuration file: {write_error}."
-                ));
-            }
-            match fs::read_to_string(&config_file_path) {
-                Err(read_error) => {
-                    println!("[Configuration Loader] Unable to open newly created configuration file: {read_error}.");
-                    return Err(format!(
-                        "Unable to open newly created configuration file: {read_error}."
-                    ));
-                }
-                Ok(serialized_config) => match toml::from_str(&serialized_config) {
-                    Err(deserialize_error) => {
-                        println!("[Configuration Loader] Unable to deserialize default configuration file: {deserialize_error}.");
-                        Err(format!("Unable to deserialize default configuration file: {deserialize_error}."))
-                    }
-                    Ok(config) => Ok(config),
-                },
-            }
-        }
-    }
-}
-
-struct ConfigSaver {
-    last_serialized: String,
-    last_saved: SystemTime,
-    autosave_interval: Duration,
-}
+use std::time::{Duration, Instant};
+// Makes `log!` available to every module.
+#[macro_use]
+extern crate soundboard_core;
+use soundboard_core::audio::*;
+use soundboard_core::channel_test::*;
+use soundboard_core::check::check_profiles;
+use soundboard_core::config::*;
+use soundboard_core::dsp::{db_to_amplitude, Ducker, DuckingSettings};
+use soundboard_core::event::*;
+use soundboard_core::foreign::ForeignApp;
+use soundboard_core::gamepad::*;
+use soundboard_core::i18n::{language_name, list_languages, manual, set_language, tr};
+use soundboard_core::input::*;
+use soundboard_core::log::{log_to_stderr, recent_lines, wake_on_error, Level};
+use soundboard_core::microphone::*;
+use soundboard_core::retry::*;
+use soundboard_core::trigger::*;
+use soundboard_core::waker::*;
 
-impl ConfigSaver {
-    fn new(autosave_interval: Duration) -> Self {
-        Self {
-            last_serialized: String::new(),
-            last_saved: SystemTime::now(),
-            autosave_interval,
-        }
-    }
-
-    /// Save the toml configuration to [`get_config_file_path`].
-    /// Returns true if saved, false if not saved, or a string describing an error.
-    fn save(&mut self, config: &Config) -> Result<bool, String> {
-        if SystemTime::now() - self.autosave_interval < self.last_saved {
-            return Ok(false);
-        }
-
-        match toml::to_string_pretty(config) {
-            Err(error) => {
-                println!("[Configuration Saver] Unable to serialize configuration file: {error}.");
-                Err(format!("Unable to serialize configuration file: {error}."))
-            }
-            Ok(serialized_config) => {
-                if serialized_config == self.last_serialized {
-                    return Ok(false);
-                }
-                self.last_serialized = serialized_config;
-                self.last_saved = SystemTime::now();
-                let config_file_path = get_config_file_path()?;
-                println!(
-                    "[Configuration Saver] Saving configuration file \"{}\".",
-                    config_file_path.display()
-                );
-                match fs::write(&config_file_path, &self.last_serialized) {
-                    Err(error) => {
-                        println!(
-                            "[Configuration Saver] Unable to write configuration file: {error}."
-                        );
-                        Err(format!("Unable to write configuration file: {error}."))
-                    }
-                    Ok(_) => Ok(true),
-                }
-            }
-        }
-    }
-}
-
-/// Format a [`SystemTime`] as T+{ms} or T-{ms} relative to the current system time.
-fn format_timestamp(timestamp: SystemTime) -> String {
-    match timestamp.elapsed() {
-        Ok(duration) => format!("T-{}ms", duration.as_millis()),
-        Err(system_time_error) => format!("T+{}ms", system_time_error.duration().as_millis()),
-    }
-}
-
-#[derive(Clone)]
-struct KeyButton {
-    pub key: Key,
-    listening: bool,
-}
-
-impl Serialize for KeyButton {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        self.key.serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for KeyButton {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        Ok(Self {
-            key: Key::deserialize(deserializer)?,
-            listening: false,
-        })
-    }
-}
+mod control;
+use control::*;
+mod demo;
+use demo::*;
+mod hotplug;
+use hotplug::*;
+mod palette;
+use palette::*;
+mod profiler;
+use profiler::*;
+mod stats;
+use stats::*;
+mod http;
+use http::*;
+mod import;
+use import::*;
+mod support;
+use support::*;
+mod pack;
+use pack::*;
+mod sync;
+use sync::*;
+mod theme;
+use theme::*;
+mod toast;
+use toast::*;
+mod updater;
+use updater::*;
+mod waveform;
+use waveform::*;
+mod watcher;
+use watcher::*;
+mod websocket;
+mod worker;
 
-impl Default for KeyButton {
-    fn default() -> Self {
-        Self::new(Key::KEY_RESERVED)
-    }
+/// Shows a [`KeyButton`] that binds the next key released after it is clicked, with the
+/// modifier keys held at the time.
+trait KeyButtonUi {
+    const MIN_SIZE: Vec2 = Vec2::new(120.0, 10.0);
+    fn update(
+        &mut self,
+        ui: &mut egui::Ui,
+        last_key_released: Option<&KeyRelease>,
+    ) -> egui::Response;
+    fn label(&self) -> String;
 }
 
-impl KeyButton {
-    const MIN_SIZE: Vec2 = Vec2::new(120.0, 10.0);
-    fn new(key: Key) -> Self {
-        Self {
-            key,
-            listening: false,
-        }
-    }
-    fn update(&mut self, ui: &mut egui::Ui, last_key_released: Option<Key>) -> egui::Response {
+impl KeyButtonUi for KeyButton {
+    fn update(
+        &mut self,
+        ui: &mut egui::Ui,
+        last_key_released: Option<&KeyRelease>,
+    ) -> egui::Response {
         let response = if self.listening {
             // Listening for a key release...
-            if let Some(key) = last_key_released {
+            if let Some(release) = last_key_released {
                 // We have obtained a last released key. Set the new value and stop listening.
-                self.key = key;
+                self.bind(release);
                 self.listening = false;
-                ui.add(
-                    Button::new(if self.key == Key::KEY_RESERVED {
-                        "None"
-                    } else {
-                        self.key.as_ref()
-                    })
-                    .min_size(Self::MIN_SIZE),
-                )
+                ui.add(Button::new(self.label()).min_size(Self::MIN_SIZE))
             } else {
                 // No key has been released.
                 ui.add(Button::new("Binding...").min_size(Self::MIN_SIZE))
             }
         } else {
             // We aren't listening.
-            ui.add(
-                Button::new(if self.key == Key::KEY_RESERVED {
-                    "None"
-                } else {
-                    self.key.as_ref()
-                })
-                .min_size(Self::MIN_SIZE),
-            )
+            ui.add(Button::new(self.label()).min_size(Self::MIN_SIZE))
         };
 
         if response.clicked() {
             // When clicked, toggle listening.
             self.listening ^= true;
         }
-        if response.secondary_clicked() {
-            self.listening = false;
-            self.key = Key::KEY_RESERVED;
-        }
+        // Right clicking chooses the trigger or clears the key bind.
+        response.context_menu(|ui| {
+            for trigger in KeyTrigger::ALL {
+                if ui
+                    .radio_value(&mut self.trigger, trigger, trigger.label())
+                    .clicked()
+                {
+                    ui.close_menu();
+                }
+            }
+            ui.separator();
+            if ui.button("Clear").clicked() {
+                *self = KeyButton::default();
+                ui.close_menu();
+            }
+        })
+    }
 
-        response
+    fn label(&self) -> String {
+        if self.input == Input::default() {
+            "None".to_string()
+        } else {
+            self.to_string()
+        }
     }
 }
 
-fn toggle_ui(ui: &mut egui::Ui, on: &mut bool) -> egui::Response {
+fn toggle_ui(ui: &mut egui::Ui, on: &mut bool, colors: ThemeColors) -> egui::Response {
     let desired_size = Vec2::new(50.0, 25.0);
     let (rect, mut response) = ui.allocate_exact_size(desired_size, egui::Sense::click());
     if response.clicked() {
@@ -291,7 +149,7 @@ fn toggle_ui(ui: &mut egui::Ui, on: &mut bool) -> egui::Response {
         ui.painter().rect(
             rect,
             radius,
-            if *on { Color32::GREEN } else { Color32::RED },
+            if *on { colors.playing } else { colors.stopped },
             visuals.bg_stroke,
         );
         let circle_x = egui::lerp((rect.left() + radius)..=(rect.right() - radius), how_on);
@@ -303,372 +161,6230 @@ fn toggle_ui(ui: &mut egui::Ui, on: &mut bool) -> egui::Response {
     response
 }
 
-struct Soundboard {
-    config: Config,
-    client_manager: RemoteInputClientManager,
-    modified: bool,
-    config_saver: ConfigSaver,
-    output_devices: HashMap<String, OutputDevice>,
-    audio_controls: Vec<Arc<AudioControls>>,
-    playing: bool,
-    enabled: bool,
-    settings_window: bool,
-    manual_window: bool,
-    new_sound: SoundConfig,
-    dropped_file: (i64, Option<String>),
+/// Show the trim point fields of `sound`. The start is kept before the end, and both within the
+/// file once `waveform` knows its duration.
+fn trim_ui(ui: &mut egui::Ui, sound: &mut SoundConfig, waveform: Option<&WaveformState>) {
+    let duration_ms = match waveform {
+        Some(WaveformState::Loaded(waveform)) => waveform.duration.as_millis() as u64,
+        _ => u64::MAX,
+    };
+    ui.horizontal(|ui| {
+        let start = ui
+            .add(egui::DragValue::new(&mut sound.start_ms).suffix(" ms"))
+            .on_hover_text(tr("Start"));
+        if start.changed() {
+            let end_ms = match sound.end_ms {
+                0 => duration_ms,
+                end_ms => end_ms.min(duration_ms),
+            };
+            sound.start_ms = sound.start_ms.min(end_ms);
+        }
+        let end = ui
+            .add(egui::DragValue::new(&mut sound.end_ms).suffix(" ms"))
+            .on_hover_text(tr("End (0 plays to the end)"));
+        // 0 stays allowed, since it plays to the end.
+        if end.changed() && sound.end_ms != 0 {
+            sound.end_ms = sound
+                .end_ms
+                .clamp(sound.start_ms.min(duration_ms), duration_ms);
+        }
+    });
 }
 
-impl Soundboard {
-    const CONFIG_AUTOSAVE: Duration = Duration::from_secs(30);
-    const MAX_FRAME_DELAY: Duration = Duration::from_millis(100);
-    const VOLUME_RANGE: RangeInclusive<f32> = -50.0..=0.0;
-    const SOUND_VOLUME_RANGE: RangeInclusive<f32> = -50.0..=50.0;
-
-    /// Create a new [`Soundboard`].
-    fn new(_: &eframe::CreationContext<'_>) -> Self {
-        // Load configuration file.
-        let config = load_config().unwrap();
-
-        let mut self_ = Self {
-            config,
-            client_manager: RemoteInputClientManager::new(),
-            modified: false,
-            config_saver: ConfigSaver::new(Self::CONFIG_AUTOSAVE),
-            output_devices: HashMap::new(),
-            audio_controls: Vec::new(),
-            playing: true,
-            enabled: false,
-            settings_window: false,
-            manual_window: false,
-            new_sound: SoundConfig::default(),
-            dropped_file: (0, None),
+/// Show whether a sound is normalized, with the gain of `waveform` as hover text. Returns true if
+/// it was changed.
+fn normalize_ui(
+    ui: &mut egui::Ui,
+    sound: &mut SoundConfig,
+    waveform: Option<&WaveformState>,
+    loudness: &LoudnessConfig,
+) -> bool {
+    if loudness.enabled {
+        let hover = match measured_loudness(waveform, sound).map(|measured| measured.lufs) {
+            Some(Some(lufs)) => format!(
+                "Loudness normalization moves this sound from {lufs:.1} LUFS to {:.1} LUFS \
+                ({:+.1} dB)",
+                loudness.target_lufs,
+                normalization_db(waveform, sound, loudness)
+            ),
+            Some(None) => "This sound is silent".to_string(),
+            None => "Measuring the loudness of this sound".to_string(),
         };
-
-        for _ in 0..self_.config.sounds.len() {
-            self_
-                .audio_controls
-                .push(Arc::new(AudioControls::new(false, true, 0.0)));
-        }
-        self_.update_output_devices();
-
-        self_
+        ui.add_enabled(false, egui::Checkbox::new(&mut true, "Normalize"))
+            .on_disabled_hover_text(hover);
+        return false;
     }
+    let hover = match waveform {
+        Some(WaveformState::Loaded(waveform)) => format!(
+            "Change the volume so the loudest peak is at -1 dBFS ({:+.1} dB)",
+            waveform.normalization_db()
+        ),
+        _ => "Change the volume so the loudest peak is at -1 dBFS".to_string(),
+    };
+    ui.checkbox(&mut sound.normalize, "Normalize")
+        .on_hover_text(hover)
+        .changed()
+}
 
-    /// Update the list of audio output devices.
-    fn update_output_devices(&mut self) {
-        let host = cpal::default_host();
-        self.output_devices.clear();
-        match host.output_devices() {
-            Ok(devices) => {
-                println!("[Soundboard] Found output devices.");
-                self.output_devices
-                    .extend(devices.filter_map(|device| match device.name() {
-                        Ok(name) => {
-                            let mut output_device = OutputDevice::new(device);
-                            if let Some(output_config) = self.config.outputs.get(&name) {
-                                output_device.set_volume(output_config.volume);
-                                output_device.enable();
-                            }
-                            Some((name, output_device))
-                        }
-                        Err(error) => {
-                            println!("[Soundboard] Error finding device name: {error}.");
-                            None
-                        }
-                    }));
-            }
-            Err(error) => {
-                println!("[Soundboard] Error finding output devices: {error}.");
-            }
-        }
+/// Show the A/B preview button of a sound, and a stop button while `previewing` the processed
+/// sound (true) or the original file (false). Returns true if the button was clicked and false
+/// if the stop button was clicked.
+fn preview_ui(ui: &mut egui::Ui, previewing: Option<bool>, monitor_enabled: bool) -> Option<bool> {
+    let (label, hover) = match previewing {
+        Some(true) => (
+            "A",
+            "Playing the processed sound. Click to hear the original file",
+        ),
+        Some(false) => (
+            "B",
+            "Playing the original file. Click to hear the processed sound",
+        ),
+        None => (
+            "A/B",
+            "Compare the processed sound (trim, speed, loop and normalization) with the \
+            original file on the monitor output",
+        ),
+    };
+    if ui
+        .add_enabled(monitor_enabled, Button::new(label))
+        .on_hover_text(hover)
+        .on_disabled_hover_text("Select a monitor output in the settings menu to preview")
+        .clicked()
+    {
+        return Some(true);
     }
-
-    /// Play the audio file at `filename` on all output devices.
-    fn play_sound(&mut self, filename: &str, controls: &Arc<AudioControls>) {
-        for (_, device) in self.output_devices.iter_mut() {
-            device.play_sound(filename, controls.clone());
-        }
+    if previewing.is_some() && ui.button("Stop").clicked() {
+        return Some(false);
     }
+    None
 }
 
-impl eframe::App for Soundboard {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let events = self.client_manager.events();
-        let suppress_events = self.config.shortcuts.pause.listening
-            || self.config.shortcuts.stop.listening
-            || self.config.shortcuts.modifier.listening
-            || self.config.sounds.iter().any(|s| s.key.listening);
-        let last_key_released = events
-            .iter()
-            .filter_map(|input_event| {
-                if input_event.event_type == EventType::EV_KEY as u16 && input_event.value == 0 {
-                    Key::from_repr(input_event.code)
-                } else {
-                    None
-                }
+/// Show a small slider for a sound's playback speed. Changes apply the next time it is played.
+fn speed_ui(ui: &mut egui::Ui, speed: &mut f32) -> egui::Response {
+    let response = ui.add(
+        Slider::new(speed, SPEED_RANGE)
+            .logarithmic(true)
+            .fixed_decimals(2)
+            .suffix("x"),
+    );
+    fine_adjustment(ui, response, speed, SPEED_RANGE, 1.0, (0.01, 0.1))
+        .on_hover_text(tr("Speed (also changes pitch)"))
+}
+
+/// Show a slider for a position between the left (-1) and right (1) channels.
+fn pan_ui(ui: &mut egui::Ui, pan: &mut f32) -> egui::Response {
+    let response = ui.add(
+        Slider::new(pan, -1.0..=1.0)
+            .custom_formatter(|pan, _| match (pan * 100.0).round() {
+                pan if pan < 0.0 => format!("L {}", -pan),
+                pan if pan > 0.0 => format!("R {pan}"),
+                _ => "C".to_string(),
             })
-            .last();
+            .show_value(true),
+    );
+    fine_adjustment(ui, response, pan, -1.0..=1.0, 0.0, (0.01, 0.1)).on_hover_text(tr(
+        "Pan: lower the other side to place the sound left or right",
+    ))
+}
 
-        if !suppress_events {
-            for key in events.iter().filter_map(|event| {
-                if event.event_type == EventType::EV_KEY as u16
-                    && event.value == 0
-                    && event.code != Key::KEY_RESERVED as u16
-                {
-                    // The event is a key release that is not `KEY_RESERVED`.
-                    Key::from_repr(event.code)
-                } else {
-                    None
-                }
-            }) {
-                if self.enabled {
-                    for (controls, path) in self
-                        .config
-                        .sounds
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, sound)| {
-                            if sound.key.key == key {
-                                if self.modified {
-                                    if self.audio_controls[i].playing() {
-                                        self.audio_controls[i].pause()
-                                    } else {
-                                        self.audio_controls[i].play()
-                                    }
-                                    self.modified = false;
-                                    None
-                                } else {
-                                    self.audio_controls[i].stop();
-                                    self.audio_controls[i] = Arc::new(AudioControls::new(
-                                        true,
-                                        false,
-                                        self.config.volume + sound.volume,
-                                    ));
-                                    Some((self.audio_controls[i].clone(), sound.path.clone()))
-                                }
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<(_, _)>>()
-                    {
-                        self.play_sound(&path, &controls);
-                    }
-                }
+/// A slider for a volume in dB, shown in dB or as a percentage of the amplitude. Its value can
+/// be typed after clicking it, stepped with the mouse wheel (coarsely while Shift is held) and
+/// reset to 0 dB by double-clicking the slider.
+struct VolumeSlider<'a> {
+    volume_db: &'a mut f32,
+    range: RangeInclusive<f32>,
+    scale: VolumeScale,
+    text: Option<String>,
+}
 
-                for (name, output_config) in &self.config.outputs {
-                    if key == output_config.mute.key {
-                        self.output_devices[name].toggle_muted();
-                    }
-                }
+/// Return a [`VolumeSlider`].
+fn volume_slider(
+    volume_db: &mut f32,
+    range: RangeInclusive<f32>,
+    scale: VolumeScale,
+) -> VolumeSlider<'_> {
+    VolumeSlider {
+        volume_db,
+        range,
+        scale,
+        text: None,
+    }
+}
 
-                if key == self.config.shortcuts.pause.key {
-                    self.playing ^= true;
-                    for controls in &self.audio_controls {
-                        controls.set_playing(self.playing);
-                    }
-                }
+impl VolumeSlider<'_> {
+    /// Show `text` next to the slider.
+    fn text(mut self, text: impl ToString) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+}
 
-                if key == self.config.shortcuts.stop.key {
-                    self.playing = false;
-                    for controls in &self.audio_controls {
-                        controls.stop();
+impl egui::Widget for VolumeSlider<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let mut slider = Slider::new(&mut *self.volume_db, self.range.clone());
+        let silent = *self.range.start() as f64;
+        slider = match self.scale {
+            VolumeScale::Decibels => slider.suffix(" dB"),
+            VolumeScale::Linear => slider
+                .custom_formatter(|db, _| format!("{:.0}%", db_to_amplitude(db as f32) * 100.0))
+                .custom_parser(move |text| {
+                    let percent: f64 = text.trim().trim_end_matches('%').trim().parse().ok()?;
+                    if percent < 0.0 {
+                        None
+                    } else if percent == 0.0 {
+                        Some(silent)
+                    } else {
+                        Some(20.0 * (percent / 100.0).log10())
                     }
-                }
+                }),
+        };
+        if let Some(text) = &self.text {
+            slider = slider.text(text);
+        }
+        let response = ui.add(slider);
 
-                if key == self.config.shortcuts.modifier.key {
-                    self.modified ^= true;
+        let range = self.range;
+        match self.scale {
+            // Steps of 0.5 dB, or 3 dB
+            VolumeScale::Decibels => {
+                fine_adjustment(ui, response, self.volume_db, range, 0.0, (0.5, 3.0))
+            }
+            // Steps of 1% of the amplitude, or 10%, up to the top of the range, which may be above
+            // 100%
+            VolumeScale::Linear => {
+                let shown = db_to_amplitude(*self.volume_db) * 100.0;
+                let mut percent = shown;
+                let percents = 0.0..=db_to_amplitude(*range.end()) * 100.0;
+                let response =
+                    fine_adjustment(ui, response, &mut percent, percents, 100.0, (1.0, 10.0));
+                if percent != shown {
+                    *self.volume_db = if percent > 0.0 {
+                        (20.0 * (percent / 100.0).log10()).clamp(*range.start(), *range.end())
+                    } else {
+                        *range.start()
+                    };
                 }
+                response
             }
         }
+    }
+}
 
-        // Keep track of the dropped file for 5 frames. This is required because the pointer location
-        // is unknown while a file is being dragged, so .hovered will always be false when the file is dropped.
-        if self.dropped_file.1 == None || self.dropped_file.0 > 5 {
-            self.dropped_file.0 = 0;
-            self.dropped_file.1 = ctx.input(|i| {
-                i.raw
-                    .dropped_files
-                    .first()
-                    .and_then(|f| f.path.clone().and_then(|p| Some(p.display().to_string())))
-            });
-        } else {
-            self.dropped_file.0 += 1;
+/// Let the mouse wheel step `value` by `steps.0` while the `response` of its slider is hovered,
+/// or by `steps.1` while Shift is held, and reset it to `default` when the slider is
+/// double-clicked. `value` is kept in `range`.
+fn fine_adjustment(
+    ui: &egui::Ui,
+    mut response: egui::Response,
+    value: &mut f32,
+    range: RangeInclusive<f32>,
+    default: f32,
+    steps: (f32, f32),
+) -> egui::Response {
+    if response.double_clicked() {
+        *value = default;
+        response.mark_changed();
+    } else if response.hovered() {
+        // Take the scrolling, so a scroll area around the slider does not scroll too.
+        let (delta, coarse) = ui.input_mut(|input| {
+            let delta = input.scroll_delta.x + input.scroll_delta.y;
+            input.scroll_delta = Vec2::ZERO;
+            (delta, input.modifiers.shift)
+        });
+        if delta == 0.0 {
+            return response;
         }
+        let step = if coarse { steps.1 } else { steps.0 };
+        let sign = if delta > 0.0 { 1.0 } else { -1.0 };
+        // Land on a multiple of the step, so stepping back returns to the same value.
+        *value = ((*value / step).round() + sign) * step;
+        response.mark_changed();
+    } else {
+        return response;
+    }
+    *value = value.clamp(*range.start(), *range.end());
+    response
+}
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Enable toggle
-            if toggle_ui(ui, &mut self.enabled).changed() && self.enabled == false {
-                for controls in &self.audio_controls {
-                    controls.stop();
-                }
+/// Show the speed slider of a sound, what triggering it again does and a menu of the sounds in
+/// `names` it starts with. `groups` are the output groups, and `choke_groups`, `random_groups`
+/// and `sequences` the choke groups, random groups and sequences in use.
+fn playback_options_ui(
+    ui: &mut egui::Ui,
+    sound: &mut SoundConfig,
+    names: &[String],
+    groups: &[String],
+    choke_groups: &[String],
+    random_groups: &[String],
+    sequences: &[String],
+) {
+    ui.horizontal(|ui| {
+        speed_ui(ui, &mut sound.speed);
+        pan_ui(ui, &mut sound.pan);
+        let label = match (sound.retrigger, sound.max_instances) {
+            (Retrigger::Overlap, limit) if limit > 0 => format!("Overlap {limit}"),
+            (retrigger, _) => retrigger.label().to_string(),
+        };
+        let label = match sound.cooldown_ms {
+            0 => label,
+            ms => format!("{label}, {:.1} s", ms as f32 / 1000.0),
+        };
+        ui.menu_button(label, |ui| {
+            for retrigger in Retrigger::ALL {
+                ui.radio_value(&mut sound.retrigger, retrigger, retrigger.label())
+                    .on_hover_text(retrigger.description());
             }
-
-            // Connect and disconnect from remote input server.
-            if self.client_manager.connected() {
-                if ui.button("Disconnect").clicked() {
-                    self.client_manager.disconnect();
-                }
-            } else {
-                if ui
-                    .add(
-                        Button::new(RichText::new("Connect").color(Color32::BLACK))
-                            .fill(Color32::RED),
+            ui.add_enabled_ui(sound.retrigger == Retrigger::Overlap, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("At most");
+                    ui.add(
+                        egui::DragValue::new(&mut sound.max_instances)
+                            .clamp_range(0..=64)
+                            .custom_formatter(|n, _| match n as usize {
+                                0 => "any".to_string(),
+                                n => n.to_string(),
+                            }),
                     )
-                    .clicked()
-                {
-                    self.client_manager.connect(
-                        self.config.server_address.clone(),
-                        self.config.api_key.clone(),
-                    );
+                    .on_hover_text(tr("The oldest instances are stopped first. 0 for any number."));
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Cooldown");
+                ui.add(
+                    egui::DragValue::new(&mut sound.cooldown_ms)
+                        .speed(100)
+                        .clamp_range(0..=3_600_000)
+                        .suffix(" ms"),
+                )
+                .on_hover_text(
+                    tr("Ignore triggers for this long after the sound starts, so it cannot be spammed. 0 for none."),
+                );
+            });
+        })
+        .response
+        .on_hover_text(tr("Play mode: what triggering the sound while it is playing does"));
+        let label = match sound.with.len() {
+            0 => "With".to_string(),
+            count => format!("With {count}"),
+        };
+        ui.checkbox(&mut sound.priority, "Priority").on_hover_text(
+            tr("Lower the other sounds while this one plays, as set under \"Ducking\" in the settings"),
+        );
+        ui.checkbox(&mut sound.random_start, "Random Start").on_hover_text(
+            tr("Start each play at a random position, so layered ambiences do not repeat in step"),
+        );
+        ui.checkbox(&mut sound.background, "Background").on_hover_text(
+            tr("Crossfade from the background sound playing when this one starts, as set under \"Background\" in the settings"),
+        );
+        ui.menu_button(label, |ui| {
+            for name in names.iter().filter(|name| **name != sound.name) {
+                let mut with = sound.with.contains(name);
+                if ui.checkbox(&mut with, name).changed() {
+                    if with {
+                        sound.with.push(name.clone());
+                    } else {
+                        sound.with.retain(|other| other != name);
+                    }
                 }
             }
-
-            // Settings window
-            if ui.button("Settings").clicked() {
-                self.settings_window = true;
+        })
+        .response
+        .on_hover_text(tr("Sounds started together with this one and stopped with it"));
+        let label = match sound.groups.as_slice() {
+            [] => "Outputs: All".to_string(),
+            [group] => format!("Outputs: {group}"),
+            groups => format!("Outputs: {} Groups", groups.len()),
+        };
+        ui.menu_button(label, |ui| {
+            if groups.is_empty() {
+                ui.label("Put outputs in groups in the settings to route sounds to them");
+            }
+            for group in groups {
+                let mut routed = sound.groups.contains(group);
+                if ui.checkbox(&mut routed, group).changed() {
+                    if routed {
+                        sound.groups.push(group.clone());
+                    } else {
+                        sound.groups.retain(|other| other != group);
+                    }
+                }
+            }
+        })
+        .response
+        .on_hover_text(tr("The output groups this sound plays on. None plays it on every output"));
+        let label = match sound.choke_group.as_str() {
+            "" => "Choke".to_string(),
+            group => format!("Choke: {group}"),
+        };
+        ui.menu_button(label, |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut sound.choke_group)
+                    .hint_text("No choke group")
+                    .desired_width(120.0),
+            );
+            for group in choke_groups {
+                ui.radio_value(&mut sound.choke_group, group.clone(), group);
+            }
+            ui.radio_value(&mut sound.choke_group, String::new(), "None");
+        })
+        .response
+        .on_hover_text(
+            tr("Starting a sound stops the other sounds of its choke group, or queues behind them as set under \"Choke Groups\" in the settings"),
+        );
+        let label = match sound.random_group.as_str() {
+            "" => "Random".to_string(),
+            group => format!("Random: {group}"),
+        };
+        ui.menu_button(label, |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut sound.random_group)
+                    .hint_text("No random group")
+                    .desired_width(120.0),
+            );
+            for group in random_groups {
+                ui.radio_value(&mut sound.random_group, group.clone(), group);
             }
+            ui.radio_value(&mut sound.random_group, String::new(), "None");
+        })
+        .response
+        .on_hover_text(
+            tr("A key bind shared by the sounds of a random group plays one of them at random"),
+        );
+        let label = match sound.sequence.as_str() {
+            "" => "Sequence".to_string(),
+            sequence => format!("Sequence: {sequence}"),
+        };
+        ui.menu_button(label, |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut sound.sequence)
+                    .hint_text("No sequence")
+                    .desired_width(120.0),
+            );
+            for sequence in sequences {
+                ui.radio_value(&mut sound.sequence, sequence.clone(), sequence);
+            }
+            ui.radio_value(&mut sound.sequence, String::new(), "None");
+        })
+        .response
+        .on_hover_text(
+            tr("A key bind shared by the sounds of a sequence plays them in order, as set under \"Sequences\" in the settings"),
+        );
+        let label = match std::path::Path::new(&sound.loop_path).file_stem() {
+            Some(stem) => format!("Loop: {}", stem.to_string_lossy()),
+            None => "Loop".to_string(),
+        };
+        ui.menu_button(label, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut sound.loop_path).hint_text("No loop"));
+                browse_button(ui, &mut sound.loop_path);
+                if ui.button("Clear").clicked() {
+                    sound.loop_path.clear();
+                }
+            });
+        })
+        .response
+        .on_hover_text(tr("A file looped without a gap after this one until the sound is stopped"));
+    });
+}
+
+/// The sample rates and buffer sizes offered for outputs, besides the defaults of the device.
+const SAMPLE_RATES: [u32; 4] = [44100, 48000, 88200, 96000];
+const BUFFER_SIZES: [u32; 7] = [64, 128, 256, 512, 1024, 2048, 4096];
+
+/// Describe the output channels of a channel map, e.g. "Ch 3-4".
+fn channel_map_label(channel_map: &[u16]) -> String {
+    match channel_map {
+        [] => "All Channels".to_string(),
+        [channel] => format!("Ch {channel}"),
+        [first, second] if *second == first + 1 => format!("Ch {first}-{second}"),
+        channels => {
+            let channels: Vec<String> = channels.iter().map(u16::to_string).collect();
+            format!("Ch {}", channels.join(", "))
+        }
+    }
+}
+
+/// Show a menu of the sample rate, buffer size and channel map of an output, labelled with the
+/// ones set. `channels` is the channel count of the device. Returns true if any changed, so the
+/// device has to be opened again.
+fn stream_format_ui(
+    ui: &mut egui::Ui,
+    output_config: &mut OutputConfig,
+    channels: Option<u16>,
+) -> bool {
+    let mut label = match (output_config.sample_rate, output_config.buffer_frames) {
+        (0, 0) => "Default".to_string(),
+        (rate, 0) => format!("{rate} Hz"),
+        (0, frames) => format!("{frames} Frames"),
+        (rate, frames) => format!("{rate} Hz, {frames}"),
+    };
+    if !output_config.channel_map.is_empty() {
+        label = format!("{label}, {}", channel_map_label(&output_config.channel_map));
+    }
+    let mut changed = false;
+    ui.menu_button(label, |ui| {
+        // Route the mix to a pair or a single channel of an interface with more channels.
+        let channels = channels.unwrap_or(2);
+        if channels > 2 || !output_config.channel_map.is_empty() {
+            ui.label("Channels");
+            let mut maps = vec![Vec::new()];
+            maps.extend((1..channels).step_by(2).map(|first| vec![first, first + 1]));
+            maps.extend((1..=channels).map(|channel| vec![channel]));
+            if !maps.contains(&output_config.channel_map) {
+                maps.push(output_config.channel_map.clone());
+            }
+            for map in maps {
+                let label = channel_map_label(&map);
+                changed |= ui
+                    .radio_value(&mut output_config.channel_map, map, label)
+                    .changed();
+            }
+            ui.separator();
+        }
+        ui.label("Sample Rate");
+        changed |= ui
+            .radio_value(&mut output_config.sample_rate, 0, "Default")
+            .changed();
+        for rate in SAMPLE_RATES {
+            changed |= ui
+                .radio_value(&mut output_config.sample_rate, rate, format!("{rate} Hz"))
+                .changed();
+        }
+        ui.separator();
+        ui.label("Buffer Size");
+        let rate = match output_config.sample_rate {
+            0 => 48000,
+            rate => rate,
+        };
+        changed |= ui
+            .radio_value(&mut output_config.buffer_frames, 0, "Default")
+            .changed();
+        for frames in BUFFER_SIZES {
+            changed |= ui
+                .radio_value(
+                    &mut output_config.buffer_frames,
+                    frames,
+                    format!("{frames} Frames"),
+                )
+                .on_hover_text(format!(
+                    "{:.1} ms at {rate} Hz",
+                    frames as f32 * 1000.0 / rate as f32
+                ))
+                .changed();
+        }
+    })
+    .response
+    .on_hover_text(tr(
+        "Smaller buffers lower the latency but glitch more easily. Changing them opens the \
+        device again, cutting off the sounds playing on it",
+    ));
+    changed
+}
+
+/// Show a menu of the work arounds for a miswired output, labelled with the ones in use.
+fn channel_fixes_ui(ui: &mut egui::Ui, output_config: &mut OutputConfig, device: &OutputDevice) {
+    let fixes: Vec<_> = [
+        (output_config.mono, "Mono"),
+        (output_config.swap_channels, "Swapped"),
+        (output_config.invert_left, "L Inverted"),
+        (output_config.invert_right, "R Inverted"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, label)| enabled.then_some(label))
+    .collect();
+    let label = if fixes.is_empty() {
+        "Normal".to_string()
+    } else {
+        fixes.join(", ")
+    };
+    ui.menu_button(label, |ui| {
+        if ui
+            .checkbox(&mut output_config.mono, "Mono")
+            .on_hover_text(tr("Play the average of the channels on every channel"))
+            .changed()
+        {
+            device.set_mono(output_config.mono);
+        }
+        if ui
+            .checkbox(&mut output_config.swap_channels, "Swap L/R")
+            .on_hover_text(tr(
+                "Play the left channel on the right and the right on the left",
+            ))
+            .changed()
+        {
+            device.set_swap_channels(output_config.swap_channels);
+        }
+        let left = ui
+            .checkbox(&mut output_config.invert_left, "Invert Left")
+            .on_hover_text(tr("Invert the phase of the left channel"));
+        let right = ui
+            .checkbox(&mut output_config.invert_right, "Invert Right")
+            .on_hover_text(tr("Invert the phase of the right channel"));
+        if left.changed() || right.changed() {
+            device.set_inverted(output_config.invert_left, output_config.invert_right);
+        }
+    });
+}
+
+/// Show a menu listing the instances of a sound that are playing or paused, with a button to
+/// stop each one. `duration` is the length of the file if known.
+fn instances_ui(ui: &mut egui::Ui, playback: &SoundPlayback, duration: Option<Duration>) {
+    let count = playback.instances().count();
+    ui.menu_button(format!("{count}"), |ui| {
+        egui::Grid::new("instances").show(ui, |ui| {
+            for instance in playback.instances() {
+                let started = chrono::DateTime::<chrono::Local>::from(instance.started);
+                ui.label(started.format("%H:%M:%S").to_string());
+                let position = instance.position().as_secs_f32();
+                ui.label(match duration {
+                    Some(duration) => format!("{position:.1} / {:.1} s", duration.as_secs_f32()),
+                    None => format!("{position:.1} s"),
+                });
+                if instance.controls.playing() {
+                    ui.label("Playing");
+                } else {
+                    ui.label("Paused");
+                }
+                if ui.button("Stop").clicked() {
+                    instance.controls.stop(None);
+                }
+                ui.end_row();
+            }
+        });
+    })
+    .response
+    .on_hover_text(tr("Instances playing"));
+}
+
+/// Count down the cooldown of `sound` while it rejects triggers.
+fn cooldown_ui(ui: &mut egui::Ui, playback: &SoundPlayback, sound: &SoundConfig) {
+    if let Some(remaining) = playback.cooldown_remaining(sound.cooldown()) {
+        ui.weak(format!("{:.0} s", remaining.as_secs_f32().ceil()))
+            .on_hover_text(tr(
+                "Cooling down. Triggers are ignored until the countdown ends.",
+            ));
+        // Repaint when the shown second changes.
+        let fraction = remaining.as_secs_f32().fract();
+        ui.ctx()
+            .request_repaint_after(Duration::from_secs_f32(if fraction > 0.0 {
+                fraction
+            } else {
+                1.0
+            }));
+    }
+}
+
+/// Name `response` in its widget information, for widgets without a visible label of their own,
+/// such as the text fields and sliders of a sound. Screen readers only get it with the
+/// "accesskit" feature of eframe.
+fn describe(response: &egui::Response, typ: WidgetType, label: impl Fn() -> String) {
+    response.widget_info(|| WidgetInfo::labeled(typ, label()));
+}
+
+/// Show the play state of the sound `name`, highlighted while it is selected with the keyboard.
+fn state_ui(
+    ui: &mut egui::Ui,
+    playback: &SoundPlayback,
+    colors: ThemeColors,
+    selected: bool,
+    name: &str,
+) -> egui::Response {
+    let (icon, color, state) = if playback.idle() {
+        ("\u{23F9}", colors.stopped, "stopped")
+    } else if playback.playing() {
+        ("\u{25B6}", colors.playing, "playing")
+    } else {
+        ("\u{23F8}", colors.paused, "paused")
+    };
+    let response = ui.add(SelectableLabel::new(
+        selected,
+        RichText::new(icon).color(color),
+    ));
+    response.widget_info(|| {
+        WidgetInfo::selected(
+            WidgetType::SelectableLabel,
+            selected,
+            format!("{name}, {state}"),
+        )
+    });
+    response
+}
+
+/// Add a button that opens a file picker for an audio file and stores the chosen path in
+/// `path`. The picker blocks the window until it is closed. Returns true if a file was chosen.
+fn browse_button(ui: &mut egui::Ui, path: &mut String) -> bool {
+    if !ui.button(tr("Browse...")).clicked() {
+        return false;
+    }
+    let mut dialog = rfd::FileDialog::new()
+        .add_filter("Audio", &["wav", "mp3", "ogg", "flac"])
+        .add_filter("All Files", &["*"]);
+    if let Some(directory) = std::path::Path::new(path.as_str()).parent() {
+        if directory.is_dir() {
+            dialog = dialog.set_directory(directory);
+        }
+    }
+    match dialog.pick_file() {
+        None => false,
+        Some(picked) => {
+            *path = picked.display().to_string();
+            true
+        }
+    }
+}
+
+/// Check if two configuration values differ. Maps are compared regardless of order.
+fn config_changed<T: serde::Serialize>(old: &T, new: &T) -> bool {
+    serde_json::to_value(old).ok() != serde_json::to_value(new).ok()
+}
+
+/// Describe a [`Follow`] mode for the cue list.
+fn follow_label(follow: Follow) -> &'static str {
+    match follow {
+        Follow::Manual => "Manual",
+        Follow::AfterStart => "After Start",
+        Follow::AfterEnd => "After End",
+    }
+}
+
+/// Show a dropdown selecting a sound from `sounds` by name.
+fn sound_selector(
+    ui: &mut egui::Ui,
+    id_source: impl std::hash::Hash,
+    selected: &mut String,
+    sounds: &[SoundConfig],
+) -> egui::Response {
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(selected.as_str())
+        .width(150.0)
+        .show_ui(ui, |ui| {
+            for sound in sounds.iter().filter(|sound| !sound.hidden()) {
+                ui.selectable_value(selected, sound.name.clone(), &sound.name);
+            }
+        })
+        .response
+}
+
+/// Draw a sound's waveform, or a placeholder while it is being computed.
+/// The parts outside of the sound's trim points are shaded.
+fn waveform_ui(
+    ui: &mut egui::Ui,
+    state: Option<&WaveformState>,
+    sound: &SoundConfig,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(Vec2::new(150.0, 20.0), egui::Sense::hover());
+    let visuals = ui.style().visuals.clone();
+    ui.painter()
+        .rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    match state {
+        None => response,
+        Some(WaveformState::Loading) => response.on_hover_text(tr("Loading waveform")),
+        Some(WaveformState::Missing(_)) => {
+            ui.painter().line_segment(
+                [rect.left_center(), rect.right_center()],
+                (1.0, visuals.warn_fg_color),
+            );
+            response.on_hover_text(tr("File not found"))
+        }
+        Some(WaveformState::Failed(error)) => {
+            ui.painter().line_segment(
+                [rect.left_center(), rect.right_center()],
+                (1.0, visuals.error_fg_color),
+            );
+            response.on_hover_text(error)
+        }
+        Some(WaveformState::Loaded(waveform)) => {
+            let stroke = (1.0, visuals.widgets.inactive.fg_stroke.color);
+            let step = rect.width() / waveform.peaks.len().max(1) as f32;
+            for (i, peak) in waveform.peaks.iter().enumerate() {
+                let x = rect.left() + (i as f32 + 0.5) * step;
+                let height = (peak * rect.height() / 2.0).max(0.5);
+                ui.painter().line_segment(
+                    [
+                        egui::pos2(x, rect.center().y - height),
+                        egui::pos2(x, rect.center().y + height),
+                    ],
+                    stroke,
+                );
+            }
+
+            // Trim points
+            let duration_ms = waveform.duration.as_millis().max(1) as f32;
+            let x = |ms: u64| rect.left() + rect.width() * (ms as f32 / duration_ms).min(1.0);
+            let shade = Color32::from_black_alpha(160);
+            if sound.start_ms > 0 {
+                ui.painter().rect_filled(
+                    egui::Rect::from_x_y_ranges(rect.left()..=x(sound.start_ms), rect.y_range()),
+                    0.0,
+                    shade,
+                );
+            }
+            if sound.end_ms > 0 {
+                ui.painter().rect_filled(
+                    egui::Rect::from_x_y_ranges(x(sound.end_ms)..=rect.right(), rect.y_range()),
+                    0.0,
+                    shade,
+                );
+            }
+            response.on_hover_text(format!("{:.1} s", waveform.duration.as_secs_f32()))
+        }
+    }
+}
+
+/// Tracks the most recently fired cue so the cue on standby can follow it.
+struct CueTimer {
+    started: Instant,
+    controls: Option<Arc<AudioControls>>,
+    ended: Option<Instant>,
+}
+
+/// Why a triggered sound was not played. Sent to WebSocket clients in snake case, e.g.
+/// "out_of_season".
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Rejection {
+    Disabled,
+    Archived,
+    /// The bank of the sound is out of season.
+    OutOfSeason,
+    NoOutputs,
+    /// Rehearsing without an enabled monitor output.
+    NoMonitor,
+    /// The sound ignores retriggers and is still playing.
+    AlreadyPlaying,
+    /// The cooldown of the sound since it was last started has not passed.
+    CoolingDown,
+    /// No output played it, e.g. because its file could not be read or its output groups have
+    /// no enabled outputs.
+    NotStarted,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Rejection::Disabled => "the soundboard is disabled",
+            Rejection::Archived => "it is archived",
+            Rejection::OutOfSeason => "its bank is out of season",
+            Rejection::NoOutputs => "no output is selected",
+            Rejection::NoMonitor => "rehearsing without a monitor output",
+            Rejection::AlreadyPlaying => "it is already playing",
+            Rejection::CoolingDown => "it is cooling down",
+            Rejection::NotStarted => "no output could play it",
+        })
+    }
+}
+
+/// What triggering a sound did. Sent to API clients in snake case.
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TriggerOutcome {
+    Started,
+    /// The sound queues retriggers and plays again once it ends, or waits for the other sounds
+    /// of its choke group.
+    Queued,
+    /// The modifier or a toggling sound paused it.
+    Paused,
+    Resumed,
+}
+
+/// A trigger that did not play its sound, kept for review after a show.
+#[derive(serde::Serialize)]
+struct MissedTrigger {
+    /// The local time of the trigger, e.g. "2024-05-01 19:30:02".
+    time: String,
+    name: String,
+    /// What triggered it, e.g. "Key" or "HTTP API".
+    source: String,
+    reason: Rejection,
+}
+
+impl MissedTrigger {
+    /// Append this missed trigger as a line of JSON to [`get_missed_triggers_path`], so it is
+    /// kept after the soundboard is closed.
+    fn append(&self) -> Result<(), String> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(get_missed_triggers_path()?)
+            .map_err(|error| format!("Unable to open missed triggers file: {error}."))?;
+        let line = serde_json::to_string(self)
+            .map_err(|error| format!("Unable to serialize missed trigger: {error}."))?;
+        writeln!(file, "{line}")
+            .map_err(|error| format!("Unable to write missed triggers file: {error}."))
+    }
+}
+
+/// The dialog asking which profile to load at startup, shown when there are several profiles
+/// and none is the default.
+struct ProfileChooser {
+    /// Make the chosen profile the default, so it is loaded without asking next time.
+    remember: bool,
+}
+
+/// An action run from the command palette.
+#[derive(Clone)]
+enum PaletteCommand {
+    Play(usize),
+    ToggleMute(String),
+    ToggleGroupMute(String),
+    ToggleEnabled,
+    ToggleRehearsal,
+    ToggleLocked,
+    Shortcut(ShortcutAction),
+    SwitchProfile(String),
+    ShowBank(Option<String>),
+    StopBank(String),
+    PauseBank(String),
+    CancelQueued,
+    Connect,
+    Disconnect,
+    Settings,
+    Manual,
+    Log,
+    MissedTriggers,
+    Show,
+    Archive,
+    Sync,
+    Packs,
+    ImportOtherApp,
+    Statistics,
+    ToggleProfiler,
+}
+
+/// The state of the window importing the sounds of another soundboard app.
+struct ForeignImport {
+    app: ForeignApp,
+    /// The file read, or why it could not be read.
+    file: Option<Result<std::path::PathBuf, String>>,
+    /// The sounds read, each with whether it is selected and whether its file exists.
+    sounds: Vec<(SoundConfig, bool, bool)>,
+    /// The bank of the added sounds that have none.
+    bank: String,
+}
+
+/// A sound auditioned on the monitor output.
+struct Preview {
+    sound: usize,
+    /// True while the processed sound plays, false while the original file plays.
+    processed: bool,
+    instance: PlaybackInstance,
+}
+
+impl Preview {
+    fn active(&self) -> bool {
+        !self.instance.controls.stopped() && !self.instance.controls.finished()
+    }
+}
+
+/// The state of the hearing protection of the monitor output.
+struct HearingProtection {
+    last_check: Instant,
+    /// The level of the monitor output in dBFS, or None without an enabled monitor output.
+    level: Option<f32>,
+    loud_since: Option<Instant>,
+    warning: bool,
+    attenuated: bool,
+}
+
+struct Soundboard {
+    config: Config,
+    client_manager: RemoteInputClientManager,
+    /// The input events of the current frame, kept to reuse its allocation.
+    event_buffer: Vec<InputEventWrapper>,
+    modified: bool,
+    config_saver: ConfigSaver,
+    output_devices: HashMap<String, OutputDevice>,
+    /// The names of the output devices when they were last listed, without
+    /// [`SYSTEM_DEFAULT_OUTPUT`].
+    listed_outputs: BTreeSet<String>,
+    /// The name of the default output device [`SYSTEM_DEFAULT_OUTPUT`] plays on.
+    default_output: Option<String>,
+    /// Lists the output devices in the background to find the ones plugged in or unplugged.
+    device_watcher: DeviceWatcher,
+    /// The playback of each sound in `config.sounds`.
+    playback: Vec<SoundPlayback>,
+    playing: bool,
+    enabled: bool,
+    settings_window: bool,
+    manual_window: bool,
+    log_window: bool,
+    /// The triggers that did not play their sound, oldest first.
+    missed_triggers: VecDeque<MissedTrigger>,
+    missed_window: bool,
+    /// The lowest level of the lines shown in the log window.
+    log_level: Level,
+    new_sound: SoundConfig,
+    dropped_file: (i64, Option<String>),
+    profiles: Vec<String>,
+    new_profile: String,
+    /// The profile loaded at startup without asking, see [`read_default_profile`].
+    default_profile: Option<String>,
+    /// Shown instead of the main window at startup while the user picks a profile.
+    profile_chooser: Option<ProfileChooser>,
+    /// The sound edited in the Edit Sound window opened from the pad view.
+    pad_editor: Option<usize>,
+    /// The sound selected with the arrow keys, played with Enter.
+    selected_sound: Option<usize>,
+    /// The code of the language shown, switched to when the profile's language changes.
+    language: String,
+    /// The name of the sound each random group played last.
+    last_variants: HashMap<String, String>,
+    /// The name of the sound each sequence started last.
+    sequence_steps: HashMap<String, String>,
+    /// The controls of the sound playing in each sequence that advances when a sound finishes.
+    sequence_playing: HashMap<String, Arc<AudioControls>>,
+    /// Set when the selected sound changes, to scroll it into view.
+    scroll_to_selected: bool,
+    /// The pad whose long press opened the Edit Sound window, until it is released.
+    pad_long_press: Option<usize>,
+    show_window: bool,
+    new_cue: CueConfig,
+    current_cue: Option<usize>,
+    standby_cue: usize,
+    cue_timer: Option<CueTimer>,
+    input_devices: Vec<String>,
+    microphone: Option<Microphone>,
+    waveforms: WaveformCache,
+    http_server: HttpServerManager,
+    updater: UpdateChecker,
+    stats: StatsStore,
+    stats_window: bool,
+    archive_window: bool,
+    stats_summary: Option<StatsSummary>,
+    new_bank: BankConfig,
+    /// The remote input device being added in the settings menu.
+    new_remote_device: String,
+    bank_changes: Vec<String>,
+    /// The profile as changed by another program while it had unsaved changes here, until the
+    /// user picks which to keep. Nothing is saved meanwhile.
+    pending_reload: Option<Config>,
+    /// The bank shown in the sounds table, or None to show every bank.
+    bank_filter: Option<String>,
+    palette: CommandPalette,
+    toasts: Toasts,
+    /// The theme and big screen scale of the current style.
+    applied_theme: Option<(ThemeConfig, Option<f32>)>,
+    /// The accessibility settings of the operating system, read at startup.
+    system_accessibility: SystemAccessibility,
+    /// The output device running a channel test and the test's progress.
+    channel_test: Option<(String, Arc<ChannelTestProgress>)>,
+    preview: Option<Preview>,
+    /// Set while every sound is played on the monitor output only. Not saved, so the soundboard
+    /// never starts up rehearsing.
+    rehearsal: bool,
+    /// Reads gamepads while they are enabled.
+    gamepad_input: Option<GamepadInput>,
+    /// Detects double taps and long presses.
+    triggers: TriggerDetector,
+    /// Lowers the other sounds while a priority sound plays.
+    ducker: Arc<Ducker>,
+    /// The remote keys held down.
+    held_keys: HashSet<Key>,
+    /// The key or button held down for talk-over.
+    talk_over_input: Option<Input>,
+    /// The connection to the remote input server the held keys were pressed on, see
+    /// [`RemoteInputClientManager::connection`].
+    remote_connection: Option<u64>,
+    /// When the maximum gain of each output last lowered the volume of a sound.
+    limited_outputs: HashMap<String, Instant>,
+    /// When the mix of each output last went above full scale.
+    clipped_outputs: HashMap<String, Instant>,
+    hearing_protection: HearingProtection,
+    /// The path of the last support bundle, or why it could not be created.
+    support_bundle: Option<Result<std::path::PathBuf, String>>,
+    /// Why the profile could not be loaded at startup. The default configuration is used in its
+    /// place and not saved until another profile is loaded.
+    load_error: Option<String>,
+    /// The path of the last exported shareable configuration, or why it could not be exported.
+    shared_config: Option<Result<std::path::PathBuf, String>>,
+    /// Wakes the window from background threads.
+    waker: Waker,
+    /// When the window is woken for a skipped autosave, if it will be.
+    autosave_wake: Option<Instant>,
+    /// Watches the current profile's file for changes made by other programs.
+    config_watcher: Option<ConfigWatcher>,
+    /// Receives commands such as `soundboard play` from the command line.
+    control_server: Option<ControlServer>,
+    /// Measures the frames of the window while its overlay is shown.
+    profiler: Profiler,
+    /// Transcodes sounds into the media library.
+    importer: Importer,
+    /// Exports and imports sound packs.
+    packs: SoundPacks,
+    packs_window: bool,
+    /// Set while the window importing the sounds of another app is open.
+    foreign_import: Option<ForeignImport>,
+    /// The indices of the sounds selected for the next exported pack.
+    pack_selection: BTreeSet<usize>,
+    /// Compares the sounds with another soundboard.
+    sound_sync: SoundSync,
+    sync_window: bool,
+    /// The token of the other soundboard's HTTP API. Not saved, since it is a secret of the
+    /// other computer.
+    sync_token: String,
+}
+
+impl Soundboard {
+    const CONFIG_AUTOSAVE: Duration = Duration::from_secs(30);
+    const MAX_FRAME_DELAY: Duration = Duration::from_millis(100);
+    const VOLUME_RANGE: RangeInclusive<f32> = -50.0..=0.0;
+    const SOUND_VOLUME_RANGE: RangeInclusive<f32> = -50.0..=50.0;
+    /// The most missed triggers kept. The oldest are dropped first.
+    const MAX_MISSED_TRIGGERS: usize = 1000;
+    const MAX_GAIN_RANGE: RangeInclusive<f32> = -50.0..=50.0;
+    const LIMITER_CEILING_RANGE: RangeInclusive<f32> = -30.0..=0.0;
+    const DEFAULT_LIMITER_CEILING: f32 = -1.0;
+    const LOUDNESS_TARGET_RANGE: RangeInclusive<f32> = -40.0..=0.0;
+    /// How long an output is shown as limited after its maximum gain lowered a volume.
+    const LIMITED_DISPLAY: Duration = Duration::from_secs(2);
+    /// How long an output is shown as clipping after its mix went above full scale.
+    const CLIPPED_DISPLAY: Duration = Duration::from_secs(2);
+    const HEARING_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+    /// How long a pad in the pad view is held to edit its sound instead of playing it.
+    const PAD_LONG_PRESS: Duration = Duration::from_millis(500);
+
+    /// Create a new [`Soundboard`].
+    fn new(creation_context: &eframe::CreationContext<'_>) -> Self {
+        // Load the default profile's configuration file, or the first profile's while the user
+        // chooses one. A profile that cannot be loaded, e.g. one saved by a newer version, is
+        // left as it is and the default configuration is used without saving it.
+        let mut load_error = None;
+        let profiles = initialize_profiles().unwrap_or_else(|error| {
+            log!(Error, "[Soundboard] {error}");
+            load_error = Some(error);
+            vec!["Default".to_string()]
+        });
+        let default_profile = read_default_profile();
+        let startup = startup_profile(&profiles, default_profile.as_deref());
+        let profile_chooser = startup
+            .is_none()
+            .then_some(ProfileChooser { remember: false });
+        let profile = startup.unwrap_or_else(|| profiles[0].clone());
+        let config = load_config(&profile).unwrap_or_else(|error| {
+            log!(
+                Error,
+                "[Soundboard] Unable to load profile {profile}: {error}"
+            );
+            load_error.get_or_insert(error);
+            default_config()
+        });
+        let ctx = creation_context.egui_ctx.clone();
+        let waker = Waker::new(move || ctx.request_repaint());
+        wake_on_error(waker.clone());
+
+        let mut self_ = Self {
+            config,
+            client_manager: RemoteInputClientManager::new(waker.clone()),
+            event_buffer: Vec::new(),
+            modified: false,
+            config_saver: if load_error.is_some() {
+                ConfigSaver::read_only(profile, Self::CONFIG_AUTOSAVE)
+            } else {
+                ConfigSaver::new(profile, Self::CONFIG_AUTOSAVE)
+            },
+            load_error,
+            output_devices: HashMap::new(),
+            listed_outputs: BTreeSet::new(),
+            default_output: None,
+            device_watcher: DeviceWatcher::new(waker.clone()),
+            playback: Vec::new(),
+            playing: true,
+            enabled: false,
+            settings_window: false,
+            manual_window: false,
+            log_window: false,
+            missed_triggers: VecDeque::new(),
+            missed_window: false,
+            log_level: Level::Info,
+            new_sound: SoundConfig::default(),
+            dropped_file: (0, None),
+            profiles,
+            new_profile: String::new(),
+            default_profile,
+            profile_chooser,
+            pad_editor: None,
+            selected_sound: None,
+            language: String::new(),
+            last_variants: HashMap::new(),
+            sequence_steps: HashMap::new(),
+            sequence_playing: HashMap::new(),
+            scroll_to_selected: false,
+            pad_long_press: None,
+            show_window: false,
+            new_cue: CueConfig::default(),
+            current_cue: None,
+            standby_cue: 0,
+            cue_timer: None,
+            input_devices: Vec::new(),
+            microphone: None,
+            waveforms: WaveformCache::new(waker.clone()),
+            http_server: HttpServerManager::new(waker.clone()),
+            updater: UpdateChecker::new(waker.clone()),
+            importer: Importer::new(waker.clone()),
+            packs: SoundPacks::new(waker.clone()),
+            packs_window: false,
+            foreign_import: None,
+            pack_selection: BTreeSet::new(),
+            sound_sync: SoundSync::new(waker.clone()),
+            sync_window: false,
+            sync_token: String::new(),
+            stats: StatsStore::new(),
+            stats_window: false,
+            archive_window: false,
+            stats_summary: None,
+            new_bank: BankConfig::default(),
+            new_remote_device: String::new(),
+            bank_changes: Vec::new(),
+            pending_reload: None,
+            bank_filter: None,
+            palette: CommandPalette::new(),
+            toasts: Toasts::new(),
+            applied_theme: None,
+            system_accessibility: SystemAccessibility::detect(),
+            channel_test: None,
+            preview: None,
+            gamepad_input: None,
+            triggers: TriggerDetector::default(),
+            ducker: Arc::new(Ducker::default()),
+            held_keys: HashSet::new(),
+            talk_over_input: None,
+            remote_connection: None,
+            rehearsal: false,
+            limited_outputs: HashMap::new(),
+            clipped_outputs: HashMap::new(),
+            hearing_protection: HearingProtection {
+                last_check: Instant::now(),
+                level: None,
+                loud_since: None,
+                warning: false,
+                attenuated: false,
+            },
+            support_bundle: None,
+            shared_config: None,
+            control_server: None,
+            waker,
+            autosave_wake: None,
+            config_watcher: None,
+            profiler: Profiler::new(),
+        };
+
+        self_.watch_profile();
+        self_.reset_playback();
+        self_.bank_changes = self_.apply_bank_seasons();
+        self_.apply_audio_host();
+        self_.update_input_devices();
+        self_.update_output_devices();
+        self_.apply_integrations();
+        self_.update_gamepads();
+
+        remove_replaced_executable();
+        if self_.config.check_for_updates {
+            self_.updater.check();
+        }
+
+        self_
+    }
+
+    /// Stop every sound and replace all [`SoundPlayback`]s with idle ones matching
+    /// `self.config.sounds`.
+    fn reset_playback(&mut self) {
+        for playback in &self.playback {
+            playback.stop(None);
+        }
+        self.playback.clear();
+        self.playback
+            .resize_with(self.config.sounds.len(), SoundPlayback::default);
+        self.selected_sound = None;
+    }
+
+    /// Save the current profile and load `profile` in its place.
+    fn switch_profile(&mut self, profile: &str) {
+        if profile == self.config_saver.profile() {
+            return;
+        }
+        let config = match load_config(profile) {
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Soundboard] Unable to switch to profile {profile}: {error}"
+                );
+                return;
+            }
+            Ok(config) => config,
+        };
+        log!("[Soundboard] Switching to profile {profile}.");
+        let _ = self.config_saver.save_now(&self.config);
+        // Switching profiles while live does not unlock editing.
+        let locked = self.config.locked;
+        self.load_profile(profile, config);
+        self.config.locked |= locked;
+    }
+
+    /// Restore a backup of the current profile. The current configuration is backed up first,
+    /// so restoring can be undone by restoring the newest backup.
+    fn restore_backup(&mut self, backup: usize) {
+        let profile = self.config_saver.profile().to_string();
+        let config = match load_backup(&profile, backup) {
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Soundboard] Unable to restore backup {backup} of {profile}: {error}"
+                );
+                return;
+            }
+            Ok(config) => config,
+        };
+        log!("[Soundboard] Restoring backup {backup} of profile {profile}.");
+        let _ = self.config_saver.save_now(&self.config);
+        self.load_profile(&profile, config);
+        if let Err(error) = self.config_saver.save_now(&self.config) {
+            log!(
+                Error,
+                "[Soundboard] Unable to save restored backup: {error}"
+            );
+        }
+    }
+
+    /// Replace the configuration with `config`, loaded from `profile`.
+    fn load_profile(&mut self, profile: &str, config: Config) {
+        self.config = config;
+        self.load_error = None;
+        self.pending_reload = None;
+        self.config_saver = ConfigSaver::new(profile.to_string(), Self::CONFIG_AUTOSAVE);
+        self.watch_profile();
+        self.modified = false;
+        self.playing = true;
+        self.current_cue = None;
+        self.standby_cue = 0;
+        self.cue_timer = None;
+        self.reset_playback();
+        if self.apply_audio_host() {
+            self.update_input_devices();
+        }
+        self.update_output_devices();
+        self.apply_integrations();
+        self.update_gamepads();
+        let changes = self.apply_bank_seasons();
+        self.bank_changes.extend(changes);
+    }
+
+    /// Watch the current profile's file and the configuration fragments for changes made by
+    /// other programs.
+    fn watch_profile(&mut self) {
+        self.config_watcher = match get_profile_path(self.config_saver.profile())
+            .and_then(|path| Ok((path, get_fragment_directory()?)))
+        {
+            Err(error) => {
+                log!(Error, "[Soundboard] Unable to watch the profile: {error}");
+                None
+            }
+            Ok((path, fragment_directory)) => Some(ConfigWatcher::new(
+                path,
+                fragment_directory,
+                self.waker.clone(),
+            )),
+        };
+    }
+
+    /// Reload the current profile if another program changed its file. If it was also changed
+    /// here since the last save, the user is asked which changes to keep.
+    fn reload_changed_profile(&mut self) {
+        if !self
+            .config_watcher
+            .as_ref()
+            .is_some_and(ConfigWatcher::take_changed)
+        {
+            return;
+        }
+        let unsaved = self.config_saver.unsaved(&self.config);
+        match self.config_saver.reload() {
+            Err(error) => log!(Error, "[Soundboard] Unable to reload the profile: {error}"),
+            Ok(None) => (),
+            Ok(Some(config)) if unsaved => {
+                log!(
+                    Warning,
+                    "[Soundboard] Profile {} was changed by another program and here.",
+                    self.config_saver.profile()
+                );
+                self.pending_reload = Some(config);
+            }
+            Ok(Some(config)) => {
+                log!(
+                    "[Soundboard] Reloading profile {}, which was changed by another program.",
+                    self.config_saver.profile()
+                );
+                self.load_error = None;
+                self.apply_reloaded_config(config);
+            }
+        }
+    }
+
+    /// Ask whether to load the profile changed by another program or keep the unsaved changes
+    /// made here, which are then saved over it.
+    fn pending_reload_ui(&mut self, ctx: &egui::Context) {
+        if self.pending_reload.is_none() {
+            return;
+        }
+        let mut load = None;
+        egui::Window::new(tr("Profile Changed"))
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(tr(
+                    "The profile file was changed by another program, but the changes made here \
+                    have not been saved yet.",
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Load File")).clicked() {
+                        load = Some(true);
+                    }
+                    if ui.button(tr("Keep My Changes")).clicked() {
+                        load = Some(false);
+                    }
+                });
+            });
+        match load {
+            None => (),
+            Some(true) => {
+                let config = self.pending_reload.take().unwrap();
+                log!(
+                    "[Soundboard] Reloading profile {}, which was changed by another program.",
+                    self.config_saver.profile()
+                );
+                self.load_error = None;
+                self.apply_reloaded_config(config);
+            }
+            Some(false) => {
+                self.pending_reload = None;
+                if let Err(error) = self.config_saver.save_now(&self.config) {
+                    log!(Error, "[Soundboard] Unable to save the profile: {error}");
+                }
+            }
+        }
+    }
+
+    /// Replace the configuration with `config`, read again from the same profile. Sounds that
+    /// are still there keep playing, and only the devices and integrations whose settings
+    /// changed are restarted.
+    fn apply_reloaded_config(&mut self, config: Config) {
+        let old_config = std::mem::replace(&mut self.config, config);
+        self.config.locked |= old_config.locked;
+
+        // Match sounds by name and path, since their order may have changed.
+        let mut old_playback: Vec<Option<SoundPlayback>> = std::mem::take(&mut self.playback)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for sound in &mut self.config.sounds {
+            let old =
+                old_config
+                    .sounds
+                    .iter()
+                    .zip(&old_playback)
+                    .position(|(old_sound, playback)| {
+                        playback.is_some()
+                            && old_sound.name == sound.name
+                            && old_sound.path == sound.path
+                    });
+            if let Some(i) = old {
+                sound.out_of_season = old_config.sounds[i].out_of_season;
+            }
+            let playback = old.and_then(|i| old_playback[i].take());
+            self.playback.push(playback.unwrap_or_default());
+        }
+        for playback in old_playback.into_iter().flatten() {
+            playback.stop(None);
+        }
+        self.selected_sound = None;
+        self.update_sound_volumes();
+
+        if self.apply_audio_host() {
+            self.update_input_devices();
+            self.update_output_devices();
+        } else if config_changed(&old_config.outputs, &self.config.outputs) {
+            self.update_output_devices();
+        } else if config_changed(&old_config.microphone, &self.config.microphone) {
+            self.update_microphone();
+        }
+        if config_changed(&old_config.http, &self.config.http) && self.http_server.running() {
+            self.http_server.stop();
+        }
+        self.apply_integrations();
+        self.update_gamepads();
+        let changes = self.apply_bank_seasons();
+        self.bank_changes.extend(changes);
+    }
+
+    /// Hide the sounds of banks that are out of season and show those in season again.
+    /// Returns a description of each bank that changed.
+    fn apply_bank_seasons(&mut self) -> Vec<String> {
+        let today = chrono::Local::now();
+        let mut changes = Vec::new();
+        for bank in &self.config.banks {
+            let active = match bank.in_season(today.month(), today.day()) {
+                None => continue,
+                Some(active) => active,
+            };
+            let mut changed = 0;
+            for (i, sound) in self.config.sounds.iter_mut().enumerate() {
+                if sound.bank == bank.name && sound.out_of_season == active {
+                    sound.out_of_season = !active;
+                    self.playback[i].stop(None);
+                    changed += 1;
+                }
+            }
+            if changed > 0 {
+                let change = format!(
+                    "Bank {} {} ({changed} sounds).",
+                    bank.name,
+                    if active { "activated" } else { "out of season" }
+                );
+                log!("[Soundboard] {change}");
+                changes.push(change);
+            }
+        }
+        changes
+    }
+
+    /// Switch to the profile after the current one, wrapping around to the first.
+    fn next_profile(&mut self) {
+        if let Ok(profiles) = list_profiles() {
+            self.profiles = profiles;
+        }
+        let next = match self
+            .profiles
+            .iter()
+            .position(|p| p == self.config_saver.profile())
+        {
+            Some(i) => self.profiles.get(i + 1).or(self.profiles.first()),
+            None => self.profiles.first(),
+        };
+        if let Some(next) = next.cloned() {
+            self.switch_profile(&next);
+        }
+    }
+
+    /// Return the connection status of a network integration.
+    fn integration_status(&self, integration: Integration) -> ConnectionStatus {
+        match integration {
+            Integration::RemoteInput => self.client_manager.status(),
+            Integration::Http => self.http_server.status(),
+            Integration::CommandLine => self
+                .control_server
+                .as_ref()
+                .map_or(ConnectionStatus::Disconnected, ControlServer::status),
+        }
+    }
+
+    /// Start the network integrations that are enabled in `self.config.integrations` and stop
+    /// the ones that are disabled. The remote input client is only started by "Connect".
+    fn apply_integrations(&mut self) {
+        if !self.config.integrations.remote_input && self.client_manager.connected() {
+            log!("[Soundboard] Remote input is disabled. Disconnecting.");
+            self.client_manager.disconnect();
+        }
+
+        if self.config.integrations.http && !self.http_server.running() {
+            self.http_server.start(
+                self.config.http.address.clone(),
+                self.config.http.port,
+                self.config.http.token.clone(),
+            );
+        } else if !self.config.integrations.http && self.http_server.running() {
+            self.http_server.stop();
+        }
+
+        if self.config.integrations.command_line && self.control_server.is_none() {
+            self.control_server = match ControlServer::start(self.waker.clone()) {
+                Err(error) => {
+                    log!(Error, "[Control] Unable to receive commands: {error}");
+                    None
+                }
+                Ok(control_server) => Some(control_server),
+            };
+        } else if !self.config.integrations.command_line && self.control_server.is_some() {
+            self.control_server = None;
+        }
+    }
+
+    /// Start or stop reading gamepads as set in `self.config.gamepads`.
+    fn update_gamepads(&mut self) {
+        if self.config.gamepads && self.gamepad_input.is_none() {
+            self.gamepad_input = Some(GamepadInput::start(self.waker.clone()));
+        } else if !self.config.gamepads && self.gamepad_input.is_some() {
+            log!("[Gamepads] Stopped reading gamepads.");
+            self.gamepad_input = None;
+        }
+    }
+
+    /// Describe every sound for the HTTP API.
+    fn sound_infos(&self) -> Vec<SoundInfo> {
+        self.config
+            .sounds
+            .iter()
+            .enumerate()
+            .map(|(index, sound)| SoundInfo {
+                index,
+                name: sound.name.clone(),
+                key: sound.key.to_string(),
+                volume: sound.volume_db,
+                playing: self.playback[index].playing(),
+            })
+            .collect()
+    }
+
+    /// Push the current state to WebSocket clients if it changed.
+    fn publish_state(&self) {
+        if self.http_server.running() {
+            self.http_server.publish_state(self.state().to_string());
+        }
+    }
+
+    /// Arm or disarm the whole soundboard. Disabling it stops every sound and the cue timer.
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.cue_timer = None;
+            for playback in &self.playback {
+                playback.stop(None);
+            }
+        }
+    }
+
+    /// Turn rehearsal on or off. While rehearsing, sounds are only started on the monitor output
+    /// and every other output is silenced, including the sounds already playing on it and the
+    /// microphone.
+    fn set_rehearsal(&mut self, rehearsal: bool) {
+        if rehearsal && !self.monitor_enabled() {
+            log!(
+                Warning,
+                "[Soundboard] Unable to rehearse without a monitor output."
+            );
+            return;
+        }
+        if rehearsal != self.rehearsal {
+            self.rehearsal = rehearsal;
+            log!(
+                "[Soundboard] Rehearsal {}.",
+                if rehearsal { "started" } else { "stopped" }
+            );
+        }
+        self.update_output_gates();
+    }
+
+    /// Silence every output but the monitor output while rehearsing and the outputs of disabled
+    /// groups otherwise, and open them again after.
+    fn update_output_gates(&self) {
+        for (name, output_device) in &self.output_devices {
+            let gated = if self.rehearsal {
+                *name != self.config.monitor
+            } else {
+                self.config
+                    .output_group(name)
+                    .is_some_and(|group| !group.enabled)
+            };
+            output_device.set_gated(gated);
+        }
+    }
+
+    /// Return the enabled outputs in `group`.
+    fn group_devices<'a>(&'a self, group: &'a str) -> impl Iterator<Item = &'a OutputDevice> {
+        self.config
+            .outputs
+            .iter()
+            .filter(move |(_, output_config)| output_config.group == group)
+            .filter_map(|(name, _)| self.output_devices.get(name))
+    }
+
+    /// Mute every output in `group`, or unmute them if all of them are muted.
+    fn toggle_group_muted(&self, group: &str) {
+        let muted = !self.group_devices(group).all(OutputDevice::muted);
+        for device in self.group_devices(group) {
+            device.set_muted(muted);
+        }
+    }
+
+    /// Apply the volume of each output and of its group to the output.
+    fn update_output_volumes(&self) {
+        for (name, output_config) in &self.config.outputs {
+            if let Some(device) = self.output_devices.get(name) {
+                let group = self.config.output_group(name).unwrap_or_default();
+                device.set_volume_db(output_config.volume_db + group.volume_db);
+            }
+        }
+    }
+
+    /// Return true if a monitor output is selected and enabled.
+    fn monitor_enabled(&self) -> bool {
+        self.output_devices
+            .get(&self.config.monitor)
+            .is_some_and(OutputDevice::enabled)
+    }
+
+    /// Describe the state of the soundboard for the WebSocket and support bundles.
+    fn state(&self) -> serde_json::Value {
+        let outputs: Vec<serde_json::Value> = self
+            .config
+            .outputs
+            .iter()
+            .filter_map(|(name, output_config)| {
+                let device = self.output_devices.get(name)?;
+                Some(serde_json::json!({
+                    "name": name,
+                    "volume": output_config.volume_db,
+                    "muted": device.muted(),
+                    "group": output_config.group,
+                }))
+            })
+            .collect();
+        serde_json::json!({
+            "type": "state",
+            "enabled": self.enabled,
+            "rehearsal": self.rehearsal,
+            "profile": self.config_saver.profile(),
+            "volume": self.config.volume_db,
+            "sounds": self.sound_infos(),
+            "outputs": outputs,
+        })
+    }
+
+    /// Handle the requests received by the HTTP API and from the command line.
+    fn handle_api_requests(&mut self) {
+        for request in self.http_server.requests() {
+            self.handle_api_request(request, "HTTP API");
+        }
+        let requests = match &self.control_server {
+            Some(control_server) => control_server.requests(),
+            None => Vec::new(),
+        };
+        for request in requests {
+            self.handle_api_request(request, "Control");
+        }
+    }
+
+    /// Handle `request`, logging played sounds as coming from `source`.
+    fn handle_api_request(&mut self, request: ApiRequest, source: &str) {
+        match request.command {
+            ApiCommand::ListSounds => {
+                request.respond(serde_json::json!(self.sound_infos()));
+            }
+            ApiCommand::PlayIndex(index) => {
+                if index < self.config.sounds.len() {
+                    self.play_api_sound(request, index, source);
+                } else {
+                    request.fail(404, &format!("No sound with index {index}."));
+                }
+            }
+            ApiCommand::PlayName(ref name) => {
+                // A hidden sound is only found to say why it is not played.
+                let sounds = &self.config.sounds;
+                match sounds
+                    .iter()
+                    .position(|s| !s.hidden() && &s.name == name)
+                    .or_else(|| sounds.iter().position(|s| &s.name == name))
+                {
+                    Some(index) => self.play_api_sound(request, index, source),
+                    None => {
+                        let error = format!("No sound named {name}.");
+                        request.fail(404, &error);
+                    }
+                }
+            }
+            ApiCommand::MissedTriggers => {
+                request.respond(serde_json::json!(self.missed_triggers));
+            }
+            ApiCommand::Sounds => {
+                request.respond(serde_json::json!({
+                    "profile": self.config_saver.profile(),
+                    "sounds": self.config.sounds,
+                }));
+            }
+            ApiCommand::StopAll => {
+                log!("[{source}] Stopping all sounds.");
+                self.stop_all();
+                request.respond(serde_json::json!({}));
+            }
+            ApiCommand::GetVolume => {
+                request.respond(serde_json::json!({ "db": self.config.volume_db }));
+            }
+            ApiCommand::SetVolume(volume) if !volume.is_finite() => {
+                request.fail(400, "Give a finite volume in dB.");
+            }
+            ApiCommand::SetVolume(volume) => {
+                self.set_volume_db(volume);
+                request.respond(serde_json::json!({ "db": self.config.volume_db }));
+            }
+        }
+    }
+
+    /// Trigger sound `i` for `request` like a key would, and acknowledge what it did or answer
+    /// why it is not played.
+    fn play_api_sound(&mut self, request: ApiRequest, i: usize, source: &str) {
+        if self.rejection(i).is_none() {
+            log!("[{source}] Playing sound {}.", self.config.sounds[i].name);
+        }
+        match self.trigger_or_reject(i, source) {
+            Ok(outcome) => request.respond(serde_json::json!({
+                "index": i,
+                "started": outcome == TriggerOutcome::Started,
+                "outcome": outcome,
+            })),
+            Err(rejection) => {
+                let error = format!(
+                    "{} was not played: {rejection}.",
+                    self.config.sounds[i].name
+                );
+                request.respond_with_status(
+                    409,
+                    serde_json::json!({
+                        "index": i,
+                        "started": false,
+                        "error": error,
+                        "reason": rejection,
+                    }),
+                );
+            }
+        }
+    }
+
+    /// List the triggers that did not play their sound, newest at the bottom, for review after a
+    /// show.
+    fn missed_triggers_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Copy").clicked() {
+                let text = self
+                    .missed_triggers
+                    .iter()
+                    .map(|missed| {
+                        format!(
+                            "{} {} ({}): {}\n",
+                            missed.time, missed.name, missed.source, missed.reason
+                        )
+                    })
+                    .collect();
+                ui.output_mut(|output| output.copied_text = text);
+            }
+            if ui.button("Clear").clicked() {
+                self.missed_triggers.clear();
+            }
+        });
+        if self.missed_triggers.is_empty() {
+            ui.label("Every trigger played its sound.");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                egui::Grid::new("missed_triggers")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Time");
+                        ui.strong("Sound");
+                        ui.strong("Source");
+                        ui.strong("Reason");
+                        ui.end_row();
+                        for missed in &self.missed_triggers {
+                            ui.label(&missed.time);
+                            ui.label(&missed.name);
+                            ui.label(&missed.source);
+                            ui.label(missed.reason.to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    /// Show the recent log lines at or above `self.log_level`, newest at the bottom.
+    fn log_ui(&mut self, ui: &mut egui::Ui) {
+        let lines: Vec<_> = recent_lines()
+            .into_iter()
+            .filter(|line| line.level >= self.log_level)
+            .collect();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Level")
+                .selected_text(self.log_level.to_string())
+                .show_ui(ui, |ui| {
+                    for level in Level::ALL {
+                        ui.selectable_value(&mut self.log_level, level, level.to_string());
+                    }
+                })
+                .response
+                .on_hover_text(tr("Show lines of this level and above"));
+            if ui.button("Copy").clicked() {
+                let text = lines.iter().map(|line| format!("{line}\n")).collect();
+                ui.output_mut(|output| output.copied_text = text);
+            }
+        });
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        egui::ScrollArea::both().stick_to_bottom(true).show_rows(
+            ui,
+            row_height,
+            lines.len(),
+            |ui, rows| {
+                for line in &lines[rows] {
+                    let text = RichText::new(line.to_string()).text_style(TextStyle::Monospace);
+                    let text = match line.level {
+                        Level::Error => text.color(ui.visuals().error_fg_color),
+                        Level::Warning => text.color(ui.visuals().warn_fg_color),
+                        Level::Debug => text.weak(),
+                        Level::Info => text,
+                    };
+                    ui.add(egui::Label::new(text).wrap(false));
+                }
+            },
+        );
+    }
+
+    /// Forget the keys held on the remote input server when the connection to it ends, since
+    /// their releases will never arrive. Talk-over held with a key or the wheel ends too.
+    fn forget_remote_keys(&mut self) {
+        let connection = self.client_manager.connection();
+        if connection == self.remote_connection {
+            return;
+        }
+        if self.remote_connection.is_some() {
+            self.held_keys.clear();
+            if let Some(Input::Key(_) | Input::Wheel(_)) = self.talk_over_input {
+                self.talk_over_input = None;
+                self.ducker.set_talk_over(false);
+            }
+        }
+        self.remote_connection = connection;
+    }
+
+    /// Start talk-over if `press` is its key bind. `press` is the key or button pressed with the
+    /// modifier keys held at the time.
+    fn press_talk_over(&mut self, press: KeyRelease) {
+        if self
+            .config
+            .shortcuts
+            .is_bound(ShortcutAction::TalkOver, &press)
+        {
+            self.talk_over_input = Some(press.input);
+            self.ducker.set_talk_over(true);
+        }
+    }
+
+    /// End talk-over if `input` is the key or button held for it. The modifier keys may be
+    /// released first.
+    fn release_talk_over(&mut self, input: Input) {
+        if self.talk_over_input == Some(input) {
+            self.talk_over_input = None;
+            self.ducker.set_talk_over(false);
+        }
+    }
+
+    /// Perform the global action of a shortcut.
+    fn run_shortcut(&mut self, action: ShortcutAction) {
+        match action {
+            ShortcutAction::Pause => {
+                self.playing ^= true;
+                for playback in &self.playback {
+                    playback.set_playing(self.playing);
+                }
+            }
+            ShortcutAction::Stop => self.stop_all(),
+            ShortcutAction::Modifier => self.modified ^= true,
+            ShortcutAction::Go => {
+                if self.enabled {
+                    self.go();
+                }
+            }
+            ShortcutAction::NextProfile => self.next_profile(),
+            ShortcutAction::SupportBundle => self.create_support_bundle(),
+            ShortcutAction::BigScreen => self.config.big_screen.enabled ^= true,
+            ShortcutAction::Enable => self.set_enabled(!self.enabled),
+            // Toggled from the command palette. Its key bind is held instead, see `update`.
+            ShortcutAction::TalkOver => self.ducker.set_talk_over(!self.ducker.talking_over()),
+        }
+    }
+
+    /// Ask where to save a copy of the configuration that is safe to post publicly, and save it.
+    /// The picker blocks the window until it is closed.
+    fn export_shareable_config(&mut self) {
+        let path = match rfd::FileDialog::new()
+            .add_filter("Configuration", &["toml"])
+            .set_file_name(&format!("{}-shared.toml", self.config_saver.profile()))
+            .save_file()
+        {
+            None => return,
+            Some(path) => path,
+        };
+        let result = shareable_config(&self.config).and_then(|config| {
+            std::fs::write(&path, config)
+                .map_err(|error| format!("Unable to write \"{}\": {error}.", path.display()))
+        });
+        self.shared_config = Some(match result {
+            Err(error) => {
+                log!(Error, "[Soundboard] {error}");
+                Err(error)
+            }
+            Ok(()) => {
+                log!(
+                    "[Soundboard] Exported a shareable configuration to \"{}\".",
+                    path.display()
+                );
+                Ok(path)
+            }
+        });
+    }
+
+    /// Describe the audio devices for a support bundle.
+    fn device_report(&self) -> String {
+        let mut report = String::from("Output devices:\n");
+        let mut names: Vec<&String> = self.output_devices.keys().collect();
+        names.sort();
+        for name in names {
+            let device = &self.output_devices[name];
+            report += &format!("  {name}");
+            if let Some(output_config) = self.config.outputs.get(name) {
+                report += &format!(
+                    " (enabled, {} channels, volume {:.1} dB, delay {} ms, maximum gain {}, limiter {}, group {})",
+                    device.channels().map_or("?".to_string(), |c| c.to_string()),
+                    output_config.volume_db,
+                    output_config.delay_ms,
+                    output_config
+                        .max_gain_db
+                        .map_or("none".to_string(), |gain| format!("{gain:.1} dB")),
+                    output_config
+                        .limiter_db
+                        .map_or("none".to_string(), |ceiling| format!("{ceiling:.1} dBFS")),
+                    if output_config.group.is_empty() {
+                        "none"
+                    } else {
+                        &output_config.group
+                    },
+                );
+            }
+            report.push('\n');
+        }
+        report += "Input devices:\n";
+        for name in &self.input_devices {
+            report += &format!("  {name}\n");
+        }
+        report += &format!(
+            "Microphone: {}\nMonitor: {}\n",
+            self.microphone
+                .as_ref()
+                .map_or("none", |m| m.name().as_str()),
+            self.config.monitor
+        );
+        report
+    }
+
+    /// Write a support bundle and remember where it went, to be shown in the settings menu.
+    fn create_support_bundle(&mut self) {
+        let result = create_support_bundle(
+            &self.config,
+            self.config_saver.profile(),
+            &self.device_report(),
+            &self.state().to_string(),
+        );
+        if let Err(error) = &result {
+            log!(Error, "[Support] {error}");
+        }
+        self.support_bundle = Some(result);
+    }
+
+    /// Generate the demo sounds and add the ones that are missing to the demo bank, then show
+    /// only that bank.
+    fn add_demo_sounds(&mut self) {
+        let sounds = match install_demo_sounds() {
+            Err(error) => {
+                log!(Error, "[Demo] {error}");
+                return;
+            }
+            Ok(sounds) => sounds,
+        };
+        for sound in sounds {
+            if !self.config.sounds.iter().any(|s| s.path == sound.path) {
+                self.playback.push(SoundPlayback::default());
+                self.config.sounds.push(sound);
+            }
+        }
+        self.bank_filter = Some(DEMO_BANK.to_string());
+    }
+
+    /// Check if anything shown in the window changes on its own, such as a playing sound.
+    fn animating(&self) -> bool {
+        self.playback.iter().any(SoundPlayback::playing)
+            || self.cue_timer.is_some()
+            || self
+                .config_watcher
+                .as_ref()
+                .is_some_and(ConfigWatcher::pending)
+            || self
+                .channel_test
+                .as_ref()
+                .is_some_and(|(_, progress)| progress.channel().is_some())
+            || self.hearing_protection.loud_since.is_some()
+            || (self.microphone.is_some() && self.config.hearing_protection.enabled)
+            || self
+                .limited_outputs
+                .values()
+                .any(|time| time.elapsed() < Self::LIMITED_DISPLAY)
+            || self
+                .clipped_outputs
+                .values()
+                .any(|time| time.elapsed() < Self::CLIPPED_DISPLAY)
+            || self.preview.as_ref().is_some_and(Preview::active)
+            || self.profiler.enabled
+            // The countdown to the next attempt is shown.
+            || Integration::ALL.into_iter().any(|integration| {
+                matches!(
+                    self.integration_status(integration),
+                    ConnectionStatus::Connecting
+                        | ConnectionStatus::Retrying { .. }
+                        | ConnectionStatus::CircuitOpen { .. }
+                )
+            })
+    }
+
+    /// Return the distinct bank names of the sounds, sorted alphabetically.
+    fn bank_names(&self) -> Vec<String> {
+        let mut banks: Vec<String> = self
+            .config
+            .sounds
+            .iter()
+            .filter(|sound| !sound.bank.is_empty())
+            .map(|sound| sound.bank.clone())
+            .collect();
+        banks.sort();
+        banks.dedup();
+        banks
+    }
+
+    /// List every command available in the command palette.
+    fn palette_commands(&self) -> Vec<(String, PaletteCommand)> {
+        let mut commands = vec![
+            (
+                if self.enabled { "Disable" } else { "Enable" }.to_string(),
+                PaletteCommand::ToggleEnabled,
+            ),
+            (
+                if self.rehearsal {
+                    "Stop Rehearsal"
+                } else {
+                    "Start Rehearsal"
+                }
+                .to_string(),
+                PaletteCommand::ToggleRehearsal,
+            ),
+            (
+                if self.config.locked {
+                    "Unlock Editing"
+                } else {
+                    "Lock Editing"
+                }
+                .to_string(),
+                PaletteCommand::ToggleLocked,
+            ),
+            ("Open Settings".to_string(), PaletteCommand::Settings),
+            ("Open Help / Manual".to_string(), PaletteCommand::Manual),
+            ("Open Log".to_string(), PaletteCommand::Log),
+            (
+                "Open Missed Triggers".to_string(),
+                PaletteCommand::MissedTriggers,
+            ),
+            ("Open Show".to_string(), PaletteCommand::Show),
+            ("Open Archive".to_string(), PaletteCommand::Archive),
+            ("Open Sync".to_string(), PaletteCommand::Sync),
+            ("Open Sound Packs".to_string(), PaletteCommand::Packs),
+            (
+                "Import From Another App".to_string(),
+                PaletteCommand::ImportOtherApp,
+            ),
+            ("Open Statistics".to_string(), PaletteCommand::Statistics),
+            (
+                if self.profiler.enabled {
+                    "Hide Profiler"
+                } else {
+                    "Show Profiler"
+                }
+                .to_string(),
+                PaletteCommand::ToggleProfiler,
+            ),
+        ];
+        if self
+            .playback
+            .iter()
+            .any(|playback| playback.waiting().is_some())
+        {
+            commands.push((
+                "Cancel Queued Sounds".to_string(),
+                PaletteCommand::CancelQueued,
+            ));
+        }
+        if self.config.integrations.remote_input {
+            if self.client_manager.connected() {
+                commands.push(("Disconnect".to_string(), PaletteCommand::Disconnect));
+            } else {
+                commands.push(("Connect".to_string(), PaletteCommand::Connect));
+            }
+        }
+        for (i, sound) in self.config.sounds.iter().enumerate() {
+            if !sound.hidden() {
+                commands.push((format!("Play {}", sound.name), PaletteCommand::Play(i)));
+            }
+        }
+        for name in self.config.outputs.keys() {
+            commands.push((
+                format!("Toggle Mute: {name}"),
+                PaletteCommand::ToggleMute(name.clone()),
+            ));
+        }
+        for group in self.config.group_names() {
+            commands.push((
+                format!("Toggle Group Mute: {group}"),
+                PaletteCommand::ToggleGroupMute(group),
+            ));
+        }
+        for action in ShortcutAction::ALL {
+            commands.push((action.label().to_string(), PaletteCommand::Shortcut(action)));
+        }
+        for profile in &self.profiles {
+            commands.push((
+                format!("Switch Profile: {profile}"),
+                PaletteCommand::SwitchProfile(profile.clone()),
+            ));
+        }
+        commands.push(("Show All Banks".to_string(), PaletteCommand::ShowBank(None)));
+        for bank in self.bank_names() {
+            commands.push((
+                format!("Stop Bank: {bank}"),
+                PaletteCommand::StopBank(bank.clone()),
+            ));
+            commands.push((
+                format!("Pause Bank: {bank}"),
+                PaletteCommand::PauseBank(bank.clone()),
+            ));
+            commands.push((
+                format!("Show Bank: {bank}"),
+                PaletteCommand::ShowBank(Some(bank)),
+            ));
+        }
+        if self.config.locked {
+            commands.retain(|(_, command)| {
+                !matches!(
+                    command,
+                    PaletteCommand::Settings
+                        | PaletteCommand::Archive
+                        | PaletteCommand::Sync
+                        | PaletteCommand::Packs
+                        | PaletteCommand::ImportOtherApp
+                )
+            });
+        }
+        commands
+    }
+
+    /// Run a command chosen in the command palette.
+    fn run_palette_command(&mut self, command: PaletteCommand) {
+        match command {
+            PaletteCommand::Play(i) => {
+                let result = match self.rejection(i) {
+                    None => self.start_sound(i).map(|_| ()),
+                    Some(rejection) => Err(rejection),
+                };
+                if let Err(rejection) = result {
+                    self.reject_sound(i, rejection, "Command Palette");
+                }
+            }
+            PaletteCommand::ToggleMute(name) => {
+                if let Some(device) = self.output_devices.get(&name) {
+                    device.toggle_muted();
+                }
+            }
+            PaletteCommand::ToggleGroupMute(group) => self.toggle_group_muted(&group),
+            PaletteCommand::ToggleRehearsal => self.set_rehearsal(!self.rehearsal),
+            PaletteCommand::ToggleEnabled => self.set_enabled(!self.enabled),
+            PaletteCommand::Shortcut(action) => self.run_shortcut(action),
+            PaletteCommand::SwitchProfile(profile) => self.switch_profile(&profile),
+            PaletteCommand::ShowBank(bank) => self.bank_filter = bank,
+            PaletteCommand::StopBank(bank) => self.stop_bank(&bank),
+            PaletteCommand::PauseBank(bank) => self.toggle_bank_paused(&bank),
+            PaletteCommand::CancelQueued => self.cancel_waiting_sounds(),
+            PaletteCommand::ToggleLocked => self.config.locked = !self.config.locked,
+            PaletteCommand::Connect => self.client_manager.connect(
+                self.config.server_address.clone(),
+                self.config.api_key.clone(),
+                self.config.remote_devices.clone(),
+            ),
+            PaletteCommand::Disconnect => self.client_manager.disconnect(),
+            PaletteCommand::Settings => self.settings_window = true,
+            PaletteCommand::Manual => self.manual_window = true,
+            PaletteCommand::Log => self.log_window = true,
+            PaletteCommand::MissedTriggers => self.missed_window = true,
+            PaletteCommand::Show => self.show_window = true,
+            PaletteCommand::Archive => self.archive_window = true,
+            PaletteCommand::Sync => self.sync_window = true,
+            PaletteCommand::Packs => self.packs_window = true,
+            PaletteCommand::ImportOtherApp => self.open_foreign_import(),
+            PaletteCommand::Statistics => {
+                self.stats_window = true;
+                self.stats_summary = None;
+            }
+            PaletteCommand::ToggleProfiler => self.profiler.enabled ^= true,
+        }
+    }
+
+    /// Return why sound `i` cannot be played now, if it cannot.
+    fn rejection(&self, i: usize) -> Option<Rejection> {
+        let sound = &self.config.sounds[i];
+        if sound.out_of_season {
+            Some(Rejection::OutOfSeason)
+        } else if sound.archived {
+            Some(Rejection::Archived)
+        } else if !self.enabled {
+            Some(Rejection::Disabled)
+        } else if self.rehearsal && !self.monitor_enabled() {
+            Some(Rejection::NoMonitor)
+        } else if !self.output_devices.values().any(OutputDevice::enabled) {
+            Some(Rejection::NoOutputs)
+        } else if sound.retrigger == Retrigger::Ignore && !self.modified && !self.playback[i].idle()
+        {
+            // The modifier still pauses and resumes it.
+            Some(Rejection::AlreadyPlaying)
+        } else if !self.modified
+            && (sound.retrigger != Retrigger::Toggle || self.playback[i].idle())
+            && self.playback[i]
+                .cooldown_remaining(sound.cooldown())
+                .is_some()
+        {
+            // Pausing and resuming it is not a new play.
+            Some(Rejection::CoolingDown)
+        } else {
+            None
+        }
+    }
+
+    /// Keep one sound of each random group among the sounds `matching` a key bind, picked at
+    /// random from the ones that can play, and the sounds in no group.
+    fn pick_variants(&mut self, matching: Vec<usize>) -> Vec<usize> {
+        let mut kept = Vec::new();
+        let mut variants: HashMap<String, Vec<usize>> = HashMap::new();
+        for i in matching {
+            let group = &self.config.sounds[i].random_group;
+            if group.is_empty() {
+                kept.push(i);
+            } else {
+                variants.entry(group.clone()).or_default().push(i);
+            }
+        }
+        for (group, sounds) in variants {
+            let playable: Vec<usize> = sounds
+                .iter()
+                .copied()
+                .filter(|&i| self.rejection(i).is_none())
+                .collect();
+            if playable.is_empty() {
+                // Explain why the first one is not played.
+                kept.push(sounds[0]);
+                continue;
+            }
+            let last = self.last_variants.get(&group).and_then(|name| {
+                playable
+                    .iter()
+                    .copied()
+                    .find(|&i| &self.config.sounds[i].name == name)
+            });
+            let mut bytes = [0; 8];
+            if let Err(error) = getrandom::getrandom(&mut bytes) {
+                log!(
+                    Warning,
+                    "[Soundboard] Unable to pick a random sound of {group}: {error}."
+                );
+            }
+            let random_group = self.config.random_group(&group);
+            if let Some(i) = random_group.pick(&playable, last, u64::from_le_bytes(bytes)) {
+                self.last_variants
+                    .insert(group, self.config.sounds[i].name.clone());
+                kept.push(i);
+            }
+        }
+        kept.sort_unstable();
+        kept
+    }
+
+    /// Keep one sound of each sequence among the sounds `matching` a key bind, the one after the
+    /// sound it started last or its first one, and the sounds in no sequence. The modifier and
+    /// toggling sounds still pause and resume the sound started last.
+    fn pick_steps(&self, matching: Vec<usize>) -> Vec<usize> {
+        let mut kept = Vec::new();
+        let mut steps: HashMap<&str, Vec<usize>> = HashMap::new();
+        for i in matching {
+            let sequence = self.config.sounds[i].sequence.as_str();
+            if sequence.is_empty() {
+                kept.push(i);
+            } else {
+                steps.entry(sequence).or_default().push(i);
+            }
+        }
+        for (sequence, members) in steps {
+            let last = self.sequence_steps.get(sequence).and_then(|name| {
+                members
+                    .iter()
+                    .copied()
+                    .find(|&i| &self.config.sounds[i].name == name)
+            });
+            let toggles = last.is_some_and(|i| {
+                self.config.sounds[i].retrigger == Retrigger::Toggle && !self.playback[i].idle()
+            });
+            if self.modified || toggles {
+                kept.extend(last.or(members.first().copied()));
+            } else {
+                kept.extend(self.config.sequence(sequence).triggered(&members, last));
+            }
+        }
+        kept.sort_unstable();
+        kept
+    }
+
+    /// Start the next sound of each sequence whose sound finished, if it advances on its own.
+    fn advance_sequences(&mut self) {
+        let ended: Vec<String> = self
+            .sequence_playing
+            .iter()
+            .filter(|(_, controls)| controls.finished() || controls.stopped())
+            .map(|(sequence, _)| sequence.clone())
+            .collect();
+        for sequence in ended {
+            let Some(controls) = self.sequence_playing.remove(&sequence) else {
+                continue;
+            };
+            if !controls.finished() {
+                continue;
+            }
+            let members = self.config.sequence_members(&sequence);
+            let Some(current) = self.sequence_steps.get(&sequence).and_then(|name| {
+                members
+                    .iter()
+                    .copied()
+                    .find(|&i| &self.config.sounds[i].name == name)
+            }) else {
+                continue;
+            };
+            let Some(i) = self.config.sequence(&sequence).after(&members, current) else {
+                continue;
+            };
+            if let Err(rejection) = self.rejection(i).map_or_else(|| self.start_sound(i), Err) {
+                self.reject_sound(i, rejection, "Sequence");
+            }
+        }
+    }
+
+    /// Report that sound `i` was triggered by `source` but not played in the log, as a toast and
+    /// to WebSocket clients, and keep it in the missed triggers.
+    fn reject_sound(&mut self, i: usize, rejection: Rejection, source: &str) {
+        let name = &self.config.sounds[i].name;
+        let text = format!("{name} was not played: {rejection}.");
+        log!(Warning, "[{source}] {text}");
+        if self.missed_triggers.len() == Self::MAX_MISSED_TRIGGERS {
+            self.missed_triggers.pop_front();
+        }
+        let missed = MissedTrigger {
+            time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            name: name.clone(),
+            source: source.to_string(),
+            reason: rejection,
+        };
+        if let Err(error) = missed.append() {
+            log!(Error, "[Soundboard] {error}");
+        }
+        self.missed_triggers.push_back(missed);
+        if self.http_server.running() {
+            self.http_server.publish_event(
+                serde_json::json!({
+                    "type": "rejected",
+                    "index": i,
+                    "name": name,
+                    "reason": rejection,
+                })
+                .to_string(),
+            );
+        }
+        self.toasts.notify(text);
+    }
+
+    /// Start sound `i`, or pause or resume it while the modifier is active or if it toggles.
+    /// Returns what it did, or why no output played it.
+    fn trigger_sound(&mut self, i: usize) -> Result<TriggerOutcome, Rejection> {
+        let playback = &self.playback[i];
+        let toggles = self.config.sounds[i].retrigger == Retrigger::Toggle && !playback.idle();
+        if self.modified || toggles {
+            self.modified = false;
+            let playing = !playback.playing();
+            playback.set_playing(playing);
+            Ok(if playing {
+                TriggerOutcome::Resumed
+            } else {
+                TriggerOutcome::Paused
+            })
+        } else if self.waits_for_choke_group(i) {
+            self.playback[i].wait();
+            Ok(TriggerOutcome::Queued)
+        } else {
+            self.start_sound(i).map(|(_, outcome)| outcome)
+        }
+    }
+
+    /// Check if sound `i` queues behind its choke group, because another sound of the group is
+    /// playing, paused or queued already.
+    fn waits_for_choke_group(&self, i: usize) -> bool {
+        let group = &self.config.sounds[i].choke_group;
+        !group.is_empty()
+            && self.config.choke_policy(group) == ChokePolicy::Queue
+            && self
+                .config
+                .sounds
+                .iter()
+                .zip(&self.playback)
+                .enumerate()
+                .any(|(j, (sound, playback))| {
+                    j != i
+                        && sound.choke_group == *group
+                        && (!playback.idle() || playback.waiting().is_some())
+                })
+    }
+
+    /// Start the sound queued first in each choke group once no other sound of the group plays.
+    fn start_waiting_sounds(&mut self) {
+        let mut groups: Vec<String> = self
+            .config
+            .sounds
+            .iter()
+            .zip(&self.playback)
+            .filter(|(_, playback)| playback.waiting().is_some())
+            .map(|(sound, _)| sound.choke_group.clone())
+            .collect();
+        groups.sort();
+        groups.dedup();
+        for group in groups {
+            let members = || {
+                self.config
+                    .sounds
+                    .iter()
+                    .zip(&self.playback)
+                    .enumerate()
+                    .filter(|(_, (sound, _))| sound.choke_group == group)
+            };
+            // A sound taken out of its group since it was queued starts at once.
+            if !group.is_empty() && members().any(|(_, (_, playback))| !playback.idle()) {
+                continue;
+            }
+            let next = members()
+                .filter_map(|(i, (_, playback))| Some((playback.waiting()?, i)))
+                .min()
+                .map(|(_, i)| i);
+            let Some(i) = next else {
+                continue;
+            };
+            self.playback[i].stop_waiting();
+            let result = match self.rejection(i) {
+                None => self.start_sound(i).map(|_| ()),
+                Some(rejection) => Err(rejection),
+            };
+            if let Err(rejection) = result {
+                self.reject_sound(i, rejection, "Choke Queue");
+            }
+        }
+    }
+
+    /// Take every sound out of the queues of the choke groups.
+    fn cancel_waiting_sounds(&mut self) {
+        for playback in &mut self.playback {
+            playback.stop_waiting();
+        }
+    }
+
+    /// Show the sounds queued in each choke group, each taken out of the queue when clicked.
+    fn choke_queue_ui(&mut self, ui: &mut egui::Ui) {
+        let mut waiting: Vec<(std::time::Instant, usize)> = self
+            .playback
+            .iter()
+            .enumerate()
+            .filter_map(|(i, playback)| Some((playback.waiting()?, i)))
+            .collect();
+        if waiting.is_empty() {
+            return;
+        }
+        waiting.sort();
+        let mut cancelled = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Queued:");
+            for (_, i) in &waiting {
+                let sound = &self.config.sounds[*i];
+                if ui
+                    .button(format!("{} ({})", sound.name, sound.choke_group))
+                    .on_hover_text(tr("Take the sound out of the queue of its choke group"))
+                    .clicked()
+                {
+                    cancelled = Some(*i);
+                }
+            }
+            if ui.button("Cancel All").clicked() {
+                self.cancel_waiting_sounds();
+            }
+        });
+        if let Some(i) = cancelled {
+            self.playback[i].stop_waiting();
+        }
+    }
+
+    /// Trigger sound `i` for `source` if it can be played now, reporting it if it is not played.
+    fn trigger_or_reject(&mut self, i: usize, source: &str) -> Result<TriggerOutcome, Rejection> {
+        let result = match self.rejection(i) {
+            Some(rejection) => Err(rejection),
+            None => self.trigger_sound(i),
+        };
+        if let Err(rejection) = result {
+            self.reject_sound(i, rejection, source);
+        }
+        result
+    }
+
+    /// Stop all sounds, pending follow cues and the sounds queued in choke groups.
+    fn stop_all(&mut self) {
+        let fade = Some(Duration::from_millis(self.config.stop_fade_ms));
+        self.playing = false;
+        self.cue_timer = None;
+        self.cancel_waiting_sounds();
+        for playback in &self.playback {
+            playback.stop(fade);
+        }
+        self.stop_preview();
+    }
+
+    /// Play sound `i` on the monitor output only, from `position` in the file if given. The
+    /// processed sound is trimmed, sped up or slowed down, looped and normalized as configured.
+    /// The original file is played whole at its own speed. The sound volume applies to both.
+    fn start_preview(&mut self, i: usize, processed: bool, position: Option<Duration>) {
+        self.stop_preview();
+        let device = match self.output_devices.get_mut(&self.config.monitor) {
+            Some(device) if device.enabled() => device,
+            _ => {
+                log!(Warning, "[Preview] No monitor output is enabled.");
+                return;
+            }
+        };
+        let sound = &self.config.sounds[i];
+        let mut volume = self.config.volume_db + sound.volume_db;
+        let (start, range, speed, loop_filename) = if processed {
+            volume += normalization_db(
+                self.waveforms.get(&sound.path),
+                sound,
+                &self.config.loudness,
+            );
+            let (start, length) = sound.trim();
+            let end = start.saturating_add(length);
+            let start = position
+                .filter(|position| (start..end).contains(position))
+                .unwrap_or(start);
+            let loop_filename = (!sound.loop_path.is_empty()).then_some(sound.loop_path.as_str());
+            (start, (start, end - start), sound.speed, loop_filename)
+        } else {
+            let start = position.unwrap_or_default();
+            (start, (start, Duration::MAX), 1.0, None)
+        };
+        let controls = Arc::new(AudioControls::new(true, false, volume));
+        if device.play_sounds(&[SoundStart {
+            filename: &sound.path,
+            loop_filename,
+            range,
+            speed,
+            controls: controls.clone(),
+            ducker: None,
+            priority: false,
+            pan: if processed { sound.pan } else { 0.0 },
+        }]) {
+            self.preview = Some(Preview {
+                sound: i,
+                processed,
+                instance: PlaybackInstance {
+                    controls,
+                    started: std::time::SystemTime::now(),
+                    start,
+                    speed,
+                },
+            });
+        }
+    }
+
+    /// Preview sound `i`, or switch between the processed sound and the original file at the
+    /// same position if it is being previewed.
+    fn toggle_preview(&mut self, i: usize) {
+        match &self.preview {
+            Some(preview) if preview.sound == i && preview.active() => {
+                let position = preview.instance.position();
+                self.start_preview(i, !preview.processed, Some(position));
+            }
+            _ => self.start_preview(i, true, None),
+        }
+    }
+
+    fn stop_preview(&mut self) {
+        if let Some(preview) = self.preview.take() {
+            preview.instance.controls.stop(None);
+        }
+    }
+
+    /// Return the playback of every sound in `bank`.
+    fn bank_playback<'a>(&'a self, bank: &'a str) -> impl Iterator<Item = &'a SoundPlayback> {
+        self.config
+            .sounds
+            .iter()
+            .zip(&self.playback)
+            .filter(move |(sound, _)| sound.bank == bank)
+            .map(|(_, playback)| playback)
+    }
+
+    /// Stop every sound in `bank`.
+    fn stop_bank(&self, bank: &str) {
+        log!("[Soundboard] Stopping bank {bank}.");
+        for playback in self.bank_playback(bank) {
+            playback.stop(None);
+        }
+    }
+
+    /// Pause the playing sounds in `bank`, or resume its paused sounds if none are playing.
+    fn toggle_bank_paused(&self, bank: &str) {
+        let playing = self.bank_playback(bank).any(SoundPlayback::playing);
+        for playback in self.bank_playback(bank) {
+            playback.set_playing(!playing);
+        }
+    }
+
+    /// Set the global volume, clamped to [`Self::VOLUME_RANGE`].
+    fn set_volume_db(&mut self, volume_db: f32) {
+        self.config.volume_db =
+            volume_db.clamp(*Self::VOLUME_RANGE.start(), *Self::VOLUME_RANGE.end());
+        self.update_sound_volumes();
+    }
+
+    /// Apply the global and sound-specific volumes to every sound.
+    fn update_sound_volumes(&mut self) {
+        for (playback, sound) in self.playback.iter().zip(&self.config.sounds) {
+            let normalization = normalization_db(
+                self.waveforms.get(&sound.path),
+                sound,
+                &self.config.loudness,
+            );
+            playback.set_volume_db(self.config.volume_db + sound.volume_db + normalization);
+        }
+    }
+
+    /// Use the audio host of the profile for the devices listed and opened from now on, or the
+    /// default host if it is not available. Returns whether the host changed.
+    fn apply_audio_host(&self) -> bool {
+        match set_audio_host(&self.config.audio_host) {
+            Ok(changed) => changed,
+            Err(error) => {
+                log!(Error, "[Soundboard] {error} Using the default audio host.");
+                set_audio_host("").unwrap_or(false)
+            }
+        }
+    }
+
+    /// Update the list of audio output devices.
+    fn update_output_devices(&mut self) {
+        let host = audio_host();
+        self.output_devices.clear();
+        match host.output_devices() {
+            Ok(devices) => {
+                log!("[Soundboard] Found output devices.");
+                let devices: Vec<_> = devices
+                    .filter_map(|device| match device.name() {
+                        Ok(name) => Some((name.clone(), self.open_output_device(&name, device))),
+                        Err(error) => {
+                            log!(Error, "[Soundboard] Error finding device name: {error}.");
+                            None
+                        }
+                    })
+                    .collect();
+                self.output_devices.extend(devices);
+                self.listed_outputs = self.output_devices.keys().cloned().collect();
+            }
+            Err(error) => {
+                log!(Error, "[Soundboard] Error finding output devices: {error}.");
+            }
+        }
+        self.default_output = None;
+        self.open_default_output(host.default_output_device());
+        self.update_output_volumes();
+        self.update_output_gates();
+        self.reset_hearing_protection();
+        self.update_microphone();
+    }
+
+    /// Open [`SYSTEM_DEFAULT_OUTPUT`] on `device`, the default output device of the system, in
+    /// place of the one open on the previous default device. It is removed if there is no
+    /// default device.
+    fn open_default_output(&mut self, device: Option<rodio::Device>) {
+        let name = device.as_ref().and_then(|device| device.name().ok());
+        if self.default_output.is_some() {
+            log!(
+                "[Soundboard] The default output device is now {}.",
+                name.as_deref().unwrap_or("none")
+            );
+        }
+        self.default_output = name;
+        match device {
+            None => {
+                self.output_devices.remove(SYSTEM_DEFAULT_OUTPUT);
+            }
+            Some(device) => {
+                let output_device = self.open_output_device(SYSTEM_DEFAULT_OUTPUT, device);
+                self.output_devices
+                    .insert(SYSTEM_DEFAULT_OUTPUT.to_string(), output_device);
+            }
+        }
+    }
+
+    /// Return an [`OutputDevice`] for `device`, set up and enabled if `name` is in
+    /// `self.config.outputs`.
+    fn open_output_device(&self, name: &str, device: rodio::Device) -> OutputDevice {
+        let mut output_device = OutputDevice::new(device);
+        if let Some(output_config) = self.config.outputs.get(name) {
+            output_device.set_stream_format(output_config.sample_rate, output_config.buffer_frames);
+            output_device.set_channel_map(&output_config.channel_map);
+            output_device.set_delay(Duration::from_millis(output_config.delay_ms));
+            output_device.set_max_gain(output_config.max_gain_db);
+            output_device.set_limiter(output_config.limiter_db);
+            output_device.set_pan(output_config.pan);
+            output_device.set_mono(output_config.mono);
+            output_device.set_swap_channels(output_config.swap_channels);
+            output_device.set_inverted(output_config.invert_left, output_config.invert_right);
+            output_device.enable();
+        }
+        output_device
+    }
+
+    /// Open the output devices plugged in since the last poll of the [`DeviceWatcher`], in place
+    /// of any left from before they were unplugged, and remove the unplugged ones, leaving the
+    /// other devices playing. [`SYSTEM_DEFAULT_OUTPUT`] is opened again when the default device
+    /// changes.
+    fn update_plugged_devices(&mut self, listed: ListedOutputs) {
+        let names = listed.names;
+        let default_changed = listed.default != self.default_output;
+        let plugged: Vec<String> = names.difference(&self.listed_outputs).cloned().collect();
+        // Some hosts, such as ALSA, leave out the devices that are open. Enabled devices are
+        // kept, since they may still be playing.
+        let unplugged: Vec<String> = self
+            .listed_outputs
+            .difference(&names)
+            .filter(|name| self.output_devices.get(*name).is_some_and(|d| !d.enabled()))
+            .cloned()
+            .collect();
+        self.listed_outputs = names;
+        if plugged.is_empty() && unplugged.is_empty() && !default_changed {
+            return;
+        }
+        for name in &unplugged {
+            log!("[Soundboard] Output device {name} was unplugged.");
+            self.output_devices.remove(name);
+        }
+        if !plugged.is_empty() {
+            match audio_host().output_devices() {
+                Ok(devices) => {
+                    for device in devices {
+                        let Ok(name) = device.name() else {
+                            continue;
+                        };
+                        if plugged.contains(&name) {
+                            log!("[Soundboard] Output device {name} was plugged in.");
+                            let output_device = self.open_output_device(&name, device);
+                            self.output_devices.insert(name, output_device);
+                        }
+                    }
+                }
+                Err(error) => {
+                    log!(Error, "[Soundboard] Error finding output devices: {error}.");
+                }
+            }
+        }
+        if default_changed {
+            self.open_default_output(audio_host().default_output_device());
+        }
+        self.update_output_volumes();
+        self.update_output_gates();
+        let monitor = &self.config.monitor;
+        if plugged.contains(monitor)
+            || unplugged.contains(monitor)
+            || (default_changed && monitor == SYSTEM_DEFAULT_OUTPUT)
+        {
+            self.reset_hearing_protection();
+        }
+        // The microphone is mixed into every output, including the new ones.
+        self.update_microphone();
+    }
+
+    /// Play the sounds whose files were imported into the media library from their copies.
+    fn update_imports(&mut self) {
+        for (path, result) in self.importer.finished() {
+            let imported = match result {
+                Err(error) => {
+                    log!(Error, "[Import] {error}");
+                    continue;
+                }
+                Ok(imported) => imported,
+            };
+            log!("[Import] Imported {path} as {imported}.");
+            for sound in &mut self.config.sounds {
+                if sound.path == path {
+                    sound.path = imported.clone();
+                }
+                if sound.loop_path == path {
+                    sound.loop_path = imported.clone();
+                }
+            }
+        }
+    }
+
+    /// Add the sounds downloaded from another soundboard, or give the sounds whose file was
+    /// missing the downloaded file.
+    fn update_sync(&mut self) {
+        let finished = self.sound_sync.finished();
+        if finished.is_empty() {
+            return;
+        }
+        for (mut sound, result) in finished {
+            let path = match result {
+                Err(error) => {
+                    log!(Error, "[Sync] {error}");
+                    continue;
+                }
+                Ok(path) => path,
+            };
+            log!("[Sync] Downloaded the file of {} as {path}.", sound.name);
+            if let Some(local) = self.config.sounds.iter_mut().find(|local| {
+                local.name == sound.name && !std::path::Path::new(&local.path).is_file()
+            }) {
+                local.path = path;
+                continue;
+            }
+            // The loop file is only on the other computer.
+            if !std::path::Path::new(&sound.loop_path).is_file() {
+                sound.loop_path.clear();
+            }
+            sound.path = path;
+            sound.fragment.clear();
+            self.playback.push(SoundPlayback::default());
+            self.config.sounds.push(sound);
+        }
+        self.sound_sync.refresh(
+            self.config_saver.profile().to_string(),
+            self.config.sounds.clone(),
+        );
+    }
+
+    /// Add the sounds of the imported packs, except the ones that are already on the soundboard
+    /// with the same file, e.g. from importing the same pack before.
+    fn update_packs(&mut self) {
+        for sound in self.packs.finished() {
+            if !self
+                .config
+                .sounds
+                .iter()
+                .any(|s| s.name == sound.name && s.path == sound.path)
+            {
+                self.playback.push(SoundPlayback::default());
+                self.config.sounds.push(sound);
+            }
+        }
+    }
+
+    /// Open the window importing the sounds of another app.
+    fn open_foreign_import(&mut self) {
+        self.foreign_import = Some(ForeignImport {
+            app: ForeignApp::Soundpad,
+            file: None,
+            sounds: Vec::new(),
+            bank: String::new(),
+        });
+    }
+
+    /// Show the contents of the Import From Another App window: pick the app and its file, then
+    /// the sounds to add.
+    fn show_foreign_import(&mut self, ui: &mut egui::Ui) {
+        let Some(import) = &mut self.foreign_import else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            for app in ForeignApp::ALL {
+                ui.radio_value(&mut import.app, app, app.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Choose File...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter(import.app.label(), import.app.extensions())
+                    .add_filter("All Files", &["*"])
+                    .pick_file()
+                {
+                    let result = std::fs::read_to_string(&path)
+                        .map_err(|error| format!("Unable to read \"{}\": {error}.", path.display()))
+                        .and_then(|text| import.app.read(&text));
+                    import.sounds.clear();
+                    import.file = Some(match result {
+                        Err(error) => {
+                            log!(Error, "[Import] {error}");
+                            Err(error)
+                        }
+                        Ok(sounds) => {
+                            import.sounds = sounds
+                                .into_iter()
+                                .map(|sound| {
+                                    let exists = std::path::Path::new(&sound.path).is_file();
+                                    (sound, exists, exists)
+                                })
+                                .collect();
+                            Ok(path)
+                        }
+                    });
+                }
+            }
+            match &import.file {
+                Some(Ok(path)) => {
+                    ui.label(path.display().to_string());
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                None => {
+                    ui.label(match import.app {
+                        ForeignApp::Soundpad => {
+                            "Save the sound list from Soundpad's File menu, then choose it."
+                        }
+                        ForeignApp::ExpSoundboard => "Choose a soundboard saved by EXP Soundboard.",
+                        ForeignApp::Resanance => {
+                            "Choose Resanance's settings or an exported profile."
+                        }
+                    });
+                }
+            }
+        });
+        if import.sounds.is_empty() {
+            return;
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Bank");
+            ui.add(
+                TextEdit::singleline(&mut import.bank)
+                    .hint_text("None")
+                    .desired_width(100.0),
+            )
+            .on_hover_text(tr("The bank of the added sounds that have none"));
+            if ui.button("Select All").clicked() {
+                for (_, selected, _) in &mut import.sounds {
+                    *selected = true;
+                }
+            }
+            if ui.button("Select None").clicked() {
+                for (_, selected, _) in &mut import.sounds {
+                    *selected = false;
+                }
+            }
+        });
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .show(ui, |ui| {
+                egui::Grid::new("foreign_sounds").num_columns(3).show(ui, |ui| {
+                    for (sound, selected, exists) in &mut import.sounds {
+                        ui.checkbox(selected, &sound.name);
+                        ui.label(&sound.bank);
+                        if *exists {
+                            ui.label(&sound.path);
+                        } else {
+                            ui.colored_label(ui.visuals().warn_fg_color, &sound.path)
+                                .on_hover_text(tr("The file is not on this computer. Fix the path after adding the sound."));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        let count = import
+            .sounds
+            .iter()
+            .filter(|(_, selected, _)| *selected)
+            .count();
+        if !ui
+            .add_enabled(count > 0, Button::new(format!("Add {count} Sounds")))
+            .clicked()
+        {
+            return;
+        }
+        let Some(import) = self.foreign_import.take() else {
+            return;
+        };
+        for (mut sound, selected, _) in import.sounds {
+            if !selected {
+                continue;
+            }
+            if sound.bank.is_empty() {
+                sound.bank = import.bank.trim().to_string();
+            }
+            if self.config.import_to_library {
+                self.importer.import(&sound.path);
+            }
+            self.playback.push(SoundPlayback::default());
+            self.config.sounds.push(sound);
+        }
+        log!("[Import] Added {count} sounds from {}.", import.app.label());
+    }
+
+    /// Show the contents of the Sound Packs window.
+    fn show_packs(&mut self, ui: &mut egui::Ui) {
+        let sounds = &self.config.sounds;
+        self.pack_selection.retain(|&i| i < sounds.len());
+        let mut import_other_app = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.pack_selection.is_empty(),
+                    egui::Button::new("Export Pack..."),
+                )
+                .on_hover_text(tr(
+                    "Save the selected sounds with their files to a zip file",
+                ))
+                .on_disabled_hover_text("Select the sounds to export below")
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Sound Pack", &["zip"])
+                    .set_file_name(&format!("{}-pack.zip", self.config_saver.profile()))
+                    .save_file()
+                {
+                    let selected = self
+                        .pack_selection
+                        .iter()
+                        .map(|&i| sounds[i].clone())
+                        .collect();
+                    self.packs.export(selected, path);
+                }
+            }
+            if ui
+                .button("Import Pack...")
+                .on_hover_text(tr(
+                    "Add the sounds of a pack, copying its files into the media library",
+                ))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Sound Pack", &["zip"])
+                    .pick_file()
+                {
+                    self.packs.import(path);
+                }
+            }
+            if ui
+                .button("Import From Another App...")
+                .on_hover_text(tr(
+                    "Add the sounds of Soundpad, EXP Soundboard or Resanance",
+                ))
+                .clicked()
+            {
+                import_other_app = true;
+            }
+            if self.packs.busy() {
+                ui.spinner();
+            }
+        });
+        if import_other_app {
+            self.open_foreign_import();
+            return;
+        }
+        match &self.packs.message {
+            Some(Err(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            None => {}
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} selected", self.pack_selection.len()));
+            if ui.button("Select All").clicked() {
+                self.pack_selection = (0..sounds.len()).filter(|&i| !sounds[i].archived).collect();
+            }
+            if let Some(bank) = &self.bank_filter {
+                if ui
+                    .button("Select Bank")
+                    .on_hover_text(format!("Select the sounds of bank \"{bank}\""))
+                    .clicked()
+                {
+                    self.pack_selection = (0..sounds.len())
+                        .filter(|&i| !sounds[i].archived && &sounds[i].bank == bank)
+                        .collect();
+                }
+            }
+            if ui.button("Select None").clicked() {
+                self.pack_selection.clear();
+            }
+        });
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("pack_sounds")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (i, sound) in sounds.iter().enumerate() {
+                        if sound.archived {
+                            continue;
+                        }
+                        let mut selected = self.pack_selection.contains(&i);
+                        if ui.checkbox(&mut selected, &sound.name).changed() {
+                            if selected {
+                                self.pack_selection.insert(i);
+                            } else {
+                                self.pack_selection.remove(&i);
+                            }
+                        }
+                        ui.label(&sound.bank);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    /// Show the contents of the Sync window.
+    fn show_sync(&mut self, ui: &mut egui::Ui) {
+        let profile = self.config_saver.profile().to_string();
+        egui::Grid::new("sync_remote")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Address");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.config.sync_address)
+                        .hint_text("192.168.1.20:8651"),
+                )
+                .on_hover_text(tr(
+                    "The address and port of the other soundboard's HTTP API",
+                ));
+                ui.end_row();
+                ui.label("Token");
+                ui.add(egui::TextEdit::singleline(&mut self.sync_token).password(true))
+                    .on_hover_text(tr(
+                        "The token of the other soundboard's HTTP API. Not saved.",
+                    ));
+                ui.end_row();
+            });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.sound_sync.comparing && !self.config.sync_address.is_empty(),
+                    egui::Button::new("Compare"),
+                )
+                .clicked()
+            {
+                let source = RemoteSource::Network {
+                    address: self.config.sync_address.trim().to_string(),
+                    token: self.sync_token.clone(),
+                };
+                self.sound_sync
+                    .compare(profile.clone(), self.config.sounds.clone(), source);
+            }
+            if ui
+                .add_enabled(
+                    !self.sound_sync.comparing,
+                    egui::Button::new("Open Manifest..."),
+                )
+                .on_hover_text(tr("Compare with a manifest exported on the other computer"))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Manifest", &["json"])
+                    .pick_file()
+                {
+                    self.sound_sync.compare(
+                        profile.clone(),
+                        self.config.sounds.clone(),
+                        RemoteSource::File(path),
+                    );
+                }
+            }
+            if ui
+                .button("Export Manifest...")
+                .on_hover_text(tr(
+                    "Save the sounds and the hashes of their files to compare elsewhere",
+                ))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Manifest", &["json"])
+                    .set_file_name(&format!("{profile}-manifest.json"))
+                    .save_file()
+                {
+                    self.sound_sync
+                        .export(profile.clone(), self.config.sounds.clone(), path);
+                }
+            }
+            if self.sound_sync.comparing {
+                ui.spinner();
+            }
+        });
+        match &self.sound_sync.message {
+            Some(Err(error)) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            None => {}
+        }
+
+        let Some(differences) = self.sound_sync.differences() else {
+            return;
+        };
+        let Some((source, manifest)) = &self.sound_sync.remote else {
+            return;
+        };
+        let network = matches!(source, RemoteSource::Network { .. });
+        ui.separator();
+        ui.label(format!(
+            "Compared with profile \"{}\" of {}.",
+            manifest.profile,
+            match source {
+                RemoteSource::Network { address, .. } => address.clone(),
+                RemoteSource::File(path) => format!("\"{}\"", path.display()),
+            }
+        ));
+        if differences.is_empty() {
+            ui.label("The sounds are the same.");
+            return;
+        }
+        let missing: Vec<usize> = differences
+            .iter()
+            .filter(|difference| difference.difference.syncable())
+            .filter_map(|difference| difference.remote)
+            .collect();
+        let mut download = Vec::new();
+        if network
+            && !missing.is_empty()
+            && ui
+                .button(format!("Copy All Missing ({})", missing.len()))
+                .on_hover_text(tr(
+                    "Copy the missing sounds and files into the media library",
+                ))
+                .clicked()
+        {
+            download.clone_from(&missing);
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("sync").num_columns(3).show(ui, |ui| {
+                for difference in &differences {
+                    ui.label(&difference.name);
+                    ui.label(difference.difference.label());
+                    match difference.remote {
+                        _ if self.sound_sync.downloading.contains(&difference.name) => {
+                            ui.spinner();
+                        }
+                        Some(remote) if network && difference.difference.syncable() => {
+                            if ui.button("Copy Here").clicked() {
+                                download.push(remote);
+                            }
+                        }
+                        _ => {
+                            ui.label("");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        for remote in download {
+            self.sound_sync.download(remote);
+        }
+    }
+
+    /// Store the loudness of the sounds measured since the last call, so each file is measured
+    /// once, and apply it if loudness normalization is enabled. Only the shown sounds that are
+    /// normalized are measured, so archived files are not decoded.
+    fn update_loudness(&mut self) {
+        let mut changed = false;
+        for sound in &mut self.config.sounds {
+            if sound.hidden()
+                || !(sound.normalize || self.config.loudness.enabled)
+                || sound
+                    .loudness
+                    .as_ref()
+                    .is_some_and(|measured| measured.path == sound.path && measured.peak.is_some())
+            {
+                continue;
+            }
+            if let Some(measured) = measured_loudness(self.waveforms.get(&sound.path), sound) {
+                sound.loudness = Some(measured);
+                changed = true;
+            }
+        }
+        if changed && self.config.loudness.enabled {
+            self.update_sound_volumes();
+        }
+    }
+
+    /// Pass the ducking settings to the audio. Priority sounds duck the others on their own.
+    fn update_ducking(&mut self) {
+        let config = &self.config.ducking;
+        self.ducker.set_settings(DuckingSettings {
+            depth_db: config.depth_db,
+            attack: Duration::from_millis(config.attack_ms),
+            release: Duration::from_millis(config.release_ms),
+            talk_over_db: config.talk_over_db,
+        });
+    }
+
+    /// Measure the level of the monitor output and warn (and attenuate, if configured) when it
+    /// has been too loud for too long.
+    fn update_hearing_protection(&mut self) {
+        let state = &mut self.hearing_protection;
+        if state.last_check.elapsed() < Self::HEARING_CHECK_INTERVAL {
+            return;
+        }
+        state.last_check = Instant::now();
+
+        let device = match self.output_devices.get(&self.config.monitor) {
+            Some(device) if device.enabled() => device,
+            _ => {
+                state.level = None;
+                state.loud_since = None;
+                return;
+            }
+        };
+        let level = device.take_level();
+        state.level = Some(level);
+
+        let config = &self.config.hearing_protection;
+        if !config.enabled || level <= config.threshold_db {
+            state.loud_since = None;
+            return;
+        }
+        let loud_since = *state.loud_since.get_or_insert_with(Instant::now);
+        if !state.warning && loud_since.elapsed() >= Duration::from_secs(config.duration_s) {
+            log!(
+                Warning,
+                "[Hearing Protection] {} has been louder than {:.1} dBFS for {} s.",
+                self.config.monitor,
+                config.threshold_db,
+                config.duration_s
+            );
+            state.warning = true;
+            if config.attenuate {
+                device.set_attenuation(config.attenuation_db);
+                state.attenuated = true;
+            }
+        }
+    }
+
+    /// Clear the hearing protection warning and remove its attenuation from every output.
+    fn reset_hearing_protection(&mut self) {
+        for device in self.output_devices.values() {
+            device.set_attenuation(0.0);
+        }
+        self.hearing_protection.loud_since = None;
+        self.hearing_protection.warning = false;
+        self.hearing_protection.attenuated = false;
+    }
+
+    /// Update the list of audio input device names.
+    fn update_input_devices(&mut self) {
+        let host = audio_host();
+        self.input_devices.clear();
+        match host.input_devices() {
+            Ok(devices) => {
+                log!("[Soundboard] Found input devices.");
+                self.input_devices
+                    .extend(devices.filter_map(|device| match device.name() {
+                        Ok(name) => Some(name),
+                        Err(error) => {
+                            log!(Error, "[Soundboard] Error finding device name: {error}.");
+                            None
+                        }
+                    }));
+            }
+            Err(error) => {
+                log!(Error, "[Soundboard] Error finding input devices: {error}.");
+            }
+        }
+    }
+
+    /// Reopen the configured microphone and mix it into all enabled output devices.
+    fn update_microphone(&mut self) {
+        let muted = self.microphone.as_ref().is_some_and(|m| m.muted());
+        self.microphone = None;
+        if self.config.microphone.device.is_empty() {
+            return;
+        }
+
+        let host = audio_host();
+        let device = match host.input_devices() {
+            Err(error) => {
+                log!(Error, "[Soundboard] Error finding input devices: {error}.");
+                return;
+            }
+            Ok(mut devices) => {
+                match devices.find(|d| d.name().ok() == Some(self.config.microphone.device.clone()))
+                {
+                    None => {
+                        log!(
+                            Error,
+                            "[Soundboard] Unable to find input device {}.",
+                            self.config.microphone.device
+                        );
+                        return;
+                    }
+                    Some(device) => device,
+                }
+            }
+        };
+        let microphone = match Microphone::open(&device) {
+            Err(error) => {
+                log!(Error, "[Soundboard] {error}");
+                return;
+            }
+            Ok(microphone) => microphone,
+        };
+        microphone.set_volume_db(self.config.microphone.volume_db);
+        microphone.set_muted(muted);
+        for device in self.output_devices.values_mut() {
+            device.play_microphone(&microphone);
+        }
+        self.microphone = Some(microphone);
+    }
+
+    /// Play the sound at index `i` from the beginning on all output devices, stopping the
+    /// instances already playing unless the sound overlaps and the other sounds of its choke
+    /// group. Sounds that queue or ignore retriggers are left playing. Returns the new
+    /// instance's controls, or the playing one's if it was left playing, or an error if no
+    /// output played it.
+    fn start_sound(&mut self, i: usize) -> Result<(Arc<AudioControls>, TriggerOutcome), Rejection> {
+        // Resolve the sounds started with this one by name.
+        let mut group = vec![i];
+        for name in &self.config.sounds[i].with {
+            match self
+                .config
+                .sounds
+                .iter()
+                .position(|s| !s.hidden() && &s.name == name)
+            {
+                Some(j) if !group.contains(&j) => group.push(j),
+                Some(_) => (),
+                None => log!(
+                    Warning,
+                    "[Soundboard] {} starts with a missing sound {name}.",
+                    self.config.sounds[i].name
+                ),
+            }
+        }
+
+        let mut starts = Vec::with_capacity(group.len());
+        let mut started = Vec::with_capacity(group.len());
+        let mut kept = None;
+        for j in group {
+            let sound = &self.config.sounds[j];
+            if matches!(sound.retrigger, Retrigger::Queue | Retrigger::Ignore) {
+                let playback = &mut self.playback[j];
+                if let Some(controls) = playback.instances().last().map(|i| i.controls.clone()) {
+                    if j == i {
+                        if sound.retrigger == Retrigger::Queue {
+                            playback.queue();
+                        }
+                        kept = Some(controls);
+                    }
+                    continue;
+                }
+            }
+            let offset = random_start(self.waveforms.get(&sound.path), sound);
+            let normalization = normalization_db(
+                self.waveforms.get(&sound.path),
+                sound,
+                &self.config.loudness,
+            );
+            starts.push(SoundStart::new(
+                sound,
+                self.config.volume_db + sound.volume_db + normalization,
+                offset,
+                Some(self.ducker.clone()),
+            ));
+            started.push(j);
+        }
+
+        // A background sound started fades in if it crossfades from another one playing.
+        let crossfade = Duration::from_millis(self.config.background.crossfade_ms);
+        let background_playing = self.playback.iter().enumerate().any(|(k, playback)| {
+            self.config.sounds[k].background && !started.contains(&k) && !playback.idle()
+        });
+        if background_playing {
+            for (j, start) in started.iter().zip(&starts) {
+                if self.config.sounds[*j].background {
+                    start.controls.fade_in(crossfade);
+                }
+            }
+        }
+        let controls: Vec<_> = starts.iter().map(|start| start.controls.clone()).collect();
+        AudioControls::link(&controls);
+        // Disabled groups play nothing, except that while rehearsing, every sound plays on the
+        // monitor output whatever its group and nothing plays on the other outputs.
+        let config = &self.config;
+        let rehearsal = self.rehearsal;
+        let outputs = self
+            .output_devices
+            .iter_mut()
+            .filter(|(name, _)| {
+                if rehearsal {
+                    **name == config.monitor
+                } else {
+                    config.output_group(name).is_none_or(|group| group.enabled)
+                }
+            })
+            .map(|(name, device)| {
+                let group = config
+                    .outputs
+                    .get(name)
+                    .map_or("", |output_config| output_config.group.as_str());
+                (group, device)
+            });
+        let routed_played = play_routed(outputs, &starts, |k, group| {
+            rehearsal || config.sounds[started[k]].plays_on(group)
+        });
+
+        // Only the sounds an output played are kept, counted, and stop the other sounds of their
+        // choke groups and sequences.
+        let played: Vec<(usize, &SoundStart)> = started
+            .iter()
+            .copied()
+            .zip(&starts)
+            .zip(&routed_played)
+            .filter(|(_, played)| **played)
+            .map(|(start, _)| start)
+            .collect();
+        let played_indices: Vec<usize> = played.iter().map(|(j, _)| *j).collect();
+        for &(j, start) in &played {
+            let sound = &self.config.sounds[j];
+            self.playback[j].add_start(start, sound.instance_limit());
+            self.playback[j].stop_waiting();
+            self.stats.record(self.config_saver.profile(), &sound.name);
+            for (k, playback) in self.playback.iter().enumerate() {
+                let other = &self.config.sounds[k];
+                if !played_indices.contains(&k)
+                    && (!sound.choke_group.is_empty() && other.choke_group == sound.choke_group
+                        || !sound.sequence.is_empty() && other.sequence == sound.sequence)
+                {
+                    playback.stop(None);
+                }
+            }
+        }
+
+        // Crossfade from the other background sounds playing to a background sound started.
+        if background_playing
+            && played
+                .iter()
+                .any(|(j, _)| self.config.sounds[*j].background)
+        {
+            for (k, playback) in self.playback.iter().enumerate() {
+                if self.config.sounds[k].background && !played_indices.contains(&k) {
+                    for instance in playback.instances() {
+                        instance.controls.fade_out(crossfade);
+                    }
+                }
+            }
+        }
+
+        for &(j, start) in &played {
+            let sound = &self.config.sounds[j];
+            if sound.sequence.is_empty() {
+                continue;
+            }
+            self.sequence_steps
+                .insert(sound.sequence.clone(), sound.name.clone());
+            if self.config.sequence(&sound.sequence).advance == SequenceAdvance::Finish {
+                self.sequence_playing
+                    .insert(sound.sequence.clone(), start.controls.clone());
+            } else {
+                self.sequence_playing.remove(&sound.sequence);
+            }
+        }
+        if let Some(controls) = kept {
+            return Ok((controls, TriggerOutcome::Queued));
+        }
+        match played.first() {
+            Some(&(j, start)) if j == i => Ok((start.controls.clone(), TriggerOutcome::Started)),
+            _ => Err(Rejection::NotStarted),
+        }
+    }
+
+    /// Fire the cue on standby and move standby to the following cue.
+    fn go(&mut self) {
+        let sound = match self.config.cues.get(self.standby_cue) {
+            None => return,
+            Some(cue) => cue.sound.clone(),
+        };
+        let controls = match self
+            .config
+            .sounds
+            .iter()
+            .position(|s| !s.hidden() && s.name == sound)
+        {
+            Some(i) => match self.start_sound(i) {
+                Ok((controls, _)) => Some(controls),
+                Err(rejection) => {
+                    self.reject_sound(i, rejection, "Show");
+                    None
+                }
+            },
+            None => {
+                log!(
+                    Warning,
+                    "[Show] Cue {} refers to a missing sound {sound}.",
+                    self.standby_cue + 1
+                );
+                None
+            }
+        };
+        self.cue_timer = Some(CueTimer {
+            started: Instant::now(),
+            controls,
+            ended: None,
+        });
+        self.current_cue = Some(self.standby_cue);
+        self.standby_cue += 1;
+    }
+
+    /// Fire the cue on standby if it follows the current cue and its delay has elapsed.
+    fn update_follow(&mut self) {
+        if !self.enabled || self.current_cue.map(|i| i + 1) != Some(self.standby_cue) {
+            return;
+        }
+        let (timer, cue) = match (&mut self.cue_timer, self.config.cues.get(self.standby_cue)) {
+            (Some(timer), Some(cue)) => (timer, cue),
+            _ => return,
+        };
+
+        let delay = Duration::from_millis(cue.follow_delay_ms);
+        let due = match cue.follow {
+            Follow::Manual => false,
+            Follow::AfterStart => timer.started.elapsed() >= delay,
+            Follow::AfterEnd => {
+                // A cue with a missing sound ends immediately.
+                if timer.ended.is_none() && timer.controls.as_ref().is_none_or(|c| c.finished()) {
+                    timer.ended = Some(Instant::now());
+                }
+                timer.ended.is_some_and(|ended| ended.elapsed() >= delay)
+            }
+        };
+        if due {
+            self.go();
+        }
+    }
+
+    /// Show the bank filter, and the buttons stopping or pausing the selected bank.
+    fn bank_filter_ui(&mut self, ui: &mut egui::Ui) {
+        let banks = self.bank_names();
+        if !banks.is_empty() || self.bank_filter.is_some() {
+            egui::ComboBox::from_label("Bank")
+                .selected_text(self.bank_filter.as_deref().unwrap_or("All"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.bank_filter, None, "All");
+                    for bank in banks {
+                        ui.selectable_value(&mut self.bank_filter, Some(bank.clone()), bank);
+                    }
+                });
+        }
+        if let Some(bank) = &self.bank_filter {
+            if ui
+                .button("Stop Bank")
+                .on_hover_text(tr("Stop every sound in this bank"))
+                .clicked()
+            {
+                self.stop_bank(bank);
+            }
+            if ui
+                .button("Pause Bank")
+                .on_hover_text(tr("Pause the playing sounds in this bank, or resume them"))
+                .clicked()
+            {
+                self.toggle_bank_paused(bank);
+            }
+        }
+    }
+
+    /// Show why the profile could not be loaded, while the default configuration is used instead.
+    fn load_error_ui(&self, ui: &mut egui::Ui) {
+        if let Some(error) = &self.load_error {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!(
+                    "Unable to load profile {}: {error} The default configuration is used and \
+                    is not saved, so the profile is left as it is.",
+                    self.config_saver.profile()
+                ),
+            );
+        }
+    }
+
+    /// Show the hearing protection warning while the monitor output is too loud.
+    fn hearing_warning_ui(&mut self, ui: &mut egui::Ui) {
+        if self.hearing_protection.warning {
+            let config = &self.config.hearing_protection;
+            let warning = format!(
+                "{} has been louder than {:.1} dBFS for {} s{}.",
+                self.config.monitor,
+                config.threshold_db,
+                config.duration_s,
+                if self.hearing_protection.attenuated {
+                    format!(" and was lowered by {:.1} dB", config.attenuation_db)
+                } else {
+                    String::new()
+                }
+            );
+            ui.horizontal(|ui| {
+                ui.colored_label(ui.visuals().warn_fg_color, warning);
+                if self.hearing_protection.attenuated {
+                    if ui.button("Restore Volume").clicked() {
+                        self.reset_hearing_protection();
+                    }
+                } else if ui.button("Dismiss").clicked() {
+                    self.hearing_protection.loud_since = None;
+                    self.hearing_protection.warning = false;
+                }
+            });
+        }
+    }
+
+    /// Ask which profile to load, instead of the main window, until one is chosen.
+    fn profile_chooser_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(chooser) = &mut self.profile_chooser else {
+            return;
+        };
+        ui.heading(tr("Choose a Profile"));
+        ui.label(
+            "There are several profiles and none is loaded at startup. Choose the one to use, so \
+            you do not go live with the wrong board.",
+        );
+        let mut chosen = None;
+        for profile in &self.profiles {
+            if ui
+                .add(Button::new(profile).min_size(Vec2::new(200.0, 0.0)))
+                .clicked()
+            {
+                chosen = Some(profile.clone());
+            }
+        }
+        ui.checkbox(&mut chooser.remember, "Remember my choice")
+            .on_hover_text(tr("Load this profile at startup without asking. Change it in the settings menu under \"Profiles\"."));
+        let Some(profile) = chosen else {
+            return;
+        };
+        if chooser.remember {
+            self.set_default_profile(Some(profile.clone()));
+        }
+        self.profile_chooser = None;
+        self.switch_profile(&profile);
+    }
+
+    /// Load `profile` at startup without asking from now on, or ask again if None.
+    fn set_default_profile(&mut self, profile: Option<String>) {
+        match set_default_profile(profile.as_deref()) {
+            Err(error) => log!(Error, "[Soundboard] {error}"),
+            Ok(()) => self.default_profile = profile,
+        }
+    }
+
+    /// Return the indices of the sounds shown, which are not hidden and in the bank shown.
+    fn visible_sounds(&self) -> Vec<usize> {
+        self.config
+            .sounds
+            .iter()
+            .enumerate()
+            .filter(|(_, sound)| {
+                !sound.hidden()
+                    && self
+                        .bank_filter
+                        .as_ref()
+                        .is_none_or(|bank| &sound.bank == bank)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Select a visible sound with the arrow keys, Home and End, play it with Enter, and clear
+    /// the selection with Escape. Ignored while a widget has keyboard focus, so text fields and
+    /// sliders keep their keys.
+    fn navigate_sounds(&mut self, ctx: &egui::Context) {
+        if ctx.memory(|memory| memory.focus().is_some()) {
+            return;
+        }
+        let visible = self.visible_sounds();
+        let Some(last) = visible.len().checked_sub(1) else {
+            self.selected_sound = None;
+            return;
+        };
+        let pressed = |key| ctx.input_mut(|input| input.consume_key(egui::Modifiers::NONE, key));
+        let mut position = self
+            .selected_sound
+            .and_then(|i| visible.iter().position(|&j| j == i));
+        if pressed(egui::Key::ArrowDown) {
+            position = Some(position.map_or(0, |position| (position + 1).min(last)));
+        }
+        if pressed(egui::Key::ArrowUp) {
+            position = Some(position.map_or(last, |position| position.saturating_sub(1)));
+        }
+        if pressed(egui::Key::Home) {
+            position = Some(0);
+        }
+        if pressed(egui::Key::End) {
+            position = Some(last);
+        }
+        if pressed(egui::Key::Escape) {
+            position = None;
+        }
+        let selected = position.map(|position| visible[position]);
+        if selected != self.selected_sound {
+            self.selected_sound = selected;
+            self.scroll_to_selected = true;
+        }
+        if let (true, Some(i)) = (pressed(egui::Key::Enter), selected) {
+            let _ = self.trigger_or_reject(i, "Keyboard");
+        }
+    }
+
+    /// Show the visible sounds as large pads for the big screen or the pad view. Clicking a pad
+    /// triggers it like its key bind, and long-pressing an `editable` pad opens its settings
+    /// unless editing is locked.
+    fn pads_ui(&mut self, ui: &mut egui::Ui, colors: ThemeColors, editable: bool) {
+        let scale = if editable {
+            1.0
+        } else {
+            self.config.big_screen.scale
+        };
+        let long_press = Self::PAD_LONG_PRESS.as_secs_f64();
+        let mut triggered = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (i, sound) in self.config.sounds.iter().enumerate() {
+                    if sound.hidden()
+                        || self
+                            .bank_filter
+                            .as_ref()
+                            .is_some_and(|bank| &sound.bank != bank)
+                    {
+                        continue;
+                    }
+                    let playback = &self.playback[i];
+                    let text = RichText::new(format!("{}\n{}", sound.name, sound.key.label()));
+                    let button = if playback.idle() {
+                        match sound.color {
+                            None => Button::new(text),
+                            Some(rgb) => {
+                                let (fill, text_color) = pad_colors(rgb);
+                                Button::new(text.color(text_color)).fill(fill)
+                            }
+                        }
+                    } else if playback.playing() {
+                        Button::new(text.color(Color32::BLACK)).fill(colors.playing)
+                    } else {
+                        Button::new(text.color(Color32::BLACK)).fill(colors.paused)
+                    };
+                    let selected = self.selected_sound == Some(i);
+                    let button = if selected {
+                        button.stroke(ui.visuals().selection.stroke)
+                    } else {
+                        button
+                    };
+                    // Editable pads stay enabled, so they can be edited while the soundboard
+                    // is disabled.
+                    let response = ui.add_enabled(
+                        self.enabled || editable,
+                        button.min_size(Vec2::new(160.0, 90.0) * scale),
+                    );
+                    if selected && std::mem::take(&mut self.scroll_to_selected) {
+                        response.scroll_to_me(None);
+                    }
+                    if editable && !self.config.locked && response.is_pointer_button_down_on() {
+                        let held =
+                            ui.input(|input| Some(input.time - input.pointer.press_start_time()?));
+                        match held {
+                            Some(held) if held >= long_press && self.pad_long_press != Some(i) => {
+                                self.pad_long_press = Some(i);
+                                self.pad_editor = Some(i);
+                            }
+                            Some(held) if held >= long_press => (),
+                            // Wake up when the press becomes long, even if the pointer is still.
+                            Some(held) => ui
+                                .ctx()
+                                .request_repaint_after(Duration::from_secs_f64(long_press - held)),
+                            None => (),
+                        }
+                    }
+                    // The release of a long press does not trigger the sound.
+                    if response.clicked() && self.pad_long_press != Some(i) {
+                        triggered = Some(i);
+                    }
+                }
+            });
+        });
+        if !ui.input(|input| input.pointer.any_down()) {
+            self.pad_long_press = None;
+        }
+        if let Some(i) = triggered {
+            let source = if editable { "Pads" } else { "Big Screen" };
+            let _ = self.trigger_or_reject(i, source);
+        }
+    }
+
+    /// Show the visible sounds with their play state, key binds and volumes, but nothing that
+    /// changes them otherwise, while editing is locked. Each can be previewed on the monitor
+    /// output.
+    fn locked_sounds_ui(&mut self, ui: &mut egui::Ui, colors: ThemeColors) {
+        let scale = self.config.volume_scale;
+        let monitor_enabled = self.monitor_enabled();
+        let previewed = self
+            .preview
+            .as_ref()
+            .filter(|preview| preview.active())
+            .map(|preview| preview.sound);
+        let mut triggered = None;
+        let mut preview_action = None; // (start or stop, index)
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("locked_sounds")
+                .num_columns(7)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (i, sound) in self.config.sounds.iter_mut().enumerate() {
+                        if sound.hidden()
+                            || self
+                                .bank_filter
+                                .as_ref()
+                                .is_some_and(|bank| &sound.bank != bank)
+                        {
+                            continue;
+                        }
+                        let playback = &self.playback[i];
+                        let selected = self.selected_sound == Some(i);
+                        ui.horizontal(|ui| {
+                            let state = state_ui(ui, playback, colors, selected, &sound.name);
+                            if state.clicked() {
+                                self.selected_sound = Some(i);
+                            }
+                            if selected && std::mem::take(&mut self.scroll_to_selected) {
+                                state.scroll_to_me(None);
+                            }
+                            cooldown_ui(ui, playback, sound);
+                        });
+                        ui.label(&sound.name);
+                        ui.label(&sound.bank);
+                        ui.label(sound.key.label());
+                        let volume = ui.add(volume_slider(
+                            &mut sound.volume_db,
+                            Self::SOUND_VOLUME_RANGE,
+                            scale,
+                        ));
+                        describe(&volume, WidgetType::Slider, || {
+                            format!("Volume of {}: {:.1} dB", sound.name, sound.volume_db)
+                        });
+                        if volume.changed() {
+                            let normalization = normalization_db(
+                                self.waveforms.get(&sound.path),
+                                sound,
+                                &self.config.loudness,
+                            );
+                            playback.set_volume_db(
+                                self.config.volume_db + sound.volume_db + normalization,
+                            );
+                        }
+                        let play = ui.button(tr("Play"));
+                        describe(&play, WidgetType::Button, || format!("Play {}", sound.name));
+                        if play.clicked() {
+                            triggered = Some(i);
+                        }
+                        let previewing = previewed == Some(i);
+                        let preview = ui
+                            .add_enabled(
+                                monitor_enabled,
+                                Button::new(if previewing { "Stop" } else { "Preview" }),
+                            )
+                            .on_hover_text(tr("Play the sound on the monitor output only"))
+                            .on_disabled_hover_text(
+                                "Select a monitor output in the settings menu to preview",
+                            );
+                        describe(&preview, WidgetType::Button, || {
+                            format!("Preview {}", sound.name)
+                        });
+                        if preview.clicked() {
+                            preview_action = Some((!previewing, i));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+        if let Some(i) = triggered {
+            let _ = self.trigger_or_reject(i, "Locked Table");
+        }
+        match preview_action {
+            Some((true, i)) => self.start_preview(i, true, None),
+            Some((false, _)) => self.stop_preview(),
+            None => (),
+        }
+    }
+
+    /// Show the settings of the sound long-pressed in the pad view.
+    fn pad_editor_ui(&mut self, ui: &mut egui::Ui, last_key_released: Option<&KeyRelease>) {
+        let Some(i) = self.pad_editor.filter(|i| *i < self.config.sounds.len()) else {
+            self.pad_editor = None;
+            return;
+        };
+        let names: Vec<String> = self
+            .config
+            .sounds
+            .iter()
+            .filter(|sound| !sound.hidden())
+            .map(|sound| sound.name.clone())
+            .collect();
+        let groups = self.config.group_names();
+        let choke_groups = self.config.choke_group_names();
+        let random_groups = self.config.random_group_names();
+        let sequences = self.config.sequence_names();
+        let scale = self.config.volume_scale;
+        let sound = &mut self.config.sounds[i];
+        egui::Grid::new("pad_editor").show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut sound.name);
+            ui.end_row();
+
+            ui.label("Bank");
+            ui.text_edit_singleline(&mut sound.bank);
+            ui.end_row();
+
+            ui.label("Key");
+            sound.key.update(ui, last_key_released);
+            ui.end_row();
+
+            ui.label("Volume");
+            if ui
+                .add(volume_slider(
+                    &mut sound.volume_db,
+                    Self::SOUND_VOLUME_RANGE,
+                    scale,
+                ))
+                .changed()
+            {
+                let normalization = normalization_db(
+                    self.waveforms.get(&sound.path),
+                    sound,
+                    &self.config.loudness,
+                );
+                self.playback[i]
+                    .set_volume_db(self.config.volume_db + sound.volume_db + normalization);
+            }
+            ui.end_row();
+
+            ui.label("Color");
+            ui.horizontal(|ui| {
+                let mut colored = sound.color.is_some();
+                if ui.checkbox(&mut colored, "").changed() {
+                    sound.color = colored.then_some([0, 92, 128]);
+                }
+                if let Some(color) = &mut sound.color {
+                    ui.color_edit_button_srgb(color);
+                }
+            });
+            ui.end_row();
+
+            ui.label("File");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut sound.path);
+                browse_button(ui, &mut sound.path);
+            });
+            ui.end_row();
+        });
+        playback_options_ui(
+            ui,
+            sound,
+            &names,
+            &groups,
+            &choke_groups,
+            &random_groups,
+            &sequences,
+        );
+        if ui.button("Done").clicked() {
+            self.pad_editor = None;
+        }
+    }
+}
+
+impl eframe::App for Soundboard {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let big_screen = &self.config.big_screen;
+        let theme = (
+            self.config.theme.clone(),
+            big_screen.enabled.then_some(big_screen.scale),
+        );
+        if self.applied_theme.as_ref() != Some(&theme) {
+            ctx.set_style(theme_style(&theme.0, theme.1, self.system_accessibility));
+            self.applied_theme = Some(theme);
+        }
+        let colors = ThemeColors::new(&self.config.theme);
+        let scale = self.config.volume_scale;
+        let reduced_motion = self
+            .config
+            .theme
+            .reduced_motion
+            .enabled(self.system_accessibility.reduced_motion);
+        self.profiler.start_frame();
+        if self.config.language != self.language {
+            if let Err(error) = set_language(&self.config.language) {
+                log!(Error, "[Languages] {error}");
+            }
+            self.language = self.config.language.clone();
+        }
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F12)) {
+            self.profiler.enabled ^= true;
+        }
+
+        let dispatch_start = Instant::now();
+        let mut events = std::mem::take(&mut self.event_buffer);
+        self.client_manager.poll_events(&mut events);
+        let suppress_events = self.profile_chooser.is_some()
+            || self.config.shortcuts.listening()
+            || self.config.microphone.mute.listening
+            || self.config.output_groups.values().any(|g| g.mute.listening)
+            || self.config.sounds.iter().any(|s| s.key.listening)
+            || self.config.banks.iter().any(|b| b.stop_key.listening)
+            || self.config.banks.iter().any(|b| b.pause_key.listening);
+        // Track the keys held down, so that each release and scroll wheel step knows its
+        // modifier keys.
+        let mut releases = Vec::new();
+        for event in &events {
+            if event.event_type == EventType::EV_REL as u16 {
+                if let Some(wheel) = RelativeAxis::from_repr(event.code)
+                    .and_then(|axis| Wheel::from_step(axis, event.value))
+                {
+                    releases.push(KeyRelease::new(Input::Wheel(wheel), &self.held_keys));
+                }
+                continue;
+            }
+            if event.event_type != EventType::EV_KEY as u16 {
+                continue;
+            }
+            let key = match Key::from_repr(event.code) {
+                Some(key) => key,
+                None => continue,
+            };
+            match event.value {
+                0 => {
+                    self.held_keys.remove(&key);
+                    self.release_talk_over(Input::Key(key));
+                    releases.push(KeyRelease::new(Input::Key(key), &self.held_keys));
+                }
+                1 => {
+                    let press = KeyRelease::new(Input::Key(key), &self.held_keys);
+                    if !suppress_events {
+                        self.press_talk_over(press.clone());
+                    }
+                    self.held_keys.insert(key);
+                    self.triggers.press(press);
+                }
+                // Ignore auto-repeat.
+                _ => (),
+            }
+        }
+        // Gamepad buttons are not combined with the modifier keys of the remote.
+        let gamepad_events: Vec<_> = self
+            .gamepad_input
+            .iter()
+            .flat_map(GamepadInput::events)
+            .collect();
+        for (button, pressed) in gamepad_events {
+            if pressed {
+                let press = KeyRelease::new(Input::Gamepad(button), &HashSet::new());
+                if !suppress_events {
+                    self.press_talk_over(press.clone());
+                }
+                self.triggers.press(press);
+            } else {
+                self.release_talk_over(Input::Gamepad(button));
+                releases.push(KeyRelease::new(Input::Gamepad(button), &HashSet::new()));
+            }
+        }
+        self.forget_remote_keys();
+        let last_key_released = releases.last();
+
+        // Hold back first taps of double taps, and turn long held keys into long presses.
+        let config = &self.config;
+        let bound =
+            |release: &KeyRelease| config.key_buttons().any(|button| button.matches(release));
+        let mut triggered = self.triggers.expired(&config.triggers);
+        triggered.extend(self.triggers.held(&config.triggers, bound));
+        for release in &releases {
+            triggered.extend(
+                self.triggers
+                    .release(release.clone(), &config.triggers, bound),
+            );
+        }
+        if let Some(remaining) = self.triggers.next_expiry(&self.config.triggers) {
+            ctx.request_repaint_after(remaining);
+        }
+
+        if !suppress_events {
+            // Releases of `KEY_RESERVED` never match a key bind.
+            for release in &triggered {
+                // Only explain why a sound was not played if the key played nothing else, since
+                // e.g. seasonal variants of a sound may share a key.
+                let mut played = false;
+                let mut rejected = None;
+                let matching = (0..self.config.sounds.len())
+                    .filter(|&i| self.config.sounds[i].key.matches(release))
+                    .collect();
+                let picked = self.pick_variants(matching);
+                for i in self.pick_steps(picked) {
+                    match self.rejection(i).map_or_else(|| self.trigger_sound(i), Err) {
+                        Ok(_) => played = true,
+                        Err(rejection) => rejected = rejected.or(Some((i, rejection))),
+                    }
+                }
+                if let (false, Some((i, rejection))) = (played, rejected) {
+                    self.reject_sound(i, rejection, "Key");
+                }
+
+                for (name, output_config) in &self.config.outputs {
+                    if output_config.mute.matches(release) {
+                        self.output_devices[name].toggle_muted();
+                    }
+                }
+                for (group, group_config) in &self.config.output_groups {
+                    if group_config.mute.matches(release) {
+                        self.toggle_group_muted(group);
+                    }
+                }
+
+                if self.config.microphone.mute.matches(release) {
+                    if let Some(microphone) = &self.microphone {
+                        microphone.toggle_muted();
+                    }
+                }
+
+                // Talk-over lasts while its key is held, so its release ends it above.
+                for action in ShortcutAction::ALL {
+                    if action != ShortcutAction::TalkOver
+                        && self.config.shortcuts.is_bound(action, release)
+                    {
+                        self.run_shortcut(action);
+                    }
+                }
+
+                for bank in &self.config.banks {
+                    if bank.stop_key.matches(release) {
+                        self.stop_bank(&bank.name);
+                    }
+                    if bank.pause_key.matches(release) {
+                        self.toggle_bank_paused(&bank.name);
+                    }
+                }
+            }
+        }
+        if !suppress_events && !self.palette.is_open() {
+            self.navigate_sounds(ctx);
+        }
+        self.event_buffer = events;
+        self.profiler.add_dispatch(dispatch_start.elapsed());
+
+        // Warn about outputs whose maximum gain lowered the volume of a sound, and note the
+        // outputs that clipped.
+        for (name, device) in &self.output_devices {
+            if device.take_limited() {
+                let already_limited = self
+                    .limited_outputs
+                    .get(name)
+                    .is_some_and(|time| time.elapsed() < Self::LIMITED_DISPLAY);
+                if !already_limited {
+                    log!(
+                        Warning,
+                        "[Soundboard] The maximum gain of {name} lowered the volume."
+                    );
+                }
+                self.limited_outputs.insert(name.clone(), Instant::now());
+            }
+            if device.take_clipped() {
+                self.clipped_outputs.insert(name.clone(), Instant::now());
+            }
+        }
+
+        self.reload_changed_profile();
+        if let Some(names) = self.device_watcher.changed() {
+            self.update_plugged_devices(names);
+        }
+        self.update_hearing_protection();
+        self.update_ducking();
+        self.handle_api_requests();
+        self.update_follow();
+        self.waveforms.update();
+        self.update_imports();
+        self.update_sync();
+        self.update_packs();
+        self.update_loudness();
+        self.start_waiting_sounds();
+        self.advance_sequences();
+        for i in 0..self.playback.len() {
+            if self.playback[i].take_queued() && self.rejection(i).is_none() {
+                if let Err(rejection) = self.start_sound(i) {
+                    self.reject_sound(i, rejection, "Queue");
+                }
+            }
+            self.playback[i].prune();
+        }
+
+        // Keep track of the dropped file for 5 frames. This is required because the pointer location
+        // is unknown while a file is being dragged, so .hovered will always be false when the file is dropped.
+        if self.dropped_file.1.is_none() || self.dropped_file.0 > 5 {
+            self.dropped_file.0 = 0;
+            self.dropped_file.1 = ctx.input(|i| {
+                i.raw
+                    .dropped_files
+                    .first()
+                    .and_then(|f| f.path.clone().map(|p| p.display().to_string()))
+            });
+        } else {
+            self.dropped_file.0 += 1;
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if self.profile_chooser.is_some() {
+                self.profile_chooser_ui(ui);
+                return;
+            }
+
+            // Enable toggle
+            let mut enabled = self.enabled;
+            if toggle_ui(ui, &mut enabled, colors).changed() {
+                self.set_enabled(enabled);
+            }
+
+            // The big screen shows only the pads.
+            if self.config.big_screen.enabled {
+                if ui.button(tr("Exit Big Screen")).clicked() {
+                    self.config.big_screen.enabled = false;
+                }
+                self.bank_filter_ui(ui);
+                self.hearing_warning_ui(ui);
+                self.pads_ui(ui, colors, false);
+                return;
+            }
+
+            // Connect and disconnect from remote input server.
+            if !self.config.integrations.remote_input {
+                // Hidden while the integration is disabled.
+            } else {
+                let state = self.client_manager.state();
+                let (text, color, hover) = match &state {
+                    RemoteInputState::Disconnected => (
+                        tr("Connect"),
+                        colors.stopped,
+                        "Not connected to the remote input server. Click to connect.".to_string(),
+                    ),
+                    RemoteInputState::Connecting => (
+                        tr("Connecting..."),
+                        colors.paused,
+                        format!("Connecting to {}.", self.config.server_address),
+                    ),
+                    RemoteInputState::Authenticated => (
+                        tr("Connected"),
+                        colors.playing,
+                        "Connected and the API key sent. No events have been received yet."
+                            .to_string(),
+                    ),
+                    RemoteInputState::Receiving { last_event } => (
+                        tr("Receiving"),
+                        colors.playing,
+                        format!(
+                            "Receiving events. The last one was {}s ago.",
+                            last_event.elapsed().as_secs()
+                        ),
+                    ),
+                    RemoteInputState::Error { reason, status } => {
+                        (tr("Error"), colors.stopped, format!("{reason}\n{status}."))
+                    }
+                };
+                let hover = if state == RemoteInputState::Disconnected {
+                    hover
+                } else {
+                    format!("{hover}\nClick to disconnect.")
+                };
+                if ui
+                    .add(Button::new(RichText::new(text).color(Color32::BLACK)).fill(color))
+                    .on_hover_text(hover)
+                    .clicked()
+                {
+                    if state == RemoteInputState::Disconnected {
+                        self.client_manager.connect(
+                            self.config.server_address.clone(),
+                            self.config.api_key.clone(),
+                            self.config.remote_devices.clone(),
+                        );
+                    } else {
+                        self.client_manager.disconnect();
+                    }
+                }
+            }
+
+            // Rehearsal switch
+            let mut rehearsal = self.rehearsal;
+            if ui
+                .add_enabled(
+                    self.rehearsal || self.monitor_enabled(),
+                    egui::SelectableLabel::new(rehearsal, tr("Rehearsal")),
+                )
+                .on_hover_text(tr("Play sounds started from now on only on the monitor output"))
+                .on_disabled_hover_text("Select a monitor output in the settings menu to rehearse")
+                .clicked()
+            {
+                rehearsal ^= true;
+                self.set_rehearsal(rehearsal);
+            }
+
+            // Talk-over switch
+            if ui
+                .add(egui::SelectableLabel::new(
+                    self.ducker.talking_over(),
+                    "Talk-Over",
+                ))
+                .on_hover_text(format!(
+                    "Lower every sound by {:.1} dB. Hold the Talk-Over shortcut to do this only \
+                    while its key is held.",
+                    self.config.ducking.talk_over_db
+                ))
+                .clicked()
+            {
+                self.run_shortcut(ShortcutAction::TalkOver);
+            }
+
+            // Settings window
+            if !self.config.locked && ui.button(tr("Settings")).clicked() {
+                self.settings_window = true;
+            }
+
+            // Manual window
+            if ui.button(tr("Help / Manual")).clicked() {
+                self.manual_window = true;
+            }
+
+            // Log window
+            if ui.button(tr("Log")).clicked() {
+                self.log_window = true;
+            }
+
+            // Missed triggers window
+            if !self.missed_triggers.is_empty()
+                && ui
+                    .button(format!("Missed ({})", self.missed_triggers.len()))
+                    .on_hover_text(tr("Triggers that did not play their sound"))
+                    .clicked()
+            {
+                self.missed_window = true;
+            }
+
+            // Show window
+            if ui.button(tr("Show")).clicked() {
+                self.show_window = true;
+            }
+
+            // Archive window
+            if !self.config.locked && ui.button(tr("Archive")).clicked() {
+                self.archive_window = true;
+            }
+
+            // Sound packs window
+            if !self.config.locked
+                && ui
+                .button(tr("Packs"))
+                .on_hover_text(tr("Export sounds with their files to share them, or import a pack"))
+                .clicked()
+            {
+                self.packs_window = true;
+            }
+
+            // Sync window
+            if !self.config.locked
+                && ui
+                .button(tr("Sync"))
+                .on_hover_text(tr("Compare the sounds with another soundboard"))
+                .clicked()
+            {
+                self.sync_window = true;
+            }
+
+            // Statistics window
+            if ui.button(tr("Statistics")).clicked() {
+                self.stats_window = true;
+                self.stats_summary = None;
+            }
+
+            // Update notification
+            match self.updater.state() {
+                UpdateState::Available(release) => {
+                    ui.hyperlink_to(
+                        RichText::new(format!("Version {} is available", release.version))
+                            .color(ui.visuals().warn_fg_color),
+                        release.url,
+                    );
+                }
+                UpdateState::Staged(release) => {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("Restart to use version {}", release.version),
+                    );
+                }
+                _ => {}
+            }
+
+            self.bank_filter_ui(ui);
+            self.load_error_ui(ui);
+            self.hearing_warning_ui(ui);
+            self.choke_queue_ui(ui);
+
+            // Profile selection
+            let mut selected_profile = None;
+            egui::ComboBox::from_label("Profile")
+                .selected_text(self.config_saver.profile())
+                .show_ui(ui, |ui| {
+                    for profile in &self.profiles {
+                        if ui
+                            .selectable_label(profile == self.config_saver.profile(), profile)
+                            .clicked()
+                        {
+                            selected_profile = Some(profile.clone());
+                        }
+                    }
+                });
+            if let Some(profile) = selected_profile {
+                self.switch_profile(&profile);
+            }
+
+            // Volume slider
+            if ui
+                .add(
+                    volume_slider(&mut self.config.volume_db, Self::VOLUME_RANGE, scale)
+                        .text("Global Volume"),
+                )
+                .changed()
+            {
+                self.update_sound_volumes();
+            }
+
+            let mut volumes_changed = false;
+            let mut gates_changed = false;
+            egui::Grid::new("outputs").num_columns(4).show(ui, |ui| {
+                // Output groups
+                for group in self.config.group_names() {
+                    let all_muted = self.group_devices(&group).all(OutputDevice::muted);
+                    let group_config = self.config.output_groups.entry(group.clone()).or_default();
+                    if !group_config.enabled {
+                        ui.colored_label(colors.stopped, "Disabled");
+                    } else if all_muted {
+                        ui.colored_label(colors.stopped, tr("Muted"));
+                    } else {
+                        ui.colored_label(colors.playing, "Playing");
+                    }
+                    gates_changed |= ui
+                        .checkbox(&mut group_config.enabled, format!("Group: {group}"))
+                        .on_hover_text(tr("Disabled groups play nothing"))
+                        .changed();
+                    volumes_changed |= ui
+                        .add(
+                            volume_slider(&mut group_config.volume_db, Self::VOLUME_RANGE, scale)
+                                .text("Volume"),
+                        )
+                        .on_hover_text(tr("Added to the volume of each output in the group"))
+                        .changed();
+                    if ui
+                        .button(if all_muted { "Unmute" } else { "Mute" })
+                        .clicked()
+                    {
+                        self.toggle_group_muted(&group);
+                    }
+                    ui.end_row();
+                }
+
+                // Selected output devices
+                for (name, device) in &self.output_devices {
+                    if let Some(output_config) = self.config.outputs.get_mut(name) {
+                        // Status
+                        if device.muted() {
+                            ui.colored_label(colors.stopped, tr("Muted"));
+                        } else {
+                            ui.colored_label(colors.playing, "Playing");
+                        }
+                        ui.label(name);
+
+                        // Volume slider
+                        if ui
+                            .add(
+                                volume_slider(
+                                    &mut output_config.volume_db,
+                                    Self::VOLUME_RANGE,
+                                    scale,
+                                )
+                                .text("Volume"),
+                            )
+                            .changed()
+                        {
+                            volumes_changed = true;
+                        }
+
+                        // Maximum gain warning
+                        if self
+                            .limited_outputs
+                            .get(name)
+                            .is_some_and(|time| time.elapsed() < Self::LIMITED_DISPLAY)
+                        {
+                            ui.colored_label(ui.visuals().warn_fg_color, "Limited")
+                                .on_hover_text(format!(
+                                    "The maximum gain of {:.1} dB lowered the volume.",
+                                    output_config.max_gain_db.unwrap_or_default()
+                                ));
+                        }
+
+                        // Clipping indicator, flashing unless motion is reduced
+                        if let Some(time) = self
+                            .clipped_outputs
+                            .get(name)
+                            .filter(|time| time.elapsed() < Self::CLIPPED_DISPLAY)
+                        {
+                            let mut color = ui.visuals().error_fg_color;
+                            if !reduced_motion && time.elapsed().as_millis() / 250 % 2 == 1 {
+                                color = color.gamma_multiply(0.4);
+                            }
+                            ui.colored_label(color, "Clipping").on_hover_text(
+                                tr("The mix went above 0 dBFS. Lower the volumes or enable \
+                                    the limiter of this output in the settings."),
+                            );
+                        }
+                        ui.end_row();
+                    }
+                }
+
+                // Microphone passthrough
+                if let Some(microphone) = &self.microphone {
+                    if microphone.muted() {
+                        ui.colored_label(colors.stopped, tr("Muted"));
+                    } else {
+                        ui.colored_label(colors.playing, "Live");
+                    }
+                    ui.label(format!("Microphone: {}", microphone.name()));
+
+                    if ui
+                        .add(
+                            volume_slider(
+                                &mut self.config.microphone.volume_db,
+                                Self::SOUND_VOLUME_RANGE,
+                                scale,
+                            )
+                            .text("Volume"),
+                        )
+                        .changed()
+                    {
+                        microphone.set_volume_db(self.config.microphone.volume_db);
+                    }
+                    ui.end_row();
+                }
+            });
+            if volumes_changed {
+                self.update_output_volumes();
+            }
+            if gates_changed {
+                self.update_output_gates();
+            }
+
+            // Pad view and lock
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.config.pad_view, false, tr("Table"))
+                    .on_hover_text(tr("Show the sounds with all of their settings"));
+                ui.selectable_value(&mut self.config.pad_view, true, tr("Pads"))
+                    .on_hover_text(
+                        tr("Show the sounds as large buttons for a touchscreen. Long-press a pad to edit its sound."),
+                    );
+                ui.separator();
+                ui.toggle_value(&mut self.config.locked, format!("\u{1F512} {}", tr("Locked")))
+                    .on_hover_text(
+                        tr("Hide everything that changes the sounds, key binds and settings, leaving their play state and volumes"),
+                    );
+            });
+            if self.config.pad_view {
+                self.pads_ui(ui, colors, true);
+                return;
+            }
+            if self.config.locked {
+                self.locked_sounds_ui(ui, colors);
+                return;
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("sounds")
+                    .num_columns(15)
+                    .min_col_width(0.0)
+                    .show(ui, |ui| {
+                        // The sounds another sound can start with.
+                        let names: Vec<String> = self
+                            .config
+                            .sounds
+                            .iter()
+                            .filter(|sound| !sound.hidden())
+                            .map(|sound| sound.name.clone())
+                            .collect();
+                        let groups = self.config.group_names();
+                        let choke_groups = self.config.choke_group_names();
+                        let random_groups = self.config.random_group_names();
+                        let sequences = self.config.sequence_names();
+
+                        // New Sound
+                        ui.label("");
+                        ui.add(
+                            TextEdit::singleline(&mut self.new_sound.name)
+                                .min_size([100.0, 10.0].into()),
+                        );
+                        ui.add(
+                            TextEdit::singleline(&mut self.new_sound.bank)
+                                .hint_text("Bank")
+                                .desired_width(60.0),
+                        );
+                        self.new_sound.key.update(ui, last_key_released);
+                        ui.add(volume_slider(
+                            &mut self.new_sound.volume_db,
+                            Self::SOUND_VOLUME_RANGE,
+                            scale,
+                        ));
+                        waveform_ui(
+                            ui,
+                            self.waveforms.get(&self.new_sound.path),
+                            &self.new_sound,
+                        );
+                        let waveform = self.waveforms.get(&self.new_sound.path);
+                        trim_ui(ui, &mut self.new_sound, waveform);
+                        normalize_ui(ui, &mut self.new_sound, waveform, &self.config.loudness);
+                        playback_options_ui(
+                            ui,
+                            &mut self.new_sound,
+                            &names,
+                            &groups,
+                            &choke_groups,
+                            &random_groups,
+                            &sequences,
+                        );
+
+                        if ui
+                            .add(
+                                TextEdit::singleline(&mut self.new_sound.path)
+                                    .min_size([300.0, 10.0].into()),
+                            )
+                            .hovered()
+                        {
+                            if let Some(path) = self.dropped_file.1.take() {
+                                self.new_sound.path = path;
+                            }
+                        }
+                        if browse_button(ui, &mut self.new_sound.path)
+                            && self.new_sound.name.is_empty()
+                        {
+                            // Name the sound after its file.
+                            if let Some(stem) = std::path::Path::new(&self.new_sound.path)
+                                .file_stem()
+                                .and_then(|stem| stem.to_str())
+                            {
+                                self.new_sound.name = stem.to_string();
+                            }
+                        }
+
+                        if ui.button("Add").clicked() {
+                            if self.config.import_to_library {
+                                self.importer.import(&self.new_sound.path);
+                                self.importer.import(&self.new_sound.loop_path);
+                            }
+                            self.playback.insert(0, SoundPlayback::default());
+                            self.config.sounds.insert(0, self.new_sound.clone());
+                            self.selected_sound = self.selected_sound.map(|i| i + 1);
+                            self.new_sound = SoundConfig::default();
+                        }
+                        ui.end_row();
+
+                        // Other Sounds
+                        let mut action = (0, 0, 0); // ((none, remove, move, archive), index a, index b)
+                        let mut preview_action = None; // (toggle or stop, index)
+                        let monitor_enabled = self.monitor_enabled();
+                        let previewing = self
+                            .preview
+                            .as_ref()
+                            .filter(|preview| preview.active())
+                            .map(|preview| (preview.sound, preview.processed));
+                        let visible = self.visible_sounds();
+
+                        for (position, &i) in visible.iter().enumerate() {
+                            let sound = &mut self.config.sounds[i];
+
+                            // Playing
+                            let playback = &self.playback[i];
+                            ui.horizontal(|ui| {
+                                let selected = self.selected_sound == Some(i);
+                                let state = state_ui(ui, playback, colors, selected, &sound.name);
+                                if state.clicked() {
+                                    self.selected_sound = Some(i);
+                                }
+                                if selected && std::mem::take(&mut self.scroll_to_selected) {
+                                    state.scroll_to_me(None);
+                                }
+                                if sound.retrigger == Retrigger::Overlap && !playback.idle() {
+                                    let duration = match self.waveforms.get(&sound.path) {
+                                        Some(WaveformState::Loaded(waveform)) => {
+                                            Some(waveform.duration)
+                                        }
+                                        _ => None,
+                                    };
+                                    instances_ui(ui, playback, duration);
+                                }
+                                if playback.queued() > 0 {
+                                    ui.label(format!("+{}", playback.queued()))
+                                        .on_hover_text(tr("Plays queued after this one"));
+                                }
+                                if playback.waiting().is_some() {
+                                    ui.label("\u{23F3}").on_hover_text(format!(
+                                        "Queued behind the other sounds of choke group {}",
+                                        sound.choke_group
+                                    ));
+                                }
+                                cooldown_ui(ui, playback, sound);
+                            });
+
+                            // Name
+                            let name = ui.add(
+                                TextEdit::singleline(&mut sound.name)
+                                    .min_size([100.0, 10.0].into()),
+                            );
+                            describe(&name, WidgetType::TextEdit, || {
+                                format!("Name: {}", sound.name)
+                            });
+                            if !sound.fragment.is_empty() {
+                                name.on_hover_text(format!(
+                                    "From the configuration fragment {}. Changes are not saved.",
+                                    sound.fragment
+                                ));
+                            }
+
+                            // Bank
+                            let bank = ui.add(
+                                TextEdit::singleline(&mut sound.bank)
+                                    .hint_text("Bank")
+                                    .desired_width(60.0),
+                            );
+                            describe(&bank, WidgetType::TextEdit, || {
+                                format!("Bank of {}: {}", sound.name, sound.bank)
+                            });
+
+                            // Key
+                            let key = sound.key.update(ui, last_key_released);
+                            describe(&key, WidgetType::Button, || {
+                                format!("Key bind of {}: {}", sound.name, sound.key.label())
+                            });
+
+                            // Volume
+                            let volume = ui.add(volume_slider(
+                                &mut sound.volume_db,
+                                Self::SOUND_VOLUME_RANGE,
+                                scale,
+                            ));
+                            describe(&volume, WidgetType::Slider, || {
+                                format!("Volume of {}: {:.1} dB", sound.name, sound.volume_db)
+                            });
+                            if volume.changed() {
+                                let normalization = normalization_db(
+                                    self.waveforms.get(&sound.path),
+                                    sound,
+                                    &self.config.loudness,
+                                );
+                                self.playback[i].set_volume_db(
+                                    self.config.volume_db + sound.volume_db + normalization,
+                                );
+                            }
+
+                            // Waveform
+                            waveform_ui(ui, self.waveforms.get(&sound.path), sound);
+
+                            // Trim
+                            trim_ui(ui, sound, self.waveforms.get(&sound.path));
+
+                            // Normalization and A/B preview
+                            ui.horizontal(|ui| {
+                                let waveform = self.waveforms.get(&sound.path);
+                                if normalize_ui(ui, sound, waveform, &self.config.loudness) {
+                                    let normalization = normalization_db(
+                                        self.waveforms.get(&sound.path),
+                                        sound,
+                                        &self.config.loudness,
+                                    );
+                                    self.playback[i].set_volume_db(
+                                        self.config.volume_db + sound.volume_db + normalization,
+                                    );
+                                }
+                                let previewing = previewing
+                                    .filter(|(j, _)| *j == i)
+                                    .map(|(_, processed)| processed);
+                                if let Some(toggle) = preview_ui(ui, previewing, monitor_enabled) {
+                                    preview_action = Some((toggle, i));
+                                }
+                            });
+
+                            // Speed, retrigger and the sounds it starts with
+                            playback_options_ui(
+                                ui,
+                                sound,
+                                &names,
+                                &groups,
+                                &choke_groups,
+                                &random_groups,
+                                &sequences,
+                            );
+
+                            // Path
+                            let path = ui.add(
+                                TextEdit::singleline(&mut sound.path)
+                                    .min_size([300.0, 10.0].into()),
+                            );
+                            describe(&path, WidgetType::TextEdit, || {
+                                format!("File of {}: {}", sound.name, sound.path)
+                            });
+                            if path.hovered() {
+                                if let Some(path) = self.dropped_file.1.take() {
+                                    sound.path = path;
+                                }
+                            }
+                            browse_button(ui, &mut sound.path);
+
+                            // Archive Sound
+                            if ui.button("Archive").clicked() {
+                                action = (3, i, 0);
+                            }
+
+                            // Remove Sound
+                            if ui.button("Remove").clicked() {
+                                action = (1, i, 0);
+                            }
+
+                            // Move Sound past the neighbouring visible sound.
+                            if position > 0 {
+                                let up = ui.button("^").on_hover_text(tr("Move Up"));
+                                describe(&up, WidgetType::Button, || {
+                                    format!("Move {} up", sound.name)
+                                });
+                                if up.clicked() {
+                                    action = (2, i, visible[position - 1]);
+                                }
+                            }
+                            if position < visible.len() - 1 {
+                                let down = ui.button("v").on_hover_text(tr("Move Down"));
+                                describe(&down, WidgetType::Button, || {
+                                    format!("Move {} down", sound.name)
+                                });
+                                if down.clicked() {
+                                    action = (2, i, visible[position + 1]);
+                                }
+                            }
+
+                            ui.end_row();
+                        }
+
+                        // Offer the demo sounds to new users.
+                        if self.config.sounds.is_empty() {
+                            ui.label("");
+                            ui.label(tr("No sounds yet."));
+                            ui.horizontal(|ui| {
+                                if ui.button(tr("Add Demo Sounds")).clicked() {
+                                    self.add_demo_sounds();
+                                }
+                                if ui
+                                    .button("Import From Another App...")
+                                    .on_hover_text(
+                                        tr("Add the sounds of Soundpad, EXP Soundboard or Resanance"),
+                                    )
+                                    .clicked()
+                                {
+                                    self.open_foreign_import();
+                                }
+                            });
+                            ui.end_row();
+                        }
+
+                        match preview_action {
+                            Some((true, i)) => self.toggle_preview(i),
+                            Some((false, _)) => self.stop_preview(),
+                            None => (),
+                        }
+
+                        // Remove, re-order or archive a sound.
+                        if action.0 != 0 {
+                            // The previewed sound may move.
+                            self.stop_preview();
+                        }
+                        if action.0 == 1 {
+                            drop(self.config.sounds.remove(action.1));
+                            self.playback.remove(action.1);
+                            self.selected_sound = match self.selected_sound {
+                                Some(j) if j == action.1 => None,
+                                Some(j) if j > action.1 => Some(j - 1),
+                                selected => selected,
+                            };
+                        } else if action.0 == 2 {
+                            self.config.sounds.swap(action.1, action.2);
+                            self.playback.swap(action.1, action.2);
+                            self.selected_sound = match self.selected_sound {
+                                Some(j) if j == action.1 => Some(action.2),
+                                Some(j) if j == action.2 => Some(action.1),
+                                selected => selected,
+                            };
+                        } else if action.0 == 3 {
+                            self.config.sounds[action.1].archived = true;
+                            self.playback[action.1].stop(None);
+                        }
+                    });
+            });
+        });
+
+        // Locking closes every window that changes the sounds or settings.
+        if self.config.locked {
+            self.settings_window = false;
+            self.archive_window = false;
+            self.packs_window = false;
+            self.sync_window = false;
+            self.foreign_import = None;
+            self.pad_editor = None;
+        }
+
+        let mut settings_window = self.settings_window;
+        egui::Window::new(tr("Settings"))
+            .open(&mut settings_window)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                // Audio settings
+                ui.heading(tr("Audio"));
+                ui.horizontal(|ui| {
+                    ui.label("Volume Scale");
+                    let scale = &mut self.config.volume_scale;
+                    ui.radio_value(scale, VolumeScale::Decibels, "Decibels");
+                    ui.radio_value(scale, VolumeScale::Linear, "Linear")
+                        .on_hover_text(tr("Show volumes as a percentage of the amplitude"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Audio Host");
+                    let selected = match self.config.audio_host.as_str() {
+                        "" => format!("Default ({})", cpal::default_host().id().name()),
+                        host => host.to_string(),
+                    };
+                    let mut changed = false;
+                    let chosen = &mut self.config.audio_host;
+                    egui::ComboBox::from_id_source("audio_host")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            changed |= ui
+                                .selectable_value(chosen, String::new(), "Default")
+                                .changed();
+                            for host in audio_host_names() {
+                                changed |= ui
+                                    .selectable_value(chosen, host.to_string(), host)
+                                    .changed();
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr(
+                            "The audio server devices are listed and opened on, e.g. JACK on a \
+                            pro-audio Linux setup",
+                        ));
+                    if changed {
+                        self.apply_audio_host();
+                        self.update_input_devices();
+                        self.update_output_devices();
+                    }
+                });
+                let mut groups_changed = false;
+                egui::Grid::new("output_settings").show(ui, |ui| {
+                    if ui.button("Reload Devices").clicked() {
+                        self.update_input_devices();
+                        self.update_output_devices();
+                    }
+                    ui.end_row();
+
+                    ui.label("Device Name");
+                    ui.label("Mute Keybind");
+                    ui.label("Delay");
+                    ui.label("Max Gain");
+                    ui.label("Limiter");
+                    ui.label("Balance");
+                    ui.label("Channels");
+                    ui.label("Stream");
+                    ui.label("Group");
+                    ui.label("Channel Test");
+                    ui.end_row();
+
+                    for (name, device) in self.output_devices.iter_mut() {
+                        let mut checked = device.enabled();
+
+                        // Enabled checkbox
+                        let mut response = ui.checkbox(&mut checked, name);
+                        if name == SYSTEM_DEFAULT_OUTPUT {
+                            response = response.on_hover_text(format!(
+                                "Plays on the default output device of the system, now {}",
+                                device.name()
+                            ));
+                        }
+
+                        if let Some(output_config) = self.config.outputs.get_mut(name) {
+                            // Mute key bind button
+                            output_config.mute.update(ui, last_key_released);
+
+                            // Latency compensation
+                            if ui
+                                .add(
+                                    egui::DragValue::new(&mut output_config.delay_ms)
+                                        .clamp_range(0..=MAX_OUTPUT_DELAY_MS)
+                                        .suffix(" ms"),
+                                )
+                                .changed()
+                            {
+                                device.set_delay(Duration::from_millis(output_config.delay_ms));
+                            }
+
+                            // Maximum gain
+                            ui.horizontal(|ui| {
+                                let mut limited = output_config.max_gain_db.is_some();
+                                let mut max_gain = output_config.max_gain_db.unwrap_or(0.0);
+                                let checkbox = ui
+                                    .checkbox(&mut limited, "")
+                                    .on_hover_text(tr("Limit the gain after all volume controls"));
+                                let value = ui.add_enabled(
+                                    limited,
+                                    egui::DragValue::new(&mut max_gain)
+                                        .clamp_range(Self::MAX_GAIN_RANGE)
+                                        .speed(0.1)
+                                        .suffix(" dB"),
+                                );
+                                if checkbox.changed() || value.changed() {
+                                    output_config.max_gain_db = limited.then_some(max_gain);
+                                    device.set_max_gain(output_config.max_gain_db);
+                                }
+                            });
+
+                            // Limiter
+                            ui.horizontal(|ui| {
+                                let mut limited = output_config.limiter_db.is_some();
+                                let mut ceiling = output_config
+                                    .limiter_db
+                                    .unwrap_or(Self::DEFAULT_LIMITER_CEILING);
+                                let checkbox = ui.checkbox(&mut limited, "").on_hover_text(tr(
+                                    "Keep the mix of every sound on this output below a ceiling",
+                                ));
+                                let value = ui.add_enabled(
+                                    limited,
+                                    egui::DragValue::new(&mut ceiling)
+                                        .clamp_range(Self::LIMITER_CEILING_RANGE)
+                                        .speed(0.1)
+                                        .suffix(" dBFS"),
+                                );
+                                if checkbox.changed() || value.changed() {
+                                    output_config.limiter_db = limited.then_some(ceiling);
+                                    device.set_limiter(output_config.limiter_db);
+                                }
+                            });
+
+                            // Balance
+                            if pan_ui(ui, &mut output_config.pan).changed() {
+                                device.set_pan(output_config.pan);
+                            }
+
+                            // Channel fixes
+                            channel_fixes_ui(ui, output_config, device);
+
+                            // Sample rate and buffer size
+                            let channels = device.channels();
+                            if stream_format_ui(ui, output_config, channels) {
+                                // Reopened even if it failed to open, since the new format may
+                                // work.
+                                device.disable();
+                                device.set_stream_format(
+                                    output_config.sample_rate,
+                                    output_config.buffer_frames,
+                                );
+                                device.set_channel_map(&output_config.channel_map);
+                                device.enable();
+                                if let Some(microphone) = &self.microphone {
+                                    device.play_microphone(microphone);
+                                }
+                            }
+
+                            // Group
+                            groups_changed |= ui
+                                .add(
+                                    TextEdit::singleline(&mut output_config.group)
+                                        .hint_text("None")
+                                        .desired_width(60.0),
+                                )
+                                .on_hover_text(tr("Outputs in the same group share its controls"))
+                                .changed();
+
+                            // Channel test
+                            let progress = match &self.channel_test {
+                                Some((test_name, progress)) if test_name == name => {
+                                    progress.channel().map(|channel| (channel, progress))
+                                }
+                                _ => None,
+                            };
+                            match progress {
+                                Some((channel, progress)) => {
+                                    ui.horizontal(|ui| {
+                                        if ui.button("Stop").clicked() {
+                                            progress.stop();
+                                        }
+                                        ui.label(channel_name(channel, progress.channels()));
+                                    });
+                                }
+                                None => {
+                                    if ui
+                                        .button("Test")
+                                        .on_hover_text(tr("Play a sweep and beeps on each channel"))
+                                        .clicked()
+                                    {
+                                        if let Some((_, progress)) = &self.channel_test {
+                                            progress.stop();
+                                        }
+                                        self.channel_test = device
+                                            .play_channel_test()
+                                            .map(|progress| (name.clone(), progress));
+                                    }
+                                }
+                            }
+                        }
+
+                        // Add and remove device.
+                        if response.changed() {
+                            if checked {
+                                assert!(
+                                    !self.config.outputs.contains_key(name),
+                                    "a device in self.config.outputs exists when it should not"
+                                );
+                                self.config
+                                    .outputs
+                                    .insert(name.clone(), OutputConfig::default());
+                                device.set_delay(Duration::ZERO);
+                                device.set_max_gain(None);
+                                device.set_limiter(None);
+                                device.set_pan(0.0);
+                                device.set_mono(false);
+                                device.set_swap_channels(false);
+                                device.set_inverted(false, false);
+                                device.set_stream_format(0, 0);
+                                device.set_channel_map(&[]);
+                                device.enable();
+                                if let Some(microphone) = &self.microphone {
+                                    device.play_microphone(microphone);
+                                }
+                            } else {
+                                self.config.outputs.remove(name);
+                                device.disable();
+                            }
+                            groups_changed = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+                if groups_changed {
+                    self.update_output_volumes();
+                    self.update_output_gates();
+                }
+
+                // Output group settings
+                let groups = self.config.group_names();
+                if !groups.is_empty() {
+                    ui.heading(tr("Output Groups"));
+                    egui::Grid::new("group_settings").show(ui, |ui| {
+                        ui.label("Group");
+                        ui.label("Mute Keybind");
+                        ui.end_row();
+                        for group in groups {
+                            ui.label(&group);
+                            self.config
+                                .output_groups
+                                .entry(group)
+                                .or_default()
+                                .mute
+                                .update(ui, last_key_released);
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                // Hearing protection settings
+                ui.heading(tr("Hearing Protection"));
+                egui::Grid::new("hearing_settings").show(ui, |ui| {
+                    ui.label("Monitor Output");
+                    let previous_monitor = self.config.monitor.clone();
+                    egui::ComboBox::from_id_source("monitor_device")
+                        .selected_text(if self.config.monitor.is_empty() {
+                            "None"
+                        } else {
+                            &self.config.monitor
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.monitor, String::new(), "None");
+                            for name in self.config.outputs.keys() {
+                                ui.selectable_value(&mut self.config.monitor, name.clone(), name);
+                            }
+                        });
+                    if let Some(level) = self.hearing_protection.level {
+                        ui.label(format!("{level:.1} dBFS"));
+                    }
+                    ui.end_row();
+
+                    let config = &mut self.config.hearing_protection;
+                    ui.checkbox(&mut config.enabled, "Warn Above");
+                    ui.add(
+                        egui::DragValue::new(&mut config.threshold_db)
+                            .clamp_range(-60.0..=0.0)
+                            .speed(0.1)
+                            .suffix(" dBFS"),
+                    );
+                    ui.label("For");
+                    ui.add(
+                        egui::DragValue::new(&mut config.duration_s)
+                            .clamp_range(1..=3600)
+                            .suffix(" s"),
+                    );
+                    ui.end_row();
+
+                    ui.checkbox(&mut config.attenuate, "Lower By");
+                    ui.add(
+                        egui::DragValue::new(&mut config.attenuation_db)
+                            .clamp_range(0.0..=50.0)
+                            .speed(0.1)
+                            .suffix(" dB"),
+                    );
+                    ui.end_row();
+                    if self.config.monitor != previous_monitor {
+                        self.reset_hearing_protection();
+                        self.update_output_gates();
+                    }
+                });
+
+                // Ducking settings
+                ui.heading(tr("Ducking"));
+                ui.horizontal(|ui| {
+                    let config = &mut self.config.ducking;
+                    ui.label("Lower By");
+                    ui.add(
+                        egui::DragValue::new(&mut config.depth_db)
+                            .clamp_range(0.0..=60.0)
+                            .speed(0.1)
+                            .suffix(" dB"),
+                    )
+                    .on_hover_text(tr(
+                        "How far the other sounds are lowered while a priority sound plays",
+                    ));
+                    ui.label("Attack");
+                    ui.add(
+                        egui::DragValue::new(&mut config.attack_ms)
+                            .clamp_range(0..=5000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(tr("How long lowering the other sounds takes"));
+                    ui.label("Release");
+                    ui.add(
+                        egui::DragValue::new(&mut config.release_ms)
+                            .clamp_range(0..=10000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(tr(
+                        "How long raising them back takes after the priority sound ends",
+                    ));
+                    ui.label("Talk-Over");
+                    ui.add(
+                        egui::DragValue::new(&mut config.talk_over_db)
+                            .clamp_range(0.0..=60.0)
+                            .speed(0.1)
+                            .suffix(" dB"),
+                    )
+                    .on_hover_text(tr(
+                        "How far every sound is lowered while the Talk-Over shortcut is held",
+                    ));
+                });
+
+                // Background settings
+                ui.heading(tr("Background"));
+                ui.horizontal(|ui| {
+                    ui.label("Crossfade");
+                    ui.add(
+                        egui::DragValue::new(&mut self.config.background.crossfade_ms)
+                            .clamp_range(0..=30000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(tr(
+                        "How long starting a background sound fades from the one playing",
+                    ));
+                });
+
+                // Choke group settings
+                let choke_groups = self.config.choke_group_names();
+                if !choke_groups.is_empty() {
+                    ui.heading(tr("Choke Groups"));
+                    egui::Grid::new("choke_group_settings").show(ui, |ui| {
+                        ui.label("Group");
+                        ui.label("Starting a Sound While Another Plays");
+                        ui.end_row();
+                        for group in choke_groups {
+                            ui.label(&group);
+                            let config = self.config.choke_groups.entry(group).or_default();
+                            ui.horizontal(|ui| {
+                                for policy in ChokePolicy::ALL {
+                                    ui.radio_value(&mut config.policy, policy, policy.label())
+                                        .on_hover_text(policy.description());
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                // Random group settings
+                let random_groups = self.config.random_group_names();
+                if !random_groups.is_empty() {
+                    ui.heading(tr("Random Groups"));
+                    egui::Grid::new("random_group_settings").show(ui, |ui| {
+                        for group in random_groups {
+                            ui.label(&group);
+                            let config = self.config.random_groups.entry(group).or_default();
+                            ui.checkbox(&mut config.no_repeat, "No Repeat")
+                                .on_hover_text(tr("Never play the same sound twice in a row"));
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                // Sequence settings
+                let sequences = self.config.sequence_names();
+                if !sequences.is_empty() {
+                    ui.heading(tr("Sequences"));
+                    egui::Grid::new("sequence_settings").show(ui, |ui| {
+                        ui.label("Sequence");
+                        ui.label("Next Sound");
+                        ui.end_row();
+                        for sequence in sequences {
+                            ui.label(&sequence);
+                            let config = self.config.sequences.entry(sequence).or_default();
+                            ui.horizontal(|ui| {
+                                for advance in SequenceAdvance::ALL {
+                                    ui.radio_value(&mut config.advance, advance, advance.label())
+                                        .on_hover_text(advance.description());
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                }
+
+                // Loudness settings
+                ui.heading(tr("Loudness"));
+                ui.horizontal(|ui| {
+                    let mut changed = ui
+                        .checkbox(&mut self.config.loudness.enabled, "Normalize Loudness")
+                        .on_hover_text(tr(
+                            "Change the volume of every sound so they all have the same \
+                            integrated loudness (EBU R128)",
+                        ))
+                        .changed();
+                    ui.label("Target");
+                    changed |= ui
+                        .add_enabled(
+                            self.config.loudness.enabled,
+                            egui::DragValue::new(&mut self.config.loudness.target_lufs)
+                                .clamp_range(Self::LOUDNESS_TARGET_RANGE)
+                                .speed(0.1)
+                                .suffix(" LUFS"),
+                        )
+                        .on_hover_text(tr("The loudness every sound is moved to"))
+                        .changed();
+                    if changed {
+                        self.update_sound_volumes();
+                    }
+                });
+
+                // Microphone settings
+                ui.heading(tr("Microphone"));
+                egui::Grid::new("microphone_settings").show(ui, |ui| {
+                    ui.label("Input Device");
+                    let previous_device = self.config.microphone.device.clone();
+                    egui::ComboBox::from_id_source("microphone_device")
+                        .selected_text(if self.config.microphone.device.is_empty() {
+                            "None"
+                        } else {
+                            &self.config.microphone.device
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut self.config.microphone.device,
+                                String::new(),
+                                "None",
+                            );
+                            for name in &self.input_devices {
+                                ui.selectable_value(
+                                    &mut self.config.microphone.device,
+                                    name.clone(),
+                                    name,
+                                );
+                            }
+                        });
+                    if self.config.microphone.device != previous_device {
+                        self.update_microphone();
+                    }
+                    ui.end_row();
+
+                    ui.label("Mute Keybind");
+                    self.config.microphone.mute.update(ui, last_key_released);
+                    ui.end_row();
+                });
+
+                // Gamepad settings
+                ui.heading(tr("Gamepads"));
+                egui::Grid::new("gamepad_settings").show(ui, |ui| {
+                    if ui
+                        .checkbox(&mut self.config.gamepads, "Enabled")
+                        .on_hover_text(tr("Read the gamepads connected to this computer"))
+                        .changed()
+                    {
+                        self.update_gamepads();
+                    }
+                    ui.end_row();
+                    if let Some(gamepad_input) = &self.gamepad_input {
+                        let names = gamepad_input.names();
+                        ui.label("Connected");
+                        ui.label(if names.is_empty() {
+                            "None".to_string()
+                        } else {
+                            names.join(", ")
+                        });
+                        ui.end_row();
+                    }
+                });
+
+                // Remote input server settings
+                ui.heading(tr("Remote Input Server"));
+                egui::Grid::new("remote_settings").show(ui, |ui| {
+                    ui.label("Server Address");
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.server_address)
+                            .min_size(Vec2::new(100.0, 10.0)),
+                    );
+                    ui.end_row();
+                    ui.label("API Key");
+                    ui.add(
+                        TextEdit::singleline(&mut self.config.api_key)
+                            .min_size(Vec2::new(100.0, 10.0)),
+                    );
+                    ui.end_row();
+
+                    // Devices on the server to receive events from. Empty receives from all.
+                    let mut remove = None;
+                    for (i, device) in self.config.remote_devices.iter_mut().enumerate() {
+                        ui.label(if i == 0 { "Devices" } else { "" });
+                        ui.horizontal(|ui| {
+                            ui.add(TextEdit::singleline(device).min_size(Vec2::new(100.0, 10.0)));
+                            if ui.button("Remove").clicked() {
+                                remove = Some(i);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                    if let Some(i) = remove {
+                        self.config.remote_devices.remove(i);
+                    }
+                    ui.label(if self.config.remote_devices.is_empty() {
+                        "Devices"
+                    } else {
+                        ""
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            TextEdit::singleline(&mut self.new_remote_device)
+                                .hint_text("All devices")
+                                .min_size(Vec2::new(100.0, 10.0)),
+                        );
+                        if ui.button("Add").clicked() && !self.new_remote_device.is_empty() {
+                            let device = std::mem::take(&mut self.new_remote_device);
+                            self.config.remote_devices.push(device);
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr("Takes effect the next time the soundboard connects."));
+                    ui.end_row();
+                });
+
+                // HTTP API settings
+                ui.heading(tr("HTTP API"));
+                egui::Grid::new("http_settings").show(ui, |ui| {
+                    ui.label("Address");
+                    let response = ui
+                        .add(
+                            TextEdit::singleline(&mut self.config.http.address)
+                                .min_size(Vec2::new(100.0, 10.0)),
+                        )
+                        .on_hover_text(tr("127.0.0.1 only accepts requests from this computer. \
+                            0.0.0.0 accepts requests from the network."));
+                    // Restart on the new address once it has been typed.
+                    if response.lost_focus() {
+                        self.http_server.stop();
+                        self.apply_integrations();
+                    }
+                    ui.end_row();
+                    ui.label("Port");
+                    let response = ui.add(egui::DragValue::new(&mut self.config.http.port));
+                    // Restart on the new port once the value is no longer being dragged.
+                    if (response.changed() && !response.dragged()) || response.drag_released() {
+                        self.http_server.stop();
+                        self.apply_integrations();
+                    }
+                    ui.end_row();
+                    ui.label("Token");
+                    if ui
+                        .add(
+                            TextEdit::singleline(&mut self.config.http.token)
+                                .password(true)
+                                .min_size(Vec2::new(100.0, 10.0)),
+                        )
+                        .changed()
+                    {
+                        self.http_server.set_token(self.config.http.token.clone());
+                    }
+                    ui.end_row();
+                });
+
+                // Shortcuts
+                ui.heading(tr("Shortcuts"));
+                egui::Grid::new("shortcut_settings").show(ui, |ui| {
+                    for action in ShortcutAction::ALL {
+                        ui.label(action.label());
+                        self.config
+                            .shortcuts
+                            .binding_mut(action)
+                            .update(ui, last_key_released);
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let triggers = &mut self.config.triggers;
+                    ui.label("Double Tap");
+                    ui.add(
+                        egui::DragValue::new(&mut triggers.double_tap_ms)
+                            .clamp_range(50..=2000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(tr("The longest time between the two taps of a double tap"));
+                    ui.label("Long Press");
+                    ui.add(
+                        egui::DragValue::new(&mut triggers.long_press_ms)
+                            .clamp_range(100..=5000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(tr("The shortest time a key is held for a long press"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Stop Fade");
+                    ui.add(
+                        egui::DragValue::new(&mut self.config.stop_fade_ms)
+                            .clamp_range(0..=5000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text(tr(
+                        "How long the Stop shortcut fades the sounds out instead of cutting them",
+                    ));
+                });
+
+                // Profiles
+                ui.heading(tr("Profiles"));
+                egui::Grid::new("profile_settings").show(ui, |ui| {
+                    ui.label("New Profile");
+                    ui.add(
+                        TextEdit::singleline(&mut self.new_profile)
+                            .min_size(Vec2::new(100.0, 10.0)),
+                    );
+                    if ui.button("Create").clicked() {
+                        match create_profile(&self.new_profile) {
+                            Err(error) => log!(Error, "[Soundboard] {error}"),
+                            Ok(()) => {
+                                if let Ok(profiles) = list_profiles() {
+                                    self.profiles = profiles;
+                                }
+                                let profile = std::mem::take(&mut self.new_profile);
+                                self.switch_profile(&profile);
+                            }
+                        }
+                    }
+                    ui.end_row();
+
+                    ui.label("Startup Profile");
+                    let mut default_profile = self.default_profile.clone();
+                    egui::ComboBox::from_id_source("default_profile")
+                        .selected_text(default_profile.as_deref().unwrap_or("Ask"))
+                        .width(150.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut default_profile, None, "Ask")
+                                .on_hover_text(tr(
+                                    "Ask which profile to load while there are several",
+                                ));
+                            for profile in &self.profiles {
+                                ui.selectable_value(
+                                    &mut default_profile,
+                                    Some(profile.clone()),
+                                    profile,
+                                );
+                            }
+                        });
+                    if default_profile != self.default_profile {
+                        self.set_default_profile(default_profile);
+                    }
+                    ui.end_row();
+
+                    ui.label("Backups");
+                    let mut restore = None;
+                    egui::ComboBox::from_id_source("restore_backup")
+                        .selected_text("Restore Backup...")
+                        .width(150.0)
+                        .show_ui(ui, |ui| {
+                            // Only read while the list is open.
+                            let backups = list_backups(self.config_saver.profile());
+                            if backups.is_empty() {
+                                ui.label("No backups yet");
+                            }
+                            for (backup, saved) in backups {
+                                if ui
+                                    .selectable_label(false, format_local_time(saved))
+                                    .clicked()
+                                {
+                                    restore = Some(backup);
+                                }
+                            }
+                        });
+                    if let Some(backup) = restore {
+                        self.restore_backup(backup);
+                    }
+                    ui.end_row();
+
+                    ui.label("Share");
+                    if ui.button("Export Shareable Config...").clicked() {
+                        self.export_shareable_config();
+                    }
+                    match &self.shared_config {
+                        None => ui.label("Without keys, addresses or user folders."),
+                        Some(Err(error)) => ui.colored_label(ui.visuals().error_fg_color, error),
+                        Some(Ok(path)) => ui.label(format!("Saved \"{}\".", path.display())),
+                    };
+                    ui.end_row();
+                });
+
+                // Theme
+                ui.heading(tr("Theme"));
+                let theme = &mut self.config.theme;
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut theme.mode, ThemeMode::Dark, "Dark");
+                    ui.radio_value(&mut theme.mode, ThemeMode::Light, "Light");
+                    if ui.button("Reset Colors").clicked() {
+                        *theme = ThemeConfig {
+                            mode: theme.mode,
+                            high_contrast: theme.high_contrast,
+                            reduced_motion: theme.reduced_motion,
+                            ..ThemeConfig::default()
+                        };
+                    }
+                });
+                let system = self.system_accessibility;
+                egui::Grid::new("accessibility_settings").show(ui, |ui| {
+                    for (label, setting, system, hover) in [
+                        (
+                            "High Contrast",
+                            &mut theme.high_contrast,
+                            system.high_contrast,
+                            "Black and white colors with solid outlines",
+                        ),
+                        (
+                            "Reduced Motion",
+                            &mut theme.reduced_motion,
+                            system.reduced_motion,
+                            "No animated toggles and no flashing indicators",
+                        ),
+                    ] {
+                        ui.label(label).on_hover_text(hover);
+                        ui.horizontal(|ui| {
+                            ui.radio_value(
+                                setting,
+                                SystemSetting::System,
+                                format!("System ({})", if system { "On" } else { "Off" }),
+                            )
+                            .on_hover_text(tr("Follow the setting of the operating system"));
+                            ui.radio_value(setting, SystemSetting::On, "On");
+                            ui.radio_value(setting, SystemSetting::Off, "Off");
+                        });
+                        ui.end_row();
+                    }
+                });
+                egui::Grid::new("theme_settings").show(ui, |ui| {
+                    for (label, color) in [
+                        ("Accent", &mut theme.accent),
+                        ("Playing", &mut theme.playing),
+                        ("Paused", &mut theme.paused),
+                        ("Stopped", &mut theme.stopped),
+                    ] {
+                        ui.label(label);
+                        ui.color_edit_button_srgb(color);
+                        ui.end_row();
+                    }
+                });
+                let big_screen = &mut self.config.big_screen;
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut big_screen.enabled, "Big Screen")
+                        .on_hover_text(tr("Show only the sounds as large, high contrast pads"));
+                    let response = ui.add(
+                        Slider::new(&mut big_screen.scale, 1.0..=4.0)
+                            .text("Scale")
+                            .fixed_decimals(1),
+                    );
+                    let default = BigScreenConfig::default().scale;
+                    fine_adjustment(
+                        ui,
+                        response,
+                        &mut big_screen.scale,
+                        1.0..=4.0,
+                        default,
+                        (0.1, 0.5),
+                    );
+                });
+
+                // Language
+                ui.heading(tr("Language"));
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(language_name())
+                    .show_ui(ui, |ui| {
+                        for (code, name) in list_languages() {
+                            ui.selectable_value(&mut self.config.language, code, name);
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr(
+                        "Add languages as .toml files to the \"languages\" folder of the \
+                        configuration directory",
+                    ));
+
+                // Demo
+                ui.heading(tr("Demo"));
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Add Demo Sounds")).clicked() {
+                        self.add_demo_sounds();
+                    }
+                    ui.label(format!(
+                        "Adds generated test sounds to the {DEMO_BANK} bank."
+                    ));
+                });
+
+                // Support
+                ui.heading(tr("Support"));
+                ui.horizontal(|ui| {
+                    if ui.button("Create Support Bundle").clicked() {
+                        self.create_support_bundle();
+                    }
+                    match &self.support_bundle {
+                        None => ui.label("Collects the log and settings for bug reports."),
+                        Some(Err(error)) => ui.colored_label(ui.visuals().error_fg_color, error),
+                        Some(Ok(path)) => ui.label(format!("Saved \"{}\".", path.display())),
+                    };
+                });
+
+                // Banks
+                ui.heading(tr("Banks"));
+                egui::Grid::new("bank_settings").show(ui, |ui| {
+                    ui.label("Name");
+                    ui.label("Active From");
+                    ui.label("Active Until");
+                    ui.label("Stop Key");
+                    ui.label("Pause Key");
+                    ui.end_row();
 
-            // Manual window
-            if ui.button("Help / Manual").clicked() {
-                self.manual_window = true;
-            }
+                    let mut remove = None;
+                    for (i, bank) in self.config.banks.iter_mut().enumerate() {
+                        ui.add(TextEdit::singleline(&mut bank.name).desired_width(100.0));
+                        ui.add(
+                            TextEdit::singleline(&mut bank.active_from)
+                                .hint_text("MM-DD")
+                                .desired_width(50.0),
+                        );
+                        ui.add(
+                            TextEdit::singleline(&mut bank.active_until)
+                                .hint_text("MM-DD")
+                                .desired_width(50.0),
+                        );
+                        bank.stop_key.update(ui, last_key_released);
+                        bank.pause_key.update(ui, last_key_released);
+                        if ui.button("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                    if let Some(i) = remove {
+                        self.config.banks.remove(i);
+                    }
 
-            // Volume slider
-            if ui
-                .add(
-                    Slider::new(&mut self.config.volume, Self::VOLUME_RANGE)
-                        .text("Global Volume (dB)"),
-                )
-                .changed()
-            {
-                for (i, control) in self.audio_controls.iter_mut().enumerate() {
-                    control.set_volume(self.config.volume + self.config.sounds[i].volume);
+                    ui.add(TextEdit::singleline(&mut self.new_bank.name).desired_width(100.0));
+                    ui.add(
+                        TextEdit::singleline(&mut self.new_bank.active_from)
+                            .hint_text("MM-DD")
+                            .desired_width(50.0),
+                    );
+                    ui.add(
+                        TextEdit::singleline(&mut self.new_bank.active_until)
+                            .hint_text("MM-DD")
+                            .desired_width(50.0),
+                    );
+                    self.new_bank.stop_key.update(ui, last_key_released);
+                    self.new_bank.pause_key.update(ui, last_key_released);
+                    if ui.button("Add").clicked() {
+                        self.config.banks.push(std::mem::take(&mut self.new_bank));
+                    }
+                    ui.end_row();
+                });
+                if ui.button("Apply Seasons").clicked() {
+                    let changes = self.apply_bank_seasons();
+                    self.bank_changes.extend(changes);
                 }
-            }
 
-            egui::Grid::new("outputs").num_columns(3).show(ui, |ui| {
-                // Selected output devices
-                for (name, device) in &self.output_devices {
-                    if let Some(output_config) = self.config.outputs.get_mut(name) {
-                        // Status
-                        if device.muted() {
-                            ui.colored_label(Color32::RED, "Muted");
-                        } else {
-                            ui.colored_label(Color32::GREEN, "Playing");
+                // Media library
+                ui.heading(tr("Media Library"));
+                ui.checkbox(
+                    &mut self.config.import_to_library,
+                    "Import added sounds into the media library",
+                )
+                .on_hover_text(tr("Transcode the files of new sounds to 48 kHz WAV files"));
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Import All")
+                        .on_hover_text(tr(
+                            "Transcode the files of every sound into the media library",
+                        ))
+                        .clicked()
+                    {
+                        for sound in &self.config.sounds {
+                            self.importer.import(&sound.path);
+                            self.importer.import(&sound.loop_path);
                         }
-                        ui.label(name);
+                    }
+                    if self.importer.pending() > 0 {
+                        ui.label(format!("Importing {} files...", self.importer.pending()));
+                    }
+                });
 
-                        // Volume slider
-                        if ui
-                            .add(
-                                Slider::new(&mut output_config.volume, Self::VOLUME_RANGE)
-                                    .text("Volume (dB)"),
+                // Updates
+                ui.heading(tr("Updates"));
+                ui.checkbox(
+                    &mut self.config.check_for_updates,
+                    "Check for updates at startup",
+                );
+                ui.horizontal(|ui| {
+                    let state = self.updater.state();
+                    let busy = matches!(state, UpdateState::Checking | UpdateState::Downloading(_));
+                    if ui.add_enabled(!busy, Button::new("Check Now")).clicked() {
+                        self.updater.check();
+                    }
+                    match state {
+                        UpdateState::Idle => {
+                            ui.label(format!("Version {}", env!("CARGO_PKG_VERSION")))
+                        }
+                        UpdateState::Checking => ui.label("Checking..."),
+                        UpdateState::UpToDate => ui.label("Up to date"),
+                        UpdateState::Available(release) => {
+                            // Only Windows builds are published as a single executable.
+                            if cfg!(windows)
+                                && release.executable_url.is_some()
+                                && ui.button("Download").clicked()
+                            {
+                                self.updater.download(release.clone());
+                            }
+                            ui.hyperlink_to(
+                                format!("Version {} is available", release.version),
+                                release.url,
                             )
-                            .changed()
-                        {
-                            device.set_volume(output_config.volume);
                         }
+                        UpdateState::Downloading(release) => {
+                            ui.label(format!("Downloading version {}...", release.version))
+                        }
+                        UpdateState::Staged(release) => {
+                            ui.label(format!("Restart to use version {}", release.version))
+                        }
+                        UpdateState::Failed(error) => {
+                            ui.colored_label(ui.visuals().error_fg_color, error)
+                        }
+                    };
+                });
+
+                // Integration status
+                ui.heading(tr("Integrations"));
+                let mut integrations_changed = false;
+                egui::Grid::new("integration_status").show(ui, |ui| {
+                    for integration in Integration::ALL {
+                        // Enabled checkbox
+                        integrations_changed |= ui
+                            .checkbox(
+                                self.config.integrations.enabled_mut(integration),
+                                integration.name(),
+                            )
+                            .changed();
+
+                        let status = self.integration_status(integration);
+                        match status {
+                            ConnectionStatus::Connected => {
+                                ui.colored_label(colors.playing, status.to_string())
+                            }
+                            ConnectionStatus::Disconnected => ui.label(status.to_string()),
+                            ConnectionStatus::Connecting | ConnectionStatus::Retrying { .. } => {
+                                ui.colored_label(ui.visuals().warn_fg_color, status.to_string())
+                            }
+                            ConnectionStatus::CircuitOpen { .. } => {
+                                ui.colored_label(ui.visuals().error_fg_color, status.to_string())
+                            }
+                        };
                         ui.end_row();
                     }
+                });
+                if integrations_changed {
+                    self.apply_integrations();
                 }
             });
+        self.settings_window = settings_window;
 
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                egui::Grid::new("sounds")
-                    .num_columns(9)
-                    .min_col_width(0.0)
-                    .show(ui, |ui| {
-                        // New Sound
-                        ui.label("");
-                        ui.add(
-                            TextEdit::singleline(&mut self.new_sound.name)
-                                .min_size([100.0, 10.0].into()),
-                        );
-                        self.new_sound.key.update(ui, last_key_released);
-                        ui.add(Slider::new(
-                            &mut self.new_sound.volume,
-                            Self::SOUND_VOLUME_RANGE,
-                        ));
+        let mut show_window = self.show_window;
+        egui::Window::new(tr("Show"))
+            .open(&mut show_window)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    // GO button
+                    if ui
+                        .add_enabled(
+                            self.enabled && self.standby_cue < self.config.cues.len(),
+                            Button::new(RichText::new("GO").size(32.0))
+                                .min_size(Vec2::new(150.0, 60.0)),
+                        )
+                        .clicked()
+                    {
+                        self.go();
+                    }
 
-                        if ui
-                            .add(
-                                TextEdit::singleline(&mut self.new_sound.path)
-                                    .min_size([300.0, 10.0].into()),
-                            )
-                            .hovered()
-                        {
-                            if let Some(path) = self.dropped_file.1.take() {
-                                self.new_sound.path = path;
+                    // Standby controls
+                    ui.vertical(|ui| {
+                        match self.config.cues.get(self.standby_cue) {
+                            Some(cue) => {
+                                ui.label(format!("Standby: {} {}", self.standby_cue + 1, cue.sound))
                             }
-                        }
+                            None => ui.label("Standby: End of cue list"),
+                        };
+                        ui.horizontal(|ui| {
+                            if ui.button("Back").clicked() {
+                                self.standby_cue = self.standby_cue.saturating_sub(1);
+                                self.cue_timer = None;
+                            }
+                            if ui.button("Skip").clicked()
+                                && self.standby_cue < self.config.cues.len()
+                            {
+                                self.standby_cue += 1;
+                                self.cue_timer = None;
+                            }
+                            if ui.button("Reset").clicked() {
+                                self.current_cue = None;
+                                self.standby_cue = 0;
+                                self.cue_timer = None;
+                            }
+                        });
+                    });
+                });
 
-                        if ui.button("Add").clicked() {
-                            self.audio_controls.insert(
-                                0,
-                                Arc::new(AudioControls::new(
-                                    false,
-                                    false,
-                                    self.new_sound.volume + self.config.volume,
-                                )),
+                // While editing is locked, the cues are only shown.
+                let locked = self.config.locked;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("cues").num_columns(7).show(ui, |ui| {
+                        // New Cue
+                        if !locked {
+                            ui.label("");
+                            ui.label("");
+                            sound_selector(
+                                ui,
+                                ("cue", "new"),
+                                &mut self.new_cue.sound,
+                                &self.config.sounds,
                             );
-                            self.config.sounds.insert(0, self.new_sound.clone());
-                            self.new_sound = SoundConfig::default();
+                            if ui.button("Add").clicked() {
+                                self.config.cues.push(std::mem::take(&mut self.new_cue));
+                            }
+                            ui.end_row();
                         }
-                        ui.end_row();
 
-                        // Other Sounds
-                        let mut i = 0;
+                        // Other Cues
                         let mut action = (0, 0, 0); // ((none, remove, move), index a, index b)
-                        let length = self.config.sounds.len();
+                        let length = self.config.cues.len();
 
-                        for sound in self.config.sounds.iter_mut() {
-                            // Playing
-                            if self.audio_controls[i].stopped() {
-                                ui.colored_label(Color32::RED, "\u{23F9}");
-                            } else if self.audio_controls[i].playing() {
-                                ui.colored_label(Color32::GREEN, "\u{25B6}");
+                        for (i, cue) in self.config.cues.iter_mut().enumerate() {
+                            // Status
+                            if self.current_cue == Some(i) {
+                                ui.colored_label(colors.playing, "Current");
+                            } else if self.standby_cue == i {
+                                ui.colored_label(colors.paused, "Standby");
                             } else {
-                                ui.colored_label(Color32::YELLOW, "\u{23F8}");
+                                ui.label("");
                             }
 
-                            // Name
-                            ui.add(
-                                TextEdit::singleline(&mut sound.name)
-                                    .min_size([100.0, 10.0].into()),
-                            );
-
-                            // Key
-                            sound.key.update(ui, last_key_released);
+                            // Number
+                            ui.label((i + 1).to_string());
 
-                            // Volume
-                            if ui
-                                .add(Slider::new(&mut sound.volume, Self::SOUND_VOLUME_RANGE))
-                                .changed()
-                            {
-                                self.audio_controls[i]
-                                    .set_volume(self.config.volume + sound.volume);
+                            // Sound
+                            if locked {
+                                ui.label(&cue.sound);
+                            } else {
+                                sound_selector(ui, ("cue", i), &mut cue.sound, &self.config.sounds);
+                            }
+                            if !self.config.sounds.iter().any(|s| s.name == cue.sound) {
+                                ui.colored_label(ui.visuals().error_fg_color, "Missing sound");
                             }
 
-                            // Path
-                            if ui
-                                .add(
-                                    TextEdit::singleline(&mut sound.path)
-                                        .min_size([300.0, 10.0].into()),
-                                )
-                                .hovered()
-                            {
-                                if let Some(path) = self.dropped_file.1.take() {
-                                    sound.path = path;
+                            // Follow
+                            if locked {
+                                ui.label(follow_label(cue.follow));
+                                if cue.follow != Follow::Manual {
+                                    ui.label(format!("{} ms", cue.follow_delay_ms));
                                 }
+                                ui.end_row();
+                                continue;
                             }
+                            egui::ComboBox::from_id_source(("cue_follow", i))
+                                .selected_text(follow_label(cue.follow))
+                                .show_ui(ui, |ui| {
+                                    for follow in
+                                        [Follow::Manual, Follow::AfterStart, Follow::AfterEnd]
+                                    {
+                                        ui.selectable_value(
+                                            &mut cue.follow,
+                                            follow,
+                                            follow_label(follow),
+                                        );
+                                    }
+                                });
+                            ui.add_enabled(
+                                cue.follow != Follow::Manual,
+                                egui::DragValue::new(&mut cue.follow_delay_ms).suffix(" ms"),
+                            );
 
-                            // Remove Sound
+                            // Remove Cue
                             if ui.button("Remove").clicked() {
                                 action = (1, i, 0);
                             }
 
-                            // Move Sound
+                            // Move Cue
                             if i > 0 && ui.button("^").clicked() {
                                 action = (2, i, i - 1);
                             }
@@ -677,126 +6393,244 @@ impl eframe::App for Soundboard {
                             }
 
                             ui.end_row();
-
-                            i += 1;
                         }
 
-                        // Remove or re-order a sound.
+                        // Remove or re-order a cue.
                         if action.0 == 1 {
-                            drop(self.config.sounds.remove(action.1));
-                            self.audio_controls.remove(action.1);
+                            self.config.cues.remove(action.1);
+                            self.current_cue = None;
+                            self.cue_timer = None;
+                            self.standby_cue = self.standby_cue.min(self.config.cues.len());
                         } else if action.0 == 2 {
-                            self.config.sounds.swap(action.1, action.2);
-                            self.audio_controls.swap(action.1, action.2);
+                            self.config.cues.swap(action.1, action.2);
                         }
                     });
+                });
             });
-        });
-
-        let mut settings_window = self.settings_window;
-        egui::Window::new("Settings")
-            .open(&mut settings_window)
-            .collapsible(false)
-            .show(ctx, |ui| {
-                // Audio settings
-                ui.heading("Audio");
-                egui::Grid::new("output_settings").show(ui, |ui| {
-                    if ui.button("Reload Devices").clicked() {
-                        self.update_output_devices();
-                    }
-                    ui.end_row();
+        self.show_window = show_window;
 
-                    ui.label("Device Name");
-                    ui.label("Mute Keybind");
-                    ui.end_row();
+        // Command palette
+        if self.profile_chooser.is_none() && self.palette.toggle(ctx) {
+            let commands = self.palette_commands();
+            if let Some(command) = self.palette.show(ctx, &commands) {
+                self.run_palette_command(command);
+            }
+        }
 
-                    for (name, device) in self.output_devices.iter_mut() {
-                        let mut checked = device.enabled();
+        self.pending_reload_ui(ctx);
 
-                        // Enabled checkbox
-                        let response = ui.checkbox(&mut checked, name);
+        // Bank season notification
+        if !self.bank_changes.is_empty() {
+            egui::Window::new(tr("Bank Changes"))
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    for change in &self.bank_changes {
+                        ui.label(change);
+                    }
+                    if ui.button("OK").clicked() {
+                        self.bank_changes.clear();
+                    }
+                });
+        }
 
-                        if let Some(output_config) = self.config.outputs.get_mut(name) {
-                            // Mute key bind button
-                            output_config.mute.update(ui, last_key_released);
+        let mut archive_window = self.archive_window;
+        egui::Window::new(tr("Archive"))
+            .open(&mut archive_window)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                if !self.config.sounds.iter().any(|sound| sound.archived) {
+                    ui.label("No sounds are archived.");
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("archive").num_columns(4).show(ui, |ui| {
+                        let mut action = (0, 0); // ((none, restore, remove), index)
+                        for (i, sound) in self.config.sounds.iter().enumerate() {
+                            if !sound.archived {
+                                continue;
+                            }
+                            ui.label(&sound.name);
+                            ui.label(&sound.path);
+                            if ui.button("Restore").clicked() {
+                                action = (1, i);
+                            }
+                            if ui.button("Remove").clicked() {
+                                action = (2, i);
+                            }
+                            ui.end_row();
                         }
 
-                        // Add and remove device.
-                        if response.changed() {
-                            if checked {
-                                assert!(
-                                    !self.config.outputs.contains_key(name),
-                                    "a device in self.config.outputs exists when it should not"
-                                );
-                                self.config.outputs.insert(
-                                    name.clone(),
-                                    OutputConfig {
-                                        volume: 0.0,
-                                        mute: KeyButton::default(),
-                                    },
-                                );
-                                device.enable();
-                            } else {
-                                self.config.outputs.remove(name);
-                                device.disable();
-                            }
+                        // Restore or remove a sound.
+                        if action.0 == 1 {
+                            self.config.sounds[action.1].archived = false;
+                        } else if action.0 == 2 {
+                            drop(self.config.sounds.remove(action.1));
+                            self.playback.remove(action.1);
                         }
-                        ui.end_row();
-                    }
+                    });
                 });
+            });
+        self.archive_window = archive_window;
 
-                // Remote input server settings
-                ui.heading("Remote Input Server");
-                egui::Grid::new("remote_settings").show(ui, |ui| {
-                    ui.label("Server Address");
-                    ui.add(
-                        TextEdit::singleline(&mut self.config.server_address)
-                            .min_size(Vec2::new(100.0, 10.0)),
-                    );
-                    ui.end_row();
-                    ui.label("API Key");
-                    ui.add(
-                        TextEdit::singleline(&mut self.config.api_key)
-                            .min_size(Vec2::new(100.0, 10.0)),
-                    );
-                    ui.end_row();
+        if self.pad_editor.is_some() {
+            let mut open = true;
+            egui::Window::new(tr("Edit Sound"))
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| self.pad_editor_ui(ui, last_key_released));
+            if !open {
+                self.pad_editor = None;
+            }
+        }
+
+        if self.foreign_import.is_some() {
+            let mut open = true;
+            egui::Window::new(tr("Import From Another App"))
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| self.show_foreign_import(ui));
+            if !open {
+                self.foreign_import = None;
+            }
+        }
+
+        let mut packs_window = self.packs_window;
+        egui::Window::new(tr("Sound Packs"))
+            .open(&mut packs_window)
+            .collapsible(false)
+            .show(ctx, |ui| self.show_packs(ui));
+        self.packs_window = packs_window;
+
+        let mut sync_window = self.sync_window;
+        egui::Window::new(tr("Sync"))
+            .open(&mut sync_window)
+            .collapsible(false)
+            .show(ctx, |ui| self.show_sync(ui));
+        self.sync_window = sync_window;
+
+        let mut stats_window = self.stats_window;
+        egui::Window::new(tr("Statistics"))
+            .open(&mut stats_window)
+            .collapsible(false)
+            .min_width(500.0)
+            .show(ctx, |ui| {
+                if ui.button("Refresh").clicked() {
+                    self.stats_summary = None;
+                }
+                let stats = &self.stats;
+                let summary = self.stats_summary.get_or_insert_with(|| {
+                    StatsSummary::new(&stats.load().unwrap_or_else(|error| {
+                        log!(Error, "[Stats] {error}");
+                        Vec::new()
+                    }))
                 });
+                ui.label(format!(
+                    "{} sounds played in {} sessions. Statistics are only stored on this computer.",
+                    summary.total,
+                    summary.sessions.len()
+                ));
 
-                // Shortcuts
-                ui.heading("Shortcuts");
-                egui::Grid::new("shortcut_settings").show(ui, |ui| {
-                    ui.label("Pause");
-                    self.config.shortcuts.pause.update(ui, last_key_released);
-                    ui.end_row();
+                ui.heading("Sounds per Session");
+                let bars = summary
+                    .sessions
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (start, count))| {
+                        Bar::new(i as f64, *count as f64).name(format_local_time(*start))
+                    })
+                    .collect();
+                Plot::new("stats_sessions")
+                    .height(120.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .show(ui, |plot_ui| plot_ui.bar_chart(BarChart::new(bars)));
 
-                    ui.label("Stop");
-                    self.config.shortcuts.stop.update(ui, last_key_released);
-                    ui.end_row();
+                ui.heading("Busiest Hours");
+                let bars = summary
+                    .hours
+                    .iter()
+                    .enumerate()
+                    .map(|(hour, count)| {
+                        Bar::new(hour as f64, *count as f64).name(format!("{hour:02}:00"))
+                    })
+                    .collect();
+                Plot::new("stats_hours")
+                    .height(120.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .include_x(-0.5)
+                    .include_x(23.5)
+                    .show(ui, |plot_ui| plot_ui.bar_chart(BarChart::new(bars)));
 
-                    ui.label("Modifier");
-                    self.config.shortcuts.modifier.update(ui, last_key_released);
-                    ui.end_row();
+                ui.heading("Most Used Sounds");
+                egui::Grid::new("stats_sounds").show(ui, |ui| {
+                    for (sound, count) in summary.sounds.iter().take(10) {
+                        ui.label(sound);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
                 });
             });
-        self.settings_window = settings_window;
+        self.stats_window = stats_window;
 
         let mut manual_window = self.manual_window;
-        egui::Window::new("Manual")
+        egui::Window::new(tr("Manual"))
             .open(&mut manual_window)
             .collapsible(false)
             .min_width(700.0)
             .show(ctx, |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     ui.label(
-                        RichText::new(include_str!("manual.txt")).text_style(TextStyle::Monospace),
+                        RichText::new(manual(include_str!("manual.txt")))
+                            .text_style(TextStyle::Monospace),
                     );
                 });
             });
         self.manual_window = manual_window;
 
-        let _ = self.config_saver.save(&self.config);
+        let mut log_window = self.log_window;
+        egui::Window::new(tr("Log"))
+            .open(&mut log_window)
+            .min_width(700.0)
+            .show(ctx, |ui| self.log_ui(ui));
+        self.log_window = log_window;
+
+        let mut missed_window = self.missed_window;
+        egui::Window::new(tr("Missed Triggers"))
+            .open(&mut missed_window)
+            .min_width(500.0)
+            .show(ctx, |ui| self.missed_triggers_ui(ui));
+        self.missed_window = missed_window;
+
+        // Error notifications
+        if self.toasts.show(ctx) {
+            self.log_window = true;
+        }
+        self.profiler.show(ctx);
+
+        self.publish_state();
+
+        self.stats.flush();
+        if self.pending_reload.is_none() {
+            let _ = self.config_saver.save(&self.config);
+        }
+        match self.config_saver.autosave_pending() {
+            // Wake once for the skipped autosave even if nothing else happens.
+            Some(delay) => {
+                if self.autosave_wake.is_none_or(|wake| wake <= Instant::now()) {
+                    self.waker.wake_after(delay);
+                    self.autosave_wake = Some(Instant::now() + delay);
+                }
+            }
+            None => self.autosave_wake = None,
+        }
 
-        ctx.request_repaint_after(Self::MAX_FRAME_DELAY);
+        // Background threads wake the window as soon as they have something for it.
+        if self.animating() {
+            ctx.request_repaint_after(Self::MAX_FRAME_DELAY);
+        }
+        self.profiler.end_frame();
     }
 
     fn on_close_event(&mut self) -> bool {
@@ -805,10 +6639,93 @@ impl eframe::App for Soundboard {
     }
 }
 
+/// Apply the command line arguments. Returns false if the soundboard should exit.
+/// A subcommand, such as `play Airhorn`, is sent to the running soundboard instead.
+fn parse_arguments() -> bool {
+    let mut arguments = std::env::args().skip(1);
+    let mut subcommand = Vec::new();
+    // The profile to check, or an empty name for every profile.
+    let mut check = None;
+    while let Some(argument) = arguments.next() {
+        if argument == "--check" || argument.starts_with("--check=") {
+            check = Some(
+                argument
+                    .trim_start_matches("--check")
+                    .trim_start_matches('=')
+                    .to_string(),
+            );
+            continue;
+        }
+        let directory = match argument.strip_prefix("--config=") {
+            Some(directory) => Some(directory.to_string()),
+            None if argument == "--config" => arguments.next(),
+            None if argument == "--help" || argument == "-h" => {
+                println!("Usage: soundboard [--config <directory>] [<command>]");
+                println!("  --config <directory>  Store profiles and statistics in <directory>.");
+                println!("  --check[=<profile>]   Check every profile, or only <profile>, print a");
+                println!(
+                    "                        JSON report and exit with 1 if there are errors."
+                );
+                println!("Commands for the running soundboard:");
+                println!("  play <name>           Play the sound named <name>.");
+                println!("  stop-all              Stop every sound.");
+                println!("  list                  List the names of the sounds.");
+                return false;
+            }
+            None if !argument.starts_with('-') => {
+                subcommand.push(argument);
+                continue;
+            }
+            None => {
+                log!(
+                    Warning,
+                    "[Soundboard] Ignoring unknown argument \"{argument}\"."
+                );
+                continue;
+            }
+        };
+        match directory {
+            None => log!(Warning, "[Soundboard] --config requires a directory."),
+            Some(directory) => set_config_directory(directory.into()),
+        }
+    }
+    if let Some(profile) = check {
+        std::process::exit(run_check(
+            Some(profile.as_str()).filter(|profile| !profile.is_empty()),
+        ));
+    }
+    if !subcommand.is_empty() {
+        std::process::exit(run_subcommand(&subcommand));
+    }
+    true
+}
+
+/// Check `profile`, or every profile if None, and print the report as JSON. Returns the exit
+/// code: 0 without errors and 1 with errors.
+fn run_check(profile: Option<&str>) -> i32 {
+    log_to_stderr();
+    let report = check_profiles(profile);
+    match serde_json::to_string_pretty(&report) {
+        Err(error) => {
+            eprintln!("Unable to serialize the report: {error}.");
+            1
+        }
+        Ok(json) => {
+            println!("{json}");
+            i32::from(!report.ok)
+        }
+    }
+}
+
 fn main() {
-    let mut native_options = eframe::NativeOptions::default();
-    native_options.min_window_size = Some(Vec2::new(850.0, 500.0));
-    native_options.drag_and_drop_support = true;
+    if !parse_arguments() {
+        return;
+    }
+    let native_options = eframe::NativeOptions {
+        min_window_size: Some(Vec2::new(850.0, 500.0)),
+        drag_and_drop_support: true,
+        ..Default::default()
+    };
     let _ = eframe::run_native(
         "Soundboard",
         native_options,
@@ -0,0 +1,242 @@
+use crate::worker::WorkerPool;
+use serde::{Deserialize, Serialize};
+use soundboard_core::config::{get_library_directory, SoundConfig};
+use soundboard_core::waker::{Waker, WakingSender};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+/// The version of the pack format. Packs of newer versions are refused.
+const PACK_VERSION: u32 = 1;
+/// The name of the list of sounds in a pack.
+const PACK_FILE: &str = "pack.toml";
+
+/// The list of sounds in a pack. Their paths are relative to the root of the pack.
+#[derive(Serialize, Deserialize)]
+struct PackFile {
+    version: u32,
+    sounds: Vec<SoundConfig>,
+}
+
+/// Return a name for the file at `path` in the "sounds" folder of a pack that no other file in
+/// `used` has, keeping its file name where possible.
+fn pack_name(path: &Path, used: &HashSet<String>) -> String {
+    let stem = path
+        .file_stem()
+        .map_or("sound".into(), |stem| stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut name = format!("sounds/{stem}{extension}");
+    let mut number = 2;
+    while used.contains(&name) {
+        name = format!("sounds/{stem}-{number}{extension}");
+        number += 1;
+    }
+    name
+}
+
+/// Write `sounds` with their files and loop files to a pack at `path`. Returns the number of
+/// sounds written.
+fn export_pack(mut sounds: Vec<SoundConfig>, path: &Path) -> Result<usize, String> {
+    let write_error = |error: &dyn std::fmt::Display| {
+        format!("Unable to write the pack \"{}\": {error}.", path.display())
+    };
+    let file = File::create(path).map_err(|error| write_error(&error))?;
+    let mut zip = ZipWriter::new(file);
+
+    // Each file is written once, even if several sounds play it.
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut used = HashSet::new();
+    for sound in &mut sounds {
+        sound.fragment.clear();
+        // Measured again from the imported file.
+        sound.loudness = None;
+        for path in [&mut sound.path, &mut sound.loop_path] {
+            if path.is_empty() {
+                continue;
+            }
+            if let Some(name) = names.get(path.as_str()) {
+                *path = name.clone();
+                continue;
+            }
+            let mut source = File::open(path.as_str())
+                .map_err(|error| format!("Unable to read file {path}: {error}."))?;
+            let name = pack_name(Path::new(path.as_str()), &used);
+            zip.start_file(&name, FileOptions::default())
+                .map_err(|error| write_error(&error))?;
+            io::copy(&mut source, &mut zip).map_err(|error| write_error(&error))?;
+            used.insert(name.clone());
+            names.insert(path.clone(), name.clone());
+            *path = name;
+        }
+    }
+
+    let count = sounds.len();
+    let list = toml::to_string_pretty(&PackFile {
+        version: PACK_VERSION,
+        sounds,
+    })
+    .map_err(|error| format!("Unable to serialize the sounds: {error}."))?;
+    zip.start_file(PACK_FILE, FileOptions::default())
+        .and_then(|()| Ok(io::Write::write_all(&mut zip, list.as_bytes())?))
+        .map_err(|error| write_error(&error))?;
+    zip.finish().map_err(|error| write_error(&error))?;
+    Ok(count)
+}
+
+/// Extract the pack at `path` into the "packs" folder of the media library and return its
+/// sounds with the paths of the extracted files.
+fn import_pack(path: &Path) -> Result<Vec<SoundConfig>, String> {
+    let read_error = |error: &dyn std::fmt::Display| {
+        format!("Unable to read the pack \"{}\": {error}.", path.display())
+    };
+    let file = File::open(path).map_err(|error| read_error(&error))?;
+    let mut zip = ZipArchive::new(file).map_err(|error| read_error(&error))?;
+    let list = io::read_to_string(zip.by_name(PACK_FILE).map_err(|error| read_error(&error))?)
+        .map_err(|error| read_error(&error))?;
+    let pack: PackFile = toml::from_str(&list).map_err(|error| read_error(&error))?;
+    if pack.version > PACK_VERSION {
+        return Err(format!(
+            "The pack \"{}\" is version {}, but this soundboard only reads packs up to version \
+            {PACK_VERSION}. Please update the soundboard.",
+            path.display(),
+            pack.version
+        ));
+    }
+
+    let stem = path
+        .file_stem()
+        .map_or("pack".into(), |stem| stem.to_string_lossy());
+    let directory = get_library_directory()?.join("packs").join(stem.as_ref());
+    let mut extracted: HashMap<String, String> = HashMap::new();
+    let mut sounds = pack.sounds;
+    for sound in &mut sounds {
+        for file in [&mut sound.path, &mut sound.loop_path] {
+            if file.is_empty() {
+                continue;
+            }
+            if let Some(destination) = extracted.get(file.as_str()) {
+                *file = destination.clone();
+                continue;
+            }
+            let mut entry = zip.by_name(file).map_err(|error| read_error(&error))?;
+            // Paths such as "../file" would be written outside of the pack's folder.
+            let destination = match entry.enclosed_name() {
+                Some(name) => directory.join(name),
+                None => return Err(read_error(&format!("{file} is not inside the pack"))),
+            };
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|error| {
+                    format!("Unable to create \"{}\": {error}.", parent.display())
+                })?;
+            }
+            // Write to a temporary file first, so a failed import never leaves a partial copy.
+            let temporary = destination.with_extension("part");
+            File::create(&temporary)
+                .and_then(|mut output| io::copy(&mut entry, &mut output))
+                .and_then(|_| fs::rename(&temporary, &destination))
+                .map_err(|error| {
+                    format!("Unable to write \"{}\": {error}.", destination.display())
+                })?;
+            let destination = destination.display().to_string();
+            extracted.insert(file.clone(), destination.clone());
+            *file = destination;
+        }
+    }
+    Ok(sounds)
+}
+
+/// Work finished by a [`SoundPacks`].
+enum PackEvent {
+    Exported(PathBuf, Result<usize, String>),
+    Imported(PathBuf, Result<Vec<SoundConfig>, String>),
+}
+
+/// Exports sounds with their files to pack files and imports them, on a background thread, so
+/// boards can be shared between computers without their paths breaking.
+pub struct SoundPacks {
+    workers: WorkerPool,
+    sender: WakingSender<PackEvent>,
+    receiver: Receiver<PackEvent>,
+    /// The number of exports and imports running.
+    pending: usize,
+    /// Where the last pack was exported or imported from, or why it failed.
+    pub message: Option<Result<String, String>>,
+}
+
+impl SoundPacks {
+    /// `waker` is called whenever an export or import finishes.
+    pub fn new(waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            workers: WorkerPool::new("Pack Worker", 1),
+            sender: WakingSender::new(sender, waker),
+            receiver,
+            pending: 0,
+            message: None,
+        }
+    }
+
+    /// Write `sounds` and their files to a pack at `path`.
+    pub fn export(&mut self, sounds: Vec<SoundConfig>, path: PathBuf) {
+        self.pending += 1;
+        let sender = self.sender.clone();
+        self.workers.spawn(move || {
+            let result = export_pack(sounds, &path);
+            if result.is_err() {
+                // Do not leave a partial pack behind.
+                let _ = fs::remove_file(&path);
+            }
+            let _ = sender.send(PackEvent::Exported(path, result));
+        });
+    }
+
+    /// Extract the pack at `path` into the media library.
+    pub fn import(&mut self, path: PathBuf) {
+        self.pending += 1;
+        let sender = self.sender.clone();
+        self.workers.spawn(move || {
+            let result = import_pack(&path);
+            let _ = sender.send(PackEvent::Imported(path, result));
+        });
+    }
+
+    /// Check if a pack is being exported or imported.
+    pub fn busy(&self) -> bool {
+        self.pending > 0
+    }
+
+    /// Return the sounds of the packs imported since the last call.
+    pub fn finished(&mut self) -> Vec<SoundConfig> {
+        let mut imported = Vec::new();
+        for event in self.receiver.try_iter() {
+            self.pending -= 1;
+            let message = match event {
+                PackEvent::Exported(path, result) => result
+                    .map(|count| format!("Exported {count} sounds to \"{}\".", path.display())),
+                PackEvent::Imported(path, result) => result.map(|sounds| {
+                    let message = format!(
+                        "Imported {} sounds from \"{}\".",
+                        sounds.len(),
+                        path.display()
+                    );
+                    imported.extend(sounds);
+                    message
+                }),
+            };
+            match &message {
+                Err(error) => log!(Error, "[Packs] {error}"),
+                Ok(message) => log!("[Packs] {message}"),
+            }
+            self.message = Some(message);
+        }
+        imported
+    }
+}
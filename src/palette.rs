@@ -0,0 +1,181 @@
+use eframe::egui;
+use egui::{Align2, Key, TextEdit};
+
+/// A searchable list of commands opened with Ctrl+K.
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// The most commands listed at once.
+    const MAX_RESULTS: usize = 12;
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open or close the palette when Ctrl+K is pressed, and return whether it is open, so the
+    /// commands are only listed while it is shown.
+    pub fn toggle(&mut self, ctx: &egui::Context) -> bool {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, Key::K)) {
+            self.open = !self.open;
+            self.query.clear();
+            self.selected = 0;
+        }
+        self.open
+    }
+
+    /// Show the palette if it is open. `commands` holds the label of each command and its value.
+    /// Returns the value of the command chosen this frame.
+    pub fn show<T: Clone>(&mut self, ctx: &egui::Context, commands: &[(String, T)]) -> Option<T> {
+        if !self.open {
+            return None;
+        }
+
+        let mut results: Vec<(i32, &(String, T))> = commands
+            .iter()
+            .filter_map(|command| Some((fuzzy_score(&self.query, &command.0)?, command)))
+            .collect();
+        results.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        results.truncate(Self::MAX_RESULTS);
+
+        let (up, down, enter, escape) = ctx.input(|i| {
+            (
+                i.key_pressed(Key::ArrowUp),
+                i.key_pressed(Key::ArrowDown),
+                i.key_pressed(Key::Enter),
+                i.key_pressed(Key::Escape),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let mut chosen = None;
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command...")
+                        .desired_width(400.0),
+                );
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+
+                for (i, (_, (label, value))) in results.iter().enumerate() {
+                    if ui.selectable_label(i == self.selected, label).clicked()
+                        || (enter && i == self.selected)
+                    {
+                        chosen = Some(value.clone());
+                    }
+                }
+                if results.is_empty() {
+                    ui.label("No matching commands.");
+                }
+            });
+
+        if chosen.is_some() || escape {
+            self.open = false;
+        }
+        chosen
+    }
+}
+
+/// Score how well `query` matches `label`, or None if `query` is not a subsequence of `label`.
+/// Consecutive characters and the starts of words score higher.
+fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut label_chars = label.chars().flat_map(char::to_lowercase).enumerate();
+    let mut previous_match: Option<usize> = None;
+    let mut previous_char = ' ';
+    for query_char in query.chars().flat_map(char::to_lowercase) {
+        if query_char.is_whitespace() {
+            continue;
+        }
+        loop {
+            let (i, label_char) = label_chars.next()?;
+            let word_start = !previous_char.is_alphanumeric();
+            previous_char = label_char;
+            if label_char == query_char {
+                score += match previous_match {
+                    Some(previous) if previous + 1 == i => 5,
+                    _ if word_start => 3,
+                    Some(previous) => 1 - (i - previous).min(5) as i32,
+                    None => 1,
+                };
+                previous_match = Some(i);
+                break;
+            }
+        }
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the labels matching `query`, best first, as the palette lists them.
+    fn ranked<'a>(query: &str, labels: &[&'a str]) -> Vec<&'a str> {
+        let mut results: Vec<(i32, &str)> = labels
+            .iter()
+            .filter_map(|&label| Some((fuzzy_score(query, label)?, label)))
+            .collect();
+        results.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        results.into_iter().map(|(_, label)| label).collect()
+    }
+
+    #[test]
+    fn only_subsequences_match() {
+        assert!(fuzzy_score("stop", "Stop All Sounds").is_some());
+        assert!(fuzzy_score("sas", "Stop All Sounds").is_some());
+        assert!(fuzzy_score("STOP all", "stop all sounds").is_some());
+        assert_eq!(fuzzy_score("", "Stop All Sounds"), Some(0));
+        assert_eq!(fuzzy_score("pots", "Stop All Sounds"), None);
+        assert_eq!(fuzzy_score("stopx", "Stop"), None);
+    }
+
+    #[test]
+    fn consecutive_characters_rank_higher() {
+        assert!(fuzzy_score("horn", "Airhorn") > fuzzy_score("horn", "Hold On Ringing Now"));
+        assert_eq!(
+            ranked("horn", &["Hold On Ringing Now", "Airhorn"]),
+            ["Airhorn", "Hold On Ringing Now"]
+        );
+        // Characters far apart score less than close ones.
+        assert!(fuzzy_score("pe", "Pause") < fuzzy_score("pe", "Peek"));
+    }
+
+    #[test]
+    fn word_starts_rank_higher() {
+        assert!(fuzzy_score("s", "Open Settings") > fuzzy_score("s", "Pause"));
+        assert_eq!(
+            ranked("horn", &["Airhorn", "Play Horn"]),
+            ["Play Horn", "Airhorn"]
+        );
+        assert_eq!(
+            ranked("pm", &["Pump", "Open Missed", "Pause Music"]),
+            ["Pause Music", "Open Missed", "Pump"]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use eframe::egui;
+use egui::{Align2, RichText, TextStyle};
+use soundboard_core::contention::contended_locks;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Measures how long the frames of the window take, to find what makes it stutter. Shown as an
+/// overlay in the corner of the window while enabled.
+pub struct Profiler {
+    pub enabled: bool,
+    /// How long each of the last frames took to update, and how much of it dispatching the input
+    /// events took, oldest first.
+    frames: VecDeque<(Duration, Duration)>,
+    frame_start: Option<Instant>,
+    dispatch: Duration,
+    /// The contended locks counted when the rate was last measured, and when.
+    locks: (u64, Instant),
+    /// The contended locks per second.
+    lock_rate: f64,
+}
+
+impl Profiler {
+    /// The number of frames measured.
+    const FRAMES: usize = 120;
+    /// Frames that take longer than this are counted as janky.
+    const JANK: Duration = Duration::from_millis(1000 / 30);
+    const RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            frames: VecDeque::with_capacity(Self::FRAMES),
+            frame_start: None,
+            dispatch: Duration::ZERO,
+            locks: (contended_locks(), Instant::now()),
+            lock_rate: 0.0,
+        }
+    }
+
+    /// Start measuring a frame.
+    pub fn start_frame(&mut self) {
+        self.frame_start = Some(Instant::now());
+        self.dispatch = Duration::ZERO;
+    }
+
+    /// Count `duration` as spent dispatching input events in the current frame.
+    pub fn add_dispatch(&mut self, duration: Duration) {
+        self.dispatch += duration;
+    }
+
+    /// Stop measuring the current frame.
+    pub fn end_frame(&mut self) {
+        let Some(start) = self.frame_start.take() else {
+            return;
+        };
+        if self.frames.len() == Self::FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((start.elapsed(), self.dispatch));
+
+        let (count, time) = self.locks;
+        if time.elapsed() >= Self::RATE_INTERVAL {
+            let now = contended_locks();
+            self.lock_rate = (now - count) as f64 / time.elapsed().as_secs_f64();
+            self.locks = (now, Instant::now());
+        }
+    }
+
+    /// Show the overlay if enabled.
+    pub fn show(&self, ctx: &egui::Context) {
+        if !self.enabled {
+            return;
+        }
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let frames = self.frames.len().max(1) as u32;
+        let update: Duration = self.frames.iter().map(|(update, _)| *update).sum();
+        let dispatch: Duration = self.frames.iter().map(|(_, dispatch)| *dispatch).sum();
+        let slowest = self.frames.iter().map(|(update, _)| *update).max();
+        let janky = self
+            .frames
+            .iter()
+            .filter(|(update, _)| *update > Self::JANK)
+            .count();
+        let text = format!(
+            "Frame: {:.2} ms average, {:.2} ms slowest\n\
+            Janky frames (over {} ms): {janky} of the last {}\n\
+            Event dispatch: {:.3} ms average\n\
+            Contended audio locks: {} ({:.1}/s)",
+            milliseconds(update / frames),
+            milliseconds(slowest.unwrap_or_default()),
+            Self::JANK.as_millis(),
+            self.frames.len(),
+            milliseconds(dispatch / frames),
+            contended_locks(),
+            self.lock_rate,
+        );
+        egui::Area::new("Profiler")
+            .anchor(Align2::RIGHT_TOP, [-8.0, 8.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(RichText::new(text).text_style(TextStyle::Monospace));
+                });
+            });
+    }
+}
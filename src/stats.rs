@@ -0,0 +1,220 @@
+use crate::worker::WorkerPool;
+use chrono::{Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use soundboard_core::config::get_stats_path;
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The size of the stats file above which its oldest triggers are removed.
+const MAX_STATS_SIZE: u64 = 16 * 1024 * 1024;
+
+/// One recorded play of a sound. Stored as a line of JSON in [`get_stats_path`].
+#[derive(Serialize, Deserialize)]
+pub struct Trigger {
+    /// The start of the session, in seconds since the Unix epoch.
+    pub session: u64,
+    /// The time of the trigger, in seconds since the Unix epoch.
+    pub time: u64,
+    pub profile: String,
+    pub sound: String,
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Format seconds since the Unix epoch as a local date and time.
+pub fn format_local_time(time: u64) -> String {
+    match Local.timestamp_opt(time as i64, 0).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+        None => time.to_string(),
+    }
+}
+
+/// Records sound triggers on this computer. Nothing is ever sent anywhere.
+pub struct StatsStore {
+    session: u64,
+    /// The triggers not yet handed to the worker.
+    pending: Vec<Trigger>,
+    /// Appends the triggers to the stats file in the order they were recorded.
+    workers: WorkerPool,
+}
+
+impl StatsStore {
+    /// Start a new session.
+    pub fn new() -> Self {
+        Self {
+            session: unix_time(),
+            pending: Vec::new(),
+            workers: WorkerPool::new("Stats Worker", 1),
+        }
+    }
+
+    /// Record a trigger of `sound` in `profile`. It is written to the stats file by the next
+    /// [`StatsStore::flush`].
+    pub fn record(&mut self, profile: &str, sound: &str) {
+        self.pending.push(Trigger {
+            session: self.session,
+            time: unix_time(),
+            profile: profile.to_string(),
+            sound: sound.to_string(),
+        });
+    }
+
+    /// Append the triggers recorded since the last call to the stats file in the background.
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let triggers = std::mem::take(&mut self.pending);
+        self.workers.spawn(move || Self::write(&triggers));
+    }
+
+    /// Append `triggers` to the stats file, then remove the oldest triggers if it grew too
+    /// large.
+    fn write(triggers: &[Trigger]) {
+        let result = get_stats_path().and_then(|path| {
+            let mut lines = String::new();
+            for trigger in triggers {
+                let line = serde_json::to_string(trigger)
+                    .map_err(|error| format!("Unable to serialize trigger: {error}."))?;
+                lines.push_str(&line);
+                lines.push('\n');
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|error| format!("Unable to open stats file: {error}."))?;
+            file.write_all(lines.as_bytes())
+                .map_err(|error| format!("Unable to write stats file: {error}."))?;
+            let size = file.metadata().map_or(0, |metadata| metadata.len());
+            drop(file);
+            if size > MAX_STATS_SIZE {
+                trim(&path, MAX_STATS_SIZE / 2)?;
+            }
+            Ok(())
+        });
+        if let Err(error) = result {
+            log!(Error, "[Stats] {error}");
+        }
+    }
+
+    /// Read every trigger from the stats file. Lines that cannot be read are skipped.
+    pub fn load(&self) -> Result<Vec<Trigger>, String> {
+        let path = get_stats_path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        match fs::read_to_string(&path) {
+            Err(error) => Err(format!("Unable to read stats file: {error}.")),
+            Ok(data) => Ok(data
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()),
+        }
+    }
+}
+
+impl Drop for StatsStore {
+    /// Write the triggers of the last frame before the soundboard exits.
+    fn drop(&mut self) {
+        Self::write(&self.pending);
+    }
+}
+
+/// Remove the oldest triggers from the stats file at `path`, keeping the newest lines that fit
+/// in `size` bytes.
+fn trim(path: &Path, size: u64) -> Result<(), String> {
+    let data =
+        fs::read_to_string(path).map_err(|error| format!("Unable to read stats file: {error}."))?;
+    let kept = match data.len().checked_sub(size as usize) {
+        None | Some(0) => return Ok(()),
+        // Cut after a newline, which is always on a character boundary.
+        Some(cut) => match data.as_bytes()[cut - 1..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+        {
+            Some(newline) => &data[cut + newline..],
+            None => "",
+        },
+    };
+    fs::write(path, kept).map_err(|error| format!("Unable to write stats file: {error}."))?;
+    log!(
+        "[Stats] Removed the oldest triggers from the stats file, keeping {} bytes.",
+        kept.len()
+    );
+    Ok(())
+}
+
+/// Totals shown in the statistics window.
+pub struct StatsSummary {
+    pub total: usize,
+    /// The start of each session and its number of triggers, oldest first.
+    pub sessions: Vec<(u64, usize)>,
+    /// The number of triggers in each hour of the day, in local time.
+    pub hours: [usize; 24],
+    /// The number of triggers of each sound, most used first.
+    pub sounds: Vec<(String, usize)>,
+}
+
+impl StatsSummary {
+    pub fn new(triggers: &[Trigger]) -> Self {
+        let mut sessions: HashMap<u64, usize> = HashMap::new();
+        let mut hours = [0; 24];
+        let mut sounds: HashMap<&str, usize> = HashMap::new();
+        for trigger in triggers {
+            *sessions.entry(trigger.session).or_default() += 1;
+            if let Some(time) = Local.timestamp_opt(trigger.time as i64, 0).single() {
+                hours[time.hour() as usize] += 1;
+            }
+            *sounds.entry(&trigger.sound).or_default() += 1;
+        }
+
+        let mut sessions: Vec<(u64, usize)> = sessions.into_iter().collect();
+        sessions.sort();
+        let mut sounds: Vec<(String, usize)> = sounds
+            .into_iter()
+            .map(|(sound, count)| (sound.to_string(), count))
+            .collect();
+        sounds.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            total: triggers.len(),
+            sessions,
+            hours,
+            sounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trimming_keeps_the_newest_whole_lines() {
+        let path =
+            std::env::temp_dir().join(format!("soundboard-stats-{}.jsonl", std::process::id()));
+        let lines = "{\"a\":1}\n{\"b\":2}\n{\"é\":3}\n";
+        for (size, kept) in [
+            (lines.len(), lines),
+            (lines.len() - 1, "{\"b\":2}\n{\"é\":3}\n"),
+            (9, "{\"é\":3}\n"),
+            // The last line is cut in the middle of "é".
+            (7, ""),
+        ] {
+            fs::write(&path, lines).unwrap();
+            trim(&path, size as u64).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), kept, "{size}");
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+use soundboard_core::audio::audio_host;
+use soundboard_core::config::{get_config_directory, Config, CONFIG_VERSION};
+use soundboard_core::log::recent_lines;
+use std::{
+    fs,
+    io::Write,
+    path::{Component, Path, PathBuf},
+};
+use zip::{write::FileOptions, ZipWriter};
+
+/// Replaces secrets in support bundles.
+const REDACTED: &str = "<redacted>";
+
+/// Replace the string `value` with `replacement` if it is not empty.
+fn replace_string(value: Option<&mut toml::Value>, replacement: &str) {
+    if let Some(value) = value {
+        if value.as_str().is_some_and(|string| !string.is_empty()) {
+            *value = toml::Value::String(replacement.to_string());
+        }
+    }
+}
+
+/// Serialize `config` as a toml value with its secrets replaced by `replacement`: the remote
+/// input API key, the HTTP API token and, if `address` is set, the remote input server address.
+fn config_without_secrets(
+    config: &Config,
+    replacement: &str,
+    address: bool,
+) -> Result<toml::Value, String> {
+    let mut value = toml::Value::try_from(config)
+        .map_err(|error| format!("Unable to serialize configuration: {error}."))?;
+    replace_string(value.get_mut("api_key"), replacement);
+    replace_string(
+        value.get_mut("http").and_then(|http| http.get_mut("token")),
+        replacement,
+    );
+    if address {
+        replace_string(value.get_mut("server_address"), replacement);
+    }
+    Ok(value)
+}
+
+fn to_toml(value: &toml::Value) -> Result<String, String> {
+    toml::to_string_pretty(value)
+        .map_err(|error| format!("Unable to serialize configuration: {error}."))
+}
+
+/// Serialize `config` as toml with the remote input API key and the HTTP API token replaced.
+pub fn redacted_config(config: &Config) -> Result<String, String> {
+    to_toml(&config_without_secrets(config, REDACTED, false)?)
+}
+
+/// Return the deepest directory holding every path in `paths`, or None if they only share a
+/// root or a drive, or not even that.
+fn common_directory<'a>(mut paths: impl Iterator<Item = &'a Path>) -> Option<PathBuf> {
+    let mut common = paths.next()?.parent()?.to_path_buf();
+    for path in paths {
+        while !path.starts_with(&common) {
+            if !common.pop() {
+                return None;
+            }
+        }
+    }
+    // A root such as "/" or "C:\" still shows every folder below it.
+    common
+        .components()
+        .any(|component| matches!(component, Component::Normal(_)))
+        .then_some(common)
+}
+
+/// Return the absolute `path` relative to `common`, or its file name if it is not in `common`,
+/// with forward slashes, which work on every platform. Relative paths are kept.
+fn shareable_path(path: &str, common: Option<&Path>) -> String {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return path.to_string_lossy().to_string();
+    }
+    let relative = common
+        .and_then(|common| path.strip_prefix(common).ok())
+        .map(Path::to_path_buf)
+        .or_else(|| path.file_name().map(PathBuf::from))
+        .unwrap_or_default();
+    let components: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    components.join("/")
+}
+
+/// Serialize `config` as toml that can be posted publicly. The API key, HTTP token, the remote
+/// input server and sync addresses and the loudness measurements are removed, and the absolute
+/// paths of the sound files are made relative to the directory holding all of them, or cut to
+/// their file names, so they no longer show user names or folder layouts.
+pub fn shareable_config(config: &Config) -> Result<String, String> {
+    let mut value = config_without_secrets(config, "", true)?;
+    replace_string(value.get_mut("sync_address"), "");
+
+    let paths: Vec<&Path> = config
+        .sounds
+        .iter()
+        .flat_map(|sound| [&sound.path, &sound.loop_path])
+        .map(Path::new)
+        .filter(|path| path.is_absolute())
+        .collect();
+    let common = common_directory(paths.into_iter());
+    let sounds = value
+        .get_mut("sounds")
+        .and_then(|sounds| sounds.as_array_mut());
+    for sound in sounds.into_iter().flatten() {
+        // The loudness measurement holds the path measured, and is made again when it is missing.
+        if let Some(sound) = sound.as_table_mut() {
+            sound.remove("loudness");
+        }
+        for key in ["path", "loop_path"] {
+            if let Some(toml::Value::String(path)) = sound.get_mut(key) {
+                *path = shareable_path(path, common.as_deref());
+            }
+        }
+    }
+    to_toml(&value)
+}
+
+/// Describe this build and the system it runs on.
+fn version_info(profile: &str) -> String {
+    format!(
+        "Soundboard {}\nConfiguration version {CONFIG_VERSION}\nProfile {profile}\n\
+        Operating system {} ({})\nAudio host {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        audio_host().id().name(),
+    )
+}
+
+/// Write a zip file for bug reports to [configuration directory]/support and return its path.
+/// It holds the redacted configuration of `profile`, the recent log, `devices` (a description of
+/// the audio devices), `state` (the state published by the HTTP API) and version information.
+pub fn create_support_bundle(
+    config: &Config,
+    profile: &str,
+    devices: &str,
+    state: &str,
+) -> Result<PathBuf, String> {
+    let directory = get_config_directory()?.join("support");
+    if let Err(error) = fs::create_dir_all(&directory) {
+        return Err(format!("Unable to create support directory: {error}."));
+    }
+    let path = directory.join(format!(
+        "soundboard-support-{}.zip",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    let log: String = recent_lines()
+        .iter()
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let files = [
+        ("version.txt", version_info(profile)),
+        ("config.toml", redacted_config(config)?),
+        ("log.txt", log),
+        ("devices.txt", devices.to_string()),
+        ("state.json", state.to_string()),
+    ];
+
+    let file = fs::File::create(&path)
+        .map_err(|error| format!("Unable to create \"{}\": {error}.", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    for (name, contents) in files {
+        zip.start_file(name, FileOptions::default())
+            .and_then(|()| Ok(zip.write_all(contents.as_bytes())?))
+            .map_err(|error| format!("Unable to write {name} to the support bundle: {error}."))?;
+    }
+    zip.finish()
+        .map_err(|error| format!("Unable to finish the support bundle: {error}."))?;
+    log!("[Support] Created support bundle \"{}\".", path.display());
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soundboard_core::config::{MeasuredLoudness, SoundConfig};
+
+    fn config(paths: &[&str]) -> Config {
+        let mut config: Config =
+            toml::from_str(include_str!("../soundboard-core/src/default_config.toml")).unwrap();
+        config.sync_address = "192.168.1.20:8650".to_string();
+        config.sounds = paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| SoundConfig {
+                name: format!("Sound {i}"),
+                path: path.to_string(),
+                loudness: Some(MeasuredLoudness {
+                    path: path.to_string(),
+                    lufs: Some(-14.0),
+                    peak: Some(0.5),
+                }),
+                ..SoundConfig::default()
+            })
+            .collect();
+        config
+    }
+
+    fn shared_paths(config: &Config) -> Vec<String> {
+        let value: toml::Value = toml::from_str(&shareable_config(config).unwrap()).unwrap();
+        value["sounds"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|sound| sound["path"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn shared_paths_are_relative() {
+        let config = config(&[
+            "/home/alice/Sounds/airhorn.mp3",
+            "/home/alice/Sounds/Memes/bruh.wav",
+            "music/bed.ogg",
+        ]);
+        assert_eq!(
+            shared_paths(&config),
+            ["airhorn.mp3", "Memes/bruh.wav", "music/bed.ogg"]
+        );
+        let shared = shareable_config(&config).unwrap();
+        assert!(!shared.contains("192.168.1.20"));
+        assert!(!shared.contains("alice"));
+    }
+
+    #[test]
+    fn paths_without_a_common_directory_keep_their_file_names() {
+        // Only the root is shared, which would still show every folder.
+        let config = config(&["/home/alice/airhorn.mp3", "/mnt/media/bed.ogg"]);
+        assert_eq!(shared_paths(&config), ["airhorn.mp3", "bed.ogg"]);
+        let paths = [Path::new("/home/alice/a.mp3"), Path::new("/mnt/b.mp3")];
+        assert_eq!(common_directory(paths.into_iter()), None);
+        // Files on different Windows drives have no common directory at all.
+        assert_eq!(
+            shareable_path("/home/alice/Sounds/airhorn.mp3", None),
+            "airhorn.mp3"
+        );
+    }
+}
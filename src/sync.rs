@@ -0,0 +1,513 @@
+use crate::worker::WorkerPool;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use soundboard_core::as_hex::as_hex;
+use soundboard_core::config::{get_library_directory, SoundConfig};
+use soundboard_core::waker::{Waker, WakingSender};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+/// How long fetching a manifest or a file from another soundboard may take.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The file of a sound in a [`Manifest`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ManifestFile {
+    /// The SHA-256 hash of the file in lowercase hex.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// A sound in a [`Manifest`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ManifestSound {
+    pub sound: SoundConfig,
+    /// None if the file could not be read.
+    pub file: Option<ManifestFile>,
+}
+
+/// The sounds of a profile with the hashes of their files, served by `GET /manifest` and
+/// exported to compare two soundboards.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Manifest {
+    pub profile: String,
+    pub sounds: Vec<ManifestSound>,
+}
+
+/// The hashes of files by path, with the size and modification time they had when hashed.
+type Hashes = HashMap<PathBuf, (u64, SystemTime, String)>;
+
+/// The files hashed so far, so unchanged files are not read again.
+static HASHES: Mutex<Option<Hashes>> = Mutex::new(None);
+
+/// Return the hash and size of the file at `path`, or None if it cannot be read.
+fn hash_file(path: &str) -> Option<ManifestFile> {
+    let metadata = fs::metadata(path).ok()?;
+    let (size, modified) = (metadata.len(), metadata.modified().ok()?);
+    let key = PathBuf::from(path);
+    if let Some((_, _, hash)) = HASHES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .get(&key)
+        .filter(|(s, m, _)| (*s, *m) == (size, modified))
+    {
+        return Some(ManifestFile {
+            hash: hash.clone(),
+            size,
+        });
+    }
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path).ok()?, &mut hasher).ok()?;
+    let hash = as_hex(&hasher.finalize()).to_lowercase();
+    HASHES
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(key, (size, modified, hash.clone()));
+    Some(ManifestFile { hash, size })
+}
+
+/// Return the manifest of `sounds`, hashing their files. Slow for large files that were not
+/// hashed before, so it is called off the UI thread.
+pub fn build_manifest(profile: String, sounds: Vec<SoundConfig>) -> Manifest {
+    let sounds = sounds
+        .into_iter()
+        .map(|sound| ManifestSound {
+            file: hash_file(&sound.path),
+            sound,
+        })
+        .collect();
+    Manifest { profile, sounds }
+}
+
+/// How a sound differs between this soundboard and another one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difference {
+    /// Only the other soundboard has the sound.
+    Missing,
+    /// Both have the sound, but its file can only be read on the other one.
+    MissingFile,
+    /// Only this soundboard has the sound.
+    Extra,
+    /// Both have the sound with different files.
+    FileDiffers,
+    /// Both have the sound with the same file, but other settings such as its volume differ.
+    SettingsDiffer,
+}
+
+impl Difference {
+    pub fn label(self) -> &'static str {
+        match self {
+            Difference::Missing => "Only on the other soundboard",
+            Difference::MissingFile => "File missing here",
+            Difference::Extra => "Only here",
+            Difference::FileDiffers => "Different file",
+            Difference::SettingsDiffer => "Different settings",
+        }
+    }
+
+    /// Check if syncing copies the sound or its file from the other soundboard.
+    pub fn syncable(self) -> bool {
+        matches!(self, Difference::Missing | Difference::MissingFile)
+    }
+}
+
+/// A sound that differs between two manifests.
+pub struct SoundDifference {
+    pub name: String,
+    pub difference: Difference,
+    /// The index of the sound in the other soundboard's manifest, if it has it.
+    pub remote: Option<usize>,
+}
+
+/// Return the settings of `sound` that are compared, leaving out where its files are on each
+/// computer and what was measured from them.
+fn compared_settings(sound: &SoundConfig) -> serde_json::Value {
+    let mut value = serde_json::to_value(sound).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        for key in ["path", "loop_path", "loudness"] {
+            object.remove(key);
+        }
+    }
+    value
+}
+
+/// Compare the sounds of `local` and `remote` by name. A name used by several sounds is
+/// compared by its first sound.
+pub fn compare(local: &Manifest, remote: &Manifest) -> Vec<SoundDifference> {
+    let first = |manifest: &Manifest, name: &str| {
+        manifest
+            .sounds
+            .iter()
+            .position(|sound| sound.sound.name == name)
+    };
+    let mut differences = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, remote_sound) in remote.sounds.iter().enumerate() {
+        let name = &remote_sound.sound.name;
+        if !seen.insert(name) {
+            continue;
+        }
+        let difference = match first(local, name).map(|j| &local.sounds[j]) {
+            None => Difference::Missing,
+            Some(local_sound) => match (&local_sound.file, &remote_sound.file) {
+                (None, Some(_)) => Difference::MissingFile,
+                (Some(local_file), Some(remote_file)) if local_file != remote_file => {
+                    Difference::FileDiffers
+                }
+                _ if compared_settings(&local_sound.sound)
+                    != compared_settings(&remote_sound.sound) =>
+                {
+                    Difference::SettingsDiffer
+                }
+                _ => continue,
+            },
+        };
+        differences.push(SoundDifference {
+            name: name.clone(),
+            difference,
+            remote: Some(i),
+        });
+    }
+    for local_sound in &local.sounds {
+        let name = &local_sound.sound.name;
+        if first(remote, name).is_none() && seen.insert(name) {
+            differences.push(SoundDifference {
+                name: name.clone(),
+                difference: Difference::Extra,
+                remote: None,
+            });
+        }
+    }
+    differences
+}
+
+/// Fetch the manifest of the soundboard whose HTTP API listens on `address` (e.g.
+/// "192.168.1.20:8651").
+fn fetch_manifest(address: &str, token: &str) -> Result<Manifest, String> {
+    let error = |error: &dyn std::fmt::Display| {
+        format!("Unable to get the manifest from {address}: {error}.")
+    };
+    let body = ureq::get(&format!("http://{address}/manifest"))
+        .set("Authorization", &format!("Bearer {token}"))
+        .timeout(TIMEOUT)
+        .call()
+        .map_err(|e| error(&e))?
+        .into_string()
+        .map_err(|e| error(&e))?;
+    serde_json::from_str(&body)
+        .map_err(|error| format!("The manifest from {address} is malformed: {error}."))
+}
+
+/// Download the file of `sound` from the soundboard at `address` into the media library and
+/// return its path there. The file is checked against its hash, so a file changed on the other
+/// soundboard since its manifest was fetched is not used.
+fn download(address: &str, token: &str, sound: &ManifestSound) -> Result<String, String> {
+    let file = sound
+        .file
+        .as_ref()
+        .ok_or_else(|| format!("The file of {} is missing.", sound.sound.name))?;
+    let response = ureq::get(&format!("http://{address}/file"))
+        .query("hash", &file.hash)
+        .set("Authorization", &format!("Bearer {token}"))
+        .timeout(TIMEOUT)
+        .call()
+        .map_err(|error| format!("Unable to download {}: {error}.", sound.sound.name))?;
+    let mut data = Vec::new();
+    response
+        .into_reader()
+        .take(file.size + 1)
+        .read_to_end(&mut data)
+        .map_err(|error| format!("Unable to download {}: {error}.", sound.sound.name))?;
+    if as_hex(&Sha256::digest(&data)).to_lowercase() != file.hash {
+        return Err(format!(
+            "The file of {} changed while it was downloaded.",
+            sound.sound.name
+        ));
+    }
+
+    let path = Path::new(&sound.sound.path);
+    let stem = path
+        .file_stem()
+        .map_or("sound".into(), |stem| stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map_or("wav".into(), |extension| extension.to_string_lossy());
+    let directory = get_library_directory()?;
+    fs::create_dir_all(&directory)
+        .map_err(|error| format!("Unable to create the media library: {error}."))?;
+    let destination = directory.join(format!("{stem}-{}.{extension}", &file.hash[..8]));
+    // Write to a temporary file first, so a failed download never leaves a partial copy in use.
+    let temporary = destination.with_extension("part");
+    fs::write(&temporary, &data)
+        .and_then(|()| fs::rename(&temporary, &destination))
+        .map_err(|error| format!("Unable to write \"{}\": {error}.", destination.display()))?;
+    Ok(destination.display().to_string())
+}
+
+/// Where the manifest compared with was read from.
+#[derive(Clone, PartialEq, Eq)]
+pub enum RemoteSource {
+    /// The soundboard whose HTTP API listens on the address, with its token.
+    Network { address: String, token: String },
+    /// A manifest exported to the file.
+    File(PathBuf),
+}
+
+/// Work finished by a [`SoundSync`].
+enum SyncEvent {
+    Local(Manifest),
+    Remote(RemoteSource, Result<Manifest, String>),
+    Exported(Result<PathBuf, String>),
+    Downloaded(Box<ManifestSound>, Result<String, String>),
+}
+
+/// Compares the sounds of this soundboard with another one and copies the missing sounds and
+/// files from it, on a background thread.
+pub struct SoundSync {
+    workers: WorkerPool,
+    sender: WakingSender<SyncEvent>,
+    receiver: Receiver<SyncEvent>,
+    pub local: Option<Manifest>,
+    pub remote: Option<(RemoteSource, Manifest)>,
+    /// Why the last comparison or export failed, or where the manifest was exported.
+    pub message: Option<Result<String, String>>,
+    /// The names of the sounds being downloaded.
+    pub downloading: HashSet<String>,
+    /// Set while the manifests are being read.
+    pub comparing: bool,
+}
+
+impl SoundSync {
+    /// `waker` is called whenever work finishes.
+    pub fn new(waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            workers: WorkerPool::new("Sync Worker", 1),
+            sender: WakingSender::new(sender, waker),
+            receiver,
+            local: None,
+            remote: None,
+            message: None,
+            downloading: HashSet::new(),
+            comparing: false,
+        }
+    }
+
+    /// Build the manifest of the local `sounds` and read the manifest from `source` to compare
+    /// them.
+    pub fn compare(&mut self, profile: String, sounds: Vec<SoundConfig>, source: RemoteSource) {
+        self.comparing = true;
+        self.message = None;
+        self.refresh(profile, sounds);
+        let sender = self.sender.clone();
+        self.workers.spawn(move || {
+            let manifest = match &source {
+                RemoteSource::Network { address, token } => fetch_manifest(address, token),
+                RemoteSource::File(path) => fs::read_to_string(path)
+                    .map_err(|error| format!("Unable to read \"{}\": {error}.", path.display()))
+                    .and_then(|data| {
+                        serde_json::from_str(&data).map_err(|error| {
+                            format!("\"{}\" is not a manifest: {error}.", path.display())
+                        })
+                    }),
+            };
+            let _ = sender.send(SyncEvent::Remote(source, manifest));
+        });
+    }
+
+    /// Build the manifest of the local `sounds` again, e.g. after sounds were synced.
+    pub fn refresh(&self, profile: String, sounds: Vec<SoundConfig>) {
+        let sender = self.sender.clone();
+        self.workers.spawn(move || {
+            let _ = sender.send(SyncEvent::Local(build_manifest(profile, sounds)));
+        });
+    }
+
+    /// Write the manifest of `sounds` to `path`, for comparing on another computer.
+    pub fn export(&mut self, profile: String, sounds: Vec<SoundConfig>, path: PathBuf) {
+        self.message = None;
+        let sender = self.sender.clone();
+        self.workers.spawn(move || {
+            let result = serde_json::to_string_pretty(&build_manifest(profile, sounds))
+                .map_err(|error| format!("Unable to serialize the manifest: {error}."))
+                .and_then(|json| {
+                    fs::write(&path, json).map_err(|error| {
+                        format!("Unable to write \"{}\": {error}.", path.display())
+                    })
+                })
+                .map(|()| path);
+            let _ = sender.send(SyncEvent::Exported(result));
+        });
+    }
+
+    /// Return the differences between the local and remote manifests, once both are read.
+    pub fn differences(&self) -> Option<Vec<SoundDifference>> {
+        Some(compare(self.local.as_ref()?, &self.remote.as_ref()?.1))
+    }
+
+    /// Download the file of the sound at `index` in the remote manifest. Only possible when the
+    /// manifest was fetched over the network.
+    pub fn download(&mut self, index: usize) {
+        let Some((RemoteSource::Network { address, token }, manifest)) = &self.remote else {
+            return;
+        };
+        let Some(sound) = manifest.sounds.get(index).cloned() else {
+            return;
+        };
+        if !self.downloading.insert(sound.sound.name.clone()) {
+            return;
+        }
+        let (address, token) = (address.clone(), token.clone());
+        let sender = self.sender.clone();
+        self.workers.spawn(move || {
+            let result = download(&address, &token, &sound);
+            let _ = sender.send(SyncEvent::Downloaded(Box::new(sound), result));
+        });
+    }
+
+    /// Return the sounds downloaded since the last call with the path of their file in the
+    /// media library, or why they could not be downloaded.
+    pub fn finished(&mut self) -> Vec<(SoundConfig, Result<String, String>)> {
+        let mut downloaded = Vec::new();
+        for event in self.receiver.try_iter() {
+            match event {
+                SyncEvent::Local(manifest) => self.local = Some(manifest),
+                SyncEvent::Remote(source, result) => {
+                    self.comparing = false;
+                    match result {
+                        Err(error) => {
+                            log!(Error, "[Sync] {error}");
+                            self.message = Some(Err(error));
+                            self.remote = None;
+                        }
+                        Ok(manifest) => self.remote = Some((source, manifest)),
+                    }
+                }
+                SyncEvent::Exported(result) => {
+                    self.message = Some(match result {
+                        Err(error) => {
+                            log!(Error, "[Sync] {error}");
+                            Err(error)
+                        }
+                        Ok(path) => Ok(format!("Saved \"{}\".", path.display())),
+                    });
+                }
+                SyncEvent::Downloaded(sound, result) => {
+                    self.downloading.remove(&sound.sound.name);
+                    downloaded.push((sound.sound, result));
+                }
+            }
+        }
+        downloaded
+    }
+}
+
+/// Return the file served by `GET /file?hash=`: the file of a sound in `manifest` with `hash`.
+pub fn manifest_file(manifest: &Manifest, hash: &str) -> Option<String> {
+    manifest
+        .sounds
+        .iter()
+        .find(|sound| sound.file.as_ref().is_some_and(|file| file.hash == hash))
+        .map(|sound| sound.sound.path.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sound named `name` whose file has `hash`, or no file if `hash` is None.
+    fn sound(name: &str, hash: Option<&str>, path: &str) -> ManifestSound {
+        ManifestSound {
+            sound: SoundConfig {
+                name: name.to_string(),
+                path: path.to_string(),
+                ..SoundConfig::default()
+            },
+            file: hash.map(|hash| ManifestFile {
+                hash: hash.to_string(),
+                size: 1,
+            }),
+        }
+    }
+
+    fn manifest(sounds: Vec<ManifestSound>) -> Manifest {
+        Manifest {
+            profile: "Default".to_string(),
+            sounds,
+        }
+    }
+
+    fn differences(
+        local: &Manifest,
+        remote: &Manifest,
+    ) -> Vec<(String, Difference, Option<usize>)> {
+        compare(local, remote)
+            .into_iter()
+            .map(|difference| (difference.name, difference.difference, difference.remote))
+            .collect()
+    }
+
+    #[test]
+    fn added_and_removed_sounds_are_found() {
+        let local = manifest(vec![
+            sound("Airhorn", Some("a"), "a.mp3"),
+            sound("Bruh", Some("b"), "b.mp3"),
+        ]);
+        let remote = manifest(vec![
+            sound("Airhorn", Some("a"), "a.mp3"),
+            sound("Cheer", Some("c"), "c.mp3"),
+        ]);
+        assert_eq!(
+            differences(&local, &remote),
+            [
+                ("Cheer".to_string(), Difference::Missing, Some(1)),
+                ("Bruh".to_string(), Difference::Extra, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_sounds_are_found() {
+        let mut louder = sound("Bruh", Some("b"), "b.mp3");
+        louder.sound.volume_db = 3.0;
+        let local = manifest(vec![
+            sound("Airhorn", Some("a"), "a.mp3"),
+            sound("Bruh", Some("b"), "b.mp3"),
+            sound("Cheer", None, "c.mp3"),
+        ]);
+        let remote = manifest(vec![
+            sound("Airhorn", Some("changed"), "a.mp3"),
+            louder,
+            sound("Cheer", Some("c"), "c.mp3"),
+        ]);
+        assert_eq!(
+            differences(&local, &remote),
+            [
+                ("Airhorn".to_string(), Difference::FileDiffers, Some(0)),
+                ("Bruh".to_string(), Difference::SettingsDiffer, Some(1)),
+                ("Cheer".to_string(), Difference::MissingFile, Some(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_hashes_match_wherever_the_files_are() {
+        let local = manifest(vec![
+            sound("Airhorn", Some("a"), "/home/alice/airhorn.mp3"),
+            sound("Airhorn", Some("other"), "other.mp3"),
+        ]);
+        let remote = manifest(vec![sound("Airhorn", Some("a"), "C:\\Sounds\\airhorn.mp3")]);
+        assert!(differences(&local, &remote).is_empty());
+    }
+}
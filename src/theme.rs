@@ -0,0 +1,156 @@
+use eframe::egui::{self, Color32, Stroke};
+use soundboard_core::config::{ThemeConfig, ThemeMode};
+use std::process::Command;
+
+/// The status colors of a [`ThemeConfig`].
+#[derive(Clone, Copy)]
+pub struct ThemeColors {
+    pub playing: Color32,
+    pub paused: Color32,
+    pub stopped: Color32,
+}
+
+impl ThemeColors {
+    pub fn new(theme: &ThemeConfig) -> Self {
+        Self {
+            playing: color(theme.playing),
+            paused: color(theme.paused),
+            stopped: color(theme.stopped),
+        }
+    }
+}
+
+/// The accessibility settings of the operating system followed by
+/// [`SystemSetting::System`](soundboard_core::config::SystemSetting::System).
+#[derive(Clone, Copy, Default)]
+pub struct SystemAccessibility {
+    pub high_contrast: bool,
+    pub reduced_motion: bool,
+}
+
+impl SystemAccessibility {
+    /// Read the settings of the operating system. Settings that cannot be read are off.
+    pub fn detect() -> Self {
+        if cfg!(target_os = "windows") {
+            let value = |key: &str, name: &str| {
+                let output = command_output("reg", &["query", key, "/v", name])?;
+                Some(output.split_whitespace().last()?.to_string())
+            };
+            Self {
+                // HCF_HIGHCONTRASTON
+                high_contrast: value(r"HKCU\Control Panel\Accessibility\HighContrast", "Flags")
+                    .and_then(|flags| flags.parse::<u32>().ok())
+                    .is_some_and(|flags| flags & 1 != 0),
+                reduced_motion: value(r"HKCU\Control Panel\Desktop\WindowMetrics", "MinAnimate")
+                    .is_some_and(|animate| animate == "0"),
+            }
+        } else if cfg!(target_os = "macos") {
+            let enabled = |name| {
+                command_output("defaults", &["read", "com.apple.universalaccess", name])
+                    .is_some_and(|value| value == "1")
+            };
+            Self {
+                high_contrast: enabled("increaseContrast"),
+                reduced_motion: enabled("reduceMotion"),
+            }
+        } else {
+            let gsettings = |schema, key| command_output("gsettings", &["get", schema, key]);
+            Self {
+                high_contrast: std::env::var("GTK_THEME")
+                    .is_ok_and(|theme| theme.contains("HighContrast"))
+                    || gsettings("org.gnome.desktop.a11y.interface", "high-contrast")
+                        .is_some_and(|value| value == "true"),
+                reduced_motion: gsettings("org.gnome.desktop.interface", "enable-animations")
+                    .is_some_and(|value| value == "false"),
+            }
+        }
+    }
+}
+
+/// Return what `program` prints, trimmed, or None if it cannot be run or fails.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Return the egui style of a [`ThemeConfig`]. On the big screen, every font and spacing is
+/// scaled by `big_screen_scale`. On the big screen and in high contrast, the colors are changed
+/// to black and white.
+pub fn theme_style(
+    theme: &ThemeConfig,
+    big_screen_scale: Option<f32>,
+    system: SystemAccessibility,
+) -> egui::Style {
+    let mut style = egui::Style {
+        visuals: theme_visuals(theme),
+        ..Default::default()
+    };
+    if theme.reduced_motion.enabled(system.reduced_motion) {
+        style.animation_time = 0.0;
+    }
+    if big_screen_scale.is_none() && !theme.high_contrast.enabled(system.high_contrast) {
+        return style;
+    }
+
+    let scale = big_screen_scale.unwrap_or(1.0);
+    for font in style.text_styles.values_mut() {
+        font.size *= scale;
+    }
+    let spacing = &mut style.spacing;
+    spacing.item_spacing *= scale;
+    spacing.button_padding *= scale;
+    spacing.interact_size *= scale;
+    spacing.icon_width *= scale;
+    spacing.icon_width_inner *= scale;
+    spacing.slider_width *= scale;
+
+    let (background, foreground) = match theme.mode {
+        ThemeMode::Dark => (Color32::BLACK, Color32::WHITE),
+        ThemeMode::Light => (Color32::WHITE, Color32::BLACK),
+    };
+    let visuals = &mut style.visuals;
+    visuals.override_text_color = Some(foreground);
+    visuals.panel_fill = background;
+    visuals.window_fill = background;
+    visuals.extreme_bg_color = background;
+    let widgets = &mut visuals.widgets;
+    for widget in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        widget.fg_stroke.color = foreground;
+        widget.bg_stroke = Stroke::new(widget.bg_stroke.width.max(1.0) * scale, foreground);
+    }
+    style
+}
+
+fn theme_visuals(theme: &ThemeConfig) -> egui::Visuals {
+    let mut visuals = match theme.mode {
+        ThemeMode::Dark => egui::Visuals::dark(),
+        ThemeMode::Light => egui::Visuals::light(),
+    };
+    visuals.selection.bg_fill = color(theme.accent);
+    visuals
+}
+
+fn color([r, g, b]: [u8; 3]) -> Color32 {
+    Color32::from_rgb(r, g, b)
+}
+
+/// Return the fill of a pad colored `rgb` and a text color that is readable on it.
+pub fn pad_colors(rgb: [u8; 3]) -> (Color32, Color32) {
+    let [r, g, b] = rgb.map(f32::from);
+    let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+    let text = if luma > 140.0 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    };
+    (color(rgb), text)
+}
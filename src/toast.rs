@@ -0,0 +1,148 @@
+use eframe::egui;
+use egui::{Align2, RichText, Sense};
+use soundboard_core::log::{lines_after, Level, LogLine};
+use std::time::{Duration, Instant};
+
+/// Shows errors from the log and other notices as notifications in the corner of the window for a
+/// few seconds. Clicking a notification shows the whole text and keeps it open until it is
+/// dismissed.
+pub struct Toasts {
+    /// The number of the last log line shown.
+    last_line: u64,
+    toasts: Vec<Toast>,
+}
+
+struct Toast {
+    line: LogLine,
+    /// How many times the error was logged while it was shown.
+    count: usize,
+    /// When the error was last logged.
+    shown: Instant,
+    expanded: bool,
+}
+
+impl Toasts {
+    /// How long a toast is shown unless it is clicked.
+    const DURATION: Duration = Duration::from_secs(6);
+    /// The most toasts shown at once. The oldest are dropped first.
+    const MAX_TOASTS: usize = 5;
+    /// The most characters of an error shown before it is clicked.
+    const SUMMARY_LENGTH: usize = 80;
+
+    pub fn new() -> Self {
+        Self {
+            last_line: 0,
+            toasts: Vec::new(),
+        }
+    }
+
+    /// Show `text` as a warning, e.g. why a sound was not played. It should be logged as well.
+    pub fn notify(&mut self, text: String) {
+        self.push(LogLine {
+            number: 0,
+            time: chrono::Local::now(),
+            level: Level::Warning,
+            text,
+        });
+    }
+
+    /// Add a toast for `line`, or count it if the same text is already shown.
+    fn push(&mut self, line: LogLine) {
+        match self
+            .toasts
+            .iter_mut()
+            .find(|toast| toast.line.text == line.text)
+        {
+            Some(toast) => {
+                toast.count += 1;
+                toast.shown = Instant::now();
+                toast.line = line;
+            }
+            None => self.toasts.push(Toast {
+                line,
+                count: 1,
+                shown: Instant::now(),
+                expanded: false,
+            }),
+        }
+    }
+
+    /// Show the toasts, adding one for every error logged since the last call. An error logged
+    /// again while shown is counted instead. Returns true if "Open Log" was pressed.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        for line in lines_after(self.last_line, Level::Error) {
+            self.last_line = line.number;
+            self.push(line);
+        }
+        self.toasts
+            .retain(|toast| toast.expanded || toast.shown.elapsed() < Self::DURATION);
+        if self.toasts.len() > Self::MAX_TOASTS {
+            self.toasts.drain(..self.toasts.len() - Self::MAX_TOASTS);
+        }
+        if let Some(remaining) = self
+            .toasts
+            .iter()
+            .filter(|toast| !toast.expanded)
+            .map(|toast| Self::DURATION.saturating_sub(toast.shown.elapsed()))
+            .min()
+        {
+            ctx.request_repaint_after(remaining);
+        }
+        if self.toasts.is_empty() {
+            return false;
+        }
+
+        let mut open_log = false;
+        egui::Area::new("Toasts")
+            .anchor(Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                self.toasts.retain_mut(|toast| {
+                    let mut keep = true;
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let color = if toast.line.level == Level::Error {
+                            ui.visuals().error_fg_color
+                        } else {
+                            ui.visuals().warn_fg_color
+                        };
+                        let mut text = toast.line.text.clone();
+                        if !toast.expanded && text.chars().count() > Self::SUMMARY_LENGTH {
+                            text = text.chars().take(Self::SUMMARY_LENGTH).collect();
+                            text.push_str("...");
+                        }
+                        if toast.count > 1 {
+                            text = format!("{text} ({}x)", toast.count);
+                        }
+                        let response = ui.add(
+                            egui::Label::new(RichText::new(text).color(color))
+                                .sense(Sense::click()),
+                        );
+                        if !toast.expanded {
+                            if response.on_hover_text("Show details").clicked() {
+                                toast.expanded = true;
+                            }
+                            return;
+                        }
+                        ui.label(format!(
+                            "Logged at {}",
+                            toast.line.time.format("%Y-%m-%d %H:%M:%S")
+                        ));
+                        ui.horizontal(|ui| {
+                            if ui.button("Copy").clicked() {
+                                let text = toast.line.to_string();
+                                ui.output_mut(|output| output.copied_text = text);
+                            }
+                            if ui.button("Open Log").clicked() {
+                                open_log = true;
+                            }
+                            if ui.button("Dismiss").clicked() {
+                                keep = false;
+                            }
+                        });
+                    });
+                    keep
+                });
+            });
+        open_log
+    }
+}
@@ -0,0 +1,377 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use soundboard_core::waker::Waker;
+use std::{
+    cmp::Ordering,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// The releases feed queried for new versions.
+const RELEASES_URL: &str = "https://api.github.com/repos/bwestley/soundboard/releases/latest";
+/// The name of the Windows executable attached to a release.
+const EXECUTABLE_ASSET: &str = "soundboard.exe";
+/// The name of the file attached next to the executable holding its SHA-256 hash in hex, as
+/// written by `sha256sum`.
+const CHECKSUM_ASSET: &str = "soundboard.exe.sha256";
+
+/// A release published on the releases feed.
+#[derive(Clone)]
+pub struct Release {
+    pub version: String,
+    /// The release page.
+    pub url: String,
+    /// The download of the Windows executable, if the release has one.
+    pub executable_url: Option<String>,
+    /// The download of the SHA-256 hash of the executable, if the release has one.
+    pub checksum_url: Option<String>,
+}
+
+#[derive(Clone)]
+pub enum UpdateState {
+    Idle,
+    Checking,
+    UpToDate,
+    Available(Release),
+    Downloading(Release),
+    /// The new executable replaced this one and is used after a restart.
+    Staged(Release),
+    Failed(String),
+}
+
+/// The parts of a GitHub release used by the update checker.
+#[derive(Deserialize)]
+struct ReleaseResponse {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    assets: Vec<AssetResponse>,
+}
+
+#[derive(Deserialize)]
+struct AssetResponse {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Checks the releases feed for a newer version on a background thread.
+pub struct UpdateChecker {
+    state: Arc<Mutex<UpdateState>>,
+    waker: Waker,
+}
+
+impl UpdateChecker {
+    /// `waker` is called whenever a check or download finishes.
+    pub fn new(waker: Waker) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(UpdateState::Idle)),
+            waker,
+        }
+    }
+
+    /// Return the state of the last check or download.
+    pub fn state(&self) -> UpdateState {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Check the releases feed in a new thread.
+    pub fn check(&self) {
+        *self.state.lock().unwrap() = UpdateState::Checking;
+        let state = self.state.clone();
+        let waker = self.waker.clone();
+        thread::spawn(move || {
+            let new_state = match fetch_latest_release() {
+                Err(error) => {
+                    log!(Error, "[Updater] {error}");
+                    UpdateState::Failed(error)
+                }
+                Ok(release) => {
+                    if is_newer(&release.version, env!("CARGO_PKG_VERSION")) {
+                        log!("[Updater] Version {} is available.", release.version);
+                        UpdateState::Available(release)
+                    } else {
+                        log!(
+                            "[Updater] Version {} is up to date.",
+                            env!("CARGO_PKG_VERSION")
+                        );
+                        UpdateState::UpToDate
+                    }
+                }
+            };
+            *state.lock().unwrap() = new_state;
+            waker.wake();
+        });
+    }
+
+    /// Download the executable of `release` in a new thread and put it in place of this
+    /// executable, so it starts the next time the soundboard is opened.
+    pub fn download(&self, release: Release) {
+        *self.state.lock().unwrap() = UpdateState::Downloading(release.clone());
+        let state = self.state.clone();
+        let waker = self.waker.clone();
+        thread::spawn(move || {
+            let new_state = match stage_executable(&release) {
+                Err(error) => {
+                    log!(Error, "[Updater] {error}");
+                    UpdateState::Failed(error)
+                }
+                Ok(()) => {
+                    log!(
+                        "[Updater] Version {} will start next time.",
+                        release.version
+                    );
+                    UpdateState::Staged(release)
+                }
+            };
+            *state.lock().unwrap() = new_state;
+            waker.wake();
+        });
+    }
+}
+
+/// Delete the executable left behind by a previous update.
+pub fn remove_replaced_executable() {
+    if let Ok(path) = replaced_executable_path() {
+        if path.is_file() {
+            match fs::remove_file(&path) {
+                Err(error) => log!(
+                    Error,
+                    "[Updater] Unable to remove old executable \"{}\": {error}.",
+                    path.display()
+                ),
+                Ok(()) => log!("[Updater] Removed old executable \"{}\".", path.display()),
+            }
+        }
+    }
+}
+
+fn fetch_latest_release() -> Result<Release, String> {
+    log!("[Updater] Checking {RELEASES_URL} for a new version.");
+    let response = match ureq::get(RELEASES_URL)
+        .set(
+            "User-Agent",
+            concat!("soundboard/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+    {
+        Err(error) => return Err(format!("Unable to check for updates: {error}.")),
+        Ok(response) => response,
+    };
+    let body = match response.into_string() {
+        Err(error) => return Err(format!("Unable to read the releases feed: {error}.")),
+        Ok(body) => body,
+    };
+    let release: ReleaseResponse = match serde_json::from_str(&body) {
+        Err(error) => return Err(format!("Unable to parse the releases feed: {error}.")),
+        Ok(release) => release,
+    };
+
+    let asset_url = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .map(|asset| asset.browser_download_url.clone())
+    };
+    Ok(Release {
+        version: release.tag_name.trim_start_matches('v').to_string(),
+        executable_url: asset_url(EXECUTABLE_ASSET),
+        checksum_url: asset_url(CHECKSUM_ASSET),
+        url: release.html_url,
+    })
+}
+
+/// Compare two dotted version numbers such as "2.1.0" or "2.1.0-beta.2". Missing numbers count
+/// as 0, a pre-release is older than its release and build metadata after "+" is ignored.
+fn is_newer(version: &str, current: &str) -> bool {
+    version_order(version, current) == Ordering::Greater
+}
+
+/// Order two versions as described in [`is_newer`].
+fn version_order(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.split('+').next().unwrap_or_default();
+        let (numbers, pre_release) = match version.split_once('-') {
+            Some((numbers, pre_release)) => (numbers, Some(pre_release)),
+            None => (version, None),
+        };
+        let numbers: Vec<u64> = numbers
+            .split('.')
+            .map(|number| number.parse().unwrap_or(0))
+            .collect();
+        (numbers, pre_release)
+    }
+    let ((a_numbers, a_pre), (b_numbers, b_pre)) = (split(a), split(b));
+    let length = a_numbers.len().max(b_numbers.len());
+    let number = |numbers: &[u64], i| numbers.get(i).copied().unwrap_or(0);
+    (0..length)
+        .map(|i| number(&a_numbers, i).cmp(&number(&b_numbers, i)))
+        .find(|order| order.is_ne())
+        .unwrap_or_else(|| match (a_pre, b_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            // Numeric identifiers such as the 2 of "beta.2" compare as numbers, and come before
+            // names.
+            (Some(a), Some(b)) => {
+                let identifier = |part: &str| match part.parse::<u64>() {
+                    Ok(number) => (0, number, String::new()),
+                    Err(_) => (1, 0, part.to_string()),
+                };
+                a.split('.')
+                    .map(identifier)
+                    .cmp(b.split('.').map(identifier))
+            }
+        })
+}
+
+/// The path this executable is moved to while it is being replaced.
+fn replaced_executable_path() -> Result<PathBuf, String> {
+    match std::env::current_exe() {
+        Err(error) => Err(format!("Unable to obtain executable path: {error}.")),
+        Ok(path) => Ok(path.with_extension("old")),
+    }
+}
+
+/// Download `url` into memory.
+fn download(url: &str) -> Result<Vec<u8>, String> {
+    log!("[Updater] Downloading {url}.");
+    let response = match ureq::get(url).call() {
+        Err(error) => return Err(format!("Unable to download {url}: {error}.")),
+        Ok(response) => response,
+    };
+    let mut data = Vec::new();
+    if let Err(error) = response.into_reader().read_to_end(&mut data) {
+        return Err(format!("Unable to download {url}: {error}."));
+    }
+    Ok(data)
+}
+
+/// Check `executable` against the hex SHA-256 hash at the start of `checksum`.
+fn verify_checksum(executable: &[u8], checksum: &[u8]) -> Result<(), String> {
+    let expected = String::from_utf8_lossy(checksum)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if expected.len() != 64 || !expected.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(format!(
+            "The published checksum {expected:?} is not a SHA-256 hash."
+        ));
+    }
+    let actual: String = Sha256::digest(executable)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    if expected != actual {
+        return Err(format!(
+            "The downloaded executable has SHA-256 hash {actual}, but {expected:?} was published."
+        ));
+    }
+    Ok(())
+}
+
+/// Download the new executable next to this one, check it against its published hash, then
+/// swap them. A running executable cannot be overwritten on Windows, but it can be renamed.
+fn stage_executable(release: &Release) -> Result<(), String> {
+    let (url, checksum_url) = match (&release.executable_url, &release.checksum_url) {
+        (None, _) => return Err(format!("Version {} has no executable.", release.version)),
+        (Some(_), None) => {
+            return Err(format!(
+                "Version {} has no {CHECKSUM_ASSET} to check the executable with.",
+                release.version
+            ))
+        }
+        (Some(url), Some(checksum_url)) => (url, checksum_url),
+    };
+    let executable_path = match std::env::current_exe() {
+        Err(error) => return Err(format!("Unable to obtain executable path: {error}.")),
+        Ok(path) => path,
+    };
+    let new_path = executable_path.with_extension("new");
+    let replaced_path = replaced_executable_path()?;
+
+    let checksum = download(checksum_url)?;
+    let executable = download(url)?;
+    verify_checksum(&executable, &checksum)?;
+    if let Err(error) = fs::write(&new_path, executable) {
+        return Err(format!(
+            "Unable to write \"{}\": {error}.",
+            new_path.display()
+        ));
+    }
+
+    let swap = || -> io::Result<()> {
+        fs::rename(&executable_path, &replaced_path)?;
+        if let Err(error) = fs::rename(&new_path, &executable_path) {
+            // Put the running executable back so the soundboard still starts.
+            let _ = fs::rename(&replaced_path, &executable_path);
+            return Err(error);
+        }
+        Ok(())
+    };
+    swap().map_err(|error| format!("Unable to replace the executable: {error}."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_versions_are_found() {
+        assert!(is_newer("2.1.0", "2.0.9"));
+        assert!(is_newer("2.10.0", "2.9.0"));
+        assert!(!is_newer("2.0.9", "2.1.0"));
+        assert!(!is_newer("2.1.0", "2.1.0"));
+    }
+
+    #[test]
+    fn versions_of_different_lengths_are_padded() {
+        assert!(!is_newer("2.1", "2.1.0"));
+        assert!(!is_newer("2.1.0", "2.1"));
+        assert!(is_newer("2.1.0.1", "2.1"));
+        assert!(is_newer("3", "2.9.9"));
+    }
+
+    #[test]
+    fn pre_releases_come_before_their_release() {
+        assert!(is_newer("2.1.0", "2.1.0-beta"));
+        assert!(!is_newer("2.1.0-beta", "2.1.0"));
+        assert!(is_newer("2.1.0-beta", "2.0.0"));
+        assert!(is_newer("2.1.0-beta.10", "2.1.0-beta.2"));
+        assert!(is_newer("2.1.0-beta", "2.1.0-alpha.3"));
+        assert!(is_newer("2.1.0-alpha", "2.1.0-1"));
+        assert!(!is_newer("2.1.0+build.5", "2.1.0"));
+    }
+
+    #[test]
+    fn checksums_are_verified() {
+        let executable = b"soundboard";
+        let hash: String = Sha256::digest(executable)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let sha256sum = format!("{}  soundboard.exe\n", hash.to_ascii_uppercase());
+        assert!(verify_checksum(executable, sha256sum.as_bytes()).is_ok());
+        assert!(verify_checksum(executable, hash.as_bytes()).is_ok());
+
+        let mismatch = verify_checksum(b"something else", hash.as_bytes()).unwrap_err();
+        assert!(mismatch.contains(&format!("{hash:?} was published")));
+    }
+
+    #[test]
+    fn malformed_checksums_are_rejected() {
+        let executable = b"soundboard";
+        for checksum in ["", "   \n", "not a hash", &"g".repeat(64), &"a".repeat(63)] {
+            let error = verify_checksum(executable, checksum.as_bytes()).unwrap_err();
+            assert!(
+                error.contains("is not a SHA-256 hash"),
+                "{checksum:?}: {error}"
+            );
+        }
+        assert!(verify_checksum(executable, &[0xff; 64]).is_err());
+    }
+}
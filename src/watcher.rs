@@ -0,0 +1,96 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use soundboard_core::waker::Waker;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Watches a configuration file and a directory of configuration fragments for changes made by
+/// other programs, such as a text editor.
+pub struct ConfigWatcher {
+    /// Stops watching when dropped.
+    _watcher: Option<RecommendedWatcher>,
+    /// When the file last changed, if it changed since the last reload.
+    changed: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ConfigWatcher {
+    /// How long the file must be left alone before it is reloaded, since editors often save in
+    /// several steps.
+    const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+    /// Watch the file at `path` and the toml files in `fragment_directory`. The directory holding
+    /// the file is watched, so that editors replacing the file instead of writing to it are
+    /// noticed. `waker` is called on every change.
+    pub fn new(path: PathBuf, fragment_directory: PathBuf, waker: Waker) -> Self {
+        let changed = Arc::new(Mutex::new(None));
+        let watcher_changed = changed.clone();
+        let watched_path = path.clone();
+        let watched_fragments = fragment_directory.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let event = match result {
+                Err(error) => {
+                    log!(Error, "[Configuration Watcher] {error}.");
+                    return;
+                }
+                Ok(event) => event,
+            };
+            let fragment_changed = event.paths.iter().any(|path| {
+                path.parent() == Some(&watched_fragments)
+                    && path
+                        .extension()
+                        .is_some_and(|extension| extension == "toml")
+            });
+            let file_changed = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                && event.paths.contains(&watched_path);
+            // Removing a fragment also changes the configuration.
+            if file_changed || (fragment_changed && !matches!(event.kind, EventKind::Access(_))) {
+                *watcher_changed.lock().unwrap() = Some(Instant::now());
+                waker.wake();
+            }
+        })
+        .and_then(|mut watcher| {
+            let directory = path.parent().unwrap_or(&path);
+            watcher.watch(directory, RecursiveMode::NonRecursive)?;
+            if fragment_directory.is_dir() {
+                watcher.watch(&fragment_directory, RecursiveMode::NonRecursive)?;
+            }
+            Ok(watcher)
+        });
+
+        let watcher = match watcher {
+            Err(error) => {
+                log!(
+                    Error,
+                    "[Configuration Watcher] Unable to watch \"{}\": {error}.",
+                    path.display()
+                );
+                None
+            }
+            Ok(watcher) => Some(watcher),
+        };
+        Self {
+            _watcher: watcher,
+            changed,
+        }
+    }
+
+    /// Return true once the file has changed and then been left alone for
+    /// [`Self::SETTLE_TIME`].
+    pub fn take_changed(&self) -> bool {
+        let mut changed = self.changed.lock().unwrap();
+        match *changed {
+            Some(time) if time.elapsed() >= Self::SETTLE_TIME => {
+                *changed = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Return true if the file changed recently and is about to be reloaded.
+    pub fn pending(&self) -> bool {
+        self.changed.lock().unwrap().is_some()
+    }
+}
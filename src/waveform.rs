@@ -0,0 +1,261 @@
+use crate::worker::WorkerPool;
+use rodio::{Decoder, Source};
+use soundboard_core::config::{LoudnessConfig, MeasuredLoudness, SoundConfig};
+use soundboard_core::dsp::LoudnessMeter;
+use soundboard_core::supervisor::catch_panic;
+use soundboard_core::waker::{Waker, WakingSender};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    time::{Duration, Instant},
+};
+
+/// A downsampled outline of an audio file.
+pub struct Waveform {
+    /// The peak amplitude of each slice of the file, from 0.0 to 1.0.
+    pub peaks: Vec<f32>,
+    pub duration: Duration,
+    /// The integrated loudness in LUFS, or None if the file is silent.
+    pub loudness_lufs: Option<f32>,
+}
+
+impl Waveform {
+    /// The number of peaks in a waveform.
+    pub const RESOLUTION: usize = 200;
+    /// The level the loudest peak is moved to by normalization, in dBFS.
+    const NORMALIZED_PEAK: f32 = -1.0;
+    /// The most normalization raises a sound by, in dB, so near-silent files are not blown up.
+    const MAX_NORMALIZATION_GAIN: f32 = 24.0;
+
+    /// Decode the audio file at `filename` and compute its waveform.
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let file = BufReader::new(match File::open(filename) {
+            Err(error) => return Err(format!("Unable to read file {filename}: {error}.")),
+            Ok(file) => file,
+        });
+        let source = match Decoder::new(file) {
+            Err(error) => return Err(format!("Unable to decode file {filename}: {error}.")),
+            Ok(source) => source,
+        };
+        let channels = source.channels().max(1) as usize;
+        let sample_rate = source.sample_rate().max(1) as usize;
+        let mut loudness = LoudnessMeter::new(channels as u16, sample_rate as u32);
+
+        // Collect the peak of every 10 ms, then merge those into `RESOLUTION` peaks once the
+        // length of the file is known.
+        let window = (sample_rate * channels / 100).max(1);
+        let mut windows = Vec::new();
+        let mut peak: f32 = 0.0;
+        let mut samples = 0;
+        for sample in source.convert_samples::<f32>() {
+            peak = peak.max(sample.abs());
+            loudness.add(sample);
+            samples += 1;
+            if samples % window == 0 {
+                windows.push(peak);
+                peak = 0.0;
+            }
+        }
+        if samples % window != 0 {
+            windows.push(peak);
+        }
+
+        let peaks = if windows.len() <= Self::RESOLUTION {
+            windows
+        } else {
+            (0..Self::RESOLUTION)
+                .map(|i| {
+                    let start = i * windows.len() / Self::RESOLUTION;
+                    let end = (i + 1) * windows.len() / Self::RESOLUTION;
+                    windows[start..end].iter().fold(0.0, |a: f32, &b| a.max(b))
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            peaks: peaks.into_iter().map(|peak| peak.min(1.0)).collect(),
+            duration: Duration::from_secs_f64(samples as f64 / (sample_rate * channels) as f64),
+            loudness_lufs: loudness.integrated(),
+        })
+    }
+
+    /// Return the amplitude of the loudest peak, from 0.0 to 1.0.
+    pub fn peak(&self) -> f32 {
+        self.peaks.iter().fold(0.0, |a: f32, &b| a.max(b))
+    }
+
+    /// Return the gain in dB that moves the loudest peak to [`Self::NORMALIZED_PEAK`].
+    pub fn normalization_db(&self) -> f32 {
+        peak_headroom_db(self.peak())
+            .map_or(0.0, |headroom| headroom.min(Self::MAX_NORMALIZATION_GAIN))
+    }
+}
+
+/// Return the gain in dB that moves a peak of amplitude `peak` to [`Waveform::NORMALIZED_PEAK`],
+/// or None if the file is silent.
+fn peak_headroom_db(peak: f32) -> Option<f32> {
+    (peak > 0.0).then(|| Waveform::NORMALIZED_PEAK - 20.0 * peak.log10())
+}
+
+/// Return the normalization gain in dB of `sound`, or 0 if it is not normalized or its file has
+/// not been measured yet. Loudness normalization replaces the peak normalization of each sound,
+/// and raises a sound no further than its loudest peak allows without clipping.
+pub fn normalization_db(
+    waveform: Option<&WaveformState>,
+    sound: &SoundConfig,
+    loudness: &LoudnessConfig,
+) -> f32 {
+    if loudness.enabled {
+        return measured_loudness(waveform, sound).map_or(0.0, |measured| {
+            let Some(lufs) = measured.lufs else {
+                return 0.0;
+            };
+            let headroom = measured.peak.and_then(peak_headroom_db).unwrap_or(f32::MAX);
+            (loudness.target_lufs - lufs)
+                .min(headroom)
+                .min(Waveform::MAX_NORMALIZATION_GAIN)
+        });
+    }
+    match waveform {
+        Some(WaveformState::Loaded(waveform)) if sound.normalize => waveform.normalization_db(),
+        _ => 0.0,
+    }
+}
+
+/// Return the loudness of the file of `sound`: the one stored in the configuration if it was
+/// measured from the same file with its peak, otherwise the one of `waveform` if it has been
+/// computed.
+pub fn measured_loudness(
+    waveform: Option<&WaveformState>,
+    sound: &SoundConfig,
+) -> Option<MeasuredLoudness> {
+    match (&sound.loudness, waveform) {
+        (Some(measured), _) if measured.path == sound.path && measured.peak.is_some() => {
+            Some(measured.clone())
+        }
+        (_, Some(WaveformState::Loaded(waveform))) => Some(MeasuredLoudness {
+            path: sound.path.clone(),
+            lufs: waveform.loudness_lufs,
+            peak: Some(waveform.peak()),
+        }),
+        _ => None,
+    }
+}
+
+/// Return a random offset into the trimmed part of `sound` to start from if it starts at random,
+/// or 0 if not or if the length of its file is not known yet.
+pub fn random_start(waveform: Option<&WaveformState>, sound: &SoundConfig) -> Duration {
+    let duration = match waveform {
+        Some(WaveformState::Loaded(waveform)) if sound.random_start => waveform.duration,
+        _ => return Duration::ZERO,
+    };
+    let (start, length) = sound.trim();
+    let length = length.min(duration.saturating_sub(start));
+    let mut bytes = [0; 8];
+    if let Err(error) = getrandom::getrandom(&mut bytes) {
+        log!(
+            Warning,
+            "[Soundboard] Unable to pick a random start for {}: {error}.",
+            sound.name
+        );
+        return Duration::ZERO;
+    }
+    match length.as_millis() as u64 {
+        0 => Duration::ZERO,
+        millis => Duration::from_millis(u64::from_le_bytes(bytes) % millis),
+    }
+}
+
+pub enum WaveformState {
+    Loading,
+    Loaded(Waveform),
+    Failed(String),
+    /// The path was not a file when it was last checked.
+    Missing(Instant),
+}
+
+/// The result of scanning a path on a worker thread.
+enum ScanResult {
+    Missing,
+    Loaded(Result<Waveform, String>),
+}
+
+/// Computes waveforms on a pool of background threads and keeps them by path.
+/// The UI thread never touches the file system, so it does not stall on slow or
+/// numerous files.
+pub struct WaveformCache {
+    waveforms: HashMap<String, WaveformState>,
+    workers: WorkerPool,
+    sender: WakingSender<(String, ScanResult)>,
+    receiver: Receiver<(String, ScanResult)>,
+    waker: Waker,
+}
+
+impl WaveformCache {
+    /// How long to wait before checking a missing file again.
+    const MISSING_RECHECK: Duration = Duration::from_secs(5);
+
+    /// `waker` is called whenever a waveform is finished.
+    pub fn new(waker: Waker) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            waveforms: HashMap::new(),
+            workers: WorkerPool::new("Waveform Worker", 0),
+            sender: WakingSender::new(sender, waker.clone()),
+            receiver,
+            waker,
+        }
+    }
+
+    /// Store the waveforms finished by background threads.
+    pub fn update(&mut self) {
+        let mut missing = false;
+        for (path, result) in self.receiver.try_iter() {
+            let state = match result {
+                ScanResult::Missing => {
+                    missing = true;
+                    WaveformState::Missing(Instant::now())
+                }
+                ScanResult::Loaded(Err(error)) => {
+                    log!(Error, "[Waveform] {error}");
+                    WaveformState::Failed(error)
+                }
+                ScanResult::Loaded(Ok(waveform)) => WaveformState::Loaded(waveform),
+            };
+            self.waveforms.insert(path, state);
+        }
+        if missing {
+            // Repaint once to check the missing files again even if nothing else happens.
+            self.waker.wake_after(Self::MISSING_RECHECK);
+        }
+    }
+
+    /// Return the waveform of the audio file at `path`, queueing it to be computed if needed.
+    pub fn get(&mut self, path: &str) -> Option<&WaveformState> {
+        let scan = match self.waveforms.get(path) {
+            None => !path.is_empty(),
+            Some(WaveformState::Missing(checked)) => checked.elapsed() >= Self::MISSING_RECHECK,
+            Some(_) => false,
+        };
+        if scan {
+            let sender = self.sender.clone();
+            let path = path.to_string();
+            self.waveforms.insert(path.clone(), WaveformState::Loading);
+            self.workers.spawn(move || {
+                let result = if Path::new(&path).is_file() {
+                    // Some decoders panic on malformed files.
+                    ScanResult::Loaded(catch_panic(|| Waveform::load(&path)).unwrap_or_else(
+                        |error| Err(format!("Decoding file {path} panicked: {error}.")),
+                    ))
+                } else {
+                    ScanResult::Missing
+                };
+                let _ = sender.send((path, result));
+            });
+        }
+        self.waveforms.get(path)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::http::*;
+use serde::Deserialize;
+use soundboard_core::waker::WakingSender;
+use std::{
+    io::{self, prelude::*},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tungstenite::{handshake::derive_accept_key, protocol::Role, Message, WebSocket};
+
+/// A command sent by a WebSocket client as JSON, e.g. `{"command": "play", "name": "Airhorn"}`.
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum WebSocketCommand {
+    ListSounds,
+    Play {
+        index: Option<usize>,
+        name: Option<String>,
+    },
+    Stop,
+    MissedTriggers,
+    GetVolume,
+    SetVolume {
+        db: f32,
+    },
+}
+
+impl WebSocketCommand {
+    fn into_api_command(self) -> Result<ApiCommand, String> {
+        Ok(match self {
+            WebSocketCommand::ListSounds => ApiCommand::ListSounds,
+            WebSocketCommand::Play {
+                index: Some(index), ..
+            } => ApiCommand::PlayIndex(index),
+            WebSocketCommand::Play {
+                name: Some(name), ..
+            } => ApiCommand::PlayName(name),
+            WebSocketCommand::Play { .. } => return Err("Give an index or name.".to_string()),
+            WebSocketCommand::Stop => ApiCommand::StopAll,
+            WebSocketCommand::MissedTriggers => ApiCommand::MissedTriggers,
+            WebSocketCommand::GetVolume => ApiCommand::GetVolume,
+            WebSocketCommand::SetVolume { db } => ApiCommand::SetVolume(db),
+        })
+    }
+}
+
+/// How often a client's socket is checked for commands and state to push.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Complete the WebSocket handshake for the client that sent `key` and serve it in a new
+/// thread. Returns the sender used to push state to the client.
+pub fn accept(
+    mut stream: TcpStream,
+    key: &str,
+    request_sender: WakingSender<ApiRequest>,
+    stop: Arc<AtomicBool>,
+) -> io::Result<Sender<String>> {
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    )?;
+    stream.flush()?;
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+
+    let (state_sender, state_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let address = stream
+            .peer_addr()
+            .map_or("[Unknown]".to_string(), |address| address.to_string());
+        log!("[WebSocket] Client {address} connected.");
+        let socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+        match serve(socket, &request_sender, &state_receiver, &stop) {
+            Ok(()) => log!("[WebSocket] Client {address} disconnected."),
+            Err(error) => log!(Error, "[WebSocket] Client {address} disconnected: {error}."),
+        }
+    });
+    Ok(state_sender)
+}
+
+/// Push state to the client and pass its commands to the soundboard until either side closes.
+fn serve(
+    mut socket: WebSocket<TcpStream>,
+    request_sender: &WakingSender<ApiRequest>,
+    state_receiver: &Receiver<String>,
+    stop: &AtomicBool,
+) -> Result<(), String> {
+    loop {
+        if stop.load(Ordering::SeqCst) {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            return Ok(());
+        }
+
+        for state in state_receiver.try_iter() {
+            socket
+                .send(Message::Text(state))
+                .map_err(|error| error.to_string())?;
+        }
+
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let (status, body) = match serde_json::from_str::<WebSocketCommand>(&text)
+                    .map_err(|error| format!("Invalid command: {error}."))
+                    .and_then(WebSocketCommand::into_api_command)
+                {
+                    Err(error) => (400, serde_json::json!({ "error": error })),
+                    Ok(command) => send_command(command, request_sender),
+                };
+                let response = serde_json::json!({
+                    "type": "response",
+                    "status": status,
+                    "body": body,
+                });
+                socket
+                    .send(Message::Text(response.to_string()))
+                    .map_err(|error| error.to_string())?;
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error))
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(error) => return Err(error.to_string()),
+        }
+    }
+}
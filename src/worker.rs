@@ -0,0 +1,58 @@
+use soundboard_core::supervisor::catch_panic;
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed number of background threads running queued jobs in order.
+pub struct WorkerPool {
+    sender: Sender<Job>,
+}
+
+impl WorkerPool {
+    /// Start `threads` worker threads. Zero starts one per available CPU core.
+    pub fn new(name: &str, threads: usize) -> Self {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(2, |threads| threads.get()),
+            threads => threads,
+        };
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..threads {
+            let receiver = receiver.clone();
+            let name = format!("{name} {i}");
+            let thread_name = name.clone();
+            if let Err(error) = thread::Builder::new()
+                .name(name.clone())
+                .spawn(move || Self::work(&thread_name, &receiver))
+            {
+                log!(Error, "[Worker] Unable to start {name}: {error}.");
+            }
+        }
+        Self { sender }
+    }
+
+    /// Queue `job` to run on the next free worker thread.
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        let _ = self.sender.send(Box::new(job));
+    }
+
+    /// Run jobs until the pool is dropped.
+    fn work(name: &str, receiver: &Mutex<Receiver<Job>>) {
+        loop {
+            // Hold the lock only while waiting, so other workers can take the next job.
+            let job = match receiver.lock().unwrap().recv() {
+                Err(_) => return,
+                Ok(job) => job,
+            };
+            if let Err(error) = catch_panic(job) {
+                log!(Error, "[Worker] A job on {name} panicked: {error}.");
+            }
+        }
+    }
+}